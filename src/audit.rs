@@ -0,0 +1,202 @@
+//! Opt-in local audit log (`--audit-log`, `[audit]` config section)
+//!
+//! Each generation run appends one JSON line recording when and how passwords
+//! were produced: the [`CreationMetadata`] plus per-password entropy and where
+//! the output went. The passwords themselves are never written. The file is
+//! rotated to `<path>.1`, `<path>.2`, ... once it would grow past a size limit.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+
+use crate::config::{Config, Value as ConfigValue};
+use crate::metadata::CreationMetadata;
+
+/// Where generated passwords were delivered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Destination {
+    Terminal,
+    /// Standard output redirected to a pipe or file
+    Stdout,
+    /// One file per password (`--output-dir`)
+    Files,
+    /// The system clipboard (`--copy`)
+    Clipboard,
+    /// One encrypted file per recipient (`--share-with`)
+    Recipients,
+}
+
+impl Destination {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Destination::Terminal => "terminal",
+            Destination::Stdout => "stdout",
+            Destination::Files => "files",
+            Destination::Clipboard => "clipboard",
+            Destination::Recipients => "recipients",
+        }
+    }
+}
+
+/// One audit entry; holds no secret material
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditRecord {
+    pub metadata: CreationMetadata,
+    /// Entropy of each generated password, in bits
+    pub entropy_bits: Vec<f64>,
+    pub destination: Destination,
+}
+
+impl AuditRecord {
+    pub fn to_json(&self) -> Value {
+        let mut record = self.metadata.to_json();
+        record["count"] = json!(self.entropy_bits.len());
+        record["entropy_bits"] = json!(self.entropy_bits);
+        record["destination"] = json!(self.destination.as_str());
+        record
+    }
+}
+
+/// Append-only JSON-lines log with size-based rotation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditLog {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+}
+
+impl AuditLog {
+    pub const DEFAULT_MAX_BYTES: u64 = 1024 * 1024;
+    pub const DEFAULT_KEEP: usize = 5;
+
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes: Self::DEFAULT_MAX_BYTES,
+            keep: Self::DEFAULT_KEEP,
+        }
+    }
+
+    /// Rotate once the log would exceed `max_bytes`, keeping `keep` old files
+    pub fn with_rotation(mut self, max_bytes: u64, keep: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self.keep = keep;
+        self
+    }
+
+    /// The log configured by `path` (or the `[audit]` section), if any
+    ///
+    /// ```toml
+    /// [audit]
+    /// path = "~/.local/state/pwgen-rs/audit.jsonl"
+    /// max_bytes = 1048576
+    /// keep = 5
+    /// ```
+    pub fn from_config(path: Option<&Path>, config: &Config) -> Option<Self> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => expand_home(config.get_str("audit.path")?),
+        };
+        let get = |key: &str| match config.get(key) {
+            Some(ConfigValue::Integer(n)) if *n >= 0 => Some(*n as u64),
+            _ => None,
+        };
+        Some(Self::new(path).with_rotation(
+            get("audit.max_bytes").unwrap_or(Self::DEFAULT_MAX_BYTES),
+            get("audit.keep").map_or(Self::DEFAULT_KEEP, |n| n as usize),
+        ))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&self, record: &AuditRecord) -> io::Result<()> {
+        let line = format!("{}\n", record.to_json());
+        let size = fs::metadata(&self.path).map_or(0, |m| m.len());
+        if size > 0 && size + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let mut options = OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options.open(&self.path)?.write_all(line.as_bytes())
+    }
+
+    fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+
+    /// Shift `<path>.N` to `<path>.N+1`, dropping the oldest, then `<path>` to `<path>.1`
+    fn rotate(&self) -> io::Result<()> {
+        if self.keep == 0 {
+            return fs::remove_file(&self.path);
+        }
+        ignore_missing(fs::remove_file(self.rotated(self.keep)))?;
+        for n in (1..self.keep).rev() {
+            ignore_missing(fs::rename(self.rotated(n), self.rotated(n + 1)))?;
+        }
+        fs::rename(&self.path, self.rotated(1))
+    }
+}
+
+fn ignore_missing(result: io::Result<()>) -> io::Result<()> {
+    match result {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        other => other,
+    }
+}
+
+pub(crate) fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    #[test]
+    fn test_append_and_rotate() {
+        let dir = std::env::temp_dir().join(format!("pwgen-audit-{}", std::process::id()));
+        let log = AuditLog::new(dir.join("audit.jsonl")).with_rotation(300, 2);
+        let record = AuditRecord {
+            metadata: CreationMetadata {
+                created: UNIX_EPOCH,
+                generator: "Secure random".to_string(),
+                parameters: vec!["secure".to_string(), "20".to_string()],
+                policy: None,
+            },
+            entropy_bits: vec![131.1],
+            destination: Destination::Stdout,
+        };
+        for _ in 0..4 {
+            log.append(&record).unwrap();
+        }
+
+        let current = fs::read_to_string(log.path()).unwrap();
+        let entry: Value = serde_json::from_str(current.lines().next().unwrap()).unwrap();
+        assert_eq!(entry["generator"], "Secure random");
+        assert_eq!(entry["count"], 1);
+        assert_eq!(entry["destination"], "stdout");
+        assert!(log.rotated(1).exists());
+        assert!(log.rotated(2).exists());
+        assert!(!log.rotated(3).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,137 @@
+//! Personal terms a password must not contain (`--avoid`, `--avoid-file`)
+//!
+//! Names, birthdays and company terms are often banned by policy. Matching
+//! folds case and common leet substitutions and ignores separators on both
+//! sides, so avoiding `alice` also rejects `A1ic3` and `al-ice`. The folding
+//! merges a few letters (`l` and `i`), which errs towards rejecting.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+/// Shortest term after folding; shorter ones would reject too much by chance
+pub const MIN_TERM_LENGTH: usize = 3;
+
+#[derive(Debug, Error)]
+pub enum AvoidError {
+    #[error(
+        "avoid term '{0}' is too short; terms need at least {MIN_TERM_LENGTH} letters or digits"
+    )]
+    TooShort(String),
+    #[error("cannot read {path}")]
+    Io { path: PathBuf, source: io::Error },
+}
+
+/// Terms rejected in generated values
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AvoidList {
+    /// Folded terms
+    terms: Vec<String>,
+}
+
+impl AvoidList {
+    pub fn new<S: AsRef<str>>(terms: impl IntoIterator<Item = S>) -> Result<Self, AvoidError> {
+        let terms = terms
+            .into_iter()
+            .map(|term| {
+                let term = term.as_ref().trim();
+                let folded = fold(term);
+                if folded.chars().count() < MIN_TERM_LENGTH {
+                    return Err(AvoidError::TooShort(term.to_string()));
+                }
+                Ok(folded.to_string())
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { terms })
+    }
+
+    /// Terms from the command line plus those in `file`, one per line (`#` starts a comment)
+    pub fn load(terms: &[String], file: Option<&Path>) -> Result<Self, AvoidError> {
+        let mut all: Vec<String> = terms.to_vec();
+        if let Some(path) = file {
+            let text = fs::read_to_string(path).map_err(|source| AvoidError::Io {
+                path: path.to_path_buf(),
+                source,
+            })?;
+            all.extend(
+                text.lines()
+                    .map(|line| line.split('#').next().unwrap_or_default().trim())
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string),
+            );
+        }
+        Self::new(all)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    /// Whether `value` contains none of the terms
+    pub fn accepts(&self, value: &str) -> bool {
+        if self.terms.is_empty() {
+            return true;
+        }
+        let folded = fold(value);
+        !self.terms.iter().any(|term| folded.contains(term.as_str()))
+    }
+}
+
+/// Lowercase, undo leet substitutions and drop everything but letters and digits
+fn fold(text: &str) -> Zeroizing<String> {
+    Zeroizing::new(
+        text.chars()
+            .map(|c| match c.to_ascii_lowercase() {
+                '4' | '@' => 'a',
+                '8' => 'b',
+                '3' => 'e',
+                '9' => 'g',
+                '1' | '!' | '|' | 'l' => 'i',
+                '0' => 'o',
+                '5' | '$' => 's',
+                '7' | '+' => 't',
+                c => c,
+            })
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_avoid_matches_leet_and_separators() {
+        let avoid = AvoidList::new(["Alice", "1987", "ACME corp"]).unwrap();
+        assert!(!avoid.accepts("xxA1ic3yy"));
+        assert!(!avoid.accepts("al-ice-runs"));
+        assert!(!avoid.accepts("born!9B7"));
+        assert!(!avoid.accepts("4cme_C0rp"));
+        assert!(avoid.accepts("Gedulde0rhug"));
+        assert!(AvoidList::default().accepts("alice"));
+        assert!(matches!(
+            AvoidList::new(["a-b"]),
+            Err(AvoidError::TooShort(_))
+        ));
+    }
+
+    #[test]
+    fn test_avoid_file() {
+        let path = std::env::temp_dir().join(format!("pwgen-avoid-{}.txt", std::process::id()));
+        fs::write(&path, "# family\nbob\n\n  carol  # wife\n").unwrap();
+        let avoid = AvoidList::load(&["dave".to_string()], Some(&path)).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(avoid.len(), 3);
+        assert!(!avoid.accepts("xCAROLx"));
+        assert!(!avoid.accepts("b0b!"));
+    }
+}
@@ -0,0 +1,82 @@
+//! Known-breach lookup against Have I Been Pwned (`--check-breach`, `breach`
+//! feature)
+//!
+//! The Pwned Passwords range API is queried by k-anonymity: only the first
+//! five hex digits of the password's SHA-1 leave the machine, and the
+//! response lists every breached hash sharing them, so the match is made
+//! locally. Neither the password nor its full hash is ever sent. Ranges are
+//! never cached: the cached URLs would be an on-disk log of 20 bits of every
+//! password checked, so each lookup asks the API again and `--offline` makes
+//! it fail.
+
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use crate::digest::{hex, sha1};
+use crate::http::{self, HttpError};
+
+/// Range endpoint; the hash prefix is appended
+pub const RANGE_URL: &str = "https://api.pwnedpasswords.com/range/";
+
+/// Hex digits of the hash sent to the API
+pub const PREFIX_LEN: usize = 5;
+
+#[derive(Debug, Error)]
+pub enum BreachError {
+    #[error(transparent)]
+    Http(#[from] HttpError),
+    #[error("unexpected response from the Pwned Passwords API")]
+    Malformed,
+}
+
+/// Times `value` appears in known breaches, 0 if it doesn't
+pub fn breach_count(value: &str) -> Result<u64, BreachError> {
+    let hash = Zeroizing::new(hex(&sha1(value.as_bytes())).to_uppercase());
+    let (prefix, suffix) = hash.split_at(PREFIX_LEN);
+    let body = http::get(&range_url(prefix))?;
+    find_suffix(&body, suffix)
+}
+
+fn range_url(prefix: &str) -> String {
+    format!("{}{}", RANGE_URL, prefix)
+}
+
+/// The count on the `SUFFIX:COUNT` line for `suffix` in a range response
+fn find_suffix(body: &str, suffix: &str) -> Result<u64, BreachError> {
+    for line in body.lines().filter(|line| !line.trim().is_empty()) {
+        let (candidate, count) = line.trim().split_once(':').ok_or(BreachError::Malformed)?;
+        if candidate.eq_ignore_ascii_case(suffix) {
+            return count.parse().map_err(|_| BreachError::Malformed);
+        }
+    }
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_lookup() {
+        // SHA-1 of "password": 5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8
+        let hash = hex(&sha1(b"password")).to_uppercase();
+        let (prefix, suffix) = hash.split_at(PREFIX_LEN);
+        assert_eq!(
+            range_url(prefix),
+            "https://api.pwnedpasswords.com/range/5BAA6"
+        );
+
+        let body = "003D68EB55068C33ACE09247EE4C639306B:3\r\n\
+                    1E4C9B93F3F0682250B6CF8331B7EE68FD8:52256179\r\n\
+                    0A1B2C3D4E5F60718293A4B5C6D7E8F9012:0\r\n";
+        assert_eq!(find_suffix(body, suffix).unwrap(), 52256179);
+        assert_eq!(
+            find_suffix(body, "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF").unwrap(),
+            0
+        );
+        assert!(matches!(
+            find_suffix("<html>busy</html>", suffix),
+            Err(BreachError::Malformed)
+        ));
+    }
+}
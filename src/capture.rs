@@ -0,0 +1,120 @@
+//! Detection of terminal sessions that are likely being recorded
+
+use std::fs;
+
+/// A recording context that would keep a copy of anything printed to the terminal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureContext {
+    /// Running inside an asciinema recording
+    Asciinema,
+    /// Running under script(1)
+    Script,
+    /// Teleport SSH session, which records sessions by default
+    Teleport,
+    /// SSH login whose shell runs under a forced tty logger such as tlog or sudosh
+    SshLogging,
+}
+
+impl CaptureContext {
+    pub fn description(&self) -> &'static str {
+        match self {
+            CaptureContext::Asciinema => "an asciinema recording",
+            CaptureContext::Script => "script(1) session logging",
+            CaptureContext::Teleport => "a recorded Teleport SSH session",
+            CaptureContext::SshLogging => "SSH session logging",
+        }
+    }
+}
+
+/// Session recorders that sshd is commonly configured to force as the login
+/// shell, by their `comm` names (truncated to 15 bytes by the kernel)
+const TTY_LOGGERS: [&str; 5] = ["tlog-rec-sessio", "tlog-rec", "sudosh", "rootsh", "ttyrec"];
+
+/// Inspect the environment and parent process for a recording context
+pub fn detect() -> Option<CaptureContext> {
+    detect_with(|name| std::env::var(name).ok(), &ancestor_names())
+}
+
+/// Detection logic, separated from the process environment for testing
+fn detect_with(
+    env: impl Fn(&str) -> Option<String>,
+    ancestors: &[String],
+) -> Option<CaptureContext> {
+    if env("ASCIINEMA_REC").is_some() {
+        return Some(CaptureContext::Asciinema);
+    }
+    if env("TELEPORT_SESSION").is_some() {
+        return Some(CaptureContext::Teleport);
+    }
+    if ancestors.iter().any(|name| name.trim() == "script") {
+        return Some(CaptureContext::Script);
+    }
+    let over_ssh = env("SSH_CONNECTION").is_some() || env("SSH_TTY").is_some();
+    if over_ssh
+        && ancestors
+            .iter()
+            .any(|name| TTY_LOGGERS.contains(&name.trim()))
+    {
+        return Some(CaptureContext::SshLogging);
+    }
+    None
+}
+
+/// Names of the parent and grandparent processes, where procfs is available
+///
+/// script(1) is usually the shell's parent, but can also run us directly via `script -c`.
+fn ancestor_names() -> Vec<String> {
+    let mut names = Vec::new();
+    let mut pid = "self".to_string();
+    for _ in 0..2 {
+        let Some(ppid) = parent_pid(&pid) else { break };
+        if let Ok(name) = fs::read_to_string(format!("/proc/{}/comm", ppid)) {
+            names.push(name);
+        }
+        pid = ppid;
+    }
+    names
+}
+
+fn parent_pid(pid: &str) -> Option<String> {
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("PPid:"))
+        .map(|ppid| ppid.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_asciinema() {
+        let env = |name: &str| (name == "ASCIINEMA_REC").then(|| "1".to_string());
+        assert_eq!(detect_with(env, &[]), Some(CaptureContext::Asciinema));
+    }
+
+    #[test]
+    fn test_detect_script_parent() {
+        let ancestors = ["bash\n".to_string(), "script\n".to_string()];
+        let result = detect_with(|_| None, &ancestors);
+        assert_eq!(result, Some(CaptureContext::Script));
+    }
+
+    #[test]
+    fn test_detect_ssh_logging() {
+        let ancestors = ["bash\n".to_string(), "tlog-rec-sessio\n".to_string()];
+        let ssh = |name: &str| (name == "SSH_CONNECTION").then(|| "10.0.0.1 2 10.0.0.2 22".into());
+        assert_eq!(
+            detect_with(ssh, &ancestors),
+            Some(CaptureContext::SshLogging)
+        );
+        // The same process tree outside SSH is not a forced login shell
+        assert_eq!(detect_with(|_| None, &ancestors), None);
+    }
+
+    #[test]
+    fn test_detect_nothing() {
+        assert_eq!(detect_with(|_| None, &["bash\n".to_string()]), None);
+    }
+}
@@ -0,0 +1,204 @@
+//! Strength report for an existing password (`check`)
+//!
+//! The value is judged the way a cracker would see it, without knowing how it
+//! was made: by the pattern search and by the letter model, taking whichever
+//! finds it cheaper. Patterns are reported by position so the report never
+//! repeats the password.
+
+use std::fmt;
+
+use clap::ValueEnum;
+use serde_json::{json, Value};
+
+use crate::crack_time::CrackTime;
+use crate::entropy::{AttackerModel, EntropyInfo};
+use crate::estimators::pattern::{Analysis, PatternEstimator};
+use crate::estimators::MarkovEstimator;
+
+/// What the estimators make of a password
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckReport {
+    /// Length in characters
+    pub length: usize,
+    pub pattern: Analysis,
+    pub markov_bits: f64,
+    pub attacker: AttackerModel,
+    /// Times seen in known breaches, if looked up
+    pub breaches: Option<u64>,
+}
+
+impl CheckReport {
+    pub fn new(value: &str, attacker: AttackerModel) -> Self {
+        Self {
+            length: value.chars().count(),
+            pattern: PatternEstimator::new().analyze(value),
+            markov_bits: MarkovEstimator::new().bits(value),
+            attacker,
+            breaches: None,
+        }
+    }
+
+    /// The lower of the two estimates
+    pub fn entropy(&self) -> EntropyInfo {
+        if self.markov_bits < self.pattern.bits {
+            EntropyInfo::new(self.markov_bits, "markov")
+        } else {
+            EntropyInfo::new(self.pattern.bits, "pattern")
+        }
+    }
+
+    /// Average crack time against every attacker model
+    pub fn crack_times(&self) -> Vec<(AttackerModel, CrackTime)> {
+        let bits = self.entropy().bits;
+        AttackerModel::value_variants()
+            .iter()
+            .map(|&attacker| (attacker, CrackTime::new(bits, attacker)))
+            .collect()
+    }
+
+    pub fn to_json(&self) -> Value {
+        let entropy = self.entropy();
+        let patterns: Vec<Value> = self
+            .pattern
+            .matches
+            .iter()
+            .map(|m| {
+                json!({
+                    "kind": m.kind.label(),
+                    "start": m.start,
+                    "end": m.end,
+                    "bits": m.bits,
+                })
+            })
+            .collect();
+        let crack_times: Vec<Value> = self
+            .crack_times()
+            .into_iter()
+            .map(|(attacker, time)| {
+                json!({
+                    "attacker": attacker_name(attacker),
+                    "seconds": time.seconds,
+                    "guesses_per_second": time.guesses_per_second,
+                })
+            })
+            .collect();
+        let mut report = json!({
+            "length": self.length,
+            "entropy_bits": entropy.bits,
+            "strength": entropy.strength_against(self.attacker).label(),
+            "attacker": attacker_name(self.attacker),
+            "estimates": {
+                "pattern": self.pattern.bits,
+                "markov": self.markov_bits,
+            },
+            "patterns": patterns,
+            "crack_times": crack_times,
+        });
+        if let Some(breaches) = self.breaches {
+            report["breaches"] = json!(breaches);
+        }
+        report
+    }
+}
+
+fn attacker_name(attacker: AttackerModel) -> String {
+    attacker
+        .to_possible_value()
+        .map_or_else(String::new, |v| v.get_name().to_string())
+}
+
+impl fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entropy = self.entropy();
+        writeln!(f, "Length:     {} characters", self.length)?;
+        writeln!(
+            f,
+            "Entropy:    {:.1} bits ({} against {})",
+            entropy.bits,
+            entropy.strength_against(self.attacker).label(),
+            attacker_name(self.attacker)
+        )?;
+        writeln!(
+            f,
+            "Estimates:  pattern {:.1} · markov {:.1} bits (worst: {})",
+            self.pattern.bits, self.markov_bits, entropy.source
+        )?;
+        if self.pattern.matches.is_empty() {
+            writeln!(f, "Patterns:   none found")?;
+        }
+        for (i, m) in self.pattern.matches.iter().enumerate() {
+            let label = if i == 0 { "Patterns:" } else { "" };
+            writeln!(
+                f,
+                "{:<11} {} at characters {}–{} ({:.1} bits)",
+                label,
+                m.kind.label(),
+                m.start + 1,
+                m.end,
+                m.bits
+            )?;
+        }
+        match self.breaches {
+            Some(0) => writeln!(f, "Breaches:   not found in known breaches")?,
+            Some(n) => writeln!(f, "Breaches:   seen {} times in known breaches", n)?,
+            None => {}
+        }
+        let times = self.crack_times();
+        let width = times
+            .iter()
+            .map(|(attacker, _)| attacker_name(*attacker).len())
+            .max()
+            .unwrap_or(0);
+        for (i, (attacker, time)) in times.iter().enumerate() {
+            let label = if i == 0 { "Crack time:" } else { "" };
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "{:<11} {:<width$}  {}",
+                label,
+                attacker_name(*attacker),
+                time,
+                width = width
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::estimators::pattern::PatternKind;
+
+    #[test]
+    fn test_check_report() {
+        let report = CheckReport::new("Unlocked2019", AttackerModel::Online);
+        assert_eq!(report.length, 12);
+        let kinds: Vec<_> = report.pattern.matches.iter().map(|m| m.kind).collect();
+        assert_eq!(kinds, [PatternKind::Word, PatternKind::Year]);
+        assert!(report.entropy().bits <= report.pattern.bits);
+        assert_eq!(report.crack_times().len(), 4);
+
+        let text = report.to_string();
+        assert!(!text.contains("nlocked"));
+        assert!(text.contains("dictionary word at characters 1–8"));
+        assert!(text.contains("year at characters 9–12"));
+
+        let json = report.to_json();
+        assert!(json.get("breaches").is_none());
+        assert_eq!(json["attacker"], "online");
+        assert_eq!(json["patterns"][1]["kind"], "year");
+        assert_eq!(json["crack_times"][3]["attacker"], "offline-fast-hash");
+
+        let breached = CheckReport {
+            breaches: Some(3),
+            ..report
+        };
+        assert!(breached
+            .to_string()
+            .contains("seen 3 times in known breaches"));
+        assert_eq!(breached.to_json()["breaches"], 3);
+    }
+}
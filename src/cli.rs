@@ -1,4 +1,23 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::builder::BoolishValueParser;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+
+use crate::config::{Config, Value};
+use crate::data::DataSet;
+use crate::entropy::{AttackerModel, StrengthLevel};
+use crate::estimators::EstimatorKind;
+use crate::exit::ErrorFormat;
+use crate::generators::{CharClass, Composition, GroupedGenerator, SymbolSet};
+use crate::output::files;
+use crate::remind::Interval;
+
+/// Kept here for code written against the CLI types; defined with the secure generator
+pub use crate::generators::CharSet;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -6,27 +25,991 @@ use clap::{Parser, Subcommand, ValueEnum};
     author,
     version,
     about = "Generate secure, memorable passwords",
-    long_about = None
+    long_about = None,
+    disable_version_flag = true
 )]
 pub struct Cli {
     #[command(subcommand)]
-    pub command: Command,
+    pub command: Option<Command>,
+
+    /// Print version (with --format json: also embedded data and compiled features)
+    #[arg(short = 'V', long)]
+    pub version: bool,
+
+    /// Print passwords (and the version report) as text, a JSON array or JSON lines
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value = "text",
+        env = "PWGEN_RS_FORMAT"
+    )]
+    pub format: OutputFormat,
+
+    /// How to report a failure on stderr: text, or one JSON object with its exit code
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value = "text",
+        env = "PWGEN_RS_ERROR_FORMAT"
+    )]
+    pub error_format: ErrorFormat,
+
+    /// Command line to run when no subcommand is given (overrides `default_command` in config)
+    #[arg(
+        long,
+        global = true,
+        value_name = "CMD",
+        env = "PWGEN_RS_DEFAULT_COMMAND"
+    )]
+    pub default_command: Option<String>,
+
+    /// Apply the `[profile.NAME]` settings from the config file (see `profile list`)
+    #[arg(long, global = true, value_name = "NAME", env = "PWGEN_RS_PROFILE")]
+    pub profile: Option<String>,
 
     /// Number of passwords to generate
-    #[arg(short = 'n', long, default_value = "1", global = true)]
+    #[arg(
+        short = 'n',
+        long,
+        default_value = "1",
+        global = true,
+        env = "PWGEN_RS_COUNT"
+    )]
     pub count: usize,
 
     /// Suppress decorative output (auto-enabled when piped)
-    #[arg(short, long, global = true)]
+    #[arg(
+        short,
+        long,
+        global = true,
+        env = "PWGEN_RS_QUIET",
+        value_parser = BoolishValueParser::new()
+    )]
     pub quiet: bool,
 
-    /// Disable colored output
-    #[arg(long, global = true)]
+    /// How bare passwords are framed in quiet mode and on stdout with --split-streams
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "DELIM",
+        default_value = "newline",
+        env = "PWGEN_RS_RECORD_SEP"
+    )]
+    pub record_sep: RecordSeparator,
+
+    /// Log generation decisions to stderr (-v, -vv, -vvv); passwords are never logged
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Disable colored output (same as --color never)
+    #[arg(long, global = true, env = "PWGEN_RS_NO_COLOR", value_parser = BoolishValueParser::new())]
     pub no_color: bool,
+
+    /// How to judge strength; several (comma-separated) are shown side by side
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_delimiter = ',',
+        default_value = "keyspace",
+        env = "PWGEN_RS_ESTIMATOR"
+    )]
+    pub estimator: Vec<EstimatorKind>,
+
+    /// When to color the output; `auto` follows NO_COLOR, CLICOLOR and CLICOLOR_FORCE
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "WHEN",
+        default_value = "auto",
+        env = "PWGEN_RS_COLOR"
+    )]
+    pub color: ColorChoice,
+
+    /// Language for display text and, when one is installed, the wordlist (en, de, fr, es, it;
+    /// defaults to the locale)
+    #[arg(long, global = true, value_name = "CODE", env = "PWGEN_RS_LANG")]
+    pub lang: Option<String>,
+
+    /// Compact `password  [bits, strength]` line per password
+    #[arg(long, global = true, env = "PWGEN_RS_ONELINE", value_parser = BoolishValueParser::new())]
+    pub oneline: bool,
+
+    /// Screen-reader friendly output: no bars or emoji, symbols spelled by name
+    #[arg(
+        long,
+        global = true,
+        env = "PWGEN_RS_ACCESSIBLE",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub accessible: bool,
+
+    /// Don't warn when the terminal session appears to be recorded
+    #[arg(
+        long,
+        global = true,
+        env = "PWGEN_RS_NO_WARN_CAPTURE",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub no_warn_capture: bool,
+
+    /// Print one password per block even for large batches (no column grid)
+    #[arg(long, global = true, env = "PWGEN_RS_NO_GRID", value_parser = BoolishValueParser::new())]
+    pub no_grid: bool,
+
+    /// Order the batch from strongest to weakest
+    #[arg(
+        long,
+        global = true,
+        env = "PWGEN_RS_SORT_ENTROPY",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub sort_entropy: bool,
+
+    /// Stop drawing candidates after this long (e.g. 2s, 500ms), then relax --diverse or fail
+    #[arg(
+        long,
+        global = true,
+        value_name = "DURATION",
+        value_parser = parse_timeout,
+        env = "PWGEN_RS_TIMEOUT"
+    )]
+    pub timeout: Option<Duration>,
+
+    /// Never use the network: online features fall back to cached or built-in data
+    #[arg(
+        long,
+        global = true,
+        env = "PWGEN_RS_OFFLINE",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub offline: bool,
+
+    /// Proxy for online features (otherwise curl's `https_proxy` variables apply)
+    #[arg(long, global = true, value_name = "URL", env = "PWGEN_RS_PROXY")]
+    pub proxy: Option<String>,
+
+    /// Make every password in the batch look different (distinct openings, few shared characters)
+    #[arg(
+        long,
+        global = true,
+        env = "PWGEN_RS_DIVERSE",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub diverse: bool,
+
+    /// Only output passwords at or above this strength level
+    #[arg(long, value_enum, global = true, env = "PWGEN_RS_MIN_STRENGTH")]
+    pub min_strength: Option<StrengthLevel>,
+
+    /// Threat model for strength labels and --min-strength
+    #[arg(
+        long,
+        value_enum,
+        default_value = "generic",
+        global = true,
+        env = "PWGEN_RS_ATTACKER"
+    )]
+    pub attacker: AttackerModel,
+
+    /// Reject passwords containing this term, ignoring case and leet (repeat or comma-separate)
+    #[arg(
+        long,
+        value_name = "WORD",
+        global = true,
+        value_delimiter = ',',
+        env = "PWGEN_RS_AVOID"
+    )]
+    pub avoid: Vec<String>,
+
+    /// Reject passwords containing any term listed in this file, one per line
+    #[arg(long, value_name = "PATH", global = true, env = "PWGEN_RS_AVOID_FILE")]
+    pub avoid_file: Option<PathBuf>,
+
+    /// Allow configurations under 28 bits (or PINs under 6 digits) on an interactive terminal
+    #[arg(
+        long = "i-know-this-is-weak",
+        global = true,
+        env = "PWGEN_RS_I_KNOW_THIS_IS_WEAK",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub allow_weak: bool,
+
+    /// Warn when a password has fewer bits than this; 0 disables [default: 50, or `warnings.entropy_floor`]
+    #[arg(
+        long,
+        value_name = "BITS",
+        global = true,
+        env = "PWGEN_RS_ENTROPY_FLOOR"
+    )]
+    pub entropy_floor: Option<f64>,
+
+    /// Only output passwords with at least this many bits of entropy
+    #[arg(long, value_name = "BITS", global = true, env = "PWGEN_RS_MIN_BITS")]
+    pub min_bits: Option<f64>,
+
+    /// Use the shortest length, or fewest words, that reaches this entropy, e.g. 128bits
+    #[arg(
+        long,
+        value_name = "BITS",
+        global = true,
+        value_parser = parse_bits,
+        env = "PWGEN_RS_BITS"
+    )]
+    pub bits: Option<f64>,
+
+    /// Only output passwords of at least N characters, drawing again as needed
+    #[arg(long, value_name = "N", global = true, env = "PWGEN_RS_MIN_LENGTH")]
+    pub min_length: Option<usize>,
+
+    /// Only output passwords of at most N characters; passphrases favor shorter words instead
+    #[arg(long, value_name = "N", global = true, env = "PWGEN_RS_MAX_LENGTH")]
+    pub max_length: Option<usize>,
+
+    /// Entropy in bits that fills the strength bar [default: 128, or `display.scale_max`]
+    #[arg(long, value_name = "BITS", global = true, env = "PWGEN_RS_SCALE_MAX")]
+    pub scale_max: Option<f64>,
+
+    /// Logarithmic strength bar, for comparing passwords of very different strength
+    #[arg(
+        long,
+        global = true,
+        env = "PWGEN_RS_LOG_SCALE",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub log_scale: bool,
+
+    /// Show the average time the --attacker needs to guess each password
+    #[arg(
+        long,
+        global = true,
+        env = "PWGEN_RS_CRACK_TIME",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub crack_time: bool,
+
+    /// Show the display on stderr and print only the bare passwords to stdout
+    #[arg(
+        long,
+        global = true,
+        env = "PWGEN_RS_SPLIT_STREAMS",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub split_streams: bool,
+
+    /// After a key press, erase the passwords, the scrollback and the window title
+    #[arg(
+        long,
+        global = true,
+        env = "PWGEN_RS_SCRUB",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub scrub: bool,
+
+    /// Print each password as a quoted literal for pasting into this shell
+    #[arg(long, value_name = "SHELL", global = true, env = "PWGEN_RS_ESCAPE")]
+    pub escape: Option<Shell>,
+
+    /// Insert --group-char into the password every N characters (part of the value, not entropy)
+    #[arg(long, value_name = "N", global = true, env = "PWGEN_RS_GROUP_SIZE")]
+    pub group_size: Option<usize>,
+
+    /// Separator inserted by --group-size
+    #[arg(
+        long,
+        value_name = "SEP",
+        global = true,
+        requires = "group_size",
+        default_value = GroupedGenerator::DEFAULT_SEPARATOR,
+        env = "PWGEN_RS_GROUP_CHAR"
+    )]
+    pub group_char: String,
+
+    /// Show a two-word fingerprint of each password, to check a transcription
+    #[arg(
+        long,
+        global = true,
+        env = "PWGEN_RS_FINGERPRINT",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub fingerprint: bool,
+
+    /// Put the first password on the clipboard instead of printing it
+    #[arg(
+        long,
+        global = true,
+        conflicts_with = "output_dir",
+        env = "PWGEN_RS_COPY",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub copy: bool,
+
+    /// Also draw each password as a QR code, for scanning with a phone
+    #[arg(
+        long,
+        global = true,
+        conflicts_with_all = ["copy", "output_dir"],
+        env = "PWGEN_RS_QR",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub qr: bool,
+
+    /// Draw a QR code that joins the Wi-Fi network SSID with the password (implies --qr)
+    #[arg(
+        long,
+        value_name = "SSID",
+        global = true,
+        conflicts_with_all = ["copy", "output_dir"],
+        env = "PWGEN_RS_QR_WIFI"
+    )]
+    pub qr_wifi: Option<String>,
+
+    /// With --copy, clear the clipboard after this many seconds unless it changed (0: never)
+    #[arg(
+        long,
+        global = true,
+        value_name = "SECONDS",
+        default_value_t = 45,
+        env = "PWGEN_RS_CLEAR_AFTER"
+    )]
+    pub clear_after: u64,
+
+    /// Write each password to its own 0600 file in this directory instead of printing it
+    #[arg(long, value_name = "DIR", global = true, env = "PWGEN_RS_OUTPUT_DIR")]
+    pub output_dir: Option<PathBuf>,
+
+    /// File name for each password in --output-dir; {index} is its 1-based position
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        global = true,
+        requires = "output_dir",
+        default_value = files::DEFAULT_TEMPLATE,
+        env = "PWGEN_RS_FILENAME_TEMPLATE"
+    )]
+    pub filename_template: String,
+
+    /// Encrypt the passwords with `age` to each age or SSH public key (inline or a key file),
+    /// writing one NAME.age file per recipient instead of printing
+    #[arg(
+        long,
+        value_name = "KEYS",
+        global = true,
+        value_delimiter = ',',
+        conflicts_with_all = ["copy", "output_dir", "qr", "qr_wifi"],
+        env = "PWGEN_RS_SHARE_WITH"
+    )]
+    pub share_with: Vec<String>,
+
+    /// Directory for the --share-with files
+    #[arg(
+        long,
+        value_name = "DIR",
+        global = true,
+        requires = "share_with",
+        default_value = ".",
+        env = "PWGEN_RS_SHARE_DIR"
+    )]
+    pub share_dir: PathBuf,
+
+    /// Append a record of each run (never the passwords) to this file
+    #[arg(long, value_name = "PATH", global = true, env = "PWGEN_RS_AUDIT_LOG")]
+    pub audit_log: Option<PathBuf>,
+
+    /// Warn when a password appears in Have I Been Pwned (sends 5 hex digits of its SHA-1)
+    #[arg(
+        long,
+        global = true,
+        env = "PWGEN_RS_CHECK_BREACH",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub check_breach: bool,
+
+    /// Record salted hashes of generated passwords here and warn when one was generated before
+    #[arg(long, value_name = "PATH", global = true, env = "PWGEN_RS_HISTORY")]
+    pub history: Option<PathBuf>,
+
+    /// Note when the generated credentials are due for rotation, e.g. 90d, 12w, 6m, 1y
+    ///
+    /// Written to --remind-file, or to rotation.ics in --output-dir.
+    #[arg(long, value_name = "AGE", global = true, env = "PWGEN_RS_REMIND")]
+    pub remind: Option<Interval>,
+
+    /// Reminders file: an event is added to a .ics calendar, otherwise a line is appended
+    #[arg(
+        long,
+        value_name = "PATH",
+        global = true,
+        requires = "remind",
+        env = "PWGEN_RS_REMIND_FILE"
+    )]
+    pub remind_file: Option<PathBuf>,
+
+    /// Check whether the generator can meet --min-bits/--min-strength, without generating
+    ///
+    /// Exits 0 if it can and 4 (too weak) if it cannot, with a JSON reason on stderr.
+    #[arg(
+        long,
+        global = true,
+        env = "PWGEN_RS_CHECK_ONLY",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub check_only: bool,
+
+    /// Print the keyspace, entropy and expected rejection rate instead of passwords
+    #[arg(
+        long,
+        global = true,
+        conflicts_with = "check_only",
+        env = "PWGEN_RS_DRY_RUN",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub dry_run: bool,
+}
+
+impl Cli {
+    /// Maximum alias-to-alias expansions before assuming a cycle
+    const MAX_ALIAS_DEPTH: usize = 8;
+
+    /// Parse the process arguments, expanding aliases and falling back to the default command
+    ///
+    /// Options set in the config file's `[defaults]` section, and in the
+    /// `[profile.NAME]` section chosen with `--profile`, become the defaults
+    /// of those options; see [`settings`](Self::settings).
+    pub fn parse_with_config(config: &Config) -> Self {
+        Self::parse_args_with_config(std::env::args_os().collect(), config)
+    }
+
+    fn parse_args_with_config(args: Vec<OsString>, config: &Config) -> Self {
+        let aliases = Self::aliases(config);
+        let args = Self::expand_aliases(args, &aliases);
+        let cli = Self::parse_with_aliases(&args, &aliases, config);
+        if cli.command.is_some() || cli.version {
+            return cli;
+        }
+
+        // A profile can also choose the command, ahead of the global default
+        let default = cli
+            .default_command
+            .as_deref()
+            .or_else(|| {
+                let name = cli.profile.as_deref()?;
+                config.get_str(&format!("profile.{}.command", name))
+            })
+            .or_else(|| config.get_str("default_command"));
+        let Some(default) = default else {
+            Self::command()
+                .error(ErrorKind::MissingSubcommand, "a subcommand is required")
+                .exit();
+        };
+
+        // Splice the default command in front of the user's (global) options
+        let expanded = Self::split_or_exit(default, "default command");
+        let mut argv = Vec::with_capacity(args.len() + expanded.len());
+        argv.extend(args.first().cloned());
+        argv.extend(expanded.into_iter().map(OsString::from));
+        argv.extend(args.into_iter().skip(1));
+
+        let argv = Self::expand_aliases(argv, &aliases);
+        Self::parse_with_aliases(&argv, &aliases, config)
+    }
+
+    /// Option defaults from `[defaults]`, overridden by the selected `[profile.NAME]`
+    ///
+    /// Keys are option names as in `length`, `charset` or `color`, which apply
+    /// to every command with that option, or `<command>.<option>` such as
+    /// `pin.length` for one command. A profile's `command` key is its default
+    /// command rather than an option.
+    fn settings(config: &Config, profile: Option<&str>) -> BTreeMap<String, String> {
+        let mut settings: BTreeMap<String, String> = config
+            .entries("defaults")
+            .map(|(key, value)| (key.to_string(), value.to_arg()))
+            .collect();
+        if let Some(name) = profile {
+            if !config.profiles().contains(&name) {
+                Self::command()
+                    .error(
+                        ErrorKind::InvalidValue,
+                        format!(
+                            "unknown profile '{}'; `pwgen-x profile list` shows the defined ones",
+                            name
+                        ),
+                    )
+                    .exit();
+            }
+            settings.extend(
+                config
+                    .entries(&format!("profile.{}", name))
+                    .filter(|(key, _)| *key != "command")
+                    .map(|(key, value)| (key.to_string(), value.to_arg())),
+            );
+        }
+        settings
+    }
+
+    /// The `--profile` given in `args`, or in the environment
+    fn profile_arg(args: &[OsString]) -> Option<String> {
+        let mut args = args.iter().skip(1).map_while(|arg| arg.to_str());
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                break;
+            }
+            if arg == "--profile" {
+                return args.next().map(str::to_string);
+            }
+            if let Some(name) = arg.strip_prefix("--profile=") {
+                return Some(name.to_string());
+            }
+        }
+        std::env::var("PWGEN_RS_PROFILE").ok()
+    }
+
+    /// Make each setting the default value of its option
+    fn with_settings(
+        mut command: clap::Command,
+        settings: &BTreeMap<String, String>,
+    ) -> clap::Command {
+        for (key, value) in settings {
+            let (scope, name) = match key.split_once('.') {
+                Some((scope, name)) => (Some(scope), name),
+                None => (None, key.as_str()),
+            };
+            let id = name.replace('-', "_");
+            let mut found = false;
+            command = match scope {
+                None => set_default(command, &id, value, &mut found),
+                Some(scope) if command.find_subcommand(scope).is_some() => {
+                    command.mut_subcommand(scope, |sub| set_default(sub, &id, value, &mut found))
+                }
+                Some(_) => command,
+            };
+            if !found {
+                Self::command()
+                    .error(
+                        ErrorKind::UnknownArgument,
+                        format!("config file sets '{}', which is not an option", key),
+                    )
+                    .exit();
+            }
+        }
+        command
+    }
+
+    /// User aliases from the `[alias]` config section, excluding names of built-in commands
+    fn aliases(config: &Config) -> BTreeMap<String, String> {
+        let builtin = Self::command();
+        config
+            .entries("alias")
+            .filter_map(|(name, value)| match value {
+                Value::String(expansion) => Some((name.to_string(), expansion.clone())),
+                _ => None,
+            })
+            .filter(|(name, _)| builtin.find_subcommand(name).is_none())
+            .collect()
+    }
+
+    /// Parse with aliases listed as subcommands in `--help` and config settings as defaults
+    fn parse_with_aliases(
+        args: &[OsString],
+        aliases: &BTreeMap<String, String>,
+        config: &Config,
+    ) -> Self {
+        let settings = Self::settings(config, Self::profile_arg(args).as_deref());
+        let mut command = Self::with_settings(Self::command(), &settings);
+        for (name, expansion) in aliases {
+            command = command.subcommand(
+                clap::Command::new(name.clone()).about(format!("Alias for `{}`", expansion)),
+            );
+        }
+        let matches = command.get_matches_from(args);
+        Self::from_arg_matches(&matches).unwrap_or_else(|e| e.exit())
+    }
+
+    /// Replace an alias in subcommand position with its expansion
+    fn expand_aliases(
+        mut args: Vec<OsString>,
+        aliases: &BTreeMap<String, String>,
+    ) -> Vec<OsString> {
+        for _ in 0..Self::MAX_ALIAS_DEPTH {
+            let Some(pos) = Self::subcommand_position(&args) else {
+                return args;
+            };
+            let Some(expansion) = args[pos].to_str().and_then(|name| aliases.get(name)) else {
+                return args;
+            };
+            let words = Self::split_or_exit(expansion, "alias");
+            args.splice(pos..=pos, words.into_iter().map(OsString::from));
+        }
+
+        Self::command()
+            .error(
+                ErrorKind::InvalidValue,
+                "alias expansion is too deep (recursive alias?)",
+            )
+            .exit();
+    }
+
+    /// Index of the first positional argument, skipping global options and their values
+    fn subcommand_position(args: &[OsString]) -> Option<usize> {
+        let command = Self::command();
+        let takes_value = |matches: &dyn Fn(&clap::Arg) -> bool| {
+            command
+                .get_arguments()
+                .any(|arg| matches(arg) && arg.get_action().takes_values())
+        };
+
+        let mut skip_next = false;
+        for (i, arg) in args.iter().enumerate().skip(1) {
+            let arg = arg.to_str()?;
+            if std::mem::take(&mut skip_next) {
+                continue;
+            }
+            if arg == "--" {
+                return None;
+            }
+            if let Some(long) = arg.strip_prefix("--") {
+                skip_next = !long.contains('=') && takes_value(&|a| a.get_long() == Some(long));
+            } else if let Some(short) = arg.strip_prefix('-').filter(|s| !s.is_empty()) {
+                let mut chars = short.chars();
+                let first = chars.next();
+                skip_next = chars.next().is_none() && takes_value(&|a| a.get_short() == first);
+            } else {
+                return Some(i);
+            }
+        }
+        None
+    }
+
+    fn split_or_exit(line: &str, what: &str) -> Vec<String> {
+        match split_command_line(line) {
+            Some(words) if !words.is_empty() => words,
+            _ => Self::command()
+                .error(
+                    ErrorKind::InvalidValue,
+                    format!("invalid {}: {:?}", what, line),
+                )
+                .exit(),
+        }
+    }
+}
+
+/// Set `value` as the default of option `id` in `command` and all its subcommands
+fn set_default(
+    mut command: clap::Command,
+    id: &str,
+    value: &str,
+    found: &mut bool,
+) -> clap::Command {
+    if command
+        .get_arguments()
+        .any(|arg| arg.get_id() == id && !arg.is_positional())
+    {
+        *found = true;
+        command = command.mut_arg(id, |arg| arg.default_value(value.to_string()));
+    }
+    let names: Vec<String> = command
+        .get_subcommands()
+        .map(|sub| sub.get_name().to_string())
+        .collect();
+    for name in names {
+        command = command.mut_subcommand(name, |sub| set_default(sub, id, value, found));
+    }
+    command
+}
+
+/// Resolve a `--flag`/`--no-flag` pair
+///
+/// The two override each other, so only the last one given is set; the flag
+/// itself carries the default (or environment) value when neither is.
+pub fn negatable(flag: bool, negated: bool) -> bool {
+    flag && !negated
+}
+
+/// Split a command line into words, honoring single/double quotes and backslash escapes
+///
+/// Returns None on unterminated quotes or a trailing backslash.
+pub fn split_command_line(line: &str) -> Option<Vec<String>> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next()? {
+                        '\'' => break,
+                        c => current.push(c),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => current.push(chars.next()?),
+                        c => current.push(c),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                current.push(chars.next()?);
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+
+    Some(words)
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Command {
+    #[command(flatten)]
+    Generate(GeneratorCommand),
+
+    /// Generate many passwords and check their distribution for bias
+    Verify {
+        /// Number of passwords to generate (accepts scientific notation, e.g. 1e6)
+        #[arg(long, default_value = "100000", value_parser = parse_samples)]
+        samples: usize,
+
+        #[command(subcommand)]
+        target: GeneratorCommand,
+    },
+
+    /// Serve password requests from a browser extension over native messaging
+    NativeHost {
+        /// Arguments the browser passes to the host (extension origin, manifest path)
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, hide = true)]
+        browser_args: Vec<String>,
+    },
+
+    /// Print the fingerprint of a password typed at a hidden prompt or read from stdin
+    Fingerprint,
+
+    /// Show what an entropy target looks like in each mode, with a live example of each
+    Compare {
+        /// Entropy to reach, e.g. 90 or 90bits
+        #[arg(long, value_name = "BITS", default_value = "90bits", value_parser = parse_bits)]
+        target: f64,
+    },
+
+    /// Estimate the strength of a password typed at a hidden prompt or read from stdin
+    ///
+    /// Reports entropy, strength, the patterns found and crack times; exits 1
+    /// if the password falls short of --min-bits/--min-strength.
+    Check,
+
+    /// Clear the clipboard after a delay if it still holds the value hashed on stdin (used by --copy)
+    #[command(hide = true)]
+    ClipboardClear {
+        #[arg(long)]
+        after: u64,
+    },
+
+    /// Generate a master secret and split it into Shamir shares
+    Split {
+        /// Number of shares to create
+        #[arg(long, default_value = "5", env = "PWGEN_RS_SHARES")]
+        shares: u8,
+
+        /// Number of shares needed to recover the secret
+        #[arg(long, default_value = "3", env = "PWGEN_RS_THRESHOLD")]
+        threshold: u8,
+
+        /// Length of the generated secret in characters
+        #[arg(short, long, default_value = "32", env = "PWGEN_RS_LENGTH")]
+        length: usize,
+
+        /// How to write the shares
+        #[arg(long, value_enum, default_value = "hex", env = "PWGEN_RS_ENCODING")]
+        encoding: ShareEncoding,
+    },
+
+    /// Recover a secret from Shamir shares (prompted for, or read from stdin, if not given)
+    Recover {
+        /// Shares in hex or word form; quote word shares
+        shares: Vec<String>,
+    },
+
+    /// Generate a password for each user in a CSV file, ready for an account import
+    ///
+    /// Optional `length` and `policy` columns (e.g. `phrase 6`) override the
+    /// generator per row.
+    Provision {
+        /// CSV file with a header row (`-` reads stdin)
+        #[arg(long, value_name = "PATH")]
+        csv: PathBuf,
+
+        /// Column holding the user names
+        #[arg(long, default_value = "username")]
+        column: String,
+
+        /// Generator for rows without a policy (default: secure)
+        #[command(subcommand)]
+        target: Option<GeneratorCommand>,
+    },
+
+    /// Generate honeytoken passwords carrying a keyed tag, or check one with `decoy verify`
+    Decoy {
+        /// Secret key file (at least 16 bytes), e.g. made with `head -c 32 /dev/urandom`
+        #[arg(long, value_name = "PATH", global = true, env = "PWGEN_RS_DECOY_KEY")]
+        key_file: Option<PathBuf>,
+
+        /// Generator for the visible part (default: 10 alphanumeric characters), or `verify`
+        #[command(subcommand)]
+        action: Option<DecoyCommand>,
+    },
+
+    /// Answer a few questions and get a suitable password
+    Wizard,
+
+    /// Inspect available passphrase wordlists
+    Wordlist {
+        #[command(subcommand)]
+        action: WordlistCommand,
+    },
+
+    /// Inspect the profiles defined in the config file
+    Profile {
+        #[command(subcommand)]
+        action: ProfileCommand,
+    },
+
+    /// Write the data compiled into the binary out to files, for audits
+    Data {
+        #[command(subcommand)]
+        action: DataCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DecoyCommand {
+    /// Check whether a password is a decoy made with this key (exit 0 if it is, 1 if not)
+    Verify {
+        /// Password to check (prompted for, or read from stdin, if not given)
+        value: Option<String>,
+    },
+
+    #[command(flatten)]
+    Generate(Box<GeneratorCommand>),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum WordlistCommand {
+    /// List the built-in wordlist and any found in the wordlist directories
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProfileCommand {
+    /// List each `[profile.NAME]` section with its settings
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DataCommand {
+    /// Write an embedded wordlist, rule table or model to a file
+    Export {
+        /// What to export
+        #[arg(long, value_enum)]
+        what: DataSet,
+
+        /// File to write (default: the upstream file name in the current directory; `-` for stdout)
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+
+        /// Replace an existing file
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+fn parse_samples(s: &str) -> Result<usize, String> {
+    let value: f64 = s.parse().map_err(|_| format!("'{}' is not a number", s))?;
+    if value < 1.0 || value.fract() != 0.0 || value > usize::MAX as f64 {
+        return Err(format!("'{}' is not a positive whole number", s));
+    }
+    Ok(value as usize)
+}
+
+/// A duration such as `2s`, `500ms` or `1m`; a bare number counts seconds
+fn parse_timeout(s: &str) -> Result<Duration, String> {
+    let (number, unit) = match s.find(|c: char| !c.is_ascii_digit() && c != '.') {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("'{}' is not a duration (e.g. 2s, 500ms)", s))?;
+    let seconds = match unit {
+        "ms" => value / 1000.0,
+        "s" => value,
+        "m" => value * 60.0,
+        _ => return Err(format!("unknown unit '{}' (ms, s or m)", unit)),
+    };
+    if seconds <= 0.0 || !seconds.is_finite() {
+        return Err(format!("'{}' is not a positive duration", s));
+    }
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+/// A size in bytes such as `512M` or `2G` (binary units); a bare number counts bytes
+fn parse_size(s: &str) -> Result<usize, String> {
+    let (number, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+    let value: usize = number
+        .parse()
+        .map_err(|_| format!("'{}' is not a size (e.g. 512M, 2G)", s))?;
+    let shift = match unit
+        .to_ascii_uppercase()
+        .trim_end_matches("IB")
+        .trim_end_matches('B')
+    {
+        "" => 0,
+        "K" => 10,
+        "M" => 20,
+        "G" => 30,
+        _ => return Err(format!("unknown unit '{}' (K, M or G)", unit)),
+    };
+    match value.checked_mul(1 << shift) {
+        Some(0) => Err(format!("'{}' is not a positive size", s)),
+        Some(bytes) => Ok(bytes),
+        None => Err(format!("'{}' is too large", s)),
+    }
+}
+
+/// An entropy such as `90`, `90bits` or `90 bits`
+fn parse_bits(s: &str) -> Result<f64, String> {
+    let number = s.trim().trim_end_matches("bits").trim_end_matches("bit");
+    match number.trim_end().parse::<f64>() {
+        Ok(bits) if bits > 0.0 && bits.is_finite() => Ok(bits),
+        _ => Err(format!("'{}' is not a number of bits (e.g. 90bits)", s)),
+    }
+}
+
+/// Commands that produce passwords
+#[derive(Subcommand, Clone, Debug)]
+pub enum GeneratorCommand {
     /// Generate pronounceable passwords using Markov chains
     Normal {
         /// Password length (positional shorthand)
@@ -34,20 +1017,99 @@ pub enum Command {
         length_pos: Option<usize>,
 
         /// Password length
-        #[arg(short, long)]
+        #[arg(short, long, env = "PWGEN_RS_LENGTH")]
         length: Option<usize>,
 
-        /// Include numbers
-        #[arg(short = 'd', long, default_value = "true")]
+        /// Include numbers (default)
+        #[arg(
+            short = 'd',
+            long,
+            default_value = "true",
+            overrides_with = "no_digits",
+            env = "PWGEN_RS_DIGITS",
+            value_parser = BoolishValueParser::new()
+        )]
         digits: bool,
 
-        /// Include readable symbols (!@#$%&*-_=+)
-        #[arg(short, long, default_value = "false")]
+        /// Leave out numbers
+        #[arg(long, overrides_with = "digits")]
+        no_digits: bool,
+
+        /// Include a symbol (readable ones, !@#$%&*-_+, unless --symbol-set says otherwise)
+        #[arg(
+            short,
+            long,
+            default_value = "false",
+            overrides_with = "no_symbols",
+            env = "PWGEN_RS_SYMBOLS",
+            value_parser = BoolishValueParser::new()
+        )]
         symbols: bool,
 
-        /// Capitalize first letter
-        #[arg(short = 'C', long, default_value = "true")]
+        /// Leave out symbols
+        #[arg(long, overrides_with = "symbols")]
+        no_symbols: bool,
+
+        /// Insert exactly N digits at random positions (overrides --digits)
+        #[arg(long, value_name = "N", env = "PWGEN_RS_DIGIT_COUNT")]
+        digit_count: Option<usize>,
+
+        /// Insert exactly N symbols at random positions (overrides --symbols)
+        #[arg(long, value_name = "N", env = "PWGEN_RS_SYMBOL_COUNT")]
+        symbol_count: Option<usize>,
+
+        /// Symbols to insert: readable (default), full or custom:<chars>
+        #[arg(long, value_name = "SET", env = "PWGEN_RS_SYMBOL_SET")]
+        symbol_set: Option<SymbolSet>,
+
+        /// Capitalize first letter (default)
+        #[arg(
+            short = 'C',
+            long,
+            default_value = "true",
+            overrides_with = "no_capitalize",
+            env = "PWGEN_RS_CAPITALIZE",
+            value_parser = BoolishValueParser::new()
+        )]
         capitalize: bool,
+
+        /// Keep every letter lowercase
+        #[arg(long, overrides_with = "capitalize")]
+        no_capitalize: bool,
+
+        /// Redraw passwords among this percentage of the model's most probable outputs (0: never)
+        #[arg(
+            long,
+            value_name = "PERCENT",
+            default_value = "1",
+            env = "PWGEN_RS_MIN_GUESS_PERCENTILE"
+        )]
+        min_guess_percentile: f64,
+
+        /// Train the model on this text instead of the built-in wordlist (`-` reads stdin)
+        #[arg(long, value_name = "PATH", env = "PWGEN_RS_CORPUS")]
+        corpus: Option<PathBuf>,
+
+        /// Stop training once the model would use about this much memory, e.g. 64M
+        #[arg(
+            long,
+            value_name = "SIZE",
+            requires = "corpus",
+            default_value = "256M",
+            value_parser = parse_size,
+            env = "PWGEN_RS_CORPUS_MAX_MEMORY"
+        )]
+        corpus_max_memory: usize,
+
+        /// Stop training after this long (e.g. 30s, 2m) and use what was learned
+        #[arg(
+            long,
+            value_name = "DURATION",
+            requires = "corpus",
+            value_parser = parse_timeout,
+            env = "PWGEN_RS_CORPUS_TIMEOUT"
+        )]
+        corpus_timeout: Option<Duration>,
     },
 
     /// Generate cryptographically secure random passwords
@@ -57,16 +1119,148 @@ pub enum Command {
         length_pos: Option<usize>,
 
         /// Password length
-        #[arg(short, long)]
+        #[arg(short, long, env = "PWGEN_RS_LENGTH")]
         length: Option<usize>,
 
         /// Character set to use
-        #[arg(short = 'S', long, value_enum, default_value = "alphanumeric-symbols")]
+        #[arg(
+            short = 'S',
+            long,
+            value_enum,
+            default_value = "alphanumeric-symbols",
+            env = "PWGEN_RS_CHARSET"
+        )]
         charset: CharSet,
 
+        /// Draw from exactly these characters (overrides --charset)
+        #[arg(
+            long,
+            value_name = "CHARS",
+            conflicts_with_all = ["site", "compose", "symbol_set"],
+            env = "PWGEN_RS_CHARS"
+        )]
+        chars: Option<String>,
+
+        /// Leave these characters out, e.g. quotes and backslashes that break shell scripts
+        #[arg(
+            long,
+            value_name = "CHARS",
+            conflicts_with = "site",
+            env = "PWGEN_RS_EXCLUDE_CHARS"
+        )]
+        exclude_chars: Option<String>,
+
+        /// Build the charset from lowercase letters (with the other --with-* flags, instead of --charset)
+        #[arg(
+            long,
+            conflicts_with_all = ["charset", "chars", "compose", "site"],
+            env = "PWGEN_RS_WITH_LOWER",
+            value_parser = BoolishValueParser::new()
+        )]
+        with_lower: bool,
+
+        /// Build the charset from uppercase letters too
+        #[arg(
+            long,
+            conflicts_with_all = ["charset", "chars", "compose", "site"],
+            env = "PWGEN_RS_WITH_UPPER",
+            value_parser = BoolishValueParser::new()
+        )]
+        with_upper: bool,
+
+        /// Build the charset from digits too
+        #[arg(
+            long,
+            conflicts_with_all = ["charset", "chars", "compose", "site"],
+            env = "PWGEN_RS_WITH_DIGITS",
+            value_parser = BoolishValueParser::new()
+        )]
+        with_digits: bool,
+
+        /// Build the charset from symbols too (those of --symbol-set)
+        #[arg(
+            long,
+            conflicts_with_all = ["charset", "chars", "compose", "site"],
+            env = "PWGEN_RS_WITH_SYMBOLS",
+            value_parser = BoolishValueParser::new()
+        )]
+        with_symbols: bool,
+
+        /// Build the charset with the space character too
+        #[arg(
+            long,
+            conflicts_with_all = ["charset", "chars", "compose", "site"],
+            env = "PWGEN_RS_WITH_SPACE",
+            value_parser = BoolishValueParser::new()
+        )]
+        with_space: bool,
+
         /// Exclude ambiguous characters (0O1lI)
-        #[arg(long)]
+        #[arg(long, env = "PWGEN_RS_NO_AMBIGUOUS", value_parser = BoolishValueParser::new())]
         no_ambiguous: bool,
+
+        /// Symbols of alphanumeric-symbols: full (default), readable or custom:<chars>
+        #[arg(
+            long,
+            value_name = "SET",
+            conflicts_with = "site",
+            env = "PWGEN_RS_SYMBOL_SET"
+        )]
+        symbol_set: Option<SymbolSet>,
+
+        /// Follow a site's password rules, e.g. `--for github.com` (overrides --charset)
+        #[arg(long = "for", value_name = "SITE", env = "PWGEN_RS_FOR")]
+        site: Option<String>,
+
+        /// With --for, fetch the site's published rules (cached for a week)
+        #[arg(
+            long,
+            requires = "site",
+            env = "PWGEN_RS_ONLINE",
+            value_parser = BoolishValueParser::new()
+        )]
+        online: bool,
+
+        /// Use no character more than N times (drawing from the characters still allowed)
+        #[arg(long, value_name = "N", env = "PWGEN_RS_MAX_CHAR_REPEAT")]
+        max_char_repeat: Option<usize>,
+
+        /// Draw each position's class by weight, e.g. lower:60,upper:20,digit:15,symbol:5 (overrides --charset)
+        #[arg(
+            long,
+            value_name = "WEIGHTS",
+            conflicts_with = "site",
+            env = "PWGEN_RS_COMPOSE"
+        )]
+        compose: Option<Composition>,
+
+        /// Contain at least one character of each class, e.g. upper,lower,digit,symbol
+        #[arg(
+            long,
+            value_name = "CLASSES",
+            value_delimiter = ',',
+            conflicts_with = "site",
+            env = "PWGEN_RS_REQUIRE"
+        )]
+        require: Vec<CharClass>,
+
+        /// Contain at least N digits
+        #[arg(
+            long,
+            value_name = "N",
+            conflicts_with = "site",
+            env = "PWGEN_RS_MIN_DIGITS"
+        )]
+        min_digits: Option<usize>,
+
+        /// Contain at least N symbols
+        #[arg(
+            long,
+            value_name = "N",
+            conflicts_with = "site",
+            env = "PWGEN_RS_MIN_SYMBOLS"
+        )]
+        min_symbols: Option<usize>,
     },
 
     /// Generate diceware passphrases using EFF wordlist
@@ -76,24 +1270,98 @@ pub enum Command {
         words_pos: Option<usize>,
 
         /// Number of words
-        #[arg(short, long)]
+        #[arg(short, long, env = "PWGEN_RS_WORDS")]
         words: Option<usize>,
 
         /// Word separator
-        #[arg(short, long, value_enum, default_value = "dash")]
+        #[arg(
+            short,
+            long,
+            value_enum,
+            default_value = "dash",
+            env = "PWGEN_RS_SEPARATOR"
+        )]
         separator: Separator,
 
-        /// Custom separator string (overrides --separator)
-        #[arg(long)]
+        /// Custom separator string (overrides --separator); no letters or digits
+        #[arg(long, allow_hyphen_values = true, env = "PWGEN_RS_CUSTOM_SEP")]
         custom_sep: Option<String>,
 
+        /// Pick each separator at random from these characters (adds entropy)
+        #[arg(
+            long,
+            value_name = "CHARS",
+            allow_hyphen_values = true,
+            conflicts_with = "custom_sep",
+            env = "PWGEN_RS_RANDOM_SEP"
+        )]
+        random_sep: Option<String>,
+
         /// Capitalize each word
-        #[arg(short = 'C', long)]
+        #[arg(
+            short = 'C',
+            long,
+            env = "PWGEN_RS_CAPITALIZE",
+            value_parser = BoolishValueParser::new()
+        )]
         capitalize: bool,
 
+        /// Uppercase exactly one randomly chosen word (adds log2(words) bits)
+        #[arg(long, env = "PWGEN_RS_CAP_ONE", value_parser = BoolishValueParser::new())]
+        cap_one: bool,
+
         /// Disable word mutations (leet speak, truncation)
-        #[arg(long)]
+        #[arg(long, env = "PWGEN_RS_NO_MUTATE", value_parser = BoolishValueParser::new())]
         no_mutate: bool,
+
+        /// Wordlist name (see `wordlist list`) or path to a wordlist file
+        #[arg(long, env = "PWGEN_RS_WORDLIST")]
+        wordlist: Option<String>,
+
+        /// Prefer familiar words for memorability (reduces entropy; the cost is reported)
+        #[arg(long, env = "PWGEN_RS_FAMILIAR", value_parser = BoolishValueParser::new())]
+        familiar: bool,
+
+        /// Pick words whose initials spell WORD, one per letter (the entropy cost is reported)
+        #[arg(
+            long,
+            value_name = "WORD",
+            conflicts_with_all = ["words_pos", "words"],
+            env = "PWGEN_RS_ACROSTIC"
+        )]
+        acrostic: Option<String>,
+
+        /// Never use a word twice in one passphrase (the small entropy cost is reported)
+        #[arg(long, env = "PWGEN_RS_UNIQUE_WORDS", value_parser = BoolishValueParser::new())]
+        unique_words: bool,
+
+        /// Show a short story weaving the words together as a memory aid
+        #[arg(long, env = "PWGEN_RS_STORY", value_parser = BoolishValueParser::new())]
+        story: bool,
+
+        /// Keep the passphrase on one line when text wraps: separators from ._:=+ (a dot instead
+        /// of the default dash) and no hyphenated words
+        #[arg(long, env = "PWGEN_RS_WRAP_SAFE", value_parser = BoolishValueParser::new())]
+        wrap_safe: bool,
+
+        /// Print each passphrase as a block of numbered words for documents (implies --wrap-safe)
+        #[arg(
+            long,
+            env = "PWGEN_RS_PRINT_LAYOUT",
+            value_parser = BoolishValueParser::new()
+        )]
+        print_layout: bool,
+    },
+
+    /// Generate short pronounceable first-login passwords, flagged to be changed on first use
+    Temp {
+        /// Password length (positional shorthand)
+        #[arg(value_name = "LENGTH")]
+        length_pos: Option<usize>,
+
+        /// Password length
+        #[arg(short, long, env = "PWGEN_RS_LENGTH")]
+        length: Option<usize>,
     },
 
     /// Generate numeric PIN codes
@@ -103,21 +1371,210 @@ pub enum Command {
         length_pos: Option<usize>,
 
         /// PIN length
-        #[arg(short, long)]
+        #[arg(short, long, env = "PWGEN_RS_LENGTH")]
+        length: Option<usize>,
+    },
+
+    /// Generate phone and tablet unlock codes that avoid patterns smudges and onlookers give away
+    Passcode {
+        /// Passcode length (positional shorthand)
+        #[arg(value_name = "LENGTH")]
+        length_pos: Option<usize>,
+
+        /// Passcode length (default: 6 digits, or 8 characters with --alphanumeric)
+        #[arg(short, long, env = "PWGEN_RS_LENGTH")]
         length: Option<usize>,
+
+        /// Lowercase letters and digits, for the lock screen's alphanumeric option
+        #[arg(
+            long,
+            env = "PWGEN_RS_ALPHANUMERIC",
+            value_parser = BoolishValueParser::new()
+        )]
+        alphanumeric: bool,
     },
+
+    /// Generate a pronounceable word plus digits (tavo-7319) for keypad entry, with the keys to press
+    Keypad {
+        /// Letters in the word (positional shorthand)
+        #[arg(value_name = "LETTERS")]
+        length_pos: Option<usize>,
+
+        /// Letters in the word
+        #[arg(short, long, env = "PWGEN_RS_LENGTH")]
+        length: Option<usize>,
+
+        /// Digits after the word
+        #[arg(long, value_name = "N", env = "PWGEN_RS_DIGIT_COUNT")]
+        digit_count: Option<usize>,
+    },
+
+    /// Generate passwords shaped by a mask, e.g. 'Cvcvc-####-%%' or '?u?l?l?l?d?d?s'
+    Template {
+        /// hashcat classes ?l ?u ?d ?h ?H ?s ?a (?? for ?), or c/C consonant, v/V vowel,
+        /// # digit, % symbol; anything else is kept, and \ keeps the next character
+        #[arg(value_name = "MASK", env = "PWGEN_RS_TEMPLATE")]
+        mask: String,
+
+        /// Symbols for % slots: readable (default), full or custom:<chars>
+        #[arg(long, value_name = "SET", env = "PWGEN_RS_SYMBOL_SET")]
+        symbol_set: Option<SymbolSet>,
+    },
+
+    /// Generate iCloud Keychain-style passwords, e.g. 'hikmen-qirxo6-cetSyp'
+    Apple,
 }
 
-#[derive(ValueEnum, Clone, Debug)]
-pub enum CharSet {
-    /// a-z, A-Z
-    Alpha,
-    /// a-z, A-Z, 0-9
-    Alphanumeric,
-    /// a-z, A-Z, 0-9, symbols
-    AlphanumericSymbols,
-    /// All printable ASCII
-    All,
+/// When to use colors (`--color`)
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// On a terminal, unless the environment says otherwise
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether to color output going to a terminal (`is_tty`) or not
+    ///
+    /// In `auto` mode a non-empty `NO_COLOR` disables colors, then
+    /// `CLICOLOR_FORCE` other than `0` enables them even when piped, and
+    /// `CLICOLOR=0` disables them on a terminal. `var` looks up the environment.
+    pub fn enabled(self, is_tty: bool, var: impl Fn(&str) -> Option<OsString>) -> bool {
+        let set = |name: &str| var(name).filter(|value| !value.is_empty());
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto if set("NO_COLOR").is_some() => false,
+            ColorChoice::Auto if set("CLICOLOR_FORCE").is_some_and(|v| v != "0") => true,
+            ColorChoice::Auto => is_tty && var("CLICOLOR").is_none_or(|v| v != "0"),
+        }
+    }
+}
+
+/// Shell whose quoting `--escape` prints the password in
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shell {
+    /// sh, bash, zsh: `'...'`
+    Posix,
+    /// Windows PowerShell and pwsh: `'...'` with quotes doubled
+    Powershell,
+    /// cmd.exe at the interactive prompt: special characters `^`-escaped
+    Cmd,
+}
+
+/// Text encoding of a secret share
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShareEncoding {
+    /// `<index>-<hex bytes>`
+    Hex,
+    /// One word per byte, easier to read aloud or write down
+    Words,
+}
+
+/// A generator command on its own, for per-row policies in `provision`
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+struct Policy {
+    #[command(subcommand)]
+    command: GeneratorCommand,
+}
+
+impl GeneratorCommand {
+    /// Parse a command line such as `phrase 6` or `secure -S alphanumeric`
+    pub fn parse_policy(line: &str) -> Result<Self, String> {
+        let words = split_command_line(line)
+            .filter(|words| !words.is_empty())
+            .ok_or_else(|| format!("invalid policy: {:?}", line))?;
+        Policy::try_parse_from(words)
+            .map(|policy| policy.command)
+            .map_err(|e| {
+                let message = e.render().to_string();
+                let first = message.lines().next().unwrap_or_default();
+                format!(
+                    "invalid policy {:?}: {}",
+                    line,
+                    first.trim_start_matches("error: ")
+                )
+            })
+    }
+
+    /// The subcommand name, as typed
+    pub fn name(&self) -> &'static str {
+        match self {
+            GeneratorCommand::Normal { .. } => "normal",
+            GeneratorCommand::Secure { .. } => "secure",
+            GeneratorCommand::Phrase { .. } => "phrase",
+            GeneratorCommand::Temp { .. } => "temp",
+            GeneratorCommand::Pin { .. } => "pin",
+            GeneratorCommand::Passcode { .. } => "passcode",
+            GeneratorCommand::Keypad { .. } => "keypad",
+            GeneratorCommand::Template { .. } => "template",
+            GeneratorCommand::Apple => "apple",
+        }
+    }
+
+    /// The same command with a different length, or word count for passphrases
+    ///
+    /// A template's mask and the Apple format fix their length, so those are
+    /// returned unchanged.
+    pub fn with_length(mut self, n: usize) -> Self {
+        match &mut self {
+            GeneratorCommand::Normal {
+                length_pos, length, ..
+            }
+            | GeneratorCommand::Secure {
+                length_pos, length, ..
+            }
+            | GeneratorCommand::Temp { length_pos, length }
+            | GeneratorCommand::Pin { length_pos, length }
+            | GeneratorCommand::Passcode {
+                length_pos, length, ..
+            }
+            | GeneratorCommand::Keypad {
+                length_pos, length, ..
+            } => {
+                *length_pos = None;
+                *length = Some(n);
+            }
+            GeneratorCommand::Phrase {
+                words_pos, words, ..
+            } => {
+                *words_pos = None;
+                *words = Some(n);
+            }
+            GeneratorCommand::Template { .. } | GeneratorCommand::Apple => {}
+        }
+        self
+    }
+}
+
+/// Plain text for people, JSON for tooling, or sheets to print
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Decorated text; `username,password` CSV for `provision`
+    #[default]
+    #[value(alias = "csv")]
+    Text,
+    /// One JSON array holding every password
+    Json,
+    /// One JSON object per line
+    Jsonl,
+    /// A sheet per password to print and store in a safe (plain text that reads as Markdown)
+    Printable,
+}
+
+/// Framing of a batch of bare passwords (`--record-sep`)
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RecordSeparator {
+    /// One password per line, each ending in `\n`
+    #[default]
+    Newline,
+    /// Each password ends in a NUL byte, for `xargs -0` and `read -d ''`
+    Nul,
+    /// One line of comma-separated values, CSV-quoted where needed, ending in `\n`
+    Comma,
 }
 
 #[derive(ValueEnum, Clone, Debug)]
@@ -140,3 +1597,378 @@ impl Separator {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<OsString> {
+        split_command_line(line)
+            .unwrap()
+            .into_iter()
+            .map(OsString::from)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse_timeout("2s"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_timeout("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_timeout("1.5"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_timeout("1m"), Ok(Duration::from_secs(60)));
+        assert!(parse_timeout("0s").is_err());
+        assert!(parse_timeout("2h").is_err());
+        assert!(parse_timeout("soon").is_err());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("4096"), Ok(4096));
+        assert_eq!(parse_size("64M"), Ok(64 << 20));
+        assert_eq!(parse_size("2GiB"), Ok(2 << 30));
+        assert_eq!(parse_size("512kb"), Ok(512 << 10));
+        assert!(parse_size("0").is_err());
+        assert!(parse_size("1T").is_err());
+        assert!(parse_size("lots").is_err());
+    }
+
+    #[test]
+    fn test_parse_bits() {
+        assert_eq!(parse_bits("90"), Ok(90.0));
+        assert_eq!(parse_bits("90bits"), Ok(90.0));
+        assert_eq!(parse_bits("64.5 bits"), Ok(64.5));
+        assert_eq!(parse_bits("1bit"), Ok(1.0));
+        assert!(parse_bits("0bits").is_err());
+        assert!(parse_bits("many").is_err());
+
+        let cli =
+            Cli::parse_args_with_config(args("pwgen-x phrase --bits 128bits"), &Config::default());
+        assert_eq!(cli.bits, Some(128.0));
+    }
+
+    #[test]
+    fn test_split_command_line() {
+        assert_eq!(
+            split_command_line(r#"phrase --custom-sep ", " 'a b' c\ d"#).unwrap(),
+            vec!["phrase", "--custom-sep", ", ", "a b", "c d"]
+        );
+        assert_eq!(split_command_line("  ").unwrap(), Vec::<String>::new());
+        assert!(split_command_line("unterminated 'quote").is_none());
+    }
+
+    #[test]
+    fn test_with_flags_conflict_with_charset() {
+        let parsed = Cli::try_parse_from(["pwgen-x", "secure", "-S", "alpha", "--with-digits"]);
+        assert!(parsed.is_err());
+        let parsed = Cli::try_parse_from(["pwgen-x", "secure", "--chars", "ab", "--with-space"]);
+        assert!(parsed.is_err());
+    }
+
+    #[test]
+    fn test_default_command_from_config() {
+        let config = Config::parse(r#"default_command = "secure 20 --no-ambiguous""#).unwrap();
+        let cli = Cli::parse_args_with_config(args("pwgen-x -n 3"), &config);
+        assert_eq!(cli.count, 3);
+        match cli.command {
+            Some(Command::Generate(GeneratorCommand::Secure {
+                length_pos,
+                no_ambiguous,
+                ..
+            })) => {
+                assert_eq!(length_pos, Some(20));
+                assert!(no_ambiguous);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_default_command_flag_overrides_config() {
+        let config = Config::parse(r#"default_command = "secure 20""#).unwrap();
+        let cli = Cli::parse_args_with_config(args("pwgen-x --default-command 'pin 8'"), &config);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Generate(GeneratorCommand::Pin {
+                length_pos: Some(8),
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_config_defaults_and_profiles() {
+        let config = Config::parse(
+            r#"
+[defaults]
+length = 24
+no-ambiguous = true
+
+[profile.bank]
+command = "pin"
+pin.length = 8
+"#,
+        )
+        .unwrap();
+        let secure = |cli: Cli| match cli.command {
+            Some(Command::Generate(GeneratorCommand::Secure {
+                length_pos,
+                length,
+                no_ambiguous,
+                ..
+            })) => (length_pos.or(length), no_ambiguous),
+            other => panic!("unexpected command: {:?}", other),
+        };
+        let cli = Cli::parse_args_with_config(args("pwgen-x secure"), &config);
+        assert_eq!(secure(cli), (Some(24), true));
+        let cli = Cli::parse_args_with_config(args("pwgen-x secure 12"), &config);
+        assert_eq!(secure(cli), (Some(12), true));
+        let cli = Cli::parse_args_with_config(args("pwgen-x secure --length 16"), &config);
+        assert_eq!(secure(cli), (Some(16), true));
+
+        // The profile chooses the command, and its scoped length wins
+        let cli = Cli::parse_args_with_config(args("pwgen-x --profile bank"), &config);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Generate(GeneratorCommand::Pin {
+                length: Some(8),
+                ..
+            }))
+        ));
+        let cli = Cli::parse_args_with_config(args("pwgen-x secure --profile=bank"), &config);
+        assert_eq!(secure(cli), (Some(24), true));
+    }
+
+    #[test]
+    fn test_env_overrides_and_flag_precedence() {
+        // clap reads the process environment, so the parsing runs in a child
+        // test process given its own variables; every other test keeps a clean one
+        const CHILD: &str = "PWGEN_RS_TEST_ENV_CHILD";
+        if std::env::var_os(CHILD).is_none() {
+            let output = std::process::Command::new(std::env::current_exe().unwrap())
+                .args([
+                    "--exact",
+                    "cli::tests::test_env_overrides_and_flag_precedence",
+                    "--test-threads=1",
+                ])
+                .env(CHILD, "1")
+                .env("PWGEN_RS_CHARSET", "alpha")
+                .env("PWGEN_RS_NO_AMBIGUOUS", "1")
+                .env("PWGEN_RS_SORT_ENTROPY", "yes")
+                .output()
+                .unwrap();
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            assert!(
+                output.status.success() && stdout.contains("1 passed"),
+                "{}{}",
+                stdout,
+                String::from_utf8_lossy(&output.stderr)
+            );
+            return;
+        }
+
+        let cli = Cli::parse_args_with_config(args("pwgen-x secure"), &Config::default());
+        assert!(cli.sort_entropy);
+        match cli.command {
+            Some(Command::Generate(GeneratorCommand::Secure {
+                charset,
+                no_ambiguous,
+                ..
+            })) => {
+                assert!(matches!(charset, CharSet::Alpha));
+                assert!(no_ambiguous);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+
+        // Explicit flags win over the environment
+        let cli = Cli::parse_args_with_config(
+            args("pwgen-x secure --charset alphanumeric"),
+            &Config::default(),
+        );
+        assert!(matches!(
+            cli.command,
+            Some(Command::Generate(GeneratorCommand::Secure {
+                charset: CharSet::Alphanumeric,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_color_choice() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| OsString::from(value))
+            }
+        };
+        assert!(ColorChoice::Auto.enabled(true, env(&[])));
+        assert!(!ColorChoice::Auto.enabled(false, env(&[])));
+        assert!(!ColorChoice::Auto.enabled(true, env(&[("NO_COLOR", "1")])));
+        assert!(ColorChoice::Auto.enabled(true, env(&[("NO_COLOR", "")])));
+        assert!(!ColorChoice::Auto.enabled(true, env(&[("CLICOLOR", "0")])));
+        assert!(ColorChoice::Auto.enabled(false, env(&[("CLICOLOR_FORCE", "1")])));
+        assert!(!ColorChoice::Auto.enabled(false, env(&[("CLICOLOR_FORCE", "0")])));
+        assert!(
+            !ColorChoice::Auto.enabled(false, env(&[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")]))
+        );
+        assert!(ColorChoice::Always.enabled(false, env(&[("NO_COLOR", "1")])));
+        assert!(!ColorChoice::Never.enabled(true, env(&[("CLICOLOR_FORCE", "1")])));
+    }
+
+    #[test]
+    fn test_provision_policy() {
+        let policy = GeneratorCommand::parse_policy("phrase 5 -s space").unwrap();
+        assert!(matches!(
+            policy.with_length(7),
+            GeneratorCommand::Phrase {
+                words_pos: None,
+                words: Some(7),
+                ..
+            }
+        ));
+        assert!(matches!(
+            GeneratorCommand::parse_policy("pin")
+                .unwrap()
+                .with_length(8),
+            GeneratorCommand::Pin {
+                length: Some(8),
+                ..
+            }
+        ));
+        assert!(GeneratorCommand::parse_policy("bogus").is_err());
+        assert!(GeneratorCommand::parse_policy("").is_err());
+    }
+
+    #[test]
+    fn test_negatable_markov_flags() {
+        let flags = |line: &str| match Cli::parse_args_with_config(args(line), &Config::default())
+            .command
+        {
+            Some(Command::Generate(GeneratorCommand::Normal {
+                digits,
+                no_digits,
+                symbols,
+                no_symbols,
+                capitalize,
+                no_capitalize,
+                ..
+            })) => (
+                negatable(digits, no_digits),
+                negatable(symbols, no_symbols),
+                negatable(capitalize, no_capitalize),
+            ),
+            other => panic!("unexpected command: {:?}", other),
+        };
+        assert_eq!(flags("pwgen-x normal"), (true, false, true));
+        assert_eq!(flags("pwgen-x normal --digits"), (true, false, true));
+        assert_eq!(flags("pwgen-x normal --no-digits"), (false, false, true));
+        assert_eq!(flags("pwgen-x normal -d --no-digits"), (false, false, true));
+        assert_eq!(flags("pwgen-x normal --no-digits -d"), (true, false, true));
+        assert_eq!(flags("pwgen-x normal --symbols"), (true, true, true));
+        assert_eq!(flags("pwgen-x normal -s --no-symbols"), (true, false, true));
+        assert_eq!(flags("pwgen-x normal --no-symbols -s"), (true, true, true));
+        assert_eq!(
+            flags("pwgen-x normal --no-capitalize"),
+            (true, false, false)
+        );
+        assert_eq!(
+            flags("pwgen-x normal -C --no-capitalize"),
+            (true, false, false)
+        );
+        assert_eq!(
+            flags("pwgen-x normal --no-capitalize -C"),
+            (true, false, true)
+        );
+        assert_eq!(
+            flags("pwgen-x normal --no-digits --symbols --no-capitalize"),
+            (false, true, false)
+        );
+    }
+
+    #[test]
+    fn test_threshold_flags_are_global() {
+        let cli = Cli::parse_args_with_config(
+            args("pwgen-x pin 6 --min-bits 64 --check-only"),
+            &Config::default(),
+        );
+        assert_eq!(cli.min_bits, Some(64.0));
+        assert!(cli.check_only);
+
+        let cli = Cli::parse_args_with_config(
+            args("pwgen-x --min-length 8 phrase --max-length 32"),
+            &Config::default(),
+        );
+        assert_eq!((cli.min_length, cli.max_length), (Some(8), Some(32)));
+    }
+
+    #[test]
+    fn test_alias_expansion() {
+        let config = Config::parse(
+            r#"
+            [alias]
+            wifi = "secure 63 --charset alphanumeric --no-ambiguous"
+            short = "wifi"
+            pin = "phrase 3"
+            "#,
+        )
+        .unwrap();
+
+        let cli = Cli::parse_args_with_config(args("pwgen-x -n 2 wifi --quiet"), &config);
+        assert_eq!(cli.count, 2);
+        assert!(cli.quiet);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Generate(GeneratorCommand::Secure {
+                length_pos: Some(63),
+                charset: CharSet::Alphanumeric,
+                no_ambiguous: true,
+                ..
+            }))
+        ));
+
+        // Aliases may refer to other aliases
+        let cli = Cli::parse_args_with_config(args("pwgen-x short"), &config);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Generate(GeneratorCommand::Secure { .. }))
+        ));
+
+        // Built-in commands cannot be shadowed
+        let cli = Cli::parse_args_with_config(args("pwgen-x pin"), &config);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Generate(GeneratorCommand::Pin { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_default_command_may_be_alias() {
+        let config = Config::parse(
+            r#"
+            default_command = "wifi"
+            alias.wifi = "secure 40"
+            "#,
+        )
+        .unwrap();
+        let cli = Cli::parse_args_with_config(args("pwgen-x"), &config);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Generate(GeneratorCommand::Secure {
+                length_pos: Some(40),
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_explicit_subcommand_wins() {
+        let config = Config::parse(r#"default_command = "secure 20""#).unwrap();
+        let cli = Cli::parse_args_with_config(args("pwgen-x phrase 4"), &config);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Generate(GeneratorCommand::Phrase { .. }))
+        ));
+    }
+}
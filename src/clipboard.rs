@@ -0,0 +1,246 @@
+//! System clipboard through the platform's command-line tools (`--copy`)
+//!
+//! Like [`http`](crate::http), this shells out rather than linking a
+//! clipboard library into every build: `pbcopy` on macOS, `wl-copy` on
+//! Wayland, `xclip` or `xsel` on X11 and PowerShell on Windows.
+//!
+//! [`clear_later`] starts a detached copy of pwgen-x (the hidden
+//! `clipboard-clear` command) that waits, then empties the clipboard if it
+//! still holds the copied value, so anything copied since is left alone. The
+//! helper only learns the value's SHA-256, on stdin, which keeps the secret out
+//! of process listings.
+
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use crate::digest::{hex, sha256};
+
+#[derive(Debug, Error)]
+pub enum ClipboardError {
+    #[error("no clipboard tool found; install wl-clipboard, xclip or xsel")]
+    NoTool,
+    #[error("could not run {tool}")]
+    Io {
+        tool: &'static str,
+        source: io::Error,
+    },
+    #[error("{tool} failed with {status}")]
+    Failed {
+        tool: &'static str,
+        status: std::process::ExitStatus,
+    },
+}
+
+/// The command-line tool that reaches the clipboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Pbcopy,
+    WlCopy,
+    Xclip,
+    Xsel,
+    PowerShell,
+}
+
+impl Tool {
+    /// The tool for this platform and session, if it is installed
+    pub fn detect() -> Option<Tool> {
+        if cfg!(target_os = "macos") {
+            return Some(Tool::Pbcopy);
+        }
+        if cfg!(windows) {
+            return Some(Tool::PowerShell);
+        }
+        let has = |var: &str| std::env::var_os(var).is_some_and(|v| !v.is_empty());
+        [
+            (has("WAYLAND_DISPLAY"), Tool::WlCopy),
+            (has("DISPLAY"), Tool::Xclip),
+            (has("DISPLAY"), Tool::Xsel),
+        ]
+        .into_iter()
+        .find(|(session, tool)| *session && on_path(tool.copy_command().0))
+        .map(|(_, tool)| tool)
+    }
+
+    pub fn name(self) -> &'static str {
+        self.copy_command().0
+    }
+
+    /// Program and arguments that copy stdin to the clipboard
+    fn copy_command(self) -> (&'static str, &'static [&'static str]) {
+        match self {
+            Tool::Pbcopy => ("pbcopy", &[]),
+            Tool::WlCopy => ("wl-copy", &[]),
+            Tool::Xclip => ("xclip", &["-selection", "clipboard", "-in"]),
+            Tool::Xsel => ("xsel", &["--clipboard", "--input"]),
+            Tool::PowerShell => (
+                "powershell",
+                &[
+                    "-NoProfile",
+                    "-Command",
+                    "Set-Clipboard -Value ([Console]::In.ReadToEnd())",
+                ],
+            ),
+        }
+    }
+
+    /// Program and arguments that print the clipboard
+    fn paste_command(self) -> (&'static str, &'static [&'static str]) {
+        match self {
+            Tool::Pbcopy => ("pbpaste", &[]),
+            Tool::WlCopy => ("wl-paste", &["--no-newline"]),
+            Tool::Xclip => ("xclip", &["-selection", "clipboard", "-out"]),
+            Tool::Xsel => ("xsel", &["--clipboard", "--output"]),
+            Tool::PowerShell => (
+                "powershell",
+                &["-NoProfile", "-Command", "Get-Clipboard -Raw"],
+            ),
+        }
+    }
+
+    /// Put `text` on the clipboard
+    pub fn copy(self, text: &str) -> Result<(), ClipboardError> {
+        let (program, args) = self.copy_command();
+        let io_error = |source| ClipboardError::Io {
+            tool: program,
+            source,
+        };
+        // wl-copy and xclip keep serving the selection in the background; with
+        // stdout inherited they would hold a pipe open after we exit
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(io_error)?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes()).map_err(io_error)?;
+        }
+        let status = child.wait().map_err(io_error)?;
+        if !status.success() {
+            return Err(ClipboardError::Failed {
+                tool: program,
+                status,
+            });
+        }
+        Ok(())
+    }
+
+    /// The clipboard's current text, without a trailing newline
+    pub fn paste(self) -> Result<Zeroizing<String>, ClipboardError> {
+        let (program, args) = self.paste_command();
+        let output = Command::new(program)
+            .args(args)
+            .stderr(Stdio::null())
+            .output()
+            .map_err(|source| ClipboardError::Io {
+                tool: program,
+                source,
+            })?;
+        let text = Zeroizing::new(String::from_utf8_lossy(&output.stdout).into_owned());
+        Ok(Zeroizing::new(
+            text.trim_end_matches(['\r', '\n']).to_string(),
+        ))
+    }
+}
+
+/// Copy `text` with the detected tool
+pub fn copy(text: &str) -> Result<Tool, ClipboardError> {
+    let tool = Tool::detect().ok_or(ClipboardError::NoTool)?;
+    tool.copy(text)?;
+    Ok(tool)
+}
+
+/// Start a detached helper that clears the clipboard after `after`, unless it changed
+pub fn clear_later(text: &str, after: Duration) -> Result<(), ClipboardError> {
+    let io_error = |source| ClipboardError::Io {
+        tool: "pwgen-x",
+        source,
+    };
+    let exe = std::env::current_exe().map_err(io_error)?;
+    let mut command = Command::new(exe);
+    command
+        .args(["clipboard-clear", "--after", &after.as_secs().to_string()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // Out of the terminal's process group, so Ctrl-C after we exit doesn't reach it
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    let mut child = command.spawn().map_err(io_error)?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(hex(&sha256(text.as_bytes())).as_bytes())
+            .map_err(io_error)?;
+    }
+    Ok(())
+}
+
+/// The helper's side of [`clear_later`]: read the hash from `input`, wait, then clear
+///
+/// Returns whether the clipboard was cleared.
+pub fn clear_if_unchanged(input: &mut dyn Read, after: Duration) -> Result<bool, ClipboardError> {
+    let mut hash = String::new();
+    input
+        .read_to_string(&mut hash)
+        .map_err(|source| ClipboardError::Io {
+            tool: "stdin",
+            source,
+        })?;
+    std::thread::sleep(after);
+
+    let tool = Tool::detect().ok_or(ClipboardError::NoTool)?;
+    let current = tool.paste()?;
+    if hex(&sha256(current.as_bytes())) != hash.trim() {
+        return Ok(false);
+    }
+    tool.copy("")?;
+    Ok(true)
+}
+
+fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH").is_some_and(|path| {
+        std::env::split_paths(&path).any(|dir| is_executable(&dir.join(program)))
+    })
+}
+
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata()
+            .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_commands() {
+        for tool in [
+            Tool::Pbcopy,
+            Tool::WlCopy,
+            Tool::Xclip,
+            Tool::Xsel,
+            Tool::PowerShell,
+        ] {
+            assert!(!tool.copy_command().0.is_empty());
+            assert!(!tool.paste_command().0.is_empty());
+        }
+        assert!(!on_path("pwgen-x-no-such-tool"));
+    }
+}
@@ -0,0 +1,231 @@
+//! What a given entropy looks like in each mode (`compare --target`)
+//!
+//! Each mode is sized up from its default settings until its estimated
+//! entropy reaches the target, and one live example is drawn, so the cost of
+//! a format in characters, words or digits can be weighed against the others.
+
+use std::fmt;
+
+use rand::RngCore;
+use serde_json::{json, Value};
+use zeroize::Zeroizing;
+
+use crate::error::PwgenError;
+use crate::generators::{
+    CharSet, MarkovGenerator, PassphraseGenerator, PasswordGenerator, PinGenerator, SecureGenerator,
+};
+
+/// Largest size tried before a mode is reported as out of reach
+pub const MAX_SIZE: usize = 1024;
+
+/// One mode at the smallest size that reaches the target
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    /// Subcommand and options that produce it, e.g. `secure -S alphanumeric`
+    pub mode: &'static str,
+    pub size: usize,
+    /// What the size counts, e.g. "characters"
+    pub unit: &'static str,
+    pub bits: f64,
+    pub example: Zeroizing<String>,
+}
+
+/// Every mode sized for `target` bits
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub target: f64,
+    pub rows: Vec<Row>,
+    /// Modes that cannot reach the target within [`MAX_SIZE`]
+    pub unreachable: Vec<&'static str>,
+}
+
+type Build = fn(usize) -> Result<Box<dyn PasswordGenerator>, PwgenError>;
+
+/// The modes compared, with the unit their size is counted in
+const MODES: [(&str, &str, Build); 5] = [
+    ("secure", "characters", |n| {
+        Ok(Box::new(SecureGenerator::builder().length(n).build()?))
+    }),
+    ("secure -S alphanumeric", "characters", |n| {
+        Ok(Box::new(
+            SecureGenerator::builder()
+                .length(n)
+                .charset(CharSet::Alphanumeric)
+                .build()?,
+        ))
+    }),
+    ("normal", "characters", |n| {
+        Ok(Box::new(MarkovGenerator::builder().length(n).build()?))
+    }),
+    ("phrase", "words", |n| {
+        Ok(Box::new(PassphraseGenerator::new(
+            n,
+            "-".to_string(),
+            false,
+            true,
+        )?))
+    }),
+    ("pin", "digits", |n| Ok(Box::new(PinGenerator::new(n)?))),
+];
+
+impl Comparison {
+    pub fn new(target: f64, rng: &mut dyn RngCore) -> Self {
+        let mut comparison = Self {
+            target,
+            rows: Vec::new(),
+            unreachable: Vec::new(),
+        };
+        for (mode, unit, build) in MODES {
+            match smallest(target, build) {
+                Some((size, generator, bits)) => comparison.rows.push(Row {
+                    mode,
+                    size,
+                    unit,
+                    bits,
+                    example: generator.generate(rng).value,
+                }),
+                None => comparison.unreachable.push(mode),
+            }
+        }
+        comparison
+    }
+
+    pub fn to_json(&self) -> Value {
+        let rows: Vec<Value> = self
+            .rows
+            .iter()
+            .map(|row| {
+                json!({
+                    "mode": row.mode,
+                    "size": row.size,
+                    "unit": row.unit,
+                    "entropy_bits": row.bits,
+                    "example": row.example.as_str(),
+                })
+            })
+            .collect();
+        json!({
+            "target_bits": self.target,
+            "modes": rows,
+            "unreachable": self.unreachable,
+        })
+    }
+}
+
+/// The smallest size up to [`MAX_SIZE`] whose estimate reaches `target`,
+/// its generator and bits
+///
+/// Estimates grow with size, so the size is found by doubling, then
+/// bisection. Sizes `build` rejects count as too small until some size is
+/// accepted, such as lengths too short for required characters, and as too
+/// large after, such as lengths over a limit.
+pub fn smallest<E>(
+    target: f64,
+    build: impl Fn(usize) -> Result<Box<dyn PasswordGenerator>, E>,
+) -> Option<(usize, Box<dyn PasswordGenerator>, f64)> {
+    // None if rejected, Some(None) if short of the target
+    let probe = |size: usize| {
+        let generator = build(size).ok()?;
+        let bits = generator.estimated_entropy().filter(|&bits| bits >= target);
+        Some(bits.map(|bits| (generator, bits)))
+    };
+    let mut accepted = false;
+    let mut high = 1;
+    loop {
+        match probe(high) {
+            Some(Some(_)) => break,
+            None if accepted => break,
+            Some(None) => accepted = true,
+            None => {}
+        }
+        if high == MAX_SIZE {
+            return None;
+        }
+        high = (high * 2).min(MAX_SIZE);
+    }
+    let mut low = high / 2 + 1;
+    while low < high {
+        let mid = low + (high - low) / 2;
+        match probe(mid) {
+            Some(Some(_)) => high = mid,
+            None if accepted => high = mid,
+            _ => low = mid + 1,
+        }
+    }
+    let (generator, bits) = probe(low)??;
+    Some((low, generator, bits))
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sizes: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| format!("{} {}", row.size, row.unit))
+            .collect();
+        let mode_width = self.rows.iter().map(|r| r.mode.len()).max().unwrap_or(0);
+        let size_width = sizes.iter().map(String::len).max().unwrap_or(0);
+        writeln!(f, "Target: {} bits", self.target)?;
+        for (row, size) in self.rows.iter().zip(&sizes) {
+            writeln!(
+                f,
+                "  {:<mw$}  {:<sw$}  {:>5.1} bits  {}",
+                row.mode,
+                size,
+                row.bits,
+                row.example.as_str(),
+                mw = mode_width,
+                sw = size_width
+            )?;
+        }
+        for mode in &self.unreachable {
+            writeln!(f, "  {:<mw$}  out of reach", mode, mw = mode_width)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_comparison_sizes() {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let comparison = Comparison::new(90.0, &mut rng);
+        assert!(comparison.unreachable.is_empty());
+        for row in &comparison.rows {
+            assert!(row.bits >= 90.0, "{}", row.mode);
+            let smaller = MODES
+                .iter()
+                .find(|(mode, ..)| *mode == row.mode)
+                .map(|(_, _, build)| build(row.size - 1).unwrap().estimated_entropy().unwrap())
+                .unwrap();
+            assert!(smaller < 90.0, "{}", row.mode);
+        }
+        let pin = comparison.rows.iter().find(|r| r.mode == "pin").unwrap();
+        assert_eq!(pin.size, 28);
+        assert_eq!(pin.example.len(), 28);
+
+        let far = Comparison::new(20_000.0, &mut rng);
+        assert!(far.rows.is_empty());
+        assert_eq!(far.unreachable.len(), MODES.len());
+    }
+
+    #[test]
+    fn test_smallest_below_a_size_limit() {
+        // PINs longer than 40 digits are refused, as a length limit would
+        let build = |n: usize| {
+            if n > 40 {
+                return Err(PwgenError::LengthTooLarge { length: n, max: 40 });
+            }
+            Ok(Box::new(PinGenerator::new(n)?) as Box<dyn PasswordGenerator>)
+        };
+        let (size, _, bits) = smallest(128.0, build).unwrap();
+        assert_eq!(size, 39);
+        assert!(bits >= 128.0);
+        assert!(smallest(140.0, build).is_none());
+    }
+}
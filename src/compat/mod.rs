@@ -0,0 +1,193 @@
+//! Command lines of the tools pwgen-x replaces
+//!
+//! A compat mode is chosen by the name the binary is invoked as (e.g. a
+//! `pwgen` symlink) or by a leading `--<tool>-compat` flag; the remaining
+//! arguments are then read with that tool's syntax instead of pwgen-x's.
+
+use std::ffi::OsString;
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::error::PwgenError;
+
+pub mod pwgen;
+pub mod xkcdpass;
+
+/// A foreign command line that pwgen-x understands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compat {
+    /// The classic C `pwgen(1)`
+    Pwgen,
+    /// The Python `xkcdpass` passphrase generator
+    Xkcdpass,
+}
+
+impl Compat {
+    fn program(&self) -> &'static str {
+        match self {
+            Compat::Pwgen => "pwgen",
+            Compat::Xkcdpass => "xkcdpass",
+        }
+    }
+
+    fn flag(&self) -> &'static str {
+        match self {
+            Compat::Pwgen => "--pwgen-compat",
+            Compat::Xkcdpass => "--xkcdpass-compat",
+        }
+    }
+
+    /// The compat mode `args` (including argv[0]) ask for, with the tool's own arguments
+    pub fn detect(args: &[OsString]) -> Option<(Self, &[OsString])> {
+        let all = [Compat::Pwgen, Compat::Xkcdpass];
+        let invoked_as = args
+            .first()
+            .and_then(|arg0| Path::new(arg0).file_stem())
+            .and_then(|stem| stem.to_str());
+        if let Some(compat) = all.into_iter().find(|c| invoked_as == Some(c.program())) {
+            return Some((compat, args.get(1..).unwrap_or_default()));
+        }
+        let flag = args.get(1)?.to_str()?;
+        all.into_iter()
+            .find(|c| c.flag() == flag)
+            .map(|compat| (compat, &args[2..]))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CompatError {
+    #[error("unknown option '{0}'")]
+    UnknownOption(String),
+    #[error("option '{0}' needs a value")]
+    MissingValue(String),
+    #[error("invalid number '{0}'")]
+    InvalidNumber(String),
+    #[error("invalid value for {0}")]
+    InvalidValue(String),
+    #[error("unexpected argument '{0}'")]
+    UnexpectedArgument(String),
+    #[error("option '{0}' is not supported by pwgen-x")]
+    Unsupported(String),
+    #[error("could not generate enough passwords without ambiguous characters")]
+    Exhausted,
+    #[error(transparent)]
+    Invalid(#[from] PwgenError),
+}
+
+/// An option a foreign command line accepts, named by `key`
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct OptionSpec {
+    pub key: &'static str,
+    pub short: Option<char>,
+    pub long: Option<&'static str>,
+    pub takes_value: bool,
+}
+
+/// One item of a parsed command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Parsed {
+    Option {
+        key: &'static str,
+        value: Option<String>,
+    },
+    Positional(String),
+}
+
+/// Split `args` getopt style: bundled short flags (`-sBy`), attached or separate
+/// values (`-N5`, `-N 5`), `--long=value` and `--long value`, and `--` to end options
+pub(crate) fn getopt(args: &[OsString], specs: &[OptionSpec]) -> Result<Vec<Parsed>, CompatError> {
+    let mut parsed = Vec::new();
+    let mut args = args.iter().map(|arg| arg.to_string_lossy().into_owned());
+    let mut only_positional = false;
+
+    while let Some(arg) = args.next() {
+        if only_positional || arg == "-" || !arg.starts_with('-') {
+            parsed.push(Parsed::Positional(arg));
+        } else if arg == "--" {
+            only_positional = true;
+        } else if let Some(long) = arg.strip_prefix("--") {
+            let (name, inline) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (long, None),
+            };
+            let spec = specs
+                .iter()
+                .find(|spec| spec.long == Some(name))
+                .ok_or_else(|| CompatError::UnknownOption(arg.clone()))?;
+            let value = match (spec.takes_value, inline) {
+                (true, Some(value)) => Some(value),
+                (true, None) => Some(
+                    args.next()
+                        .ok_or_else(|| CompatError::MissingValue(arg.clone()))?,
+                ),
+                (false, Some(_)) => return Err(CompatError::UnexpectedArgument(arg.clone())),
+                (false, None) => None,
+            };
+            parsed.push(Parsed::Option {
+                key: spec.key,
+                value,
+            });
+        } else {
+            let flags = &arg[1..];
+            for (i, short) in flags.char_indices() {
+                let spec = specs
+                    .iter()
+                    .find(|spec| spec.short == Some(short))
+                    .ok_or_else(|| CompatError::UnknownOption(format!("-{}", short)))?;
+                if !spec.takes_value {
+                    parsed.push(Parsed::Option {
+                        key: spec.key,
+                        value: None,
+                    });
+                    continue;
+                }
+                let rest = &flags[i + short.len_utf8()..];
+                let value = if rest.is_empty() {
+                    args.next()
+                        .ok_or_else(|| CompatError::MissingValue(format!("-{}", short)))?
+                } else {
+                    rest.to_string()
+                };
+                parsed.push(Parsed::Option {
+                    key: spec.key,
+                    value: Some(value),
+                });
+                break;
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+/// Parse a count or length
+pub(crate) fn number(text: &str) -> Result<usize, CompatError> {
+    text.parse()
+        .map_err(|_| CompatError::InvalidNumber(text.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<OsString> {
+        line.split_whitespace().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_detect() {
+        let argv = args("/usr/bin/pwgen -s 16");
+        let (compat, rest) = Compat::detect(&argv).unwrap();
+        assert_eq!(compat, Compat::Pwgen);
+        assert_eq!(rest, &argv[1..]);
+
+        let argv = args("pwgen-x --pwgen-compat -B 12");
+        assert_eq!(Compat::detect(&argv), Some((Compat::Pwgen, &argv[2..])));
+        assert_eq!(Compat::detect(&args("pwgen-x -n 3 --pwgen-compat")), None);
+        assert_eq!(
+            Compat::detect(&args("xkcdpass -n 4")).map(|(c, _)| c),
+            Some(Compat::Xkcdpass)
+        );
+        assert_eq!(Compat::detect(&args("pwgen-x secure")), None);
+    }
+}
@@ -0,0 +1,353 @@
+//! The classic `pwgen [OPTIONS] [pw_length] [num_pw]` command line
+//!
+//! Options map to the closest pwgen-x generator: pronounceable passwords come
+//! from the Markov generator, `-s` (and anything that implies it in pwgen,
+//! such as `-v`, `-r` or a length below 5) from the secure generator with the
+//! alphabet pwgen would use. Output is bare passwords, in columns on a
+//! terminal as pwgen prints them.
+
+use std::ffi::OsString;
+
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+use super::{getopt, number, CompatError, OptionSpec, Parsed};
+use crate::error::PwgenError;
+use crate::generators::{
+    MarkovGenerator, PasswordGenerator, PasswordGeneratorExt, SecureGenerator,
+    MAX_ATTEMPTS_PER_PASSWORD,
+};
+
+pub const DEFAULT_LENGTH: usize = 8;
+
+/// Characters `-B` leaves out, as in pwgen
+pub const AMBIGUOUS: &str = "B8G6I1l0OQDS5Z2";
+
+/// Characters `-v` leaves out, as in pwgen
+pub const VOWELS: &str = "01aeiouyAEIOUY";
+
+/// Symbols `-y` adds in secure mode
+pub const SYMBOLS: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+/// Rows printed in column mode when no count is given
+const ROWS: usize = 20;
+
+const fn option(
+    key: &'static str,
+    short: char,
+    long: Option<&'static str>,
+    takes_value: bool,
+) -> OptionSpec {
+    OptionSpec {
+        key,
+        short: Some(short),
+        long,
+        takes_value,
+    }
+}
+
+const OPTIONS: &[OptionSpec] = &[
+    option("capitalize", 'c', Some("capitalize"), false),
+    option("no-capitalize", 'A', Some("no-capitalize"), false),
+    option("numerals", 'n', Some("numerals"), false),
+    option("no-numerals", '0', Some("no-numerals"), false),
+    option("symbols", 'y', Some("symbols"), false),
+    option("remove-chars", 'r', Some("remove-chars"), true),
+    option("secure", 's', Some("secure"), false),
+    option("ambiguous", 'B', Some("ambiguous"), false),
+    option("help", 'h', Some("help"), false),
+    option("num-passwords", 'N', Some("num-passwords"), true),
+    option("columns", 'C', None, false),
+    option("one-column", '1', None, false),
+    option("no-vowels", 'v', Some("no-vowels"), false),
+    option("alt-phonics", 'a', Some("alt-phonics"), false),
+    option("sha1", 'H', Some("sha1"), true),
+];
+
+pub const USAGE: &str = "\
+Usage: pwgen [ OPTIONS ] [ pw_length ] [ num_pw ]
+
+Options supported by pwgen-x in pwgen compatibility mode:
+  -c or --capitalize
+\tInclude at least one capital letter in the password
+  -A or --no-capitalize
+\tDon't include capital letters in the password
+  -n or --numerals
+\tInclude at least one number in the password
+  -0 or --no-numerals
+\tDon't include numbers in the password
+  -y or --symbols
+\tInclude at least one special symbol in the password
+  -r <chars> or --remove-chars=<chars>
+\tRemove characters from the set of characters to generate passwords
+  -s or --secure
+\tGenerate completely random passwords
+  -B or --ambiguous
+\tDon't include ambiguous characters in the password
+  -h or --help
+\tPrint a help message
+  -N <num> or --num-passwords=<num>
+\tNumber of passwords to generate
+  -C
+\tPrint the generated passwords in columns
+  -1
+\tDon't print the generated passwords in columns
+  -v or --no-vowels
+\tDo not use any vowels so as to avoid accidental nasty words
+";
+
+/// A parsed pwgen command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PwgenOptions {
+    pub length: usize,
+    pub count: Option<usize>,
+    pub secure: bool,
+    pub capitalize: bool,
+    pub numerals: bool,
+    pub symbols: bool,
+    pub no_ambiguous: bool,
+    pub no_vowels: bool,
+    pub remove: String,
+    /// `Some(true)` for `-C`, `Some(false)` for `-1`, otherwise columns only on a terminal
+    pub columns: Option<bool>,
+    pub help: bool,
+}
+
+impl Default for PwgenOptions {
+    fn default() -> Self {
+        Self {
+            length: DEFAULT_LENGTH,
+            count: None,
+            secure: false,
+            capitalize: true,
+            numerals: true,
+            symbols: false,
+            no_ambiguous: false,
+            no_vowels: false,
+            remove: String::new(),
+            columns: None,
+            help: false,
+        }
+    }
+}
+
+impl PwgenOptions {
+    /// Parse pwgen's arguments (without argv[0]), getopt style: `-sBy`, `-N5`, `--num-passwords=5`
+    pub fn parse(args: &[OsString]) -> Result<Self, CompatError> {
+        let mut options = Self::default();
+        let mut positional = Vec::new();
+        for item in getopt(args, OPTIONS)? {
+            let (key, value) = match item {
+                Parsed::Positional(arg) => {
+                    positional.push(arg);
+                    continue;
+                }
+                Parsed::Option { key, value } => (key, value.unwrap_or_default()),
+            };
+            match key {
+                "capitalize" => options.capitalize = true,
+                "no-capitalize" => options.capitalize = false,
+                "numerals" => options.numerals = true,
+                "no-numerals" => options.numerals = false,
+                "symbols" => options.symbols = true,
+                "secure" => options.secure = true,
+                "ambiguous" => options.no_ambiguous = true,
+                "help" => options.help = true,
+                "columns" => options.columns = Some(true),
+                "one-column" => options.columns = Some(false),
+                // pwgen-x has a single phonetic model, so the alternative is the default
+                "alt-phonics" => {}
+                "no-vowels" => {
+                    options.no_vowels = true;
+                    options.secure = true;
+                }
+                "remove-chars" => {
+                    options.remove.push_str(&value);
+                    options.secure = true;
+                }
+                "num-passwords" => options.count = Some(number(&value)?),
+                "sha1" => return Err(CompatError::Unsupported("-H/--sha1".to_string())),
+                _ => unreachable!("every key is listed in OPTIONS"),
+            }
+        }
+
+        let mut positional = positional.into_iter();
+        if let Some(length) = positional.next() {
+            options.length = number(&length)?;
+        }
+        if let Some(count) = positional.next() {
+            options.count = Some(number(&count)?);
+        }
+        if let Some(extra) = positional.next() {
+            return Err(CompatError::UnexpectedArgument(extra));
+        }
+        if options.length < 5 {
+            // Too short for pronounceable output; pwgen switches to random passwords too
+            options.secure = true;
+        }
+        Ok(options)
+    }
+
+    /// Alphabet of a secure-mode password
+    pub fn alphabet(&self) -> String {
+        let mut alphabet = String::from("abcdefghijklmnopqrstuvwxyz");
+        if self.capitalize {
+            alphabet.push_str("ABCDEFGHIJKLMNOPQRSTUVWXYZ");
+        }
+        if self.numerals {
+            alphabet.push_str("0123456789");
+        }
+        if self.symbols {
+            alphabet.push_str(SYMBOLS);
+        }
+        alphabet.retain(|c| {
+            !(self.no_ambiguous && AMBIGUOUS.contains(c)
+                || self.no_vowels && VOWELS.contains(c)
+                || self.remove.contains(c))
+        });
+        alphabet
+    }
+
+    pub fn generator(&self) -> Result<Box<dyn PasswordGenerator>, PwgenError> {
+        if self.secure {
+            let generator = SecureGenerator::builder()
+                .length(self.length)
+                .alphabet(self.alphabet())
+                .build()?;
+            return Ok(Box::new(generator));
+        }
+        let generator = MarkovGenerator::builder()
+            .length(self.length)
+            .digits(self.numerals)
+            .symbols(self.symbols)
+            .capitalize(self.capitalize)
+            .build()?;
+        Ok(Box::new(generator))
+    }
+
+    /// Passwords printed when no count is given: a screenful in columns, otherwise one
+    pub fn default_count(&self, columns: bool, width: usize) -> usize {
+        if columns {
+            self.per_line(width) * ROWS
+        } else {
+            1
+        }
+    }
+
+    fn per_line(&self, width: usize) -> usize {
+        (width / (self.length + 1)).max(1)
+    }
+
+    pub fn generate(
+        &self,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<Zeroizing<String>>, CompatError> {
+        let generator = self.generator()?;
+        // The Markov model has no notion of ambiguous characters, so drop candidates that use them
+        let passwords: Vec<_> = generator
+            .iter(rng)
+            .take(count.saturating_mul(MAX_ATTEMPTS_PER_PASSWORD))
+            .map(|p| p.value)
+            .filter(|value| !self.no_ambiguous || !value.contains(|c| AMBIGUOUS.contains(c)))
+            .take(count)
+            .collect();
+        if passwords.len() < count {
+            return Err(CompatError::Exhausted);
+        }
+        Ok(passwords)
+    }
+
+    /// Lines to print: passwords separated by spaces in columns, or one per line
+    pub fn layout(
+        &self,
+        passwords: &[Zeroizing<String>],
+        columns: bool,
+        width: usize,
+    ) -> Vec<Zeroizing<String>> {
+        let per_line = if columns { self.per_line(width) } else { 1 };
+        passwords
+            .chunks(per_line)
+            .map(|line| {
+                Zeroizing::new(
+                    line.iter()
+                        .map(|p| p.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn parse(line: &str) -> Result<PwgenOptions, CompatError> {
+        let args: Vec<OsString> = line.split_whitespace().map(OsString::from).collect();
+        PwgenOptions::parse(&args)
+    }
+
+    #[test]
+    fn test_parse() {
+        let options = parse("-B -A -0 -y -s 16 20").unwrap();
+        assert_eq!(options.length, 16);
+        assert_eq!(options.count, Some(20));
+        assert!(options.secure && options.no_ambiguous && options.symbols);
+        assert!(!options.capitalize && !options.numerals);
+        assert_eq!(parse("-sBAy0 16 20").unwrap(), options);
+        assert_eq!(
+            parse("--secure --ambiguous --no-capitalize --symbols --no-numerals 16 20").unwrap(),
+            options
+        );
+
+        let options = parse("-N5 -r xyz --remove-chars=q 12").unwrap();
+        assert_eq!(options.count, Some(5));
+        assert_eq!(options.remove, "xyzq");
+        assert!(options.secure, "-r implies random passwords");
+        assert!(parse("4").unwrap().secure);
+
+        assert_eq!(
+            parse("-x"),
+            Err(CompatError::UnknownOption("-x".to_string()))
+        );
+        assert_eq!(
+            parse("-N"),
+            Err(CompatError::MissingValue("-N".to_string()))
+        );
+        assert!(matches!(parse("-H file"), Err(CompatError::Unsupported(_))));
+        assert!(matches!(
+            parse("8 1 1"),
+            Err(CompatError::UnexpectedArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_secure_alphabet() {
+        let options = parse("-s -B -A -0 -y").unwrap();
+        let alphabet = options.alphabet();
+        assert!(alphabet.contains('a') && alphabet.contains('#'));
+        assert!(!alphabet.contains('l') && !alphabet.contains('A') && !alphabet.contains('7'));
+        assert!(!parse("-s -v").unwrap().alphabet().contains('e'));
+    }
+
+    #[test]
+    fn test_generate_and_layout() {
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let options = parse("-B 10").unwrap();
+        let passwords = options.generate(6, &mut rng).unwrap();
+        assert!(passwords
+            .iter()
+            .all(|p| p.chars().count() == 10 && !p.contains(|c| AMBIGUOUS.contains(c))));
+
+        assert_eq!(options.default_count(false, 80), 1);
+        assert_eq!(options.default_count(true, 80), 7 * ROWS);
+        let lines = options.layout(&passwords, true, 25);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].len(), 21);
+        assert_eq!(options.layout(&passwords, false, 25).len(), 6);
+    }
+}
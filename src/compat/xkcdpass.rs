@@ -0,0 +1,300 @@
+//! The `xkcdpass` command line (Python xkcdpass)
+//!
+//! Words are drawn uniformly from the list after xkcdpass's `--min`/`--max`
+//! length filter, so the entropy is `numwords * log2(list size)`, plus one bit
+//! per word for `--case random`. The default list is the embedded EFF list
+//! rather than xkcdpass's own.
+
+use std::ffi::OsString;
+
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+use zeroize::Zeroizing;
+
+use super::{getopt, number, CompatError, OptionSpec, Parsed};
+use crate::wordlist::{self, Wordlist, WordlistError};
+
+/// Delimiters `-R` chooses from unless `-D` gives others, as in xkcdpass
+pub const VALID_DELIMITERS: &str = "0123456789!$%^&*-_+=:|~?/.;";
+
+const fn option(
+    key: &'static str,
+    short: Option<char>,
+    long: &'static str,
+    takes_value: bool,
+) -> OptionSpec {
+    OptionSpec {
+        key,
+        short,
+        long: Some(long),
+        takes_value,
+    }
+}
+
+const OPTIONS: &[OptionSpec] = &[
+    option("wordfile", Some('w'), "wordfile", true),
+    option("min", None, "min", true),
+    option("max", None, "max", true),
+    option("numwords", Some('n'), "numwords", true),
+    option("count", Some('c'), "count", true),
+    option("delimiter", Some('d'), "delimiter", true),
+    option("random-delimiters", Some('R'), "random-delimiters", false),
+    option("valid-delimiters", Some('D'), "valid-delimiters", true),
+    option("separator", Some('s'), "separator", true),
+    option("case", Some('C'), "case", true),
+    option("verbose", Some('V'), "verbose", false),
+    option("allow-weak-rng", None, "allow-weak-rng", false),
+    option("help", Some('h'), "help", false),
+    option("interactive", Some('i'), "interactive", false),
+    option("valid-chars", Some('v'), "valid-chars", true),
+    option("acrostic", Some('a'), "acrostic", true),
+];
+
+pub const USAGE: &str = "\
+usage: xkcdpass [-h] [-w WORDFILE] [--min MIN_LENGTH] [--max MAX_LENGTH]
+                [-n NUMWORDS] [-V] [-c COUNT] [-d DELIM] [-R] [-D DELIMITERS]
+                [-s SEP] [-C CASE]
+
+Options supported by pwgen-x in xkcdpass compatibility mode:
+  -w, --wordfile WORDFILE   wordlist name (see `pwgen-x wordlist list`) or file
+  --min MIN_LENGTH          shortest word to use (default 5)
+  --max MAX_LENGTH          longest word to use (default 9)
+  -n, --numwords NUMWORDS   words per passphrase (default 6)
+  -V, --verbose             report the list size and entropy on stderr
+  -c, --count COUNT         passphrases to generate (default 1)
+  -d, --delimiter DELIM     separator between words (default a space)
+  -R, --random-delimiters   use a random delimiter between each pair of words
+  -D, --valid-delimiters    characters -R chooses from
+  -s, --separator SEP       separator between passphrases (default a newline)
+  -C, --case CASE           alternating, upper, lower (default), random,
+                            first, capitalize or as-is
+";
+
+/// Letter case applied to the chosen words (`-C`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
+    AsIs,
+    Lower,
+    Upper,
+    /// Every other word in upper case
+    Alternating,
+    /// Each word in upper or lower case at random, one extra bit per word
+    Random,
+    /// First letter of every word in upper case
+    First,
+    /// First letter of the passphrase in upper case
+    Capitalize,
+}
+
+impl Case {
+    fn parse(name: &str) -> Result<Self, CompatError> {
+        Ok(match name {
+            "as-is" => Case::AsIs,
+            "lower" => Case::Lower,
+            "upper" => Case::Upper,
+            "alternating" => Case::Alternating,
+            "random" => Case::Random,
+            "first" => Case::First,
+            "capitalize" => Case::Capitalize,
+            _ => return Err(CompatError::InvalidValue(format!("--case {}", name))),
+        })
+    }
+}
+
+/// A parsed xkcdpass command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XkcdpassOptions {
+    pub wordfile: Option<String>,
+    pub min_length: usize,
+    pub max_length: usize,
+    pub numwords: usize,
+    pub count: usize,
+    pub delimiter: String,
+    /// Characters to pick a delimiter from for each gap, with `-R`
+    pub random_delimiters: Option<String>,
+    pub separator: String,
+    pub case: Case,
+    pub verbose: bool,
+    pub help: bool,
+}
+
+impl Default for XkcdpassOptions {
+    fn default() -> Self {
+        Self {
+            wordfile: None,
+            min_length: 5,
+            max_length: 9,
+            numwords: 6,
+            count: 1,
+            delimiter: " ".to_string(),
+            random_delimiters: None,
+            separator: "\n".to_string(),
+            case: Case::Lower,
+            verbose: false,
+            help: false,
+        }
+    }
+}
+
+impl XkcdpassOptions {
+    /// Parse xkcdpass's arguments (without argv[0])
+    pub fn parse(args: &[OsString]) -> Result<Self, CompatError> {
+        let mut options = Self::default();
+        let mut valid_delimiters = None;
+        let mut random = false;
+        for item in getopt(args, OPTIONS)? {
+            let (key, value) = match item {
+                Parsed::Positional(arg) => return Err(CompatError::UnexpectedArgument(arg)),
+                Parsed::Option { key, value } => (key, value.unwrap_or_default()),
+            };
+            match key {
+                "wordfile" => options.wordfile = Some(value),
+                "min" => options.min_length = number(&value)?,
+                "max" => options.max_length = number(&value)?,
+                "numwords" => options.numwords = number(&value)?,
+                "count" => options.count = number(&value)?,
+                "delimiter" => options.delimiter = value,
+                "random-delimiters" => random = true,
+                "valid-delimiters" => valid_delimiters = Some(value),
+                "separator" => options.separator = value,
+                "case" => options.case = Case::parse(&value)?,
+                "verbose" => options.verbose = true,
+                "help" => options.help = true,
+                // thread_rng is always a CSPRNG, so there is no weak fallback to allow
+                "allow-weak-rng" => {}
+                "interactive" => return Err(CompatError::Unsupported("-i/--interactive".into())),
+                "valid-chars" => return Err(CompatError::Unsupported("-v/--valid-chars".into())),
+                "acrostic" => return Err(CompatError::Unsupported("-a/--acrostic".into())),
+                _ => unreachable!("every key is listed in OPTIONS"),
+            }
+        }
+        if random {
+            options.random_delimiters =
+                Some(valid_delimiters.unwrap_or_else(|| VALID_DELIMITERS.to_string()));
+        }
+        Ok(options)
+    }
+
+    /// The wordlist after the `--min`/`--max` filter
+    pub fn wordlist(&self) -> Result<Wordlist, WordlistError> {
+        let list = match &self.wordfile {
+            Some(name) => wordlist::resolve(name)?,
+            None => Wordlist::builtin(),
+        };
+        let words = list
+            .words()
+            .iter()
+            .filter(|w| (self.min_length..=self.max_length).contains(&w.chars().count()))
+            .cloned()
+            .collect();
+        Wordlist::from_words(list.name(), words)
+    }
+
+    /// Bits of entropy of one passphrase drawn from `list`
+    pub fn entropy_bits(&self, list: &Wordlist) -> f64 {
+        let mut per_word = list.entropy_per_word();
+        if self.case == Case::Random {
+            per_word += 1.0;
+        }
+        let mut bits = self.numwords as f64 * per_word;
+        if let Some(delimiters) = &self.random_delimiters {
+            let distinct = delimiters.chars().count() as f64;
+            bits += self.numwords.saturating_sub(1) as f64 * distinct.log2();
+        }
+        bits
+    }
+
+    /// One passphrase from `list`
+    pub fn generate(&self, list: &Wordlist, rng: &mut dyn RngCore) -> Zeroizing<String> {
+        let delimiters: Vec<char> = self
+            .random_delimiters
+            .as_deref()
+            .map(|d| d.chars().collect())
+            .unwrap_or_default();
+        let mut passphrase = Zeroizing::new(String::new());
+        for i in 0..self.numwords {
+            if i > 0 {
+                match delimiters.choose(rng) {
+                    Some(&delimiter) => passphrase.push(delimiter),
+                    None => passphrase.push_str(&self.delimiter),
+                }
+            }
+            let word = Zeroizing::new(
+                list.words()
+                    .choose(rng)
+                    .expect("lists are never empty")
+                    .clone(),
+            );
+            let upper = match self.case {
+                Case::Upper => true,
+                Case::Alternating => i % 2 == 1,
+                Case::Random => rng.gen_bool(0.5),
+                _ => false,
+            };
+            let first = match self.case {
+                Case::First => true,
+                Case::Capitalize => i == 0,
+                _ => false,
+            };
+            let mut chars = word.chars();
+            if upper {
+                passphrase.push_str(&word.to_uppercase());
+            } else if self.case == Case::AsIs {
+                passphrase.push_str(&word);
+            } else {
+                if first {
+                    passphrase.extend(chars.next().map(|c| c.to_uppercase()).into_iter().flatten());
+                }
+                passphrase.push_str(&chars.as_str().to_lowercase());
+            }
+        }
+        passphrase
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn parse(line: &str) -> Result<XkcdpassOptions, CompatError> {
+        let args: Vec<OsString> = line.split_whitespace().map(OsString::from).collect();
+        XkcdpassOptions::parse(&args)
+    }
+
+    #[test]
+    fn test_parse() {
+        let options = parse("-n 4 -d . --min 4 --max=6 -C first -c3").unwrap();
+        assert_eq!(options.numwords, 4);
+        assert_eq!(options.delimiter, ".");
+        assert_eq!((options.min_length, options.max_length), (4, 6));
+        assert_eq!(options.case, Case::First);
+        assert_eq!(options.count, 3);
+        assert_eq!(
+            parse("-R -D +=").unwrap().random_delimiters.as_deref(),
+            Some("+=")
+        );
+        assert!(matches!(
+            parse("-C shouty"),
+            Err(CompatError::InvalidValue(_))
+        ));
+        assert!(matches!(parse("-i"), Err(CompatError::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_generate() {
+        let options = parse("-n 4 -d . --min 4 --max 6 -C first").unwrap();
+        let list = options.wordlist().unwrap();
+        assert!(list.words().iter().all(|w| (4..=6).contains(&w.len())));
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let passphrase = options.generate(&list, &mut rng);
+        let words: Vec<&str> = passphrase.split('.').collect();
+        assert_eq!(words.len(), 4);
+        assert!(words.iter().all(|w| w.starts_with(char::is_uppercase)));
+        assert!((options.entropy_bits(&list) - 4.0 * list.entropy_per_word()).abs() < 1e-9);
+
+        let random = parse("-n 4 -C random").unwrap();
+        assert!((random.entropy_bits(&list) - 4.0 * (list.entropy_per_word() + 1.0)).abs() < 1e-9);
+    }
+}
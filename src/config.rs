@@ -0,0 +1,290 @@
+//! User configuration file (`~/.config/pwgen-rs/config.toml`)
+//!
+//! Only the subset of TOML needed for pwgen-rs settings is supported:
+//! `[section]` headers, `key = value` pairs with string, integer and boolean
+//! values, and `#` comments. Keys are flattened to dotted paths, so
+//! `[alias]` followed by `wifi = "..."` is stored as `alias.wifi`.
+//!
+//! `[defaults]` and `[profile.<name>]` sections hold option defaults; see
+//! [`Cli::parse_with_config`](crate::cli::Cli::parse_with_config).
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+}
+
+impl Value {
+    /// The value as it would be typed on the command line
+    pub fn to_arg(&self) -> String {
+        match self {
+            Value::String(s) => s.clone(),
+            Value::Integer(n) => n.to_string(),
+            Value::Boolean(b) => b.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("{path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("config line {line}: {message}")]
+    Syntax { line: usize, message: String },
+}
+
+#[derive(Debug, Default)]
+pub struct Config {
+    values: BTreeMap<String, Value>,
+}
+
+impl Config {
+    /// Default config file location, honoring `XDG_CONFIG_HOME`
+    pub fn default_path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(base.join("pwgen-rs").join("config.toml"))
+    }
+
+    /// Load the config from the default location; a missing file is an empty config
+    pub fn load() -> Result<Self, ConfigError> {
+        match Self::default_path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, ConfigError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(source) => Err(ConfigError::Io {
+                path: path.to_path_buf(),
+                source,
+            }),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let mut values = BTreeMap::new();
+        let mut section = String::new();
+
+        for (idx, raw) in text.lines().enumerate() {
+            let line_no = idx + 1;
+            let syntax = |message: &str| ConfigError::Syntax {
+                line: line_no,
+                message: message.to_string(),
+            };
+            let line = strip_comment(raw).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[') {
+                let name = header
+                    .strip_suffix(']')
+                    .ok_or_else(|| syntax("unterminated section header"))?
+                    .trim();
+                if name.is_empty() {
+                    return Err(syntax("empty section name"));
+                }
+                section = name.to_string();
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| syntax("expected `key = value`"))?;
+            let key = unquote_key(key.trim()).ok_or_else(|| syntax("invalid key"))?;
+            let value = parse_value(value.trim()).ok_or_else(|| syntax("invalid value"))?;
+
+            let full_key = if section.is_empty() {
+                key
+            } else {
+                format!("{}.{}", section, key)
+            };
+            values.insert(full_key, value);
+        }
+
+        Ok(Self { values })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.values.get(key)
+    }
+
+    /// Key/value pairs of a section, with the section prefix stripped
+    pub fn entries<'a>(&'a self, section: &str) -> impl Iterator<Item = (&'a str, &'a Value)> {
+        let prefix = format!("{}.", section);
+        self.values
+            .iter()
+            .filter_map(move |(key, value)| Some((key.strip_prefix(&prefix)?, value)))
+    }
+
+    /// Names of the `[profile.<name>]` sections, sorted
+    pub fn profiles(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .values
+            .keys()
+            .filter_map(|key| key.strip_prefix("profile.")?.split_once('.'))
+            .map(|(name, _)| name)
+            .collect();
+        names.dedup();
+        names
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        match self.get(key)? {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+/// Remove a trailing `#` comment, ignoring `#` inside quoted strings
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    for (i, c) in line.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('#', None) => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn unquote_key(key: &str) -> Option<String> {
+    if let Some(inner) = key.strip_prefix('"').and_then(|k| k.strip_suffix('"')) {
+        return Some(inner.to_string());
+    }
+    let valid = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.');
+    valid.then(|| key.to_string())
+}
+
+fn parse_value(value: &str) -> Option<Value> {
+    if let Some(inner) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        return unescape(inner).map(Value::String);
+    }
+    if let Some(inner) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        return Some(Value::String(inner.to_string()));
+    }
+    match value {
+        "true" => Some(Value::Boolean(true)),
+        "false" => Some(Value::Boolean(false)),
+        _ => value.replace('_', "").parse().ok().map(Value::Integer),
+    }
+}
+
+/// Basic string escapes: `\"`, `\\`, `\n`, `\t`
+fn unescape(s: &str) -> Option<String> {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next()? {
+            '"' => out.push('"'),
+            '\\' => out.push('\\'),
+            'n' => out.push('\n'),
+            't' => out.push('\t'),
+            _ => return None,
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_values_and_sections() {
+        let config = Config::parse(
+            r#"
+            # top-level settings
+            default_command = "secure 20 --no-ambiguous"  # trailing comment
+            count = 3
+            quiet = false
+
+            [alias]
+            wifi = 'secure 63 --charset alphanumeric-symbols'
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.get_str("default_command"),
+            Some("secure 20 --no-ambiguous")
+        );
+        assert_eq!(config.get("count"), Some(&Value::Integer(3)));
+        assert_eq!(config.get("quiet"), Some(&Value::Boolean(false)));
+        assert_eq!(
+            config.get_str("alias.wifi"),
+            Some("secure 63 --charset alphanumeric-symbols")
+        );
+    }
+
+    #[test]
+    fn test_section_entries() {
+        let config = Config::parse("[alias]\na = \"x\"\nb = \"y\"\n[other]\nc = 1\n").unwrap();
+        let names: Vec<&str> = config.entries("alias").map(|(k, _)| k).collect();
+        assert_eq!(names, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_profiles() {
+        let config = Config::parse(
+            "[defaults]\nlength = 20\n[profile.work]\ncharset = \"alphanumeric\"\n\
+             no_ambiguous = true\n[profile.home]\nwords = 5\n",
+        )
+        .unwrap();
+        assert_eq!(config.profiles(), vec!["home", "work"]);
+        let work: Vec<(&str, String)> = config
+            .entries("profile.work")
+            .map(|(k, v)| (k, v.to_arg()))
+            .collect();
+        assert_eq!(
+            work,
+            vec![
+                ("charset", "alphanumeric".to_string()),
+                ("no_ambiguous", "true".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hash_inside_string_is_not_a_comment() {
+        let config = Config::parse(r#"sep = "a#b""#).unwrap();
+        assert_eq!(config.get_str("sep"), Some("a#b"));
+    }
+
+    #[test]
+    fn test_syntax_error_reports_line() {
+        let err = Config::parse("ok = 1\nnot a pair\n").unwrap_err();
+        assert!(matches!(err, ConfigError::Syntax { line: 2, .. }));
+    }
+
+    #[test]
+    fn test_missing_file_is_empty() {
+        let config = Config::load_from(Path::new("/nonexistent/pwgen-rs/config.toml")).unwrap();
+        assert!(config.get("default_command").is_none());
+    }
+}
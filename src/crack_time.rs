@@ -0,0 +1,138 @@
+//! Time-to-crack estimates (`--crack-time`)
+//!
+//! Converts entropy into the average time an attacker needs to find the value
+//! by brute force (half the keyspace) at the attacker model's guess rate, e.g.
+//! "≈ 3,000 years at 10¹² guesses/s".
+
+use std::fmt;
+
+use crate::entropy::AttackerModel;
+
+const MINUTE: f64 = 60.0;
+const HOUR: f64 = 60.0 * MINUTE;
+const DAY: f64 = 24.0 * HOUR;
+const YEAR: f64 = 365.25 * DAY;
+
+/// Average brute-force time for a value with `bits` of entropy
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CrackTime {
+    pub seconds: f64,
+    pub guesses_per_second: f64,
+}
+
+impl CrackTime {
+    pub fn new(bits: f64, attacker: AttackerModel) -> Self {
+        let guesses_per_second = attacker.guesses_per_second();
+        Self {
+            seconds: 2f64.powf(bits - 1.0) / guesses_per_second,
+            guesses_per_second,
+        }
+    }
+
+    /// The duration alone, e.g. "≈ 3,000 years"
+    pub fn duration(&self) -> String {
+        let s = self.seconds;
+        if s < 1.0 {
+            return "less than a second".to_string();
+        }
+        let (value, unit) = match s {
+            s if s < MINUTE => (s, "second"),
+            s if s < HOUR => (s / MINUTE, "minute"),
+            s if s < DAY => (s / HOUR, "hour"),
+            s if s < YEAR => (s / DAY, "day"),
+            s => (s / YEAR, "year"),
+        };
+        let value = round_significant(value, 2);
+        let plural = if value == 1.0 { "" } else { "s" };
+        format!("≈ {} {}{}", amount(value), unit, plural)
+    }
+}
+
+impl fmt::Display for CrackTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {} guesses/s",
+            self.duration(),
+            power_of_ten(self.guesses_per_second.log10().round() as i32)
+        )
+    }
+}
+
+/// Round to `digits` significant digits
+fn round_significant(value: f64, digits: i32) -> f64 {
+    let magnitude = 10f64.powi(value.log10().floor() as i32 - digits + 1);
+    (value / magnitude).round() * magnitude
+}
+
+/// Readable quantity: `1.5`, `3,000`, `42 million`, `10²⁰`
+fn amount(value: f64) -> String {
+    let number = |v: f64| {
+        if v < 10.0 && v.fract() != 0.0 {
+            format!("{:.1}", v)
+        } else {
+            group_digits(v)
+        }
+    };
+    match value {
+        v if v >= 1e12 => power_of_ten(v.log10().floor() as i32),
+        v if v >= 1e9 => format!("{} billion", number(v / 1e9)),
+        v if v >= 1e6 => format!("{} million", number(v / 1e6)),
+        v => number(v),
+    }
+}
+
+/// `1234567` -> `"1,234,567"`
+fn group_digits(value: f64) -> String {
+    let digits = format!("{:.0}", value);
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+/// `12` -> `"10¹²"`
+fn power_of_ten(exponent: i32) -> String {
+    const SUPERSCRIPTS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+    let mut text = String::from("10");
+    if exponent < 0 {
+        text.push('⁻');
+    }
+    for digit in exponent.unsigned_abs().to_string().bytes() {
+        text.push(SUPERSCRIPTS[(digit - b'0') as usize]);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duration_units() {
+        let time = |seconds| CrackTime {
+            seconds,
+            guesses_per_second: 1e12,
+        };
+        assert_eq!(time(0.2).duration(), "less than a second");
+        assert_eq!(time(1.0).duration(), "≈ 1 second");
+        assert_eq!(time(90.0).duration(), "≈ 1.5 minutes");
+        assert_eq!(time(3.0 * DAY).duration(), "≈ 3 days");
+        assert_eq!(time(3_040.0 * YEAR).duration(), "≈ 3,000 years");
+        assert_eq!(time(4.2e7 * YEAR).duration(), "≈ 42 million years");
+        assert_eq!(time(5e20 * YEAR).duration(), "≈ 10²⁰ years");
+    }
+
+    #[test]
+    fn test_display() {
+        // Half of 2^41 guesses at 10^12/s
+        let fast = CrackTime::new(41.0, AttackerModel::OfflineFastHash);
+        assert_eq!(fast.to_string(), "≈ 1.1 seconds at 10¹² guesses/s");
+        let online = CrackTime::new(20.0, AttackerModel::Online);
+        assert_eq!(online.to_string(), "≈ 15 hours at 10¹ guesses/s");
+    }
+}
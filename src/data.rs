@@ -0,0 +1,101 @@
+//! Embedded data written back out to files (`data export`)
+//!
+//! The binary carries its wordlist, site rules and the Markov model trained
+//! from the wordlist. Exporting them lets an auditor read exactly what a given
+//! build generates from, and diff it against the upstream files: the wordlist
+//! and rules are written byte for byte as embedded, so their SHA-256 matches
+//! `--version --format json`.
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::generators::markov::MarkovGenerator;
+use crate::rules;
+use crate::wordlist;
+
+#[derive(Debug, Error)]
+pub enum DataError {
+    #[error("{0} already exists; pass --force to overwrite it")]
+    Exists(PathBuf),
+    #[error("cannot write {path}")]
+    Io { path: PathBuf, source: io::Error },
+}
+
+/// Embedded data that can be exported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DataSet {
+    /// The EFF large wordlist used for passphrases
+    EffLarge,
+    /// Bigram and transition weights of the pronounceable generator
+    MarkovModel,
+    /// The `--for` site password rules
+    SiteRules,
+}
+
+impl DataSet {
+    /// Default output name; the embedded files keep their upstream names
+    pub fn file_name(self) -> &'static str {
+        match self {
+            DataSet::EffLarge => "eff_large_wordlist.txt",
+            DataSet::MarkovModel => "markov_model.tsv",
+            DataSet::SiteRules => "password_rules.txt",
+        }
+    }
+
+    pub fn contents(self) -> String {
+        match self {
+            DataSet::EffLarge => wordlist::EMBEDDED.to_string(),
+            DataSet::MarkovModel => MarkovGenerator::model_table(),
+            DataSet::SiteRules => rules::SITE_RULES.to_string(),
+        }
+    }
+}
+
+/// Write `set` to `path`, refusing to replace an existing file unless `force`
+pub fn export(set: DataSet, path: &Path, force: bool) -> Result<(), DataError> {
+    let io_error = |source| DataError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let mut options = OpenOptions::new();
+    options.write(true);
+    if force {
+        options.create(true).truncate(true);
+    } else {
+        options.create_new(true);
+    }
+    let mut file = options.open(path).map_err(|err| match err.kind() {
+        io::ErrorKind::AlreadyExists => DataError::Exists(path.to_path_buf()),
+        _ => io_error(err),
+    })?;
+    file.write_all(set.contents().as_bytes()).map_err(io_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digest::{hex, sha256};
+    use crate::version;
+
+    #[test]
+    fn test_export_matches_version_report() {
+        let path = std::env::temp_dir().join(format!("pwgen-data-{}", std::process::id()));
+        export(DataSet::EffLarge, &path, false).unwrap();
+        assert!(matches!(
+            export(DataSet::EffLarge, &path, false),
+            Err(DataError::Exists(_))
+        ));
+        export(DataSet::EffLarge, &path, true).unwrap();
+
+        let written = std::fs::read(&path).unwrap();
+        let report = version::to_json();
+        assert_eq!(
+            report["data"]["wordlists"][0]["sha256"],
+            hex(&sha256(&written))
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
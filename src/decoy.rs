@@ -0,0 +1,129 @@
+//! Honeytoken passwords (`decoy`, `decoy verify`)
+//!
+//! A decoy is an ordinary generated password followed by a short tag derived
+//! with HMAC-SHA-256 from a secret key and the rest of the value. Seeded into a
+//! honeypot or planted as a canary credential, it looks like any other
+//! password, but whoever holds the key can tell that a password seen in a
+//! login attempt or a leak is one of theirs. Without the key the tag is
+//! indistinguishable from random characters.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use crate::digest::hmac_sha256;
+
+/// Characters of tag appended to each decoy (about 35.7 bits)
+pub const TAG_LENGTH: usize = 6;
+/// Shortest accepted key
+pub const MIN_KEY_BYTES: usize = 16;
+
+/// Prefix of every MAC input, so decoy tags can't collide with other uses of the key
+const DOMAIN: &[u8] = b"pwgen-x decoy v1\0";
+const TAG_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+#[derive(Debug, Error)]
+pub enum DecoyError {
+    #[error("decoy key has {found} bytes; at least {MIN_KEY_BYTES} are required")]
+    KeyTooShort { found: usize },
+    #[error("cannot read {path}")]
+    Io { path: PathBuf, source: io::Error },
+}
+
+/// Secret key decoys are tagged with
+pub struct DecoyKey {
+    bytes: Zeroizing<Vec<u8>>,
+}
+
+impl DecoyKey {
+    pub fn new(bytes: Vec<u8>) -> Result<Self, DecoyError> {
+        let bytes = Zeroizing::new(bytes);
+        if bytes.len() < MIN_KEY_BYTES {
+            return Err(DecoyError::KeyTooShort { found: bytes.len() });
+        }
+        Ok(Self { bytes })
+    }
+
+    /// The raw contents of `path` as the key
+    pub fn load(path: &Path) -> Result<Self, DecoyError> {
+        let bytes = fs::read(path).map_err(|source| DecoyError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::new(bytes)
+    }
+
+    /// `body` with its tag appended
+    pub fn mark(&self, body: &str) -> Zeroizing<String> {
+        let mut value = Zeroizing::new(body.to_string());
+        value.push_str(&self.tag(body));
+        value
+    }
+
+    /// Whether `value` ends in the tag of the rest of it
+    pub fn is_decoy(&self, value: &str) -> bool {
+        let Some((split, _)) = value.char_indices().rev().nth(TAG_LENGTH - 1) else {
+            return false;
+        };
+        let (body, tag) = value.split_at(split);
+        let expected = self.tag(body);
+        // Compare every byte so the time taken doesn't reveal a matching prefix
+        tag.len() == expected.len()
+            && tag
+                .bytes()
+                .zip(expected.bytes())
+                .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+
+    /// Tag characters drawn without modulo bias from successive MAC blocks
+    fn tag(&self, body: &str) -> Zeroizing<String> {
+        let limit = (256 / TAG_ALPHABET.len() * TAG_ALPHABET.len()) as u8;
+        let mut tag = Zeroizing::new(String::with_capacity(TAG_LENGTH));
+        for counter in 0u32.. {
+            let mut message = DOMAIN.to_vec();
+            message.extend_from_slice(&counter.to_be_bytes());
+            message.extend_from_slice(body.as_bytes());
+            let mac = hmac_sha256(&self.bytes, &message);
+            message.fill(0);
+            for byte in mac.into_iter().filter(|&b| b < limit) {
+                tag.push(TAG_ALPHABET[usize::from(byte) % TAG_ALPHABET.len()] as char);
+                if tag.len() == TAG_LENGTH {
+                    return tag;
+                }
+            }
+        }
+        unreachable!("every MAC block contributes tag characters")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decoy_roundtrip() {
+        let key = DecoyKey::new(vec![7; 32]).unwrap();
+        let decoy = key.mark("Tamuvorasel");
+        assert_eq!(decoy.chars().count(), "Tamuvorasel".len() + TAG_LENGTH);
+        assert!(decoy.starts_with("Tamuvorasel"));
+        assert!(key.is_decoy(&decoy));
+
+        // Another key, an altered body or a short value are not ours
+        let other = DecoyKey::new(vec![8; 32]).unwrap();
+        assert!(!other.is_decoy(&decoy));
+        let mut altered = decoy.to_string();
+        altered.replace_range(0..1, "X");
+        assert!(!key.is_decoy(&altered));
+        assert!(!key.is_decoy("abc"));
+        assert!(key.is_decoy(&key.mark("é🙂")));
+
+        assert!(matches!(
+            DecoyKey::new(vec![0; 8]),
+            Err(DecoyError::KeyTooShort { found: 8 })
+        ));
+    }
+}
@@ -0,0 +1,98 @@
+//! Candidates that look different from each other (`--diverse`)
+//!
+//! A batch shown to a person to pick from is only a real choice if the
+//! candidates differ: pronounceable passwords from the Markov model often share
+//! an opening and much of their shape. A candidate is admitted only if it
+//! starts with a different pair of characters than every admitted one and
+//! differs from each in at least half of its characters (Levenshtein distance,
+//! ignoring case). Rejecting look-alikes removes a small fraction of the
+//! keyspace for each later candidate; the first is unaffected.
+
+use zeroize::Zeroizing;
+
+/// Admits candidates unlike those admitted before
+#[derive(Default)]
+pub struct Diversity {
+    /// Lowercased admitted values
+    kept: Vec<Zeroizing<Vec<char>>>,
+}
+
+impl Diversity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Admit `value` if it differs enough from every admitted value
+    pub fn admit(&mut self, value: &str) -> bool {
+        let chars = Zeroizing::new(
+            value
+                .chars()
+                .flat_map(char::to_lowercase)
+                .collect::<Vec<_>>(),
+        );
+        if self.kept.iter().any(|kept| similar(kept, &chars)) {
+            return false;
+        }
+        self.kept.push(chars);
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.kept.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.kept.is_empty()
+    }
+}
+
+/// Same opening pair of characters, or fewer edits apart than half the shorter value
+fn similar(a: &[char], b: &[char]) -> bool {
+    if a.len() >= 2 && b.len() >= 2 && a[..2] == b[..2] {
+        return true;
+    }
+    let min_distance = a.len().min(b.len()).div_ceil(2);
+    levenshtein(a, b) < min_distance
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(levenshtein(&chars(""), &chars("abc")), 3);
+        assert_eq!(levenshtein(&chars("same"), &chars("same")), 0);
+    }
+
+    #[test]
+    fn test_diversity() {
+        let mut diversity = Diversity::new();
+        assert!(diversity.admit("Tamuvorasel"));
+        // Same opening bigram, whatever the case
+        assert!(!diversity.admit("taXXXXXXXXX"));
+        // Only two edits away
+        assert!(!diversity.admit("Bamuvorasex"));
+        assert!(diversity.admit("Kelpidrunog"));
+        assert_eq!(diversity.len(), 2);
+    }
+}
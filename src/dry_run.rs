@@ -0,0 +1,186 @@
+//! Keyspace and entropy estimates without printing a password (`--dry-run`)
+//!
+//! The entropy comes from the generator's own estimate. When a length range or
+//! threshold is set, or the generator's entropy varies per password, a
+//! calibration sample of candidates is drawn to measure the rejection rate and
+//! the lowest entropy; those candidates are discarded unseen.
+
+use std::fmt;
+
+use rand::RngCore;
+
+use crate::crack_time::CrackTime;
+use crate::entropy::{AttackerModel, EntropyInfo};
+use crate::generators::{PasswordGenerator, PasswordGeneratorExt};
+use crate::length_range::LengthRange;
+use crate::threshold::Threshold;
+
+/// What a configuration would produce, worked out before generating
+#[derive(Debug, Clone, PartialEq)]
+pub struct DryRunReport {
+    pub generator: String,
+    /// Entropy of every accepted password, or the lowest seen in the sample when it varies
+    pub entropy_bits: Option<f64>,
+    /// Whether `entropy_bits` holds for every password rather than the sample
+    pub exact: bool,
+    /// Bits lost to the length range, already subtracted from `entropy_bits`
+    pub length_cost: f64,
+    /// Share of candidates the length range and threshold reject, if sampled
+    pub rejection_rate: Option<f64>,
+    /// Candidates drawn to measure the rejection rate
+    pub samples: usize,
+    pub attacker: AttackerModel,
+}
+
+impl DryRunReport {
+    pub fn new(
+        generator: &dyn PasswordGenerator,
+        length_range: &LengthRange,
+        threshold: &Threshold,
+        attacker: AttackerModel,
+        rng: &mut dyn RngCore,
+        samples: usize,
+    ) -> Self {
+        let estimate = generator.estimated_entropy();
+        let mut report = Self {
+            generator: generator.description().to_string(),
+            entropy_bits: estimate,
+            exact: estimate.is_some(),
+            length_cost: 0.0,
+            rejection_rate: None,
+            samples: 0,
+            attacker,
+        };
+        if estimate.is_some() && length_range.is_empty() && threshold.is_empty() {
+            return report;
+        }
+
+        let candidates: Vec<EntropyInfo> = generator
+            .iter(rng)
+            .take(samples)
+            .filter(|p| length_range.accepts(&p.value))
+            .map(|p| p.entropy)
+            .collect();
+        if !length_range.is_empty() && !candidates.is_empty() {
+            report.length_cost = (samples as f64 / candidates.len() as f64).log2();
+        }
+        let lowest = candidates
+            .iter()
+            .map(|e| e.bits)
+            .min_by(f64::total_cmp)
+            .map(|bits| bits - report.length_cost);
+        report.entropy_bits = estimate.map(|bits| bits - report.length_cost).or(lowest);
+        let accepted = candidates
+            .into_iter()
+            .filter(|e| threshold.accepts(&EntropyInfo::new(e.bits - report.length_cost, e.source)))
+            .count();
+        report.rejection_rate = Some(1.0 - accepted as f64 / samples as f64);
+        report.samples = samples;
+        report
+    }
+
+    /// Candidates drawn per accepted password on average, or None if none are accepted
+    pub fn expected_draws(&self) -> Option<f64> {
+        match self.rejection_rate {
+            Some(rate) if rate >= 1.0 => None,
+            Some(rate) => Some(1.0 / (1.0 - rate)),
+            None => Some(1.0),
+        }
+    }
+}
+
+impl fmt::Display for DryRunReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Generator:  {}", self.generator)?;
+        match self.entropy_bits {
+            Some(bits) => {
+                let bound = if self.exact { "" } else { "at least " };
+                writeln!(
+                    f,
+                    "Keyspace:   {}2^{:.1} (≈ {:.2e})",
+                    bound,
+                    bits,
+                    2f64.powf(bits)
+                )?;
+                let entropy = EntropyInfo::new(bits, "estimate");
+                writeln!(
+                    f,
+                    "Entropy:    {}{:.1} bits ({})",
+                    bound,
+                    bits,
+                    entropy.strength_against(self.attacker).label()
+                )?;
+                writeln!(f, "Crack time: {}", CrackTime::new(bits, self.attacker))?;
+                if !self.exact {
+                    writeln!(
+                        f,
+                        "            (varies per password; lowest of {} samples)",
+                        self.samples
+                    )?;
+                }
+            }
+            None => writeln!(f, "Entropy:    unknown (no candidate was accepted)")?,
+        }
+        if self.length_cost > 0.0 {
+            writeln!(f, "Length cap: costs {:.1} bits", self.length_cost)?;
+        }
+        match (self.rejection_rate, self.expected_draws()) {
+            (None, _) => write!(f, "Rejection:  none (no length range or threshold)"),
+            (Some(_), None) => write!(
+                f,
+                "Rejection:  all {} sampled candidates; this configuration cannot succeed",
+                self.samples
+            ),
+            (Some(rate), Some(draws)) => write!(
+                f,
+                "Rejection:  {:.1}% of {} sampled candidates ({:.2} draws per password)",
+                rate * 100.0,
+                self.samples,
+                draws
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::CharSet;
+    use crate::generators::SecureGenerator;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_dry_run_report() {
+        let generator = SecureGenerator::new(8, &CharSet::Alphanumeric, false).unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let open = DryRunReport::new(
+            &generator,
+            &LengthRange::default(),
+            &Threshold::default(),
+            AttackerModel::default(),
+            &mut rng,
+            64,
+        );
+        assert!(open.exact);
+        assert_eq!(open.samples, 0);
+        assert_eq!(open.expected_draws(), Some(1.0));
+        assert!((open.entropy_bits.unwrap() - 8.0 * 62f64.log2()).abs() < 1e-9);
+        assert!(open.to_string().contains("Keyspace:   2^47.6"));
+
+        let threshold = Threshold {
+            min_bits: Some(60.0),
+            ..Threshold::default()
+        };
+        let impossible = DryRunReport::new(
+            &generator,
+            &LengthRange::default(),
+            &threshold,
+            AttackerModel::default(),
+            &mut rng,
+            64,
+        );
+        assert_eq!(impossible.rejection_rate, Some(1.0));
+        assert_eq!(impossible.expected_draws(), None);
+    }
+}
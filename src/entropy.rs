@@ -15,22 +15,137 @@ impl EntropyInfo {
 
     /// Strength category based on entropy bits
     pub fn strength(&self) -> StrengthLevel {
+        self.strength_against(AttackerModel::default())
+    }
+
+    /// Strength category against a particular kind of attacker
+    pub fn strength_against(&self, attacker: AttackerModel) -> StrengthLevel {
+        let [weak, moderate, strong, very_strong] = attacker.thresholds();
         match self.bits as u32 {
-            0..=24 => StrengthLevel::VeryWeak,
-            25..=49 => StrengthLevel::Weak,
-            50..=74 => StrengthLevel::Moderate,
-            75..=99 => StrengthLevel::Strong,
+            n if n < weak => StrengthLevel::VeryWeak,
+            n if n < moderate => StrengthLevel::Weak,
+            n if n < strong => StrengthLevel::Moderate,
+            n if n < very_strong => StrengthLevel::Strong,
             _ => StrengthLevel::VeryStrong,
         }
     }
 
     /// Percentage for progress bar (0-100, capped at 128 bits)
     pub fn percentage(&self) -> u8 {
-        ((self.bits / 128.0) * 100.0).min(100.0) as u8
+        self.percentage_on(&Scale::default())
+    }
+
+    /// Percentage for progress bar on the given scale
+    pub fn percentage_on(&self, scale: &Scale) -> u8 {
+        let bits = self.bits.max(0.0);
+        let fraction = if scale.logarithmic {
+            (1.0 + bits).log2() / (1.0 + scale.max_bits).log2()
+        } else {
+            bits / scale.max_bits
+        };
+        (fraction * 100.0).min(100.0) as u8
+    }
+}
+
+/// How entropy maps onto the strength bar
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scale {
+    /// Entropy that fills the bar
+    pub max_bits: f64,
+    /// Grow the bar with log2(1 + bits), giving weak passwords more room
+    pub logarithmic: bool,
+}
+
+impl Scale {
+    pub const DEFAULT_MAX_BITS: f64 = 128.0;
+
+    /// A scale filling at `max_bits`, which must be positive and finite
+    pub fn new(max_bits: f64, logarithmic: bool) -> Option<Self> {
+        (max_bits.is_finite() && max_bits > 0.0).then_some(Self {
+            max_bits,
+            logarithmic,
+        })
+    }
+}
+
+impl Default for Scale {
+    fn default() -> Self {
+        Self {
+            max_bits: Self::DEFAULT_MAX_BITS,
+            logarithmic: false,
+        }
+    }
+}
+
+/// Shannon entropy in bits of drawing one item with the given relative weights
+pub fn weighted_entropy(weights: &[f64]) -> f64 {
+    let total: f64 = weights.iter().sum();
+    weights
+        .iter()
+        .filter(|&&w| w > 0.0)
+        .map(|&w| {
+            let p = w / total;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Serialized with the derived strength level alongside the raw bits
+#[cfg(feature = "serde")]
+impl serde::Serialize for EntropyInfo {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("EntropyInfo", 3)?;
+        state.serialize_field("bits", &self.bits)?;
+        state.serialize_field("source", self.source)?;
+        state.serialize_field("strength", &self.strength())?;
+        state.end()
+    }
+}
+
+/// Who is guessing, which decides how much entropy is enough
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum AttackerModel {
+    /// Balanced buckets for when the threat is unknown
+    #[default]
+    Generic,
+    /// Rate-limited guessing against a login form
+    Online,
+    /// Stolen hashes from a slow KDF (bcrypt, scrypt, Argon2)
+    OfflineSlowHash,
+    /// Stolen hashes from a fast hash (MD5, SHA-1, NTLM) on GPUs
+    OfflineFastHash,
+}
+
+impl AttackerModel {
+    /// Minimum bits for Weak, Moderate, Strong and Very Strong
+    pub fn thresholds(self) -> [u32; 4] {
+        match self {
+            AttackerModel::Generic => [25, 50, 75, 100],
+            AttackerModel::Online => [20, 30, 40, 60],
+            AttackerModel::OfflineSlowHash => [30, 45, 60, 75],
+            AttackerModel::OfflineFastHash => [40, 60, 80, 100],
+        }
+    }
+
+    /// Assumed guesses per second, for time-to-crack estimates
+    pub fn guesses_per_second(self) -> f64 {
+        match self {
+            AttackerModel::Generic => 1e10,
+            AttackerModel::Online => 1e1,
+            AttackerModel::OfflineSlowHash => 1e4,
+            AttackerModel::OfflineFastHash => 1e12,
+        }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Strength levels, ordered from weakest to strongest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum StrengthLevel {
     VeryWeak,
     Weak,
@@ -107,12 +222,64 @@ mod tests {
         assert_eq!(info.percentage(), 50);
     }
 
+    #[test]
+    fn test_percentage_on_scale() {
+        let info = EntropyInfo::new(200.0, "test");
+        let wide = Scale::new(256.0, false).unwrap();
+        assert_eq!(info.percentage_on(&wide), 78);
+        let log = Scale::new(256.0, true).unwrap();
+        assert_eq!(EntropyInfo::new(15.0, "test").percentage_on(&log), 49);
+        assert!(Scale::new(0.0, false).is_none());
+    }
+
+    #[test]
+    fn test_attacker_models() {
+        let info = EntropyInfo::new(45.0, "test");
+        assert_eq!(info.strength(), StrengthLevel::Weak);
+        assert_eq!(
+            info.strength_against(AttackerModel::Online),
+            StrengthLevel::Strong
+        );
+        assert_eq!(
+            info.strength_against(AttackerModel::OfflineSlowHash),
+            StrengthLevel::Moderate
+        );
+        assert_eq!(
+            info.strength_against(AttackerModel::OfflineFastHash),
+            StrengthLevel::Weak
+        );
+    }
+
+    #[test]
+    fn test_weighted_entropy() {
+        assert!((weighted_entropy(&[1.0; 8]) - 3.0).abs() < 1e-9);
+        assert!((weighted_entropy(&[3.0, 1.0]) - 0.8113).abs() < 1e-4);
+        assert_eq!(weighted_entropy(&[5.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn test_strength_ordering() {
+        assert!(StrengthLevel::VeryWeak < StrengthLevel::Weak);
+        assert!(StrengthLevel::Strong < StrengthLevel::VeryStrong);
+        assert!(EntropyInfo::new(80.0, "test").strength() >= StrengthLevel::Moderate);
+    }
+
     #[test]
     fn test_strength_labels() {
         assert_eq!(StrengthLevel::VeryWeak.label(), "Very Weak");
         assert_eq!(StrengthLevel::Strong.label(), "Strong");
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_entropy_serialize() {
+        let json = serde_json::to_value(EntropyInfo::new(80.5, "Random")).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"bits": 80.5, "source": "Random", "strength": "strong"})
+        );
+    }
+
     #[test]
     fn test_strength_emoji() {
         assert_eq!(StrengthLevel::VeryWeak.emoji(), "💀");
@@ -0,0 +1,202 @@
+use thiserror::Error;
+
+use crate::exit::Failure;
+
+/// Errors from invalid generator configurations
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PwgenError {
+    #[error("password length must be at least 1")]
+    ZeroLength,
+
+    #[error("word count must be at least 1")]
+    ZeroWords,
+
+    #[error("count must be at least 1")]
+    ZeroCount,
+
+    #[error("length {length} exceeds the maximum of {max}")]
+    LengthTooLarge { length: usize, max: usize },
+
+    #[error("{words} words exceeds the maximum of {max}")]
+    TooManyWords { words: usize, max: usize },
+
+    #[error("count {count} exceeds the maximum of {max}")]
+    CountTooLarge { count: usize, max: usize },
+
+    #[error("minimum length {min} is greater than the maximum of {max}")]
+    InvalidLengthRange { min: usize, max: usize },
+
+    #[error("{inserted} digits and symbols leave no room for letters in {length} characters")]
+    TooManyInsertions { inserted: usize, length: usize },
+
+    #[error("no {words}-word passphrase fits in {max_length} characters")]
+    PassphraseTooLong { words: usize, max_length: usize },
+
+    #[error(
+        "separator {0:?} contains letters, digits or control characters, \
+         which blur the word boundaries"
+    )]
+    InvalidSeparator(String),
+
+    #[error(
+        "separator {0:?} lets a line break inside the passphrase; \
+         wrap-safe passphrases use only {}",
+        crate::generators::passphrase::WRAP_SAFE_SEPARATORS
+    )]
+    WrapUnsafeSeparator(String),
+
+    #[error("the random separator set is empty")]
+    EmptySeparatorSet,
+
+    #[error("{words} distinct words need a list of at least {words}, but it has {available}")]
+    NotEnoughWords { words: usize, available: usize },
+
+    #[error("acrostic {0:?} must be a word of letters only")]
+    InvalidAcrostic(String),
+
+    #[error("no word in the list starts with '{0}'")]
+    NoWordsWithInitial(char),
+
+    #[error("an acrostic cannot be combined with a maximum length")]
+    AcrosticWithMaxLength,
+
+    #[error("a symbol set needs the alphanumeric-symbols charset")]
+    SymbolSetWithoutSymbols,
+
+    #[error("a symbol set needs the symbol class among the included ones (--with-symbols)")]
+    SymbolSetWithoutSymbolClass,
+
+    #[error("a weighted composition cannot be combined with a repeat limit")]
+    ComposeWithMaxRepeat,
+
+    #[error("character-class requirements cannot be combined with {0}")]
+    RequirementsConflict(&'static str),
+
+    #[error("the required characters need {needed} positions, more than the length of {length}")]
+    RequirementsTooLong { needed: usize, length: usize },
+
+    #[error("the character set has no {0} characters to require")]
+    RequiredClassMissing(&'static str),
+
+    #[error("group size must be at least 1")]
+    ZeroGroupSize,
+
+    #[error("group size {size} must be smaller than the password length of {length}")]
+    GroupSizeTooLarge { size: usize, length: usize },
+
+    #[error(
+        "group separator {0:?} is empty or contains letters, digits or control characters, \
+         which blur the group boundaries"
+    )]
+    InvalidGroupSeparator(String),
+
+    #[error("character set is empty")]
+    EmptyCharset,
+
+    #[error("the alphabet needs at least 2 distinct characters, or every password is the same")]
+    SingleSymbolAlphabet,
+
+    #[error("no characters are left once the excluded ones are removed")]
+    AllCharsExcluded,
+
+    #[error("excluded characters cannot be combined with site rules")]
+    ExcludedCharsWithRules,
+
+    #[error("an exact {unit} length needs at least one single-{unit} character in the set")]
+    LengthModeUnsatisfiable { unit: &'static str },
+
+    #[error("length {length} is outside the site's allowed range ({min}..={max})")]
+    LengthOutsideRules {
+        length: usize,
+        min: usize,
+        max: usize,
+    },
+
+    #[error(
+        "{length} characters cannot be filled using each character at most {max_repeat} times"
+    )]
+    RepeatLimitUnsatisfiable { length: usize, max_repeat: usize },
+
+    #[error(
+        "this configuration gives only about {bits} bits, under the {minimum}-bit danger zone; \
+         lengthen it or pass --i-know-this-is-weak"
+    )]
+    TooWeak { bits: u32, minimum: u32 },
+
+    #[error("the guess percentile must be at least 0 and below {max}")]
+    GuessPercentileOutOfRange { max: u32 },
+
+    #[error("a device passcode needs at least {min} characters, not {length}")]
+    PasscodeTooShort { length: usize, min: usize },
+
+    #[error("numeric passcodes have at most {max} digits; use --alphanumeric for {length}")]
+    PasscodeTooLong { length: usize, max: usize },
+
+    #[error("invalid template {template:?}: {reason}")]
+    InvalidTemplate { template: String, reason: String },
+
+    #[error("template {0:?} has no placeholders to fill, so every password would be the same")]
+    TemplateWithoutSlots(String),
+
+    #[error("the site's password rules cannot be satisfied at length {length}")]
+    RulesUnsatisfiable { length: usize },
+
+    #[error("only {found} distinct passwords found out of {requested} requested")]
+    NotEnoughUnique { requested: usize, found: usize },
+}
+
+impl PwgenError {
+    /// What kind of failure this is, which sets the CLI's exit code
+    ///
+    /// Malformed or conflicting options are usage errors, sharing clap's code;
+    /// well-formed constraints that no password can meet are unsatisfiable.
+    pub fn failure(&self) -> Failure {
+        match self {
+            PwgenError::ZeroLength
+            | PwgenError::ZeroWords
+            | PwgenError::ZeroCount
+            | PwgenError::LengthTooLarge { .. }
+            | PwgenError::TooManyWords { .. }
+            | PwgenError::CountTooLarge { .. }
+            | PwgenError::ZeroGroupSize
+            | PwgenError::GroupSizeTooLarge { .. }
+            | PwgenError::InvalidGroupSeparator(_)
+            | PwgenError::InvalidSeparator(_)
+            | PwgenError::EmptySeparatorSet
+            | PwgenError::WrapUnsafeSeparator(_)
+            | PwgenError::InvalidAcrostic(_)
+            | PwgenError::AcrosticWithMaxLength
+            | PwgenError::SymbolSetWithoutSymbols
+            | PwgenError::SymbolSetWithoutSymbolClass
+            | PwgenError::ComposeWithMaxRepeat
+            | PwgenError::RequirementsConflict(_)
+            | PwgenError::InvalidLengthRange { .. }
+            | PwgenError::EmptyCharset
+            | PwgenError::SingleSymbolAlphabet
+            | PwgenError::AllCharsExcluded
+            | PwgenError::ExcludedCharsWithRules
+            | PwgenError::GuessPercentileOutOfRange { .. }
+            | PwgenError::PasscodeTooShort { .. }
+            | PwgenError::PasscodeTooLong { .. }
+            | PwgenError::InvalidTemplate { .. }
+            | PwgenError::TemplateWithoutSlots(_) => Failure::Usage,
+            PwgenError::NotEnoughWords { .. }
+            | PwgenError::NoWordsWithInitial(_)
+            | PwgenError::RequirementsTooLong { .. }
+            | PwgenError::RequiredClassMissing(_)
+            | PwgenError::PassphraseTooLong { .. }
+            | PwgenError::TooManyInsertions { .. }
+            | PwgenError::LengthModeUnsatisfiable { .. }
+            | PwgenError::LengthOutsideRules { .. }
+            | PwgenError::RulesUnsatisfiable { .. }
+            | PwgenError::RepeatLimitUnsatisfiable { .. }
+            | PwgenError::NotEnoughUnique { .. } => Failure::Unsatisfiable,
+            PwgenError::TooWeak { .. } => Failure::TooWeak,
+        }
+    }
+
+    /// Process exit code for the CLI, see [`Failure`]
+    pub fn exit_code(&self) -> u8 {
+        self.failure().code()
+    }
+}
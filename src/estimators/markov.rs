@@ -0,0 +1,153 @@
+//! Likelihood of a password under a letter trigram model
+//!
+//! The model is trained on the built-in English wordlist with add-one
+//! smoothing. Letters cost -log2 P(letter | previous two letters); capitals add
+//! a bit each, and digits and symbols cost their class size and restart the
+//! context. Pronounceable or word-like values score well below their keyspace.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use super::StrengthEstimator;
+use crate::generators::GeneratedPassword;
+use crate::wordlist::Wordlist;
+
+const LETTERS: f64 = 26.0;
+const DIGITS: f64 = 10.0;
+const SYMBOLS: f64 = 33.0;
+/// Rough alphabet size for characters outside ASCII
+const OTHER: f64 = 100.0;
+
+/// Letter counts from the training words
+#[derive(Debug, Default)]
+struct Model {
+    first: HashMap<char, u32>,
+    first_total: u32,
+    /// First letter -> second letter counts
+    second: HashMap<char, HashMap<char, u32>>,
+    /// Two previous letters -> next letter counts
+    next: HashMap<(char, char), HashMap<char, u32>>,
+}
+
+impl Model {
+    fn train(words: &[String]) -> Self {
+        let mut model = Self::default();
+        for word in words {
+            let letters: Vec<char> = word
+                .chars()
+                .filter(char::is_ascii_alphabetic)
+                .map(|c| c.to_ascii_lowercase())
+                .collect();
+            let Some(&first) = letters.first() else {
+                continue;
+            };
+            *model.first.entry(first).or_default() += 1;
+            model.first_total += 1;
+            if let Some(&second) = letters.get(1) {
+                *model
+                    .second
+                    .entry(first)
+                    .or_default()
+                    .entry(second)
+                    .or_default() += 1;
+            }
+            for window in letters.windows(3) {
+                *model
+                    .next
+                    .entry((window[0], window[1]))
+                    .or_default()
+                    .entry(window[2])
+                    .or_default() += 1;
+            }
+        }
+        model
+    }
+
+    /// -log2 of the smoothed probability of `c` given the counts following a context
+    fn cost(counts: Option<&HashMap<char, u32>>, c: char) -> f64 {
+        let (hits, total) = counts.map_or((0, 0), |counts| {
+            (
+                counts.get(&c).copied().unwrap_or(0),
+                counts.values().sum::<u32>(),
+            )
+        });
+        -((hits as f64 + 1.0) / (total as f64 + LETTERS)).log2()
+    }
+
+    fn letter_cost(&self, context: &[char], c: char) -> f64 {
+        match context {
+            [] => -((self.first.get(&c).copied().unwrap_or(0) as f64 + 1.0)
+                / (self.first_total as f64 + LETTERS))
+                .log2(),
+            [a] => Self::cost(self.second.get(a), c),
+            [.., a, b] => Self::cost(self.next.get(&(*a, *b)), c),
+        }
+    }
+}
+
+fn model() -> &'static Model {
+    static MODEL: OnceLock<Model> = OnceLock::new();
+    MODEL.get_or_init(|| Model::train(Wordlist::builtin().words()))
+}
+
+/// Bits of a password under a trigram model of English words
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MarkovEstimator;
+
+impl MarkovEstimator {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn bits(&self, value: &str) -> f64 {
+        let model = model();
+        let mut context: Vec<char> = Vec::new();
+        let mut bits = 0.0;
+        for c in value.chars() {
+            if c.is_ascii_alphabetic() {
+                let lower = c.to_ascii_lowercase();
+                bits += model.letter_cost(&context, lower);
+                if c.is_ascii_uppercase() {
+                    bits += 1.0;
+                }
+                context.push(lower);
+                continue;
+            }
+            context.clear();
+            bits += match c {
+                c if c.is_ascii_digit() => DIGITS,
+                c if c.is_ascii() => SYMBOLS,
+                _ => OTHER,
+            }
+            .log2();
+        }
+        bits
+    }
+}
+
+impl StrengthEstimator for MarkovEstimator {
+    fn name(&self) -> &'static str {
+        "markov"
+    }
+
+    fn estimate(&self, password: &GeneratedPassword) -> f64 {
+        self.bits(&password.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words_score_below_random_letters() {
+        let estimator = MarkovEstimator::new();
+        let word = estimator.bits("sandwich");
+        let random = estimator.bits("qzxjvkwq");
+        assert!(word < random, "{} vs {}", word, random);
+        // Never more than a uniform letter per character costs, plus smoothing slack
+        assert!(word < 8.0 * LETTERS.log2());
+        assert!((estimator.bits("42") - 2.0 * DIGITS.log2()).abs() < 1e-9);
+        assert!((estimator.bits("A") - estimator.bits("a") - 1.0).abs() < 1e-9);
+    }
+}
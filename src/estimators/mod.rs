@@ -0,0 +1,133 @@
+//! Strength estimators (`--estimator`)
+//!
+//! The entropy a generator reports is the keyspace it drew from, which is the
+//! right measure against an attacker who knows the configuration. The other
+//! estimators judge the value alone, the way a cracker without that knowledge
+//! would: by its likelihood under a character model, or by the dictionary words,
+//! sequences and keyboard runs it contains. Showing them side by side gives a
+//! best and worst case.
+
+use crate::generators::GeneratedPassword;
+
+pub mod markov;
+pub mod pattern;
+
+pub use markov::MarkovEstimator;
+pub use pattern::PatternEstimator;
+
+/// A way of judging how many guesses a password takes, in bits (log2 of guesses)
+pub trait StrengthEstimator {
+    /// Short name shown next to the estimate
+    fn name(&self) -> &'static str;
+
+    /// Bits an attacker following this model needs to find `password`
+    fn estimate(&self, password: &GeneratedPassword) -> f64;
+}
+
+/// The generator's own entropy: the keyspace it drew from
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyspaceEstimator;
+
+impl StrengthEstimator for KeyspaceEstimator {
+    fn name(&self) -> &'static str {
+        "keyspace"
+    }
+
+    fn estimate(&self, password: &GeneratedPassword) -> f64 {
+        password.entropy.bits
+    }
+}
+
+/// Selectable estimators
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum EstimatorKind {
+    /// Entropy of the generator's configuration (default)
+    #[default]
+    Keyspace,
+    /// Log-likelihood under a letter trigram model trained on English words
+    Markov,
+    /// zxcvbn-style search for words, sequences, repeats, keyboard runs and years
+    Pattern,
+}
+
+impl EstimatorKind {
+    pub fn build(self) -> Box<dyn StrengthEstimator> {
+        match self {
+            EstimatorKind::Keyspace => Box::new(KeyspaceEstimator),
+            EstimatorKind::Markov => Box::new(MarkovEstimator::new()),
+            EstimatorKind::Pattern => Box::new(PatternEstimator::new()),
+        }
+    }
+}
+
+/// One estimator's verdict on a password
+#[derive(Debug, Clone, PartialEq)]
+pub struct Estimate {
+    pub name: &'static str,
+    pub bits: f64,
+}
+
+/// Run every estimator on `password`
+pub fn estimate_all(
+    estimators: &[Box<dyn StrengthEstimator>],
+    password: &GeneratedPassword,
+) -> Vec<Estimate> {
+    estimators
+        .iter()
+        .map(|e| Estimate {
+            name: e.name(),
+            bits: e.estimate(password),
+        })
+        .collect()
+}
+
+/// Bits to brute-force one character of the classes present in `value`
+pub(crate) fn bruteforce_bits(value: &str) -> f64 {
+    let mut cardinality = 0;
+    if value.chars().any(|c| c.is_ascii_lowercase()) {
+        cardinality += 26;
+    }
+    if value.chars().any(|c| c.is_ascii_uppercase()) {
+        cardinality += 26;
+    }
+    if value.chars().any(|c| c.is_ascii_digit()) {
+        cardinality += 10;
+    }
+    if value.chars().any(|c| c.is_ascii_punctuation() || c == ' ') {
+        cardinality += 33;
+    }
+    if !value.is_ascii() {
+        cardinality += 100;
+    }
+    (cardinality.max(1) as f64).log2()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entropy::EntropyInfo;
+    use zeroize::Zeroizing;
+
+    #[test]
+    fn test_estimate_all() {
+        let password = GeneratedPassword {
+            value: Zeroizing::new("password".to_string()),
+            entropy: EntropyInfo::new(47.0, "test"),
+            mnemonic: None,
+        };
+        let estimators: Vec<_> = [EstimatorKind::Keyspace, EstimatorKind::Pattern]
+            .into_iter()
+            .map(EstimatorKind::build)
+            .collect();
+        let estimates = estimate_all(&estimators, &password);
+        assert_eq!(
+            estimates[0],
+            Estimate {
+                name: "keyspace",
+                bits: 47.0
+            }
+        );
+        assert_eq!(estimates[1].name, "pattern");
+        assert!(estimates[1].bits < 20.0);
+    }
+}
@@ -0,0 +1,362 @@
+//! zxcvbn-style pattern analysis
+//!
+//! The value is searched for dictionary words (including common leet
+//! substitutions), ascending or descending sequences, repeated characters,
+//! keyboard row runs, years and dates. Each match has a guess count; characters no
+//! pattern covers are brute-forced over the classes present. The estimate is
+//! the cheapest way to cover the whole value, found by dynamic programming.
+
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use super::{bruteforce_bits, StrengthEstimator};
+use crate::generators::GeneratedPassword;
+use crate::wordlist::Wordlist;
+
+/// Shortest run counted as a word, sequence or repeat
+const MIN_MATCH: usize = 3;
+/// Shortest keyboard run (three adjacent keys are too common by chance)
+const MIN_KEYBOARD: usize = 4;
+const KEYBOARD_ROWS: [&str; 4] = ["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+/// Keys a keyboard run can start from
+const KEYS: f64 = 47.0;
+const LEET: [(char, char); 8] = [
+    ('4', 'a'),
+    ('@', 'a'),
+    ('3', 'e'),
+    ('1', 'l'),
+    ('0', 'o'),
+    ('5', 's'),
+    ('$', 's'),
+    ('7', 't'),
+];
+/// Years 1900 to 2099
+const YEARS: f64 = 200.0;
+/// Two-digit years
+const SHORT_YEARS: f64 = 100.0;
+/// Day and month combinations
+const DAYS: f64 = 366.0;
+/// Day-month-year, month-day-year and year-month-day
+const DATE_ORDERS: f64 = 3.0;
+const DATE_SEPARATORS: [char; 3] = ['/', '-', '.'];
+
+/// Kinds of pattern the search recognizes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternKind {
+    Word,
+    Sequence,
+    Repeat,
+    Keyboard,
+    Year,
+    Date,
+}
+
+impl PatternKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            PatternKind::Word => "dictionary word",
+            PatternKind::Sequence => "sequence",
+            PatternKind::Repeat => "repeat",
+            PatternKind::Keyboard => "keyboard walk",
+            PatternKind::Year => "year",
+            PatternKind::Date => "date",
+        }
+    }
+}
+
+/// A pattern covering characters `start..end` of the value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PatternMatch {
+    pub kind: PatternKind,
+    pub start: usize,
+    pub end: usize,
+    pub bits: f64,
+}
+
+/// The cheapest decomposition of a value
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Analysis {
+    pub bits: f64,
+    /// Patterns used, in order; characters between them are brute-forced
+    pub matches: Vec<PatternMatch>,
+}
+
+fn dictionary() -> &'static HashSet<String> {
+    static WORDS: OnceLock<HashSet<String>> = OnceLock::new();
+    WORDS.get_or_init(|| {
+        Wordlist::builtin()
+            .words()
+            .iter()
+            .map(|w| w.to_lowercase())
+            .collect()
+    })
+}
+
+/// Bits for a password by its cheapest decomposition into patterns
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PatternEstimator;
+
+impl PatternEstimator {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn bits(&self, value: &str) -> f64 {
+        self.analyze(value).bits
+    }
+
+    /// The estimate along with the patterns it was made of
+    pub fn analyze(&self, value: &str) -> Analysis {
+        let chars: Vec<char> = value.chars().collect();
+        let per_char = bruteforce_bits(value);
+        let words = dictionary();
+        let dictionary_bits = (words.len().max(1) as f64).log2();
+
+        // best[i]: cheapest cover of the first i characters; last[i]: the
+        // pattern ending there in that cover, None if character i is brute-forced
+        let mut best = vec![f64::INFINITY; chars.len() + 1];
+        let mut last: Vec<Option<PatternMatch>> = vec![None; chars.len() + 1];
+        best[0] = 0.0;
+        for end in 1..=chars.len() {
+            best[end] = best[end - 1] + per_char;
+            for start in 0..end.saturating_sub(MIN_MATCH - 1) {
+                let token = &chars[start..end];
+                let matched = [
+                    (PatternKind::Word, word_bits(token, words, dictionary_bits)),
+                    (PatternKind::Sequence, sequence_bits(token)),
+                    (PatternKind::Repeat, repeat_bits(token)),
+                    (PatternKind::Keyboard, keyboard_bits(token)),
+                    (PatternKind::Year, year_bits(token)),
+                    (PatternKind::Date, date_bits(token)),
+                ]
+                .into_iter()
+                .filter_map(|(kind, bits)| Some((kind, bits?)))
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+                if let Some((kind, bits)) = matched {
+                    if best[start] + bits < best[end] {
+                        best[end] = best[start] + bits;
+                        last[end] = Some(PatternMatch {
+                            kind,
+                            start,
+                            end,
+                            bits,
+                        });
+                    }
+                }
+            }
+        }
+
+        let mut matches = Vec::new();
+        let mut end = chars.len();
+        while end > 0 {
+            match last[end] {
+                Some(m) => {
+                    matches.push(m);
+                    end = m.start;
+                }
+                None => end -= 1,
+            }
+        }
+        matches.reverse();
+        Analysis {
+            bits: best[chars.len()],
+            matches,
+        }
+    }
+}
+
+impl StrengthEstimator for PatternEstimator {
+    fn name(&self) -> &'static str {
+        "pattern"
+    }
+
+    fn estimate(&self, password: &GeneratedPassword) -> f64 {
+        self.bits(&password.value)
+    }
+}
+
+/// A dictionary word, with bits for its capitalization and leet substitutions
+fn word_bits(token: &[char], words: &HashSet<String>, dictionary_bits: f64) -> Option<f64> {
+    let mut substitutions = 0;
+    let plain: String = token
+        .iter()
+        .map(|&c| match LEET.iter().find(|(leet, _)| *leet == c) {
+            Some(&(_, letter)) => {
+                substitutions += 1;
+                letter
+            }
+            None => c.to_ascii_lowercase(),
+        })
+        .collect();
+    if !words.contains(&plain) {
+        return None;
+    }
+    let upper = token.iter().filter(|c| c.is_ascii_uppercase()).count();
+    let case_bits = match upper {
+        0 => 0.0,
+        // Capitalized or all caps: the usual variations
+        _ if upper == token.len() || (upper == 1 && token[0].is_ascii_uppercase()) => 1.0,
+        _ => log2_binomial(token.len(), upper),
+    };
+    Some(dictionary_bits + case_bits + substitutions as f64)
+}
+
+/// Characters with a constant step of +1 or -1, e.g. "abcd" or "9876"
+fn sequence_bits(token: &[char]) -> Option<f64> {
+    let step = token[1] as i32 - token[0] as i32;
+    if step.abs() != 1 || token.windows(2).any(|w| w[1] as i32 - w[0] as i32 != step) {
+        return None;
+    }
+    let digits = token[0].is_ascii_digit();
+    if token
+        .iter()
+        .any(|c| !c.is_ascii_alphanumeric() || c.is_ascii_digit() != digits)
+    {
+        return None;
+    }
+    let class = if digits { 10.0 } else { 26.0 };
+    // Start character, direction and length
+    Some((class * 2.0 * token.len() as f64).log2())
+}
+
+/// One character repeated, e.g. "aaaa"
+fn repeat_bits(token: &[char]) -> Option<f64> {
+    if token.iter().any(|&c| c != token[0]) {
+        return None;
+    }
+    Some(bruteforce_bits(&token[0].to_string()) + (token.len() as f64).log2())
+}
+
+/// Adjacent keys along one QWERTY row, in either direction
+fn keyboard_bits(token: &[char]) -> Option<f64> {
+    if token.len() < MIN_KEYBOARD {
+        return None;
+    }
+    let lower: String = token.iter().map(|c| c.to_ascii_lowercase()).collect();
+    let reversed: String = lower.chars().rev().collect();
+    KEYBOARD_ROWS
+        .iter()
+        .any(|row| row.contains(&lower) || row.contains(&reversed))
+        .then(|| (KEYS * 2.0 * token.len() as f64).log2())
+}
+
+/// A four-digit year from 1900 to 2099
+fn year_bits(token: &[char]) -> Option<f64> {
+    let text: String = token.iter().collect();
+    let year: u32 = text.parse().ok().filter(|_| token.len() == 4)?;
+    (1900..2100).contains(&year).then(|| YEARS.log2())
+}
+
+/// A day, month and year in digits, e.g. "25121987", "19871225" or "1/2/03"
+fn date_bits(token: &[char]) -> Option<f64> {
+    let text: String = token.iter().collect();
+    let separator = token.iter().find(|c| !c.is_ascii_digit());
+    let (splits, separator_bits) = match separator {
+        Some(sep) if DATE_SEPARATORS.contains(sep) => {
+            let parts: Vec<&str> = text.split(*sep).collect();
+            (vec![parts], (DATE_SEPARATORS.len() as f64).log2())
+        }
+        Some(_) => return None,
+        None => match text.len() {
+            6 => (vec![vec![&text[..2], &text[2..4], &text[4..]]], 0.0),
+            8 => (
+                vec![
+                    vec![&text[..2], &text[2..4], &text[4..]],
+                    vec![&text[..4], &text[4..6], &text[6..]],
+                ],
+                0.0,
+            ),
+            _ => return None,
+        },
+    };
+    let years = splits
+        .iter()
+        .filter(|parts| parts.len() == 3)
+        .filter_map(|parts| {
+            let [a, b, c] = [parts[0], parts[1], parts[2]];
+            [(a, b, c), (b, a, c), (c, b, a)]
+                .into_iter()
+                .find_map(|(day, month, year)| date_years(day, month, year))
+        })
+        .min_by(f64::total_cmp)?;
+    Some((DAYS * years * DATE_ORDERS).log2() + separator_bits)
+}
+
+/// Years a valid date with these parts could be drawn from, or None if it isn't one
+fn date_years(day: &str, month: &str, year: &str) -> Option<f64> {
+    let number = |part: &str, max_len: usize| {
+        (!part.is_empty() && part.len() <= max_len)
+            .then(|| part.parse::<u32>().ok())
+            .flatten()
+    };
+    let day = number(day, 2)?;
+    let month = number(month, 2)?;
+    let year_digits = year.len();
+    let year = number(year, 4)?;
+    if !(1..=31).contains(&day) || !(1..=12).contains(&month) {
+        return None;
+    }
+    match year_digits {
+        2 => Some(SHORT_YEARS),
+        4 if (1900..2100).contains(&year) => Some(YEARS),
+        _ => None,
+    }
+}
+
+fn log2_binomial(n: usize, k: usize) -> f64 {
+    (0..k.min(n - k))
+        .map(|i| ((n - i) as f64 / (i + 1) as f64).log2())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patterns_score_low() {
+        let estimator = PatternEstimator::new();
+        let word = (dictionary().len() as f64).log2();
+        assert!((estimator.bits("unlocked") - word).abs() < 1e-9);
+        assert!((estimator.bits("Unl0cked") - word - 2.0).abs() < 1e-9);
+        assert!(estimator.bits("abcdefgh") < 10.0);
+        assert!(estimator.bits("qwertyui") < 10.0);
+        assert!(estimator.bits("zzzzzzzz") < 10.0);
+        assert!((estimator.bits("1987") - YEARS.log2()).abs() < 1e-9);
+
+        // Nothing to find: every character is brute-forced
+        let random = "q7Xk!v2Pz";
+        assert!((estimator.bits(random) - 9.0 * bruteforce_bits(random)).abs() < 1e-9);
+        assert!(estimator.analyze(random).matches.is_empty());
+    }
+
+    #[test]
+    fn test_analyze_finds_matches() {
+        let estimator = PatternEstimator::new();
+        let analysis = estimator.analyze("xUnlocked1987!");
+        let found: Vec<_> = analysis
+            .matches
+            .iter()
+            .map(|m| (m.kind, m.start, m.end))
+            .collect();
+        assert_eq!(
+            found,
+            [(PatternKind::Word, 1, 9), (PatternKind::Year, 9, 13)]
+        );
+        let pattern_bits: f64 = analysis.matches.iter().map(|m| m.bits).sum();
+        let rest = 2.0 * bruteforce_bits("xUnlocked1987!");
+        assert!((analysis.bits - pattern_bits - rest).abs() < 1e-9);
+
+        for date in ["25121987", "19871225", "12/25/87", "1.2.2003"] {
+            let kinds: Vec<_> = estimator
+                .analyze(date)
+                .matches
+                .iter()
+                .map(|m| m.kind)
+                .collect();
+            assert_eq!(kinds, [PatternKind::Date], "{}", date);
+        }
+        assert_eq!(date_bits(&"32131987".chars().collect::<Vec<_>>()), None);
+        assert_eq!(date_bits(&"12/25-87".chars().collect::<Vec<_>>()), None);
+    }
+}
@@ -0,0 +1,199 @@
+//! Documented exit codes and `--error-format json`
+//!
+//! Scripts and orchestration tools branch on why a run failed, so each kind
+//! of failure keeps a fixed exit code and, with `--error-format json`, the
+//! error is printed on stderr as one JSON object instead of text.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde_json::{json, Value};
+
+use crate::clipboard::ClipboardError;
+use crate::compat::CompatError;
+use crate::config::ConfigError;
+use crate::error::PwgenError;
+use crate::http::HttpError;
+use crate::output::files::FilesError;
+use crate::output::share::ShareError;
+use crate::provision::ProvisionError;
+use crate::rules::RulesError;
+use crate::wordlist::WordlistError;
+
+/// Why a run failed, each with a fixed exit code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// 1: anything not covered below
+    General = 1,
+    /// 2: invalid arguments or configuration (as for command-line syntax errors)
+    Usage = 2,
+    /// 3: no password can satisfy the requested policy
+    Unsatisfiable = 3,
+    /// 4: the passwords would fall below the required or minimum entropy
+    TooWeak = 4,
+    /// 5: an output file, encrypted copy, clipboard, history or audit log failed
+    Storage = 5,
+    /// 6: a network lookup failed
+    Network = 6,
+}
+
+impl Failure {
+    pub const ALL: [Failure; 6] = [
+        Failure::General,
+        Failure::Usage,
+        Failure::Unsatisfiable,
+        Failure::TooWeak,
+        Failure::Storage,
+        Failure::Network,
+    ];
+
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    /// Stable name used in JSON errors
+    pub fn name(self) -> &'static str {
+        match self {
+            Failure::General => "general",
+            Failure::Usage => "usage",
+            Failure::Unsatisfiable => "unsatisfiable",
+            Failure::TooWeak => "too-weak",
+            Failure::Storage => "storage",
+            Failure::Network => "network",
+        }
+    }
+
+    /// An error that ends the run with this failure's code and `message`
+    pub fn because(self, message: impl Into<String>) -> Reason {
+        Reason {
+            failure: self,
+            message: message.into(),
+        }
+    }
+
+    /// The failure `err` stands for
+    ///
+    /// An explicit [`Reason`] anywhere in the context wins; otherwise the
+    /// first error in the chain with a known type decides.
+    pub fn of(err: &anyhow::Error) -> Self {
+        if let Some(reason) = err.downcast_ref::<Reason>() {
+            return reason.failure;
+        }
+        err.chain().find_map(classify).unwrap_or(Failure::General)
+    }
+}
+
+fn classify(err: &(dyn std::error::Error + 'static)) -> Option<Failure> {
+    if let Some(err) = err.downcast_ref::<PwgenError>() {
+        return Some(err.failure());
+    }
+    if err.is::<FilesError>() || err.is::<ShareError>() || err.is::<ClipboardError>() {
+        return Some(Failure::Storage);
+    }
+    #[cfg(feature = "breach")]
+    if err.is::<crate::breach::BreachError>() {
+        return Some(Failure::Network);
+    }
+    if err.is::<HttpError>() {
+        return Some(Failure::Network);
+    }
+    if err.is::<ConfigError>()
+        || err.is::<RulesError>()
+        || err.is::<WordlistError>()
+        || err.is::<CompatError>()
+        || err.is::<ProvisionError>()
+    {
+        return Some(Failure::Usage);
+    }
+    None
+}
+
+/// Error message tagged with the [`Failure`] it stands for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reason {
+    failure: Failure,
+    message: String,
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Reason {}
+
+/// How the CLI reports a failed run on stderr (`--error-format`)
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// `error: <message>`
+    #[default]
+    Text,
+    /// `{"error":"<kind>","exit_code":<code>,"message":"<message>"}` on one line
+    Json,
+}
+
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// Report errors from now on in `format`
+pub fn set_error_format(format: ErrorFormat) {
+    JSON_ERRORS.store(format == ErrorFormat::Json, Ordering::Relaxed);
+}
+
+pub fn error_format() -> ErrorFormat {
+    if JSON_ERRORS.load(Ordering::Relaxed) {
+        ErrorFormat::Json
+    } else {
+        ErrorFormat::Text
+    }
+}
+
+/// The JSON form of `err`, with its causes joined into the message
+pub fn to_json(err: &anyhow::Error) -> Value {
+    let failure = Failure::of(err);
+    json!({
+        "error": failure.name(),
+        "exit_code": failure.code(),
+        "message": format!("{:#}", err),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_failure_of_errors() {
+        let err = anyhow::Error::new(PwgenError::ZeroLength);
+        assert_eq!(Failure::of(&err), Failure::Usage);
+
+        let err = anyhow::Error::new(PwgenError::RulesUnsatisfiable { length: 4 })
+            .context("building the generator");
+        assert_eq!(Failure::of(&err), Failure::Unsatisfiable);
+
+        let err = anyhow::Error::new(HttpError::Offline { url: "x".into() });
+        assert_eq!(Failure::of(&err), Failure::Network);
+
+        let err: anyhow::Error = anyhow::anyhow!("something else");
+        assert_eq!(Failure::of(&err), Failure::General);
+
+        // An explicit reason wins over the error it wraps
+        let io: Result<(), _> = Err(std::io::Error::other("disk full"));
+        let err = io
+            .with_context(|| Failure::Storage.because("writing audit log audit.jsonl"))
+            .unwrap_err();
+        assert_eq!(Failure::of(&err), Failure::Storage);
+        assert_eq!(
+            to_json(&err),
+            json!({
+                "error": "storage",
+                "exit_code": 5,
+                "message": "writing audit log audit.jsonl: disk full",
+            })
+        );
+
+        let codes: Vec<u8> = Failure::ALL.iter().map(|f| f.code()).collect();
+        assert_eq!(codes, [1, 2, 3, 4, 5, 6]);
+    }
+}
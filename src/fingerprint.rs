@@ -0,0 +1,50 @@
+//! Transcription fingerprints (`--fingerprint`, `fingerprint` subcommand)
+//!
+//! Two words derived from a SHA-256 hash of the value. After typing a password
+//! into another device, running `pwgen-x fingerprint` there and comparing the
+//! words confirms it was transcribed correctly without showing it again.
+//!
+//! A fingerprint reveals about 26 bits about the value to anyone who sees it,
+//! so it is meant for the screen, not for storage.
+
+use crate::digest::sha256;
+use crate::wordlist::Wordlist;
+
+/// Words in a fingerprint
+pub const WORDS: usize = 2;
+
+/// Prefix hashed before the value, so fingerprints can't be mistaken for plain hashes
+const DOMAIN: &[u8] = b"pwgen-x fingerprint v1\0";
+
+/// Fingerprint of `value`, e.g. `"otter lantern"`
+pub fn fingerprint(value: &str) -> String {
+    let mut input = DOMAIN.to_vec();
+    input.extend_from_slice(value.as_bytes());
+    let digest = sha256(&input);
+    input.fill(0);
+
+    let list = Wordlist::builtin();
+    let words = list.words();
+    digest
+        .chunks(4)
+        .take(WORDS)
+        .map(|chunk| {
+            let n = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+            words[n as usize % words.len()].as_str()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint() {
+        let print = fingerprint("correct horse battery staple");
+        assert_eq!(print.split(' ').count(), WORDS);
+        assert_eq!(print, fingerprint("correct horse battery staple"));
+        assert_ne!(print, fingerprint("correct horse battery stapel"));
+    }
+}
@@ -0,0 +1,147 @@
+use rand::Rng;
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+use crate::entropy::EntropyInfo;
+use crate::generators::{GeneratedPassword, PasswordGenerator};
+
+/// iCloud Keychain-style passwords such as `hikmen-qirxo6-cetSyp` (`apple`)
+///
+/// Three six-letter chunks shaped consonant-vowel-consonant-consonant-vowel-
+/// consonant, joined by hyphens. One digit replaces the first or last letter
+/// of a chunk, and exactly one of the remaining letters is uppercase. Every
+/// choice is uniform and each combination gives a different password, so the
+/// entropy is exactly log2 of their number, about 71.5 bits.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AppleGenerator;
+
+impl AppleGenerator {
+    /// Letters per chunk, and the consonant/vowel shape of each
+    const SHAPE: &'static str = "cvccvc";
+    const CHUNKS: usize = 3;
+    /// Without `l`, which reads as `1` or `I`
+    const CONSONANTS: &'static str = "bcdfghjkmnpqrstvwxyz";
+    const VOWELS: &'static str = "aeiou";
+
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// log2 of the number of passwords the construction can produce
+    pub fn entropy_bits() -> f64 {
+        let consonants = Self::SHAPE.matches('c').count() * Self::CHUNKS;
+        let vowels = Self::SHAPE.matches('v').count() * Self::CHUNKS;
+        let letters = consonants + vowels;
+        // The digit always replaces a consonant, at one of two ends of a chunk
+        let digit_places = 2 * Self::CHUNKS;
+        (consonants - 1) as f64 * (Self::CONSONANTS.len() as f64).log2()
+            + vowels as f64 * (Self::VOWELS.len() as f64).log2()
+            + ((digit_places * 10) as f64).log2()
+            + ((letters - 1) as f64).log2()
+    }
+}
+
+impl PasswordGenerator for AppleGenerator {
+    fn generate(&self, rng: &mut dyn RngCore) -> GeneratedPassword {
+        let pick =
+            |rng: &mut dyn RngCore, set: &str| set.as_bytes()[rng.gen_range(0..set.len())] as char;
+        let len = Self::SHAPE.len();
+        let mut chars: Zeroizing<Vec<char>> =
+            Zeroizing::new(Vec::with_capacity(len * Self::CHUNKS));
+        for _ in 0..Self::CHUNKS {
+            for shape in Self::SHAPE.chars() {
+                let set = if shape == 'c' {
+                    Self::CONSONANTS
+                } else {
+                    Self::VOWELS
+                };
+                chars.push(pick(rng, set));
+            }
+        }
+
+        let end = rng.gen_range(0..2 * Self::CHUNKS);
+        let digit = (end / 2) * len + if end % 2 == 0 { 0 } else { len - 1 };
+        chars[digit] = (b'0' + rng.gen_range(0..10)) as char;
+        let upper = rng.gen_range(0..chars.len() - 1);
+        let upper = if upper >= digit { upper + 1 } else { upper };
+        chars[upper] = chars[upper].to_ascii_uppercase();
+
+        let mut value = Zeroizing::new(String::with_capacity(chars.len() + Self::CHUNKS));
+        for (i, chunk) in chars.chunks(len).enumerate() {
+            if i > 0 {
+                value.push('-');
+            }
+            value.extend(chunk);
+        }
+
+        GeneratedPassword {
+            value,
+            entropy: EntropyInfo::new(Self::entropy_bits(), "Apple"),
+            mnemonic: None,
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        "Apple-style password"
+    }
+
+    fn length(&self) -> Option<usize> {
+        Some(Self::SHAPE.len() * Self::CHUNKS + Self::CHUNKS - 1)
+    }
+
+    fn estimated_entropy(&self) -> Option<f64> {
+        Some(Self::entropy_bits())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_apple_format() {
+        let gen = AppleGenerator::new();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let mut digit_places = std::collections::HashSet::new();
+        for _ in 0..500 {
+            let password = gen.generate(&mut rng);
+            let value = password.value.as_str();
+            let chunks: Vec<&str> = value.split('-').collect();
+            assert_eq!(chunks.len(), 3, "{}", value);
+            assert!(chunks.iter().all(|chunk| chunk.len() == 6), "{}", value);
+            assert_eq!(value.chars().filter(char::is_ascii_uppercase).count(), 1);
+            let digit = value.find(|c: char| c.is_ascii_digit()).unwrap();
+            assert_eq!(value.chars().filter(char::is_ascii_digit).count(), 1);
+            assert!([0, 5, 7, 12, 14, 19].contains(&digit), "{}", value);
+            digit_places.insert(digit);
+            for chunk in chunks {
+                for (c, shape) in chunk.chars().zip("cvccvc".chars()) {
+                    let c = c.to_ascii_lowercase();
+                    match shape {
+                        'v' => assert!(AppleGenerator::VOWELS.contains(c), "{}", value),
+                        _ => assert!(
+                            c.is_ascii_digit() || AppleGenerator::CONSONANTS.contains(c),
+                            "{}",
+                            value
+                        ),
+                    }
+                }
+            }
+        }
+        assert_eq!(digit_places.len(), 6);
+    }
+
+    #[test]
+    fn test_apple_entropy() {
+        // 11 consonants, 6 vowels, the digit's place and value, the capital's place
+        let expected = 11.0 * 20f64.log2() + 6.0 * 5f64.log2() + 60f64.log2() + 17f64.log2();
+        assert!((AppleGenerator::entropy_bits() - expected).abs() < 1e-9);
+        assert!((AppleGenerator::entropy_bits() - 71.47).abs() < 0.01);
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let password = AppleGenerator::new().generate(&mut rng);
+        assert_eq!(password.entropy.bits, AppleGenerator::entropy_bits());
+        assert_eq!(AppleGenerator::new().uniform_alphabet(), None);
+    }
+}
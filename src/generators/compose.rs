@@ -0,0 +1,143 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// A character class of a [`Composition`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharClass {
+    Lower,
+    Upper,
+    Digit,
+    Symbol,
+}
+
+impl CharClass {
+    pub const ALL: [CharClass; 4] = [
+        CharClass::Lower,
+        CharClass::Upper,
+        CharClass::Digit,
+        CharClass::Symbol,
+    ];
+
+    /// Class of `c`; anything that is not a letter or digit counts as a symbol
+    pub fn of(c: char) -> Self {
+        if c.is_lowercase() {
+            CharClass::Lower
+        } else if c.is_uppercase() {
+            CharClass::Upper
+        } else if c.is_numeric() {
+            CharClass::Digit
+        } else {
+            CharClass::Symbol
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CharClass::Lower => "lower",
+            CharClass::Upper => "upper",
+            CharClass::Digit => "digit",
+            CharClass::Symbol => "symbol",
+        }
+    }
+}
+
+impl FromStr for CharClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CharClass::ALL
+            .into_iter()
+            .find(|class| class.name() == s.trim())
+            .ok_or_else(|| {
+                format!(
+                    "unknown class '{}' (lower, upper, digit or symbol)",
+                    s.trim()
+                )
+            })
+    }
+}
+
+/// Weighted character classes for the secure generator (`--compose`)
+///
+/// `lower:60,upper:20,digit:15,symbol:5` draws each position's class with
+/// those relative weights, then a character uniformly from the class, for
+/// passwords that are mostly letters but usually have a few of the rest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Composition {
+    classes: Vec<(CharClass, f64)>,
+}
+
+impl Composition {
+    /// Classes with their weights, in the order given
+    pub fn classes(&self) -> &[(CharClass, f64)] {
+        &self.classes
+    }
+}
+
+impl FromStr for Composition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut classes: Vec<(CharClass, f64)> = Vec::new();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (name, weight) = part
+                .split_once(':')
+                .ok_or_else(|| format!("'{}' is not <class>:<weight>", part))?;
+            let class: CharClass = name.parse()?;
+            let weight: f64 = weight
+                .trim()
+                .parse()
+                .ok()
+                .filter(|w: &f64| w.is_finite() && *w > 0.0)
+                .ok_or_else(|| format!("weight of {} must be a positive number", class.name()))?;
+            if classes.iter().any(|(c, _)| *c == class) {
+                return Err(format!("{} is given twice", class.name()));
+            }
+            classes.push((class, weight));
+        }
+        if classes.is_empty() {
+            return Err("give at least one class, e.g. lower:80,digit:20".to_string());
+        }
+        Ok(Self { classes })
+    }
+}
+
+impl fmt::Display for Composition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self
+            .classes
+            .iter()
+            .map(|(class, weight)| format!("{}:{}", class.name(), weight))
+            .collect();
+        f.write_str(&parts.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_composition() {
+        let composition: Composition = "lower:60, upper:20,digit:15,symbol:5".parse().unwrap();
+        assert_eq!(
+            composition.classes(),
+            &[
+                (CharClass::Lower, 60.0),
+                (CharClass::Upper, 20.0),
+                (CharClass::Digit, 15.0),
+                (CharClass::Symbol, 5.0)
+            ]
+        );
+        assert_eq!(
+            composition.to_string(),
+            "lower:60,upper:20,digit:15,symbol:5"
+        );
+
+        assert!("lower".parse::<Composition>().is_err());
+        assert!("vowel:5".parse::<Composition>().is_err());
+        assert!("lower:0".parse::<Composition>().is_err());
+        assert!("lower:5,lower:6".parse::<Composition>().is_err());
+        assert!("".parse::<Composition>().is_err());
+    }
+}
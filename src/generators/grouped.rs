@@ -0,0 +1,137 @@
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+use super::{GeneratedPassword, PasswordGenerator};
+use crate::error::PwgenError;
+use crate::graphemes;
+
+/// Wraps another generator, inserting a separator into the value every `size` characters
+///
+/// Unlike display-only grouping, the separator is part of the password
+/// (`ABCDE-FGHIJ-KLMNO`, as in licence keys). It is fixed, so it adds no entropy.
+pub struct GroupedGenerator {
+    inner: Box<dyn PasswordGenerator>,
+    size: usize,
+    separator: String,
+}
+
+impl GroupedGenerator {
+    pub const DEFAULT_SEPARATOR: &'static str = "-";
+
+    pub fn new(
+        inner: Box<dyn PasswordGenerator>,
+        size: usize,
+        separator: impl Into<String>,
+    ) -> Result<Self, PwgenError> {
+        let separator = separator.into();
+        if size == 0 {
+            return Err(PwgenError::ZeroGroupSize);
+        }
+        if let Some(length) = inner.length().filter(|&length| size >= length) {
+            return Err(PwgenError::GroupSizeTooLarge { size, length });
+        }
+        // Like passphrase separators: alphanumerics would be read as part of a group
+        if separator.is_empty()
+            || separator
+                .chars()
+                .any(|c| c.is_alphanumeric() || c.is_control())
+        {
+            return Err(PwgenError::InvalidGroupSeparator(separator));
+        }
+        Ok(Self {
+            inner,
+            size,
+            separator,
+        })
+    }
+}
+
+impl PasswordGenerator for GroupedGenerator {
+    fn generate(&self, rng: &mut dyn RngCore) -> GeneratedPassword {
+        let password = self.inner.generate(rng);
+        let grouped = graphemes::split(&password.value)
+            .chunks(self.size)
+            .map(|chunk| chunk.concat())
+            .collect::<Vec<_>>()
+            .join(&self.separator);
+        GeneratedPassword {
+            value: Zeroizing::new(grouped),
+            ..password
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        self.inner.description()
+    }
+
+    fn length(&self) -> Option<usize> {
+        let length = self.inner.length()?;
+        Some(length + (length - 1) / self.size * graphemes::split(&self.separator).len())
+    }
+
+    fn estimated_entropy(&self) -> Option<f64> {
+        self.inner.estimated_entropy()
+    }
+
+    fn is_temporary(&self) -> bool {
+        self.inner.is_temporary()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::PinGenerator;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_grouping() {
+        let pin = PinGenerator::builder().length(12).build().unwrap();
+        let generator = GroupedGenerator::new(Box::new(pin), 5, " ").unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let password = generator.generate(&mut rng);
+
+        let groups: Vec<&str> = password.value.split(' ').collect();
+        assert_eq!(
+            groups.iter().map(|g| g.len()).collect::<Vec<_>>(),
+            [5, 5, 2]
+        );
+        // The separator adds no entropy
+        assert!((password.entropy.bits - 12.0 * 10f64.log2()).abs() < 1e-9);
+        assert_eq!(generator.estimated_entropy(), password.entropy.bits.into());
+
+        assert_eq!(generator.length(), Some(14));
+    }
+
+    #[test]
+    fn test_grouping_rejects() {
+        let pin = || Box::new(PinGenerator::builder().length(16).build().unwrap());
+        assert_eq!(
+            GroupedGenerator::new(pin(), 0, "-").err(),
+            Some(PwgenError::ZeroGroupSize)
+        );
+        assert_eq!(
+            GroupedGenerator::new(pin(), 50, "-").err(),
+            Some(PwgenError::GroupSizeTooLarge {
+                size: 50,
+                length: 16
+            })
+        );
+        assert_eq!(
+            GroupedGenerator::new(pin(), 16, "-").err(),
+            Some(PwgenError::GroupSizeTooLarge {
+                size: 16,
+                length: 16
+            })
+        );
+        assert!(GroupedGenerator::new(pin(), 15, "-").is_ok());
+        for separator in ["", "a", "7", "\t"] {
+            assert_eq!(
+                GroupedGenerator::new(pin(), 4, separator).err(),
+                Some(PwgenError::InvalidGroupSeparator(separator.to_string()))
+            );
+        }
+        assert!(GroupedGenerator::new(pin(), 4, " · ").is_ok());
+    }
+}
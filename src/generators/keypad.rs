@@ -0,0 +1,183 @@
+use rand::Rng;
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+use crate::entropy::EntropyInfo;
+use crate::error::PwgenError;
+use crate::generators::{GeneratedPassword, PasswordGenerator};
+
+/// A short pronounceable word followed by digits, e.g. `tavo-7319`
+///
+/// For voicemail, door and alarm systems that accept alphanumeric codes typed
+/// on a phone keypad: the word is easy to say and remember, and
+/// [`keypad_digits`] gives the keys to press for it. The word alternates
+/// consonants and vowels, starting with a consonant; the dash is only there
+/// for reading and is not part of what gets keyed in.
+pub struct KeypadGenerator {
+    letters: usize,
+    digits: usize,
+}
+
+/// Builder for [`KeypadGenerator`], validated at [`build`](Self::build) time
+#[derive(Debug, Clone)]
+pub struct KeypadGeneratorBuilder {
+    letters: usize,
+    digits: usize,
+}
+
+impl Default for KeypadGeneratorBuilder {
+    fn default() -> Self {
+        Self {
+            letters: KeypadGenerator::DEFAULT_LETTERS,
+            digits: KeypadGenerator::DEFAULT_DIGITS,
+        }
+    }
+}
+
+impl KeypadGeneratorBuilder {
+    /// Letters in the word
+    pub fn letters(mut self, letters: usize) -> Self {
+        self.letters = letters;
+        self
+    }
+
+    /// Digits after the word
+    pub fn digits(mut self, digits: usize) -> Self {
+        self.digits = digits;
+        self
+    }
+
+    pub fn build(self) -> Result<KeypadGenerator, PwgenError> {
+        KeypadGenerator::new(self.letters, self.digits)
+    }
+}
+
+impl KeypadGenerator {
+    pub const DEFAULT_LETTERS: usize = 4;
+    pub const DEFAULT_DIGITS: usize = 4;
+
+    /// Consonants that are hard to mishear when read out
+    const CONSONANTS: &'static [u8] = b"bdfgklmnprstvz";
+    const VOWELS: &'static [u8] = b"aeiou";
+
+    pub fn new(letters: usize, digits: usize) -> Result<Self, PwgenError> {
+        if letters == 0 {
+            return Err(PwgenError::ZeroLength);
+        }
+        Ok(Self { letters, digits })
+    }
+
+    pub fn builder() -> KeypadGeneratorBuilder {
+        KeypadGeneratorBuilder::default()
+    }
+
+    /// Entropy of a code with this many letters and digits
+    pub fn entropy_bits(letters: usize, digits: usize) -> f64 {
+        let consonants = letters.div_ceil(2) as f64;
+        let vowels = (letters / 2) as f64;
+        consonants * (Self::CONSONANTS.len() as f64).log2()
+            + vowels * (Self::VOWELS.len() as f64).log2()
+            + digits as f64 * 10f64.log2()
+    }
+}
+
+impl PasswordGenerator for KeypadGenerator {
+    fn generate(&self, rng: &mut dyn RngCore) -> GeneratedPassword {
+        let mut code = String::with_capacity(self.letters + 1 + self.digits);
+        for i in 0..self.letters {
+            let set = if i % 2 == 0 {
+                Self::CONSONANTS
+            } else {
+                Self::VOWELS
+            };
+            code.push(set[rng.gen_range(0..set.len())] as char);
+        }
+        if self.digits > 0 {
+            code.push('-');
+        }
+        for _ in 0..self.digits {
+            code.push((b'0' + rng.gen_range(0..10)) as char);
+        }
+
+        GeneratedPassword {
+            value: Zeroizing::new(code),
+            entropy: EntropyInfo::new(Self::entropy_bits(self.letters, self.digits), "Keypad"),
+            mnemonic: None,
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        "Keypad code (word + digits)"
+    }
+
+    fn length(&self) -> Option<usize> {
+        Some(self.letters + usize::from(self.digits > 0) + self.digits)
+    }
+
+    fn estimated_entropy(&self) -> Option<f64> {
+        Some(Self::entropy_bits(self.letters, self.digits))
+    }
+}
+
+/// The phone keypad keys (ITU E.161) that enter `value`, e.g. `8286-7319` for `tavo-7319`
+///
+/// Letters become their key's digit; everything else is kept as it is.
+pub fn keypad_digits(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c.to_ascii_lowercase() {
+            'a'..='c' => '2',
+            'd'..='f' => '3',
+            'g'..='i' => '4',
+            'j'..='l' => '5',
+            'm'..='o' => '6',
+            'p'..='s' => '7',
+            't'..='v' => '8',
+            'w'..='z' => '9',
+            _ => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_keypad_codes() {
+        let generator = KeypadGenerator::builder().build().unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        for _ in 0..20 {
+            let password = generator.generate(&mut rng);
+            let (word, digits) = password.value.split_once('-').unwrap();
+            assert_eq!(word.len(), KeypadGenerator::DEFAULT_LETTERS);
+            assert!(word
+                .bytes()
+                .step_by(2)
+                .all(|c| KeypadGenerator::CONSONANTS.contains(&c)));
+            assert!(word
+                .bytes()
+                .skip(1)
+                .step_by(2)
+                .all(|c| KeypadGenerator::VOWELS.contains(&c)));
+            assert_eq!(digits.len(), KeypadGenerator::DEFAULT_DIGITS);
+            assert!(digits.bytes().all(|c| c.is_ascii_digit()));
+        }
+
+        // 14 * 5 * 14 * 5 words and 10^4 digit strings
+        let expected = (4900f64 * 10_000.0).log2();
+        assert!((generator.estimated_entropy().unwrap() - expected).abs() < 1e-9);
+
+        let bare = KeypadGenerator::new(5, 0).unwrap().generate(&mut rng);
+        assert!(!bare.value.contains('-'));
+        assert!(KeypadGenerator::new(0, 4).is_err());
+    }
+
+    #[test]
+    fn test_keypad_digits() {
+        assert_eq!(keypad_digits("tavo-7319"), "8286-7319");
+        assert_eq!(keypad_digits("Jaz"), "529");
+    }
+}
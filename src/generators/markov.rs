@@ -1,102 +1,120 @@
 use rand::distributions::WeightedIndex;
 use rand::prelude::Distribution;
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::seq::index;
 use rand::RngCore;
+use rand::{Rng, SeedableRng};
 use std::collections::HashMap;
+use std::sync::OnceLock;
 use zeroize::Zeroizing;
 
 use crate::entropy::EntropyInfo;
-use crate::generators::{GeneratedPassword, PasswordGenerator};
+use crate::error::PwgenError;
+use crate::generators::{GeneratedPassword, PasswordGenerator, SymbolSet};
 
 /// EFF wordlist for training the Markov model
-const TRAINING_WORDS: &str = include_str!("../../data/eff_large_wordlist.txt");
+pub(crate) const TRAINING_WORDS: &str = include_str!("../../data/eff_large_wordlist.txt");
 
-/// 2nd-order Markov model for generating pronounceable passwords
-pub struct MarkovGenerator {
+/// Trained 2nd-order model: which characters follow each bigram, and how often
+#[derive(Debug, Clone)]
+pub struct MarkovModel {
     /// (char1, char2) -> vec of (next_char, weight)
     transitions: HashMap<(char, char), Vec<(char, u32)>>,
     /// Starting bigrams with their weights
     start_pairs: Vec<((char, char), u32)>,
     /// Average branching factor for entropy calculation
     avg_branching_factor: f64,
-    /// Target password length
-    length: usize,
-    /// Include digits
-    include_digits: bool,
-    /// Include symbols
-    include_symbols: bool,
-    /// Capitalize first letter
-    capitalize: bool,
 }
 
-impl MarkovGenerator {
-    const READABLE_SYMBOLS: [char; 10] = ['!', '@', '#', '$', '%', '&', '*', '-', '_', '+'];
-    const VOWELS: [char; 5] = ['a', 'e', 'i', 'o', 'u'];
+impl MarkovModel {
+    /// The model trained on the built-in EFF wordlist
+    pub fn builtin() -> Self {
+        let mut counts = ModelCounts::default();
+        for line in TRAINING_WORDS.lines() {
+            // `<dice>\t<word>`
+            if let [_, word] = line.split('\t').collect::<Vec<_>>()[..] {
+                counts.add_word(word);
+            }
+        }
+        counts
+            .into_model()
+            .expect("the built-in wordlist trains a model")
+    }
 
-    pub fn new(length: usize, include_digits: bool, include_symbols: bool, capitalize: bool) -> Self {
-        let (transitions, start_pairs, avg_branching_factor) = Self::build_model();
+    /// Distinct bigrams that have a successor
+    pub fn bigrams(&self) -> usize {
+        self.transitions.len()
+    }
 
-        Self {
-            transitions,
-            start_pairs,
-            avg_branching_factor,
-            length,
-            include_digits,
-            include_symbols,
-            capitalize,
-        }
+    pub fn avg_branching_factor(&self) -> f64 {
+        self.avg_branching_factor
     }
+}
 
-    /// Build the Markov model from the training wordlist
-    #[allow(clippy::type_complexity)]
-    fn build_model() -> (
-        HashMap<(char, char), Vec<(char, u32)>>,
-        Vec<((char, char), u32)>,
-        f64,
-    ) {
-        let mut bigram_counts: HashMap<(char, char), HashMap<char, u32>> = HashMap::new();
-        let mut start_counts: HashMap<(char, char), u32> = HashMap::new();
+/// Trigram counts gathered word by word, turned into a [`MarkovModel`] at the end
+#[derive(Debug, Default)]
+pub struct ModelCounts {
+    bigrams: HashMap<(char, char), HashMap<char, u32>>,
+    starts: HashMap<(char, char), u32>,
+    /// Distinct (bigram, next character) pairs
+    transitions: usize,
+    words: u64,
+}
 
-        // Parse training words and count transitions
-        for line in TRAINING_WORDS.lines() {
-            let parts: Vec<&str> = line.split('\t').collect();
-            if parts.len() != 2 {
-                continue;
+impl ModelCounts {
+    /// Rough heap cost of each distinct bigram, transition and starting bigram,
+    /// including hash table overhead
+    const BIGRAM_BYTES: usize = 96;
+    const TRANSITION_BYTES: usize = 24;
+    const START_BYTES: usize = 24;
+
+    /// Count one word; it is lowercased, non-letters are dropped, and words
+    /// shorter than three letters are skipped
+    pub fn add_word(&mut self, word: &str) {
+        let chars: Vec<char> = word
+            .chars()
+            .filter(|c| c.is_alphabetic())
+            .flat_map(char::to_lowercase)
+            .collect();
+        if chars.len() < 3 {
+            return;
+        }
+        self.words += 1;
+        *self.starts.entry((chars[0], chars[1])).or_insert(0) += 1;
+        for window in chars.windows(3) {
+            let next = self.bigrams.entry((window[0], window[1])).or_default();
+            let count = next.entry(window[2]).or_insert(0);
+            if *count == 0 {
+                self.transitions += 1;
             }
+            *count = count.saturating_add(1);
+        }
+    }
 
-            let word = parts[1].to_lowercase();
-            let chars: Vec<char> = word.chars().filter(|c| c.is_alphabetic()).collect();
+    /// Words counted so far
+    pub fn words(&self) -> u64 {
+        self.words
+    }
 
-            if chars.len() < 3 {
-                continue;
-            }
+    /// Approximate bytes the counts occupy
+    pub fn memory(&self) -> usize {
+        self.bigrams.len() * Self::BIGRAM_BYTES
+            + self.transitions * Self::TRANSITION_BYTES
+            + self.starts.len() * Self::START_BYTES
+    }
 
-            // Count starting bigram
-            let start = (chars[0], chars[1]);
-            *start_counts.entry(start).or_insert(0) += 1;
-
-            // Count trigram transitions
-            for window in chars.windows(3) {
-                let key = (window[0], window[1]);
-                let next = window[2];
-                *bigram_counts
-                    .entry(key)
-                    .or_default()
-                    .entry(next)
-                    .or_insert(0) += 1;
-            }
+    /// The model, or `None` if no word was long enough to count
+    pub fn into_model(self) -> Option<MarkovModel> {
+        if self.starts.is_empty() {
+            return None;
         }
-
         // Convert to weighted vectors for efficient sampling
-        let transitions: HashMap<(char, char), Vec<(char, u32)>> = bigram_counts
+        let transitions: HashMap<(char, char), Vec<(char, u32)>> = self
+            .bigrams
             .into_iter()
-            .map(|(key, counts)| {
-                let vec: Vec<(char, u32)> = counts.into_iter().collect();
-                (key, vec)
-            })
+            .map(|(key, counts)| (key, counts.into_iter().collect()))
             .collect();
-
-        let start_pairs: Vec<((char, char), u32)> = start_counts.into_iter().collect();
+        let start_pairs: Vec<((char, char), u32)> = self.starts.into_iter().collect();
 
         // Calculate average branching factor
         let total_transitions: usize = transitions.values().map(|v| v.len()).sum();
@@ -106,34 +124,351 @@ impl MarkovGenerator {
             26.0 // fallback
         };
 
-        (transitions, start_pairs, avg_branching_factor)
+        Some(MarkovModel {
+            transitions,
+            start_pairs,
+            avg_branching_factor,
+        })
+    }
+}
+
+/// 2nd-order Markov model for generating pronounceable passwords
+pub struct MarkovGenerator {
+    model: MarkovModel,
+    /// Target password length
+    length: usize,
+    /// Digits inserted at random positions
+    digit_count: usize,
+    /// Symbols inserted at random positions
+    symbol_count: usize,
+    /// Symbols the inserted ones are drawn from
+    symbol_set: Vec<char>,
+    /// Capitalize first letter
+    capitalize: bool,
+    /// Candidates among this share (in percent) of the most probable outputs are redrawn
+    min_guess_percentile: f64,
+    /// The model's own outputs, sampled on first use to rank candidates against
+    guess_table: OnceLock<Option<GuessTable>>,
+}
+
+/// Where a password falls among everything the model generates
+///
+/// Estimated as if the attacker knew the model and guessed its outputs from
+/// the most probable down (Dell'Amico and Filippone's Monte Carlo method).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GuessRank {
+    /// Share of the model's outputs, in percent, at least as probable as this one
+    pub percentile: f64,
+    /// log2 of the guesses needed to reach it
+    pub log2_guesses: f64,
+}
+
+/// Surprisals of sampled outputs, most probable first, with running guess counts
+#[derive(Debug)]
+struct GuessTable {
+    surprisals: Vec<f64>,
+    /// Guesses spent on outputs more probable than each sample
+    guesses: Vec<f64>,
+}
+
+impl GuessTable {
+    fn new(mut surprisals: Vec<f64>) -> Option<Self> {
+        if surprisals.is_empty() {
+            return None;
+        }
+        surprisals.sort_by(f64::total_cmp);
+        let n = surprisals.len() as f64;
+        let mut total = 0.0;
+        let guesses = surprisals
+            .iter()
+            .map(|&bits| {
+                let before = total;
+                // Each sample stands for 1/(n·p) outputs as probable as itself
+                total += 2f64.powf(bits) / n;
+                before
+            })
+            .collect();
+        Some(Self {
+            surprisals,
+            guesses,
+        })
+    }
+
+    fn rank(&self, bits: f64) -> GuessRank {
+        let more_probable = self.surprisals.partition_point(|&s| s < bits);
+        let guesses = match more_probable {
+            0 => 1.0,
+            i => {
+                self.guesses[i - 1]
+                    + 2f64.powf(self.surprisals[i - 1]) / self.surprisals.len() as f64
+            }
+        };
+        GuessRank {
+            percentile: 100.0 * more_probable as f64 / self.surprisals.len() as f64,
+            log2_guesses: guesses.max(1.0).log2(),
+        }
+    }
+}
+
+/// Builder for [`MarkovGenerator`], validated at [`build`](Self::build) time
+#[derive(Debug, Clone)]
+pub struct MarkovGeneratorBuilder {
+    length: usize,
+    digits: bool,
+    symbols: bool,
+    digit_count: Option<usize>,
+    symbol_count: Option<usize>,
+    symbol_set: SymbolSet,
+    capitalize: bool,
+    min_guess_percentile: f64,
+    model: Option<MarkovModel>,
+}
+
+impl Default for MarkovGeneratorBuilder {
+    fn default() -> Self {
+        Self {
+            length: MarkovGenerator::DEFAULT_LENGTH,
+            digits: true,
+            symbols: false,
+            digit_count: None,
+            symbol_count: None,
+            symbol_set: SymbolSet::Readable,
+            capitalize: true,
+            min_guess_percentile: MarkovGenerator::DEFAULT_MIN_GUESS_PERCENTILE,
+            model: None,
+        }
+    }
+}
+
+impl MarkovGeneratorBuilder {
+    pub fn length(mut self, length: usize) -> Self {
+        self.length = length;
+        self
+    }
+
+    /// Include a digit, if the password is long enough to hold one
+    pub fn digits(mut self, include: bool) -> Self {
+        self.digits = include;
+        self
+    }
+
+    /// Include a symbol, if the password is long enough to hold one
+    pub fn symbols(mut self, include: bool) -> Self {
+        self.symbols = include;
+        self
+    }
+
+    /// Insert exactly `count` digits, overriding [`digits`](Self::digits)
+    pub fn digit_count(mut self, count: usize) -> Self {
+        self.digit_count = Some(count);
+        self
+    }
+
+    /// Insert exactly `count` symbols, overriding [`symbols`](Self::symbols)
+    pub fn symbol_count(mut self, count: usize) -> Self {
+        self.symbol_count = Some(count);
+        self
+    }
+
+    /// Draw inserted symbols from `set` instead of the readable ones
+    pub fn symbol_set(mut self, set: SymbolSet) -> Self {
+        self.symbol_set = set;
+        self
+    }
+
+    /// Capitalize first letter
+    pub fn capitalize(mut self, capitalize: bool) -> Self {
+        self.capitalize = capitalize;
+        self
+    }
+
+    /// Redraw candidates among this share, in percent, of the model's most
+    /// probable outputs; 0 keeps every candidate
+    pub fn min_guess_percentile(mut self, percentile: f64) -> Self {
+        self.min_guess_percentile = percentile;
+        self
+    }
+
+    /// Use a model trained elsewhere, e.g. on a custom corpus, instead of the built-in one
+    pub fn model(mut self, model: MarkovModel) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    pub fn build(self) -> Result<MarkovGenerator, PwgenError> {
+        if !(0.0..MarkovGenerator::MAX_GUESS_PERCENTILE).contains(&self.min_guess_percentile) {
+            return Err(PwgenError::GuessPercentileOutOfRange {
+                max: MarkovGenerator::MAX_GUESS_PERCENTILE as u32,
+            });
+        }
+        let model = self.model.unwrap_or_else(MarkovModel::builtin);
+        let mut generator = MarkovGenerator::with_model(
+            model,
+            self.length,
+            self.digits,
+            self.symbols,
+            self.capitalize,
+        )?;
+        generator.symbol_set = self.symbol_set.chars();
+        generator.min_guess_percentile = self.min_guess_percentile;
+        if self.digit_count.is_none() && self.symbol_count.is_none() {
+            return Ok(generator);
+        }
+        let digit_count = self.digit_count.unwrap_or(generator.digit_count);
+        let symbol_count = self.symbol_count.unwrap_or(generator.symbol_count);
+        // The first character always comes from the model
+        if digit_count + symbol_count >= self.length {
+            return Err(PwgenError::TooManyInsertions {
+                inserted: digit_count + symbol_count,
+                length: self.length,
+            });
+        }
+        generator.digit_count = digit_count;
+        generator.symbol_count = symbol_count;
+        Ok(generator)
+    }
+}
+
+impl MarkovGenerator {
+    pub const DEFAULT_LENGTH: usize = 12;
+    /// Revision of the training procedure; bump whenever it changes the model
+    pub const MODEL_VERSION: u32 = 1;
+    /// Characters of context each transition is conditioned on
+    pub const ORDER: usize = 2;
+
+    /// Share of the most probable outputs redrawn by default, in percent
+    pub const DEFAULT_MIN_GUESS_PERCENTILE: f64 = 1.0;
+    /// Redrawing more would cost whole bits and skew the output
+    pub const MAX_GUESS_PERCENTILE: f64 = 50.0;
+    /// Model outputs sampled to rank candidates against
+    const GUESS_SAMPLES: usize = 4096;
+    /// Fixed so ranks are reproducible; the sample is public knowledge anyway
+    const GUESS_SEED: u64 = 0x7077_6765_6e2d_7273;
+
+    const VOWELS: [char; 5] = ['a', 'e', 'i', 'o', 'u'];
+    /// Consonants that pad a model dead end after a vowel
+    const PADDING_CONSONANTS: [char; 14] = [
+        'b', 'c', 'd', 'f', 'g', 'h', 'k', 'l', 'm', 'n', 'p', 'r', 's', 't',
+    ];
+    /// Model steps before the rest of a password is padded
+    const MAX_MODEL_STEPS: usize = 100;
+    /// Model candidates tried before switching to syllables
+    const MAX_ATTEMPTS: usize = 100;
+    /// Consonant-vowel pairs used when the model fails to yield a pronounceable password
+    const FALLBACK_SYLLABLES: &'static [&'static str] = &[
+        "ba", "be", "bi", "bo", "bu", "da", "de", "di", "do", "du", "fa", "fe", "fi", "fo", "fu",
+        "ga", "ge", "gi", "go", "gu", "ha", "he", "hi", "ho", "hu", "ka", "ke", "ki", "ko", "ku",
+        "la", "le", "li", "lo", "lu", "ma", "me", "mi", "mo", "mu", "na", "ne", "ni", "no", "nu",
+        "pa", "pe", "pi", "po", "pu", "ra", "re", "ri", "ro", "ru", "sa", "se", "si", "so", "su",
+        "ta", "te", "ti", "to", "tu", "va", "ve", "vi", "vo", "vu", "wa", "we", "wi", "wo", "za",
+        "ze", "zi", "zo", "zu",
+    ];
+
+    pub fn new(
+        length: usize,
+        include_digits: bool,
+        include_symbols: bool,
+        capitalize: bool,
+    ) -> Result<Self, PwgenError> {
+        Self::with_model(
+            MarkovModel::builtin(),
+            length,
+            include_digits,
+            include_symbols,
+            capitalize,
+        )
+    }
+
+    fn with_model(
+        model: MarkovModel,
+        length: usize,
+        include_digits: bool,
+        include_symbols: bool,
+        capitalize: bool,
+    ) -> Result<Self, PwgenError> {
+        if length == 0 {
+            return Err(PwgenError::ZeroLength);
+        }
+        // Short passwords keep at least two letters, so they may get no digit or symbol
+        let digit_count = usize::from(include_digits && length > 2);
+        let symbol_count = usize::from(include_symbols && length > 2 + digit_count);
+
+        Ok(Self {
+            model,
+            length,
+            digit_count,
+            symbol_count,
+            symbol_set: SymbolSet::Readable.chars(),
+            capitalize,
+            min_guess_percentile: Self::DEFAULT_MIN_GUESS_PERCENTILE,
+            guess_table: OnceLock::new(),
+        })
+    }
+
+    pub fn builder() -> MarkovGeneratorBuilder {
+        MarkovGeneratorBuilder::default()
+    }
+
+    /// The trained model as sorted, tab-separated lines (`data export`)
+    ///
+    /// `start` lines give the weight of each opening bigram and `next` lines
+    /// the weight of each character after a bigram, so two builds can be
+    /// compared with `diff`.
+    pub fn model_table() -> String {
+        let MarkovModel {
+            transitions,
+            start_pairs,
+            ..
+        } = MarkovModel::builtin();
+        let mut starts: Vec<_> = start_pairs.into_iter().collect();
+        starts.sort();
+        let mut nexts: Vec<_> = transitions
+            .into_iter()
+            .flat_map(|(key, next)| next.into_iter().map(move |(c, weight)| (key, c, weight)))
+            .collect();
+        nexts.sort();
+
+        let mut table = format!(
+            "# Markov model v{}, order {}, trained on {}\n\
+             # start <bigram> <weight> | next <bigram> <char> <weight>\n",
+            Self::MODEL_VERSION,
+            Self::ORDER,
+            crate::wordlist::BUILTIN_NAME
+        );
+        for ((a, b), weight) in starts {
+            table.push_str(&format!("start\t{}{}\t{}\n", a, b, weight));
+        }
+        for ((a, b), c, weight) in nexts {
+            table.push_str(&format!("next\t{}{}\t{}\t{}\n", a, b, c, weight));
+        }
+        table
     }
 
     /// Generate base pronounceable string using Markov chain
     fn generate_base(&self, rng: &mut dyn RngCore) -> Option<String> {
-        if self.start_pairs.is_empty() {
+        if self.model.start_pairs.is_empty() {
             return None;
         }
 
-        let mut result = String::with_capacity(self.length);
+        // Characters rather than a String: models trained on other scripts have multi-byte letters
+        let mut result: Vec<char> = Vec::with_capacity(self.length);
 
         // Pick starting bigram
-        let start_weights: Vec<u32> = self.start_pairs.iter().map(|(_, w)| *w).collect();
+        let start_weights: Vec<u32> = self.model.start_pairs.iter().map(|(_, w)| *w).collect();
         let dist = WeightedIndex::new(&start_weights).ok()?;
         let start_idx = dist.sample(rng);
-        let (c1, c2) = self.start_pairs[start_idx].0;
+        let (c1, c2) = self.model.start_pairs[start_idx].0;
 
         result.push(c1);
         result.push(c2);
 
         // Generate remaining characters
         let mut attempts = 0;
-        while result.len() < self.length && attempts < 100 {
+        while result.len() < self.length && attempts < Self::MAX_MODEL_STEPS {
             let len = result.len();
-            let chars: Vec<char> = result.chars().collect();
-            let key = (chars[len - 2], chars[len - 1]);
+            let key = (result[len - 2], result[len - 1]);
 
-            if let Some(transitions) = self.transitions.get(&key) {
+            if let Some(transitions) = self.model.transitions.get(&key) {
                 let weights: Vec<u32> = transitions.iter().map(|(_, w)| *w).collect();
                 if let Ok(dist) = WeightedIndex::new(&weights) {
                     let idx = dist.sample(rng);
@@ -150,10 +485,10 @@ impl MarkovGenerator {
 
         // Pad with random vowels/consonants if needed
         while result.len() < self.length {
-            let last_char = result.chars().last().unwrap_or('a');
+            let last_char = result.last().copied().unwrap_or('a');
             if Self::VOWELS.contains(&last_char) {
                 // Add a consonant
-                let consonants = ['b', 'c', 'd', 'f', 'g', 'h', 'k', 'l', 'm', 'n', 'p', 'r', 's', 't'];
+                let consonants = Self::PADDING_CONSONANTS;
                 result.push(consonants[rng.gen_range(0..consonants.len())]);
             } else {
                 // Add a vowel
@@ -162,37 +497,122 @@ impl MarkovGenerator {
         }
 
         result.truncate(self.length);
-        Some(result)
+        Some(result.into_iter().collect())
     }
 
-    /// Post-process: add digits/symbols, capitalize
-    fn post_process(&self, mut password: String, rng: &mut dyn RngCore) -> String {
-        let mut chars: Vec<char> = password.chars().collect();
+    /// Bits of surprise of `letters` as the start of a [`generate_base`](Self::generate_base)
+    /// output: -log2 of its probability, or None if it cannot be produced
+    fn surprisal(&self, letters: &[char]) -> Option<f64> {
+        let [first, second, ..] = *letters else {
+            return None;
+        };
+        fn weight<T: PartialEq>(choices: &[(T, u32)], choice: T) -> Option<f64> {
+            let total: u32 = choices.iter().map(|(_, w)| w).sum();
+            let (_, w) = choices.iter().find(|(c, _)| *c == choice)?;
+            Some(-(*w as f64 / total as f64).log2())
+        }
+        let mut bits = weight(&self.model.start_pairs, (first, second))?;
+        let mut modelled = true;
+        for i in 2..letters.len() {
+            let next = letters[i];
+            modelled &= i - 2 < Self::MAX_MODEL_STEPS;
+            if modelled {
+                if let Some(choices) = self
+                    .model
+                    .transitions
+                    .get(&(letters[i - 2], letters[i - 1]))
+                {
+                    bits += weight(choices, next)?;
+                    continue;
+                }
+                modelled = false;
+            }
+            // Padding alternates uniformly drawn consonants and vowels
+            let (set, size): (&[char], usize) = if Self::VOWELS.contains(&letters[i - 1]) {
+                (&Self::PADDING_CONSONANTS, Self::PADDING_CONSONANTS.len())
+            } else {
+                (&Self::VOWELS, Self::VOWELS.len())
+            };
+            if !set.contains(&next) {
+                return None;
+            }
+            bits += (size as f64).log2();
+        }
+        Some(bits)
+    }
 
-        // Capitalize first letter if requested
-        if self.capitalize && !chars.is_empty() {
-            chars[0] = chars[0].to_uppercase().next().unwrap_or(chars[0]);
+    fn guess_table(&self) -> Option<&GuessTable> {
+        self.guess_table
+            .get_or_init(|| {
+                let letters = self.length - self.inserted();
+                let mut rng = StdRng::seed_from_u64(Self::GUESS_SEED);
+                let surprisals = (0..Self::GUESS_SAMPLES)
+                    .filter_map(|_| {
+                        let base: Vec<char> = self.generate_base(&mut rng)?.chars().collect();
+                        self.surprisal(&base[..letters.min(base.len())])
+                    })
+                    .collect();
+                GuessTable::new(surprisals)
+            })
+            .as_ref()
+    }
+
+    /// Where the letters of `value` rank among this generator's outputs, or
+    /// None if the model cannot produce them
+    pub fn guess_rank(&self, value: &str) -> Option<GuessRank> {
+        let letters: Vec<char> = value
+            .chars()
+            .filter(|c| c.is_alphabetic())
+            .flat_map(char::to_lowercase)
+            .collect();
+        let bits = self.surprisal(&letters)?;
+        Some(self.guess_table()?.rank(bits))
+    }
+
+    /// The rank of a candidate built on `base` when it falls among the most
+    /// probable outputs, which [`generate`](PasswordGenerator::generate) redraws
+    fn too_probable(&self, base: &str) -> Option<GuessRank> {
+        if self.min_guess_percentile <= 0.0 {
+            return None;
         }
+        let letters: Vec<char> = base.chars().take(self.length - self.inserted()).collect();
+        let rank = self.guess_table()?.rank(self.surprisal(&letters)?);
+        (rank.percentile < self.min_guess_percentile).then_some(rank)
+    }
+
+    fn inserted(&self) -> usize {
+        self.digit_count + self.symbol_count
+    }
+
+    /// Post-process: place digits/symbols, capitalize
+    fn post_process(&self, password: &str, rng: &mut dyn RngCore) -> String {
+        let letters = self.length - self.inserted();
+        let mut chars: Vec<Option<char>> = vec![None; self.length];
 
-        password = chars.into_iter().collect();
+        // Distinct positions after the first character; the first `digit_count` get digits
+        let positions = index::sample(rng, self.length - 1, self.inserted());
+        for (i, pos) in positions.into_iter().enumerate() {
+            chars[pos + 1] = Some(if i < self.digit_count {
+                (b'0' + rng.gen_range(0..10)) as char
+            } else {
+                self.symbol_set[rng.gen_range(0..self.symbol_set.len())]
+            });
+        }
 
-        // Insert a digit at random position (not at start)
-        if self.include_digits && password.len() > 2 {
-            let pos = rng.gen_range(1..password.len());
-            let digit = (b'0' + rng.gen_range(0..10)) as char;
-            password.insert(pos, digit);
-            password.truncate(self.length);
+        // The model's letters fill the remaining slots in order
+        let mut base = password.chars().take(letters);
+        for slot in chars.iter_mut().filter(|slot| slot.is_none()) {
+            *slot = base.next();
         }
 
-        // Insert a symbol at random position (not at start)
-        if self.include_symbols && password.len() > 2 {
-            let pos = rng.gen_range(1..password.len());
-            let symbol = Self::READABLE_SYMBOLS[rng.gen_range(0..Self::READABLE_SYMBOLS.len())];
-            password.insert(pos, symbol);
-            password.truncate(self.length);
+        let mut chars: Vec<char> = chars.into_iter().flatten().collect();
+
+        // Capitalize first letter if requested
+        if self.capitalize && !chars.is_empty() {
+            chars[0] = chars[0].to_uppercase().next().unwrap_or(chars[0]);
         }
 
-        password
+        chars.into_iter().collect()
     }
 
     /// Check if password passes pronounceability filter
@@ -228,54 +648,103 @@ impl MarkovGenerator {
     }
 }
 
+impl MarkovGenerator {
+    /// Entropy from the model's average branching factor (a conservative estimate),
+    /// less what redrawing the most probable outputs costs
+    fn markov_entropy(&self) -> f64 {
+        let letters = (self.length - self.inserted()) as f64;
+        letters * self.model.avg_branching_factor.log2() + self.insertion_entropy()
+            - self.guess_gate_cost()
+    }
+
+    /// Bits lost by never producing the most probable `min_guess_percentile` percent
+    pub fn guess_gate_cost(&self) -> f64 {
+        -(1.0 - self.min_guess_percentile / 100.0).log2()
+    }
+
+    /// Entropy of the syllable fallback: one syllable choice per two characters
+    fn fallback_entropy(&self) -> f64 {
+        let letters = (self.length - self.inserted()) as f64;
+        letters * (Self::FALLBACK_SYLLABLES.len() as f64).log2() / 2.0 + self.insertion_entropy()
+    }
+
+    /// Bits from the inserted characters: which positions, which of them are digits, and their values
+    fn insertion_entropy(&self) -> f64 {
+        log2_binomial(self.length - 1, self.inserted())
+            + log2_binomial(self.inserted(), self.digit_count)
+            + self.digit_count as f64 * 10f64.log2()
+            + self.symbol_count as f64 * (self.symbol_set.len() as f64).log2()
+    }
+}
+
+/// log2 of the binomial coefficient C(n, k)
+fn log2_binomial(n: usize, k: usize) -> f64 {
+    (0..k.min(n - k))
+        .map(|i| ((n - i) as f64 / (i + 1) as f64).log2())
+        .sum()
+}
+
 impl PasswordGenerator for MarkovGenerator {
     fn generate(&self, rng: &mut dyn RngCore) -> GeneratedPassword {
         // Retry until we get a pronounceable password
-        for _ in 0..100 {
+        for attempt in 0..Self::MAX_ATTEMPTS {
             if let Some(base) = self.generate_base(rng) {
-                let password = self.post_process(base, rng);
+                let password = self.post_process(&base, rng);
 
                 if Self::is_pronounceable(&password) {
+                    if let Some(rank) = self.too_probable(&base) {
+                        crate::info!(
+                            "markov: redrawing a candidate among the {:.2}% most probable \
+                             outputs (about 2^{:.0} guesses for an attacker with the model)",
+                            rank.percentile,
+                            rank.log2_guesses
+                        );
+                        continue;
+                    }
+                    crate::trace!("markov: pronounceable after {} retries", attempt);
                     // Calculate entropy based on model's branching factor
                     // This is a conservative estimate
-                    let base_entropy = (self.length as f64) * self.avg_branching_factor.log2();
+                    let base_entropy = self.markov_entropy();
 
                     return GeneratedPassword {
                         value: Zeroizing::new(password),
                         entropy: EntropyInfo::new(base_entropy, "Markov pronounceable"),
+                        mnemonic: None,
                     };
                 }
             }
         }
 
         // Fallback: generate a random pronounceable password
+        crate::debug!(
+            "markov: no pronounceable candidate in {} attempts, using syllable fallback",
+            Self::MAX_ATTEMPTS
+        );
         let mut password = String::new();
-        let syllables = ["ba", "be", "bi", "bo", "bu", "da", "de", "di", "do", "du",
-                        "fa", "fe", "fi", "fo", "fu", "ga", "ge", "gi", "go", "gu",
-                        "ha", "he", "hi", "ho", "hu", "ka", "ke", "ki", "ko", "ku",
-                        "la", "le", "li", "lo", "lu", "ma", "me", "mi", "mo", "mu",
-                        "na", "ne", "ni", "no", "nu", "pa", "pe", "pi", "po", "pu",
-                        "ra", "re", "ri", "ro", "ru", "sa", "se", "si", "so", "su",
-                        "ta", "te", "ti", "to", "tu", "va", "ve", "vi", "vo", "vu",
-                        "wa", "we", "wi", "wo", "za", "ze", "zi", "zo", "zu"];
+        let syllables = Self::FALLBACK_SYLLABLES;
 
         while password.len() < self.length {
             password.push_str(syllables[rng.gen_range(0..syllables.len())]);
         }
         password.truncate(self.length);
 
-        let password = self.post_process(password, rng);
-        let entropy = (self.length as f64) * (syllables.len() as f64).log2() / 2.0;
+        let password = self.post_process(&password, rng);
+        let entropy = self.fallback_entropy();
 
         GeneratedPassword {
             value: Zeroizing::new(password),
             entropy: EntropyInfo::new(entropy, "Syllable fallback"),
+            mnemonic: None,
         }
     }
 
     fn description(&self) -> &'static str {
         "Pronounceable (Markov chain)"
     }
+
+    fn estimated_entropy(&self) -> Option<f64> {
+        Some(self.markov_entropy().min(self.fallback_entropy()))
+    }
 }
 
 #[cfg(test)]
@@ -284,9 +753,18 @@ mod tests {
     use rand::SeedableRng;
     use rand_chacha::ChaCha8Rng;
 
+    #[test]
+    fn test_model_table() {
+        let table = MarkovGenerator::model_table();
+        assert_eq!(table, MarkovGenerator::model_table());
+        assert!(table.starts_with("# Markov model v1, order 2"));
+        let line = table.lines().find(|l| l.starts_with("next\tab\t")).unwrap();
+        assert_eq!(line.split('\t').count(), 4);
+    }
+
     #[test]
     fn test_markov_length() {
-        let gen = MarkovGenerator::new(12, false, false, false);
+        let gen = MarkovGenerator::new(12, false, false, false).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
         assert_eq!(password.value.len(), 12);
@@ -294,7 +772,7 @@ mod tests {
 
     #[test]
     fn test_markov_capitalize() {
-        let gen = MarkovGenerator::new(12, false, false, true);
+        let gen = MarkovGenerator::new(12, false, false, true).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
         let first = password.value.chars().next().unwrap();
@@ -303,7 +781,7 @@ mod tests {
 
     #[test]
     fn test_markov_with_digits() {
-        let gen = MarkovGenerator::new(12, true, false, false);
+        let gen = MarkovGenerator::new(12, true, false, false).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
         assert!(password.value.chars().any(|c| c.is_ascii_digit()));
@@ -311,16 +789,16 @@ mod tests {
 
     #[test]
     fn test_markov_with_symbols() {
-        let gen = MarkovGenerator::new(12, false, true, false);
+        let gen = MarkovGenerator::new(12, false, true, false).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
-        let symbols: Vec<char> = MarkovGenerator::READABLE_SYMBOLS.to_vec();
+        let symbols = SymbolSet::Readable.chars();
         assert!(password.value.chars().any(|c| symbols.contains(&c)));
     }
 
     #[test]
     fn test_markov_pronounceable() {
-        let gen = MarkovGenerator::new(12, false, false, false);
+        let gen = MarkovGenerator::new(12, false, false, false).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         // Generate multiple passwords and check they all pass pronounceability
         for _ in 0..10 {
@@ -342,11 +820,142 @@ mod tests {
         assert!(MarkovGenerator::is_pronounceable("banana"));
     }
 
+    #[test]
+    fn test_markov_builder() {
+        let gen = MarkovGenerator::builder()
+            .length(10)
+            .digits(false)
+            .capitalize(false)
+            .build()
+            .unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let password = gen.generate(&mut rng);
+        assert_eq!(password.value.len(), 10);
+        assert!(password.value.chars().all(|c| c.is_ascii_lowercase()));
+        assert_eq!(
+            MarkovGenerator::builder().length(0).build().err(),
+            Some(PwgenError::ZeroLength)
+        );
+    }
+
+    #[test]
+    fn test_markov_digit_and_symbol_counts() {
+        let gen = MarkovGenerator::builder()
+            .length(12)
+            .digit_count(2)
+            .symbol_count(3)
+            .build()
+            .unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        for _ in 0..20 {
+            let password = gen.generate(&mut rng);
+            let value = password.value.as_str();
+            assert_eq!(value.chars().count(), 12);
+            assert_eq!(value.chars().filter(|c| c.is_ascii_digit()).count(), 2);
+            let symbols = value
+                .chars()
+                .filter(|c| SymbolSet::READABLE.contains(*c))
+                .count();
+            assert_eq!(symbols, 3);
+            assert!(value.starts_with(|c: char| c.is_ascii_uppercase()));
+        }
+
+        // 11 positions for 5 insertions, 10 ways to split them, 10^5 values
+        let expected = (462.0f64 * 10.0).log2() + 5.0 * 10f64.log2();
+        assert!((gen.insertion_entropy() - expected).abs() < 1e-9);
+        assert_eq!(
+            MarkovGenerator::builder()
+                .length(4)
+                .digit_count(4)
+                .build()
+                .err(),
+            Some(PwgenError::TooManyInsertions {
+                inserted: 4,
+                length: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_markov_symbol_set() {
+        let gen = MarkovGenerator::builder()
+            .symbol_count(2)
+            .symbol_set(SymbolSet::Custom("^~".to_string()))
+            .build()
+            .unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        for _ in 0..20 {
+            let password = gen.generate(&mut rng);
+            let symbols: String = password
+                .value
+                .chars()
+                .filter(|c| !c.is_ascii_alphanumeric())
+                .collect();
+            assert_eq!(symbols.len(), 2);
+            assert!(symbols.chars().all(|c| c == '^' || c == '~'));
+        }
+        // One bit per symbol instead of log2(10)
+        let readable = MarkovGenerator::builder().symbol_count(2).build().unwrap();
+        let saved = 2.0 * (10f64.log2() - 1.0);
+        assert!((readable.insertion_entropy() - gen.insertion_entropy() - saved).abs() < 1e-9);
+    }
+
     #[test]
     fn test_markov_entropy_positive() {
-        let gen = MarkovGenerator::new(12, false, false, false);
+        let gen = MarkovGenerator::new(12, false, false, false).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
         assert!(password.entropy.bits > 0.0);
     }
+
+    #[test]
+    fn test_guess_table_ranks() {
+        // Outputs of 1, 2, 3 and 4 bits: each sample stands for 2^bits / 4 guesses
+        let table = GuessTable::new(vec![3.0, 1.0, 4.0, 2.0]).unwrap();
+        let rank = table.rank(2.5);
+        assert_eq!(rank.percentile, 50.0);
+        assert!((rank.log2_guesses - (0.5f64 + 1.0).log2()).abs() < 1e-9);
+        assert_eq!(table.rank(0.5).percentile, 0.0);
+        assert_eq!(table.rank(0.5).log2_guesses, 0.0);
+        assert_eq!(table.rank(9.0).percentile, 100.0);
+        assert!(GuessTable::new(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_markov_redraws_probable_outputs() {
+        let gen = MarkovGenerator::builder()
+            .min_guess_percentile(10.0)
+            .build()
+            .unwrap();
+        let open = MarkovGenerator::builder()
+            .min_guess_percentile(0.0)
+            .build()
+            .unwrap();
+        assert!((gen.guess_gate_cost() - -(0.9f64).log2()).abs() < 1e-9);
+        assert!(gen.estimated_entropy().unwrap() < open.estimated_entropy().unwrap());
+
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let ranks: Vec<GuessRank> = (0..100)
+            .filter_map(|_| gen.guess_rank(gen.generate(&mut rng).expose()))
+            .collect();
+        assert!(ranks.len() > 90);
+        assert!(ranks.iter().all(|rank| rank.percentile >= 10.0));
+        assert!(ranks.iter().all(|rank| rank.log2_guesses > 0.0));
+
+        // Without the gate the most probable outputs do come up
+        let lowest = (0..100)
+            .filter_map(|_| open.guess_rank(open.generate(&mut rng).expose()))
+            .map(|rank| rank.percentile)
+            .fold(100.0, f64::min);
+        assert!(lowest < 10.0);
+
+        assert_eq!(gen.guess_rank("x"), None);
+        assert_eq!(
+            MarkovGenerator::builder()
+                .min_guess_percentile(50.0)
+                .build()
+                .err(),
+            Some(PwgenError::GuessPercentileOutOfRange { max: 50 })
+        );
+    }
 }
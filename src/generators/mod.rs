@@ -1,12 +1,79 @@
+use rand::RngCore;
 use zeroize::Zeroizing;
 
 use crate::entropy::EntropyInfo;
+use crate::error::PwgenError;
+use crate::redacted::Redacted;
 
 /// Result of password generation
 /// The password value is wrapped in Zeroizing to ensure secure memory cleanup on drop
+///
+/// Outside this crate the value is only reachable through [`expose`](Self::expose);
+/// `Debug` and `Display` print `[REDACTED len=20]` instead.
 pub struct GeneratedPassword {
-    pub value: Zeroizing<String>,
+    pub(crate) value: Zeroizing<String>,
     pub entropy: EntropyInfo,
+    /// Memory aid derived from the value (e.g. a story for a passphrase)
+    pub(crate) mnemonic: Option<Zeroizing<String>>,
+}
+
+impl GeneratedPassword {
+    pub fn new(value: Zeroizing<String>, entropy: EntropyInfo) -> Self {
+        Self {
+            value,
+            entropy,
+            mnemonic: None,
+        }
+    }
+
+    pub fn with_mnemonic(mut self, mnemonic: Zeroizing<String>) -> Self {
+        self.mnemonic = Some(mnemonic);
+        self
+    }
+
+    /// The password itself
+    pub fn expose(&self) -> &str {
+        &self.value
+    }
+
+    /// The memory aid, which reveals as much as the password
+    pub fn expose_mnemonic(&self) -> Option<&str> {
+        self.mnemonic.as_deref().map(String::as_str)
+    }
+}
+
+/// Debug output never includes the password value
+impl std::fmt::Debug for GeneratedPassword {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GeneratedPassword")
+            .field("value", &Redacted::of(&self.value))
+            .field("entropy", &self.entropy)
+            .field("mnemonic", &self.mnemonic.as_ref().map(|m| Redacted::of(m)))
+            .finish()
+    }
+}
+
+impl std::fmt::Display for GeneratedPassword {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Redacted::of(&self.value).fmt(f)
+    }
+}
+
+/// Serializing is an explicit request for the value, so it is included in plain text
+#[cfg(feature = "serde")]
+impl serde::Serialize for GeneratedPassword {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("GeneratedPassword", 3)?;
+        state.serialize_field("value", self.value.as_str())?;
+        state.serialize_field("entropy", &self.entropy)?;
+        match &self.mnemonic {
+            Some(mnemonic) => state.serialize_field("mnemonic", mnemonic.as_str())?,
+            None => state.skip_field("mnemonic")?,
+        }
+        state.end()
+    }
 }
 
 /// Trait for all password generators
@@ -16,14 +83,189 @@ pub trait PasswordGenerator {
 
     /// Human-readable description of this generator type
     fn description(&self) -> &'static str;
+
+    /// Size of the alphabet if every character is drawn independently and uniformly from it
+    ///
+    /// Used by `verify` to decide which statistical checks apply.
+    fn uniform_alphabet(&self) -> Option<usize> {
+        None
+    }
+
+    /// Characters (grapheme clusters) every generated password has, when that is fixed
+    fn length(&self) -> Option<usize> {
+        None
+    }
+
+    /// Entropy in bits every generated password will have, known without generating
+    ///
+    /// None when it depends on the random outcome and no useful lower bound exists.
+    fn estimated_entropy(&self) -> Option<f64> {
+        None
+    }
+
+    /// Whether the values are first-login credentials that must be changed on first use
+    fn is_temporary(&self) -> bool {
+        false
+    }
+}
+
+/// Iterator helpers available on every generator
+pub trait PasswordGeneratorExt: PasswordGenerator {
+    /// Infinite stream of passwords drawn from `rng`
+    fn iter<'a>(&'a self, rng: &'a mut dyn RngCore) -> Passwords<'a, Self> {
+        Passwords {
+            generator: self,
+            rng,
+        }
+    }
 }
 
+impl<G: PasswordGenerator + ?Sized> PasswordGeneratorExt for G {}
+
+/// Candidates drawn per wanted password before a filtered or deduplicated draw
+/// gives up (`take_unique`, `--min-bits`, `pwgen -B`)
+pub const MAX_ATTEMPTS_PER_PASSWORD: usize = 100;
+
+/// Infinite iterator of generated passwords, see [`PasswordGeneratorExt::iter`]
+pub struct Passwords<'a, G: ?Sized> {
+    generator: &'a G,
+    rng: &'a mut dyn RngCore,
+}
+
+impl<G: PasswordGenerator + ?Sized> Passwords<'_, G> {
+    /// Collect `n` pairwise distinct passwords
+    ///
+    /// Fails instead of looping forever when the configuration's keyspace is
+    /// too small to produce `n` distinct values.
+    pub fn take_unique(self, n: usize) -> Result<Vec<GeneratedPassword>, PwgenError> {
+        let mut unique: Vec<GeneratedPassword> = Vec::with_capacity(n);
+        if n == 0 {
+            return Ok(unique);
+        }
+        for candidate in self.take(n.saturating_mul(MAX_ATTEMPTS_PER_PASSWORD)) {
+            if !unique.iter().any(|p| p.value == candidate.value) {
+                unique.push(candidate);
+                if unique.len() == n {
+                    break;
+                }
+            }
+        }
+
+        crate::debug!("unique: kept {} of {} requested", unique.len(), n);
+        if unique.len() < n {
+            return Err(PwgenError::NotEnoughUnique {
+                requested: n,
+                found: unique.len(),
+            });
+        }
+        Ok(unique)
+    }
+}
+
+impl<G: PasswordGenerator + ?Sized> Iterator for Passwords<'_, G> {
+    type Item = GeneratedPassword;
+
+    fn next(&mut self) -> Option<GeneratedPassword> {
+        Some(self.generator.generate(self.rng))
+    }
+}
+
+pub mod apple;
+pub mod compose;
+pub mod grouped;
+pub mod keypad;
 pub mod markov;
+pub mod passcode;
 pub mod passphrase;
 pub mod pin;
+pub mod requirements;
 pub mod secure;
+pub mod symbols;
+pub mod template;
+pub mod temporary;
+
+pub use apple::AppleGenerator;
+pub use compose::{CharClass, Composition};
+pub use grouped::GroupedGenerator;
+pub use keypad::{KeypadGenerator, KeypadGeneratorBuilder};
+pub use markov::{MarkovGenerator, MarkovGeneratorBuilder, MarkovModel};
+pub use passcode::{PasscodeGenerator, PasscodeGeneratorBuilder};
+pub use passphrase::{PassphraseGenerator, PassphraseGeneratorBuilder};
+pub use pin::{PinGenerator, PinGeneratorBuilder};
+pub use requirements::ClassRequirements;
+pub use secure::{CharSet, LengthMode, SecureGenerator, SecureGeneratorBuilder};
+pub use symbols::SymbolSet;
+pub use template::{TemplateGenerator, TemplateGeneratorBuilder};
+pub use temporary::{TemporaryGenerator, TemporaryGeneratorBuilder};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_iter_is_unbounded() {
+        let gen = PinGenerator::new(4).unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let pins: Vec<_> = gen.iter(&mut rng).take(50).collect();
+        assert_eq!(pins.len(), 50);
+    }
+
+    #[test]
+    fn test_iter_works_through_trait_object() {
+        let gen: Box<dyn PasswordGenerator> = Box::new(PinGenerator::new(4).unwrap());
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let long_enough = gen.iter(&mut rng).take(5).all(|p| p.value.len() == 4);
+        assert!(long_enough);
+    }
 
-pub use markov::MarkovGenerator;
-pub use passphrase::PassphraseGenerator;
-pub use pin::PinGenerator;
-pub use secure::SecureGenerator;
+    #[test]
+    fn test_debug_redacts_value() {
+        let gen = PinGenerator::new(8).unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let password = gen.generate(&mut rng);
+        let debug = format!("{:?}", password);
+        assert!(debug.contains("[REDACTED len=8]"));
+        assert!(!debug.contains(password.expose()));
+        assert_eq!(password.to_string(), "[REDACTED len=8]");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize_includes_value_and_entropy() {
+        let gen = PinGenerator::new(6).unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let password = gen.generate(&mut rng);
+        let json = serde_json::to_value(&password).unwrap();
+        assert_eq!(json["value"], password.value.as_str());
+        assert_eq!(json["entropy"]["source"], "Numeric");
+        assert_eq!(json["entropy"]["strength"], "very-weak");
+    }
+
+    #[test]
+    fn test_take_unique() {
+        let gen = PinGenerator::new(2).unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let pins = gen.iter(&mut rng).take_unique(50).unwrap();
+        assert_eq!(pins.len(), 50);
+        for (i, a) in pins.iter().enumerate() {
+            assert!(pins[i + 1..].iter().all(|b| a.value != b.value));
+        }
+    }
+
+    #[test]
+    fn test_take_unique_exhausted_keyspace() {
+        // Only 10 one-digit PINs exist
+        let gen = PinGenerator::new(1).unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let result = gen.iter(&mut rng).take_unique(11);
+        assert_eq!(
+            result.err(),
+            Some(PwgenError::NotEnoughUnique {
+                requested: 11,
+                found: 10
+            })
+        );
+    }
+}
@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+use crate::entropy::EntropyInfo;
+use crate::error::PwgenError;
+use crate::generators::{GeneratedPassword, PasswordGenerator};
+
+/// A phone or tablet unlock code, numeric (`482619`) or alphanumeric (`k7rm2qxe`)
+///
+/// Lock screens face a different attacker than web logins: someone holding the
+/// device, limited to a handful of guesses before a lockout, who can read
+/// smudges on the glass or watch the code being entered. Numeric codes
+/// therefore never repeat a digit back to back or use one more than twice,
+/// never run three keys in a straight line on the keypad (`258`, `159`) or
+/// count up or down (`345`), and never start with a year or a day and month.
+/// Alphanumeric codes use lowercase letters and digits, which need no shift
+/// key on a phone keyboard, never repeat a character back to back, and always
+/// contain a letter. Codes are drawn uniformly from those that pass, and the
+/// entropy counts exactly that many.
+pub struct PasscodeGenerator {
+    length: usize,
+    alphanumeric: bool,
+    bits: f64,
+}
+
+/// Builder for [`PasscodeGenerator`], validated at [`build`](Self::build) time
+#[derive(Debug, Clone, Default)]
+pub struct PasscodeGeneratorBuilder {
+    length: Option<usize>,
+    alphanumeric: bool,
+}
+
+impl PasscodeGeneratorBuilder {
+    /// Digits, or characters for an alphanumeric code
+    pub fn length(mut self, length: usize) -> Self {
+        self.length = Some(length);
+        self
+    }
+
+    /// Lowercase letters and digits instead of digits only
+    pub fn alphanumeric(mut self, alphanumeric: bool) -> Self {
+        self.alphanumeric = alphanumeric;
+        self
+    }
+
+    pub fn build(self) -> Result<PasscodeGenerator, PwgenError> {
+        let length = self.length.unwrap_or(if self.alphanumeric {
+            PasscodeGenerator::DEFAULT_ALPHANUMERIC_LENGTH
+        } else {
+            PasscodeGenerator::DEFAULT_DIGITS
+        });
+        PasscodeGenerator::new(length, self.alphanumeric)
+    }
+}
+
+impl PasscodeGenerator {
+    pub const DEFAULT_DIGITS: usize = 6;
+    pub const DEFAULT_ALPHANUMERIC_LENGTH: usize = 8;
+
+    /// Shortest code either lock screen accepts
+    pub const MIN_LENGTH: usize = 4;
+
+    /// Longest numeric code; past this an alphanumeric code is easier to type
+    pub const MAX_DIGITS: usize = 8;
+
+    const ALPHABET: &'static [u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+
+    /// Advice that goes with a device passcode, one line each
+    pub const GUIDANCE: [&'static str; 2] = [
+        "a passcode is only as strong as the lockout behind it: keep \"Erase Data\" (iOS) \
+         or the auto factory reset (Android) on",
+        "enter it with the screen out of view and wipe the glass now and then, since smudges \
+         give away which keys are used",
+    ];
+
+    pub fn new(length: usize, alphanumeric: bool) -> Result<Self, PwgenError> {
+        if length < Self::MIN_LENGTH {
+            return Err(PwgenError::PasscodeTooShort {
+                length,
+                min: Self::MIN_LENGTH,
+            });
+        }
+        if !alphanumeric && length > Self::MAX_DIGITS {
+            return Err(PwgenError::PasscodeTooLong {
+                length,
+                max: Self::MAX_DIGITS,
+            });
+        }
+        Ok(Self {
+            length,
+            alphanumeric,
+            bits: Self::entropy_bits(length, alphanumeric),
+        })
+    }
+
+    pub fn builder() -> PasscodeGeneratorBuilder {
+        PasscodeGeneratorBuilder::default()
+    }
+
+    /// Entropy of a code of this kind and length: log2 of the codes allowed
+    pub fn entropy_bits(length: usize, alphanumeric: bool) -> f64 {
+        if alphanumeric {
+            // No character repeated back to back, minus the codes of digits only
+            let n = length as i32 - 1;
+            let allowed = 36.0 * 35f64.powi(n) - 10.0 * 9f64.powi(n);
+            allowed.log2()
+        } else {
+            let mut prefix = Vec::with_capacity(length);
+            (count_digit_codes(&mut prefix, length, &mut HashMap::new()) as f64).log2()
+        }
+    }
+}
+
+/// Whether digit `next` may follow `prefix`, which is itself allowed
+fn extends(prefix: &[u8], next: u8) -> bool {
+    if prefix.last() == Some(&next) || prefix.iter().filter(|&&d| d == next).count() >= 2 {
+        return false;
+    }
+    if let [.., a, b] = prefix {
+        if keypad_line(*a, *b, next) || counting(*a, *b, next) {
+            return false;
+        }
+    }
+    match prefix {
+        [a, b, c] => !dated(&[*a, *b, *c, next]),
+        _ => true,
+    }
+}
+
+/// Whether every digit of `code` is allowed after the ones before it
+fn allowed(code: &[u8]) -> bool {
+    (0..code.len()).all(|i| extends(&code[..i], code[i]))
+}
+
+/// Allowed codes of `length` digits starting with `prefix`
+///
+/// Past the fourth digit only the last two digits and how often each digit
+/// was used matter, so those counts are shared through `memo`.
+fn count_digit_codes(
+    prefix: &mut Vec<u8>,
+    length: usize,
+    memo: &mut HashMap<(usize, u8, u8, u32), u64>,
+) -> u64 {
+    if prefix.len() == length {
+        return 1;
+    }
+    let key = match prefix[..] {
+        [.., a, b] if prefix.len() >= 4 => {
+            let uses = prefix.iter().fold(0, |uses, &d| uses + 3u32.pow(d as u32));
+            Some((prefix.len(), a, b, uses))
+        }
+        _ => None,
+    };
+    if let Some(&count) = key.as_ref().and_then(|key| memo.get(key)) {
+        return count;
+    }
+    let mut count = 0;
+    for digit in 0..10 {
+        if extends(prefix, digit) {
+            prefix.push(digit);
+            count += count_digit_codes(prefix, length, memo);
+            prefix.pop();
+        }
+    }
+    if let Some(key) = key {
+        memo.insert(key, count);
+    }
+    count
+}
+
+/// Row and column of a digit on a phone keypad (0 sits under 8)
+fn key_position(digit: u8) -> (i8, i8) {
+    match digit {
+        0 => (3, 1),
+        d => (((d - 1) / 3) as i8, ((d - 1) % 3) as i8),
+    }
+}
+
+/// Three neighbouring keys in a straight line, as one swipe would draw them
+fn keypad_line(a: u8, b: u8, c: u8) -> bool {
+    let (a, b, c) = (key_position(a), key_position(b), key_position(c));
+    let step = (b.0 - a.0, b.1 - a.1);
+    step.0.abs() <= 1 && step.1.abs() <= 1 && (c.0 - b.0, c.1 - b.1) == step
+}
+
+/// Three digits counting up or down by one, wrapping 9 to 0
+fn counting(a: u8, b: u8, c: u8) -> bool {
+    let up = |x: u8, y: u8| (x + 1) % 10 == y;
+    (up(a, b) && up(b, c)) || (up(b, a) && up(c, b))
+}
+
+/// Four digits reading as a year from 1900 to 2099, or as a day and month
+fn dated(four: &[u8; 4]) -> bool {
+    let pair = |i: usize| four[i] * 10 + four[i + 1];
+    let (first, second) = (pair(0), pair(2));
+    let day = |d: u8| (1..=31).contains(&d);
+    let month = |m: u8| (1..=12).contains(&m);
+    first == 19 || first == 20 || (month(first) && day(second)) || (day(first) && month(second))
+}
+
+impl PasswordGenerator for PasscodeGenerator {
+    fn generate(&self, rng: &mut dyn RngCore) -> GeneratedPassword {
+        let code = if self.alphanumeric {
+            loop {
+                let mut code = Zeroizing::new(Vec::with_capacity(self.length));
+                for _ in 0..self.length {
+                    // Uniform over the characters other than the previous one
+                    let mut c = Self::ALPHABET[rng.gen_range(0..Self::ALPHABET.len())];
+                    while code.last() == Some(&c) {
+                        c = Self::ALPHABET[rng.gen_range(0..Self::ALPHABET.len())];
+                    }
+                    code.push(c);
+                }
+                if code.iter().any(u8::is_ascii_lowercase) {
+                    break code;
+                }
+            }
+        } else {
+            loop {
+                let digits: Zeroizing<Vec<u8>> =
+                    Zeroizing::new((0..self.length).map(|_| rng.gen_range(0..10)).collect());
+                if allowed(&digits) {
+                    break Zeroizing::new(digits.iter().map(|d| b'0' + d).collect());
+                }
+            }
+        };
+        let value: String = code.iter().map(|&b| b as char).collect();
+
+        GeneratedPassword {
+            value: Zeroizing::new(value),
+            entropy: EntropyInfo::new(self.bits, "Passcode"),
+            mnemonic: None,
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        if self.alphanumeric {
+            "Alphanumeric device passcode"
+        } else {
+            "Numeric device passcode"
+        }
+    }
+
+    fn length(&self) -> Option<usize> {
+        Some(self.length)
+    }
+
+    fn estimated_entropy(&self) -> Option<f64> {
+        Some(self.bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_numeric_passcode_rules() {
+        for code in ["112233", "121213", "258147", "159046", "345916", "098416"] {
+            let digits: Vec<u8> = code.bytes().map(|b| b - b'0').collect();
+            assert!(!allowed(&digits), "{}", code);
+        }
+        // Years, and days with months either way round
+        for code in ["1987", "2024", "2512", "1225", "3107"] {
+            let digits: Vec<u8> = code.bytes().map(|b| b - b'0').collect();
+            assert!(!allowed(&digits), "{}", code);
+        }
+        let digits: Vec<u8> = "482619".bytes().map(|b| b - b'0').collect();
+        assert!(allowed(&digits));
+
+        // The memoized count agrees with checking every 5-digit code
+        let brute = (0..100_000u32)
+            .filter(|n| {
+                let code: Vec<u8> = format!("{:05}", n).bytes().map(|b| b - b'0').collect();
+                allowed(&code)
+            })
+            .count() as f64;
+        assert!((PasscodeGenerator::entropy_bits(5, false) - brute.log2()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_numeric_passcode() {
+        let gen = PasscodeGenerator::builder().build().unwrap();
+        let bits = gen.estimated_entropy().unwrap();
+        assert!(bits > 16.0 && bits < 6.0 * 10f64.log2());
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        for _ in 0..50 {
+            let password = gen.generate(&mut rng);
+            assert_eq!(password.value.len(), 6);
+            let digits: Vec<u8> = password.value.bytes().map(|b| b - b'0').collect();
+            assert!(allowed(&digits), "{}", password.value.as_str());
+            assert_eq!(password.entropy.bits, bits);
+        }
+    }
+
+    #[test]
+    fn test_alphanumeric_passcode() {
+        let gen = PasscodeGenerator::builder()
+            .alphanumeric(true)
+            .build()
+            .unwrap();
+        let expected = (36.0 * 35f64.powi(7) - 10.0 * 9f64.powi(7)).log2();
+        assert!((gen.estimated_entropy().unwrap() - expected).abs() < 1e-9);
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        for _ in 0..50 {
+            let password = gen.generate(&mut rng);
+            let value = password.value.as_bytes();
+            assert_eq!(value.len(), 8);
+            assert!(value.iter().any(u8::is_ascii_lowercase));
+            assert!(value.windows(2).all(|w| w[0] != w[1]));
+            assert!(value
+                .iter()
+                .all(|c| PasscodeGenerator::ALPHABET.contains(c)));
+        }
+    }
+
+    #[test]
+    fn test_passcode_lengths() {
+        assert_eq!(
+            PasscodeGenerator::new(3, true).err(),
+            Some(PwgenError::PasscodeTooShort { length: 3, min: 4 })
+        );
+        assert_eq!(
+            PasscodeGenerator::new(9, false).err(),
+            Some(PwgenError::PasscodeTooLong { length: 9, max: 8 })
+        );
+        assert!(PasscodeGenerator::new(12, true).is_ok());
+    }
+}
@@ -1,56 +1,773 @@
+use rand::distributions::{Distribution, WeightedIndex};
 use rand::Rng;
 use rand::RngCore;
 use zeroize::Zeroizing;
 
-use crate::entropy::EntropyInfo;
+use crate::entropy::{weighted_entropy, EntropyInfo};
+use crate::error::PwgenError;
 use crate::generators::{GeneratedPassword, PasswordGenerator};
+use crate::graphemes;
+use crate::mnemonic;
+use crate::wordlist::{Wordlist, BUILTIN_NAME};
 
-/// EFF large wordlist (7776 words)
-const EFF_WORDLIST: &str = include_str!("../../data/eff_large_wordlist.txt");
+/// Separators that keep a passphrase on one line when the text around it wraps
+///
+/// Unicode line breaking (UAX #14) never breaks between these and adjacent
+/// letters, unlike a hyphen or a space; mail clients and PDF layout follow it.
+pub const WRAP_SAFE_SEPARATORS: &str = "._:=+";
 
 pub struct PassphraseGenerator {
-    words: Vec<&'static str>,
+    wordlist: Wordlist,
     word_count: usize,
     separator: String,
+    /// Characters drawn independently for each gap instead of `separator`
+    random_separators: Option<Vec<char>>,
     capitalize: bool,
     mutate: bool,
+    /// Familiar-word sampling distribution and its Shannon entropy per word
+    familiar: Option<(WeightedIndex<f64>, f64)>,
+    /// Attach a story mnemonic to each passphrase
+    story: bool,
+    /// Uppercase one randomly chosen word
+    cap_one: bool,
+    /// Bits lost by never repeating a word, when that is enforced
+    unique_words: Option<f64>,
+    /// Cap on the joined passphrase's length in characters
+    budget: Option<LengthBudget>,
+    /// Words whose initials spell a chosen word
+    acrostic: Option<Acrostic>,
 }
 
-impl PassphraseGenerator {
-    /// Bits of entropy per word: log2(7776) ≈ 12.925
-    const ENTROPY_PER_WORD: f64 = 12.925;
-    /// Extra entropy from mutations (conservative estimate)
-    const MUTATION_ENTROPY_BONUS: f64 = 2.0;
+/// Word choices for each letter of an acrostic (`--acrostic`)
+///
+/// Position `i` draws only from the words starting with the `i`th letter, so
+/// each word contributes the entropy of that sublist rather than the whole list.
+struct Acrostic {
+    /// The spelled word, lowercased
+    letters: Vec<char>,
+    /// Indices of the words starting with each letter, one list per position
+    sublists: Vec<Vec<usize>>,
+    /// Weighted pickers within each sublist, when word weights are not uniform
+    pickers: Option<Vec<WeightedIndex<f64>>>,
+    /// Entropy of one whole sequence of words
+    bits: f64,
+}
 
-    pub fn new(word_count: usize, separator: String, capitalize: bool, mutate: bool) -> Self {
-        // Parse EFF wordlist (format: "11111\tabacus")
-        let words: Vec<&'static str> = EFF_WORDLIST
-            .lines()
-            .filter_map(|line| {
-                let parts: Vec<&str> = line.split('\t').collect();
-                if parts.len() == 2 {
-                    Some(parts[1])
-                } else {
-                    None
+impl Acrostic {
+    fn new(word: &str, words: &[String], weights: Option<&[f64]>) -> Result<Self, PwgenError> {
+        let letters: Vec<char> = word.chars().flat_map(char::to_lowercase).collect();
+        if letters.is_empty() || !letters.iter().all(|c| c.is_alphabetic()) {
+            return Err(PwgenError::InvalidAcrostic(word.to_string()));
+        }
+        let sublists = letters
+            .iter()
+            .map(|&letter| {
+                let sublist: Vec<usize> = (0..words.len())
+                    .filter(|&i| {
+                        words[i].chars().flat_map(char::to_lowercase).next() == Some(letter)
+                    })
+                    .collect();
+                if sublist.is_empty() {
+                    return Err(PwgenError::NoWordsWithInitial(letter));
                 }
+                Ok(sublist)
             })
-            .collect();
+            .collect::<Result<Vec<_>, _>>()?;
+        let bits = sublists
+            .iter()
+            .map(|sublist| match weights {
+                Some(w) => weighted_entropy(&sublist.iter().map(|&i| w[i]).collect::<Vec<_>>()),
+                None => (sublist.len() as f64).log2(),
+            })
+            .sum();
+        let pickers = weights.map(|w| {
+            sublists
+                .iter()
+                .map(|sublist| {
+                    WeightedIndex::new(sublist.iter().map(|&i| w[i]))
+                        .expect("wordlist weights are positive")
+                })
+                .collect()
+        });
+        Ok(Self {
+            letters,
+            sublists,
+            pickers,
+            bits,
+        })
+    }
 
+    /// Index of a word for `position`
+    fn sample(&self, position: usize, rng: &mut dyn RngCore) -> usize {
+        let sublist = &self.sublists[position];
+        let pick = match &self.pickers {
+            Some(pickers) => pickers[position].sample(rng),
+            None => rng.gen_range(0..sublist.len()),
+        };
+        sublist[pick]
+    }
+
+    /// Each distinct letter with its sublist and how often the word uses it
+    fn letter_groups(&self) -> Vec<(&[usize], usize)> {
+        let mut groups: Vec<(char, &[usize], usize)> = Vec::new();
+        for (letter, sublist) in self.letters.iter().zip(&self.sublists) {
+            match groups.iter_mut().find(|(l, _, _)| l == letter) {
+                Some((_, _, uses)) => *uses += 1,
+                None => groups.push((*letter, sublist, 1)),
+            }
+        }
+        groups
+            .into_iter()
+            .map(|(_, sublist, uses)| (sublist, uses))
+            .collect()
+    }
+}
+
+/// Exact sampling of word sequences that fit a total length (`--max-length`)
+///
+/// Words are grouped by the length they can occupy after mutation and
+/// capitalization (see [`occupied_length`]). A table of
+/// the probability that the remaining words fit the remaining characters lets
+/// each word be drawn from the conditional distribution directly, so shorter
+/// words are favored only as much as needed, no draws are wasted, and the
+/// entropy of what is actually sampled is known exactly.
+struct LengthBudget {
+    max_length: usize,
+    /// Characters left for words once separators are accounted for
+    available: usize,
+    /// Word indices by occupied length
+    by_length: Vec<Vec<usize>>,
+    /// Weighted pickers within each length, when word weights are not uniform
+    pickers: Option<Vec<Option<WeightedIndex<f64>>>>,
+    /// Probability of drawing a word of each length
+    length_mass: Vec<f64>,
+    /// `fits[r][b]`: probability that `r` freely drawn words fit in `b` characters
+    fits: Vec<Vec<f64>>,
+    /// Entropy of one whole sequence of words
+    bits: f64,
+}
+
+impl LengthBudget {
+    /// `mutate` makes room for a doubled letter in every word
+    fn new(
+        words: &[String],
+        weights: Option<&[f64]>,
+        word_count: usize,
+        separator_len: usize,
+        max_length: usize,
+        mutate: bool,
+    ) -> Result<Self, PwgenError> {
+        let too_long = PwgenError::PassphraseTooLong {
+            words: word_count,
+            max_length,
+        };
+        let available = max_length
+            .checked_sub((word_count - 1) * separator_len)
+            .ok_or(too_long.clone())?;
+
+        let total: f64 = weights.map_or(words.len() as f64, |w| w.iter().sum());
+        let mut by_length: Vec<Vec<usize>> = Vec::new();
+        let mut length_mass: Vec<f64> = Vec::new();
+        let mut length_log_mass: Vec<f64> = Vec::new();
+        for (i, word) in words.iter().enumerate() {
+            let len = occupied_length(word, mutate);
+            if len >= by_length.len() {
+                by_length.resize(len + 1, Vec::new());
+                length_mass.resize(len + 1, 0.0);
+                length_log_mass.resize(len + 1, 0.0);
+            }
+            let p = weights.map_or(1.0, |w| w[i]) / total;
+            by_length[len].push(i);
+            length_mass[len] += p;
+            length_log_mass[len] += p * p.log2();
+        }
+
+        // fits[r][b] = sum over lengths l of mass[l] * fits[r-1][b-l]; `expected`
+        // accumulates the matching sum of log2-probabilities for the entropy
+        let mut fits = vec![vec![1.0; available + 1]];
+        let mut expected = vec![0.0; available + 1];
+        for r in 1..=word_count {
+            let prev = &fits[r - 1];
+            let mut row = vec![0.0; available + 1];
+            let mut expected_row = vec![0.0; available + 1];
+            for b in 0..=available {
+                for (len, &mass) in length_mass.iter().enumerate().take(b + 1) {
+                    if mass > 0.0 {
+                        row[b] += mass * prev[b - len];
+                        expected_row[b] +=
+                            length_log_mass[len] * prev[b - len] + mass * expected[b - len];
+                    }
+                }
+            }
+            fits.push(row);
+            expected = expected_row;
+        }
+
+        let fit = fits[word_count][available];
+        if fit <= 0.0 {
+            return Err(too_long);
+        }
+        let pickers = weights.map(|w| {
+            by_length
+                .iter()
+                .map(|indices| WeightedIndex::new(indices.iter().map(|&i| w[i])).ok())
+                .collect()
+        });
+        Ok(Self {
+            max_length,
+            available,
+            by_length,
+            pickers,
+            length_mass,
+            bits: fit.log2() - expected[available] / fit,
+            fits,
+        })
+    }
+
+    /// Indices of `word_count` words that fit together
+    fn sample(&self, word_count: usize, rng: &mut dyn RngCore) -> Vec<usize> {
+        let mut left = self.available;
+        (1..=word_count)
+            .rev()
+            .map(|remaining| {
+                let rest = &self.fits[remaining - 1];
+                let lengths =
+                    WeightedIndex::new(self.length_mass.iter().enumerate().map(|(len, &mass)| {
+                        if len <= left {
+                            mass * rest[left - len]
+                        } else {
+                            0.0
+                        }
+                    }))
+                    .expect("a fitting continuation always exists");
+                let len = lengths.sample(rng);
+                left -= len;
+                let candidates = &self.by_length[len];
+                let pick = match self.pickers.as_ref().and_then(|p| p[len].as_ref()) {
+                    Some(picker) => picker.sample(rng),
+                    None => rng.gen_range(0..candidates.len()),
+                };
+                candidates[pick]
+            })
+            .collect()
+    }
+}
+
+/// Builder for [`PassphraseGenerator`], validated at [`build`](Self::build) time
+#[derive(Debug, Clone)]
+pub struct PassphraseGeneratorBuilder {
+    word_count: usize,
+    separator: String,
+    random_separators: Option<String>,
+    capitalize: bool,
+    mutate: bool,
+    wordlist: Option<Wordlist>,
+    familiar: bool,
+    story: bool,
+    cap_one: bool,
+    unique_words: bool,
+    max_length: Option<usize>,
+    acrostic: Option<String>,
+    wrap_safe: bool,
+}
+
+impl Default for PassphraseGeneratorBuilder {
+    fn default() -> Self {
         Self {
-            words,
+            word_count: PassphraseGenerator::DEFAULT_WORD_COUNT,
+            separator: "-".to_string(),
+            random_separators: None,
+            capitalize: false,
+            mutate: true,
+            wordlist: None,
+            familiar: false,
+            story: false,
+            cap_one: false,
+            unique_words: false,
+            max_length: None,
+            acrostic: None,
+            wrap_safe: false,
+        }
+    }
+}
+
+impl PassphraseGeneratorBuilder {
+    pub fn word_count(mut self, word_count: usize) -> Self {
+        self.word_count = word_count;
+        self
+    }
+
+    pub fn separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Join each pair of words with a character drawn at random from `set`
+    ///
+    /// Overrides [`separator`](Self::separator); each gap adds log2 of the
+    /// number of distinct characters in the set to the entropy.
+    pub fn random_separator(mut self, set: impl Into<String>) -> Self {
+        self.random_separators = Some(set.into());
+        self
+    }
+
+    /// Capitalize each word
+    pub fn capitalize(mut self, capitalize: bool) -> Self {
+        self.capitalize = capitalize;
+        self
+    }
+
+    /// Apply random word mutations (leet speak, truncation, doubling)
+    pub fn mutate(mut self, mutate: bool) -> Self {
+        self.mutate = mutate;
+        self
+    }
+
+    /// Draw words from this list instead of the embedded EFF list
+    pub fn wordlist(mut self, wordlist: Wordlist) -> Self {
+        self.wordlist = Some(wordlist);
+        self
+    }
+
+    /// Favor familiar words over uniform selection, at a cost in entropy
+    pub fn familiar(mut self, familiar: bool) -> Self {
+        self.familiar = familiar;
+        self
+    }
+
+    /// Attach a sentence weaving the words together as a memory aid
+    pub fn story(mut self, story: bool) -> Self {
+        self.story = story;
+        self
+    }
+
+    /// Uppercase exactly one randomly chosen word, worth log2(word_count) bits
+    ///
+    /// Unlike [`capitalize`](Self::capitalize), which is deterministic and adds
+    /// nothing, the choice of word is random and counted in the entropy.
+    pub fn cap_one(mut self, cap_one: bool) -> Self {
+        self.cap_one = cap_one;
+        self
+    }
+
+    /// Never use the same word twice in one passphrase, at a small cost in entropy
+    pub fn unique_words(mut self, unique_words: bool) -> Self {
+        self.unique_words = unique_words;
+        self
+    }
+
+    /// Keep the whole passphrase within `max_length` characters by favoring shorter words
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Pick words whose initials spell `word`, one word per letter
+    ///
+    /// Overrides [`word_count`](Self::word_count). The word itself is not
+    /// secret; the entropy counts only the choice within each letter's words.
+    pub fn acrostic(mut self, word: impl Into<String>) -> Self {
+        self.acrostic = Some(word.into());
+        self
+    }
+
+    /// Keep the passphrase in one piece when the text around it is wrapped
+    ///
+    /// The separators must come from [`WRAP_SAFE_SEPARATORS`], and words with
+    /// hyphens, spaces or other non-letters are left out of the list.
+    pub fn wrap_safe(mut self, wrap_safe: bool) -> Self {
+        self.wrap_safe = wrap_safe;
+        self
+    }
+
+    pub fn build(self) -> Result<PassphraseGenerator, PwgenError> {
+        let mut generator = PassphraseGenerator::new(
+            self.word_count,
+            self.separator,
+            self.capitalize,
+            self.mutate,
+        )?;
+        if let Some(set) = self.random_separators {
+            generator = generator.with_random_separator(&set)?;
+        }
+        if let Some(wordlist) = self.wordlist {
+            generator = generator.with_wordlist(wordlist);
+        }
+        if self.wrap_safe {
+            generator = generator.with_wrap_safe()?;
+        }
+        generator.story = self.story;
+        generator.cap_one = self.cap_one;
+        let mut generator = generator.with_familiar(self.familiar);
+        if let Some(max_length) = self.max_length {
+            generator = generator.with_max_length(max_length)?;
+        }
+        if let Some(word) = self.acrostic {
+            generator = generator.with_acrostic(&word)?;
+        }
+        if self.unique_words {
+            generator = generator.with_unique_words()?;
+        }
+        crate::info!(
+            "phrase: wordlist '{}' ({} words, {:.2} bits/word{})",
+            generator.wordlist.name(),
+            generator.wordlist.len(),
+            generator.entropy_per_word(),
+            if generator.familiar.is_some() {
+                ", familiar weighting"
+            } else {
+                ""
+            }
+        );
+        Ok(generator)
+    }
+}
+
+impl PassphraseGenerator {
+    pub const DEFAULT_WORD_COUNT: usize = 6;
+
+    /// Extra entropy from mutations (conservative estimate)
+    const MUTATION_ENTROPY_BONUS: f64 = 2.0;
+
+    pub fn new(
+        word_count: usize,
+        separator: String,
+        capitalize: bool,
+        mutate: bool,
+    ) -> Result<Self, PwgenError> {
+        if word_count == 0 {
+            return Err(PwgenError::ZeroWords);
+        }
+        Self::check_separator(&separator)?;
+
+        Ok(Self {
+            wordlist: Wordlist::builtin(),
             word_count,
             separator,
+            random_separators: None,
             capitalize,
             mutate,
+            familiar: None,
+            story: false,
+            cap_one: false,
+            unique_words: None,
+            budget: None,
+            acrostic: None,
+        })
+    }
+
+    pub fn builder() -> PassphraseGeneratorBuilder {
+        PassphraseGeneratorBuilder::default()
+    }
+
+    /// Separators must stay distinguishable from the words around them
+    ///
+    /// Letters would merge into the adjacent words and digits into leet
+    /// mutations, making the word boundaries (and the entropy, which assumes
+    /// them) ambiguous. Multi-character separators such as `", "` are fine.
+    fn check_separator(separator: &str) -> Result<(), PwgenError> {
+        if separator
+            .chars()
+            .any(|c| c.is_alphanumeric() || c.is_control())
+        {
+            return Err(PwgenError::InvalidSeparator(separator.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Draw each separator from the distinct characters of `set`
+    pub fn with_random_separator(mut self, set: &str) -> Result<Self, PwgenError> {
+        Self::check_separator(set)?;
+        let mut chars: Vec<char> = set.chars().collect();
+        chars.sort_unstable();
+        chars.dedup();
+        if chars.is_empty() {
+            return Err(PwgenError::EmptySeparatorSet);
+        }
+        self.random_separators = Some(chars);
+        if let Some(budget) = &self.budget {
+            let max_length = budget.max_length;
+            self.budget = None;
+            return self.with_max_length(max_length);
+        }
+        Ok(self)
+    }
+
+    /// Only wrap-safe separators, and only words made of letters
+    pub fn with_wrap_safe(self) -> Result<Self, PwgenError> {
+        let separators: String = match &self.random_separators {
+            Some(set) => set.iter().collect(),
+            None => self.separator.clone(),
+        };
+        if separators
+            .chars()
+            .any(|c| !WRAP_SAFE_SEPARATORS.contains(c))
+        {
+            return Err(PwgenError::WrapUnsafeSeparator(separators));
+        }
+        let letters_only = |w: &str| w.chars().all(char::is_alphabetic);
+        let available = self
+            .wordlist
+            .words()
+            .iter()
+            .filter(|w| letters_only(w))
+            .count();
+        if available == self.wordlist.len() {
+            return Ok(self);
+        }
+        let wordlist =
+            self.wordlist
+                .filtered(letters_only)
+                .map_err(|_| PwgenError::NotEnoughWords {
+                    words: self.word_count,
+                    available,
+                })?;
+        Ok(self.with_wordlist(wordlist))
+    }
+
+    /// Characters each separator occupies
+    fn separator_len(&self) -> usize {
+        match &self.random_separators {
+            Some(_) => 1,
+            None => self.separator.chars().count(),
+        }
+    }
+
+    /// Bits from drawing the separators at random
+    fn separator_entropy(&self) -> f64 {
+        self.random_separators.as_ref().map_or(0.0, |set| {
+            (self.word_count - 1) as f64 * (set.len() as f64).log2()
+        })
+    }
+
+    /// Replace the embedded EFF list with another wordlist
+    ///
+    /// Clears any length cap and acrostic, which must be set again for the new list.
+    pub fn with_wordlist(mut self, wordlist: Wordlist) -> Self {
+        self.wordlist = wordlist;
+        self.budget = None;
+        self.acrostic = None;
+        let familiar = self.familiar.is_some();
+        self.with_familiar(familiar)
+    }
+
+    /// Cap the joined passphrase at `max_length` characters
+    ///
+    /// Fails if no combination of words fits, even when mutations lengthen them.
+    pub fn with_max_length(mut self, max_length: usize) -> Result<Self, PwgenError> {
+        if self.acrostic.is_some() {
+            return Err(PwgenError::AcrosticWithMaxLength);
+        }
+        let weights = self
+            .familiar
+            .is_some()
+            .then(|| self.wordlist.familiarity_weights());
+        let budget = LengthBudget::new(
+            self.wordlist.words(),
+            weights.as_deref(),
+            self.word_count,
+            self.separator_len(),
+            max_length,
+            self.mutate,
+        )?;
+        crate::debug!(
+            "phrase: {} words fit in {} characters with {:.2} bits",
+            self.word_count,
+            max_length,
+            budget.bits
+        );
+        self.budget = Some(budget);
+        Ok(self)
+    }
+
+    /// Bits lost by keeping the passphrase within its length cap
+    pub fn max_length_cost(&self) -> f64 {
+        self.budget.as_ref().map_or(0.0, |budget| {
+            self.word_count as f64 * self.entropy_per_word() - budget.bits
+        })
+    }
+
+    /// Pick one word per letter of `word` so their initials spell it
+    ///
+    /// Sets the word count to the number of letters. Fails if `word` has
+    /// anything but letters, if no word in the list starts with one of them,
+    /// or together with a length cap.
+    pub fn with_acrostic(mut self, word: &str) -> Result<Self, PwgenError> {
+        if self.budget.is_some() {
+            return Err(PwgenError::AcrosticWithMaxLength);
+        }
+        let weights = self
+            .familiar
+            .is_some()
+            .then(|| self.wordlist.familiarity_weights());
+        let acrostic = Acrostic::new(word, self.wordlist.words(), weights.as_deref())?;
+        crate::debug!(
+            "phrase: acrostic of {} letters with {:.2} bits",
+            acrostic.letters.len(),
+            acrostic.bits
+        );
+        self.word_count = acrostic.letters.len();
+        self.acrostic = Some(acrostic);
+        if self.unique_words.is_some() {
+            self.unique_words = None;
+            return self.with_unique_words();
+        }
+        Ok(self)
+    }
+
+    /// Bits lost by spelling the acrostic instead of drawing from the whole list
+    pub fn acrostic_cost(&self) -> f64 {
+        self.acrostic.as_ref().map_or(0.0, |acrostic| {
+            self.word_count as f64 * self.entropy_per_word() - acrostic.bits
+        })
+    }
+
+    /// Never repeat a word within one passphrase
+    ///
+    /// Fails if the list has fewer words than the passphrase, or if no
+    /// passphrase of distinct words fits the length cap.
+    pub fn with_unique_words(mut self) -> Result<Self, PwgenError> {
+        let available = self.wordlist.len();
+        if self.word_count > available {
+            return Err(PwgenError::NotEnoughWords {
+                words: self.word_count,
+                available,
+            });
+        }
+        if let Some(acrostic) = &self.acrostic {
+            if let Some((sublist, uses)) = acrostic
+                .letter_groups()
+                .into_iter()
+                .find(|(sublist, uses)| uses > &sublist.len())
+            {
+                return Err(PwgenError::NotEnoughWords {
+                    words: uses,
+                    available: sublist.len(),
+                });
+            }
+        }
+        if let Some(budget) = &self.budget {
+            let mut lengths: Vec<usize> = self
+                .wordlist
+                .words()
+                .iter()
+                .map(|w| occupied_length(w, self.mutate))
+                .collect();
+            lengths.sort_unstable();
+            if lengths[..self.word_count].iter().sum::<usize>() > budget.available {
+                return Err(PwgenError::PassphraseTooLong {
+                    words: self.word_count,
+                    max_length: budget.max_length,
+                });
+            }
+        }
+        self.unique_words = Some(self.unique_words_cost());
+        Ok(self)
+    }
+
+    /// Bits lost by rejecting passphrases that repeat a word: -log2 P(no repeats)
+    ///
+    /// Exact for uniform selection. With familiar weighting the chance of a
+    /// repeat is bounded from above by summing over every pair of positions,
+    /// so the reported cost errs on the high side. In an acrostic only
+    /// positions sharing a letter can repeat a word.
+    fn unique_words_cost(&self) -> f64 {
+        let weights = self
+            .familiar
+            .is_some()
+            .then(|| self.wordlist.familiarity_weights());
+        match &self.acrostic {
+            None => no_repeat_cost(self.wordlist.len(), weights.as_deref(), self.word_count),
+            Some(acrostic) => acrostic
+                .letter_groups()
+                .into_iter()
+                .map(|(sublist, uses)| {
+                    let sub_weights = weights
+                        .as_ref()
+                        .map(|w| sublist.iter().map(|&i| w[i]).collect::<Vec<_>>());
+                    no_repeat_cost(sublist.len(), sub_weights.as_deref(), uses)
+                })
+                .sum(),
         }
     }
 
+    /// Bits given up by [`with_unique_words`](Self::with_unique_words); 0 when words may repeat
+    pub fn unique_words_cost_bits(&self) -> f64 {
+        self.unique_words.unwrap_or(0.0)
+    }
+
+    /// Weight word selection by [`Wordlist::familiarity_weights`]
+    pub fn with_familiar(mut self, familiar: bool) -> Self {
+        self.familiar = familiar.then(|| {
+            let weights = self.wordlist.familiarity_weights();
+            let bits = weighted_entropy(&weights);
+            let index = WeightedIndex::new(&weights).expect("wordlist weights are positive");
+            (index, bits)
+        });
+        if let Some(acrostic) = &self.acrostic {
+            let word: String = acrostic.letters.iter().collect();
+            self.acrostic = None;
+            self = self
+                .with_acrostic(&word)
+                .expect("which words start with a letter does not depend on their weights");
+        }
+        if self.unique_words.is_some() {
+            self.unique_words = Some(self.unique_words_cost());
+        }
+        if let Some(budget) = &self.budget {
+            let max_length = budget.max_length;
+            self.budget = None;
+            return self
+                .with_max_length(max_length)
+                .expect("whether words fit does not depend on their weights");
+        }
+        self
+    }
+
+    /// Entropy of one word as actually sampled
+    pub fn entropy_per_word(&self) -> f64 {
+        match &self.familiar {
+            Some((_, bits)) => *bits,
+            None => self.wordlist.entropy_per_word(),
+        }
+    }
+
+    /// Entropy of a whole passphrase: base + mutation bonus if enabled
+    fn entropy_bits(&self) -> f64 {
+        let mut bits = match (&self.budget, &self.acrostic) {
+            (Some(budget), _) => budget.bits,
+            (None, Some(acrostic)) => acrostic.bits,
+            (None, None) => (self.word_count as f64) * self.entropy_per_word(),
+        };
+        if self.mutate {
+            bits += (self.word_count as f64) * Self::MUTATION_ENTROPY_BONUS;
+        }
+        if self.cap_one {
+            bits += (self.word_count as f64).log2();
+        }
+        bits + self.separator_entropy() - self.unique_words_cost_bits()
+    }
+
+    /// Bits lost per word to familiar-word weighting compared with uniform selection
+    pub fn entropy_reduction_per_word(&self) -> f64 {
+        self.wordlist.entropy_per_word() - self.entropy_per_word()
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.word_count
+    }
+
+    pub fn wordlist(&self) -> &Wordlist {
+        &self.wordlist
+    }
+
+    /// Uppercase the first letter, keeping all of its uppercase form (`ß` is `SS`)
     fn capitalize_word(word: &str) -> String {
-        let mut chars: Vec<char> = word.chars().collect();
-        if let Some(first) = chars.first_mut() {
-            *first = first.to_uppercase().next().unwrap_or(*first);
+        let mut chars = word.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
         }
-        chars.into_iter().collect()
     }
 
     /// Apply leet speak transformation to a character
@@ -69,8 +786,15 @@ impl PassphraseGenerator {
     }
 
     /// Apply random mutations to a word
-    fn mutate_word(word: &str, rng: &mut dyn RngCore) -> String {
-        let mut result: Vec<char> = word.chars().collect();
+    ///
+    /// With `keep_initial` the first letter is never leetified, so acrostics stay readable.
+    /// Positions count grapheme clusters, so a letter and its combining accent are
+    /// never split by truncation or separated by doubling.
+    fn mutate_word(word: &str, keep_initial: bool, rng: &mut dyn RngCore) -> String {
+        let mut result: Vec<String> = graphemes::split(word)
+            .into_iter()
+            .map(str::to_string)
+            .collect();
         let len = result.len();
 
         if len < 3 {
@@ -84,8 +808,12 @@ impl PassphraseGenerator {
             // 40% chance: Apply leet speak to 1-2 random characters
             let num_leet = rng.gen_range(1..=2.min(len));
             for _ in 0..num_leet {
-                let pos = rng.gen_range(0..len);
-                result[pos] = Self::leetify(result[pos]);
+                let pos = rng.gen_range(usize::from(keep_initial)..len);
+                let mut chars = result[pos].chars();
+                // A letter carrying a combining accent has no leet form
+                if let (Some(c), None) = (chars.next(), chars.next()) {
+                    result[pos] = Self::leetify(c).to_string();
+                }
             }
         } else if mutation_type < 70 && len > 4 {
             // 30% chance: Truncate word (only if > 4 chars)
@@ -94,33 +822,106 @@ impl PassphraseGenerator {
         } else if mutation_type < 85 {
             // 15% chance: Double a vowel or consonant
             let pos = rng.gen_range(0..len);
-            let c = result[pos];
-            if c.is_ascii_alphabetic() {
-                result.insert(pos, c);
+            if result[pos].starts_with(char::is_alphabetic) {
+                result.insert(pos, result[pos].clone());
             }
         } else {
             // 15% chance: No mutation (keep original)
         }
 
-        result.into_iter().collect()
+        result.concat()
+    }
+}
+
+/// Most characters `word` can take up in a passphrase
+///
+/// Uppercasing can lengthen a letter (`ß` becomes `SS`), so every letter
+/// counts in its uppercase form. Doubling a letter, which may carry a
+/// combining accent, is the only mutation that lengthens a word.
+fn occupied_length(word: &str, mutate: bool) -> usize {
+    let upper_len = |s: &str| -> usize { s.chars().map(|c| c.to_uppercase().count()).sum() };
+    let doubled = if mutate {
+        graphemes::split(word)
+            .into_iter()
+            .filter(|g| g.starts_with(char::is_alphabetic))
+            .map(upper_len)
+            .max()
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    upper_len(word) + doubled
+}
+
+/// -log2 of the chance that `draws` words from a pool of `pool` are all different
+///
+/// `weights` are the pool's sampling weights, uniform when absent.
+fn no_repeat_cost(pool: usize, weights: Option<&[f64]>, draws: usize) -> f64 {
+    match weights {
+        None => {
+            let total = pool as f64;
+            -(0..draws)
+                .map(|i| ((total - i as f64) / total).log2())
+                .sum::<f64>()
+        }
+        Some(weights) => {
+            let total: f64 = weights.iter().sum();
+            let repeat: f64 = weights.iter().map(|w| (w / total).powi(2)).sum();
+            let pairs = (draws * draws.saturating_sub(1) / 2) as f64;
+            -(1.0 - pairs * repeat).max(f64::MIN_POSITIVE).log2()
+        }
     }
 }
 
 impl PasswordGenerator for PassphraseGenerator {
     fn generate(&self, rng: &mut dyn RngCore) -> GeneratedPassword {
-        let selected: Vec<String> = (0..self.word_count)
-            .map(|_| {
-                let idx = rng.gen_range(0..self.words.len());
-                let word = self.words[idx];
+        let words = self.wordlist.words();
+        let unique = self.unique_words.is_some();
+        let indices: Vec<usize> = match &self.budget {
+            // Redraw whole sequences: rejecting single words would skew the lengths
+            Some(budget) => loop {
+                let indices = budget.sample(self.word_count, rng);
+                if !unique
+                    || indices
+                        .iter()
+                        .enumerate()
+                        .all(|(i, a)| !indices[..i].contains(a))
+                {
+                    break indices;
+                }
+            },
+            None => {
+                let mut indices = Vec::with_capacity(self.word_count);
+                while indices.len() < self.word_count {
+                    let index = match (&self.acrostic, &self.familiar) {
+                        (Some(acrostic), _) => acrostic.sample(indices.len(), rng),
+                        (None, Some((index, _))) => index.sample(rng),
+                        (None, None) => rng.gen_range(0..words.len()),
+                    };
+                    if !(unique && indices.contains(&index)) {
+                        indices.push(index);
+                    }
+                }
+                indices
+            }
+        };
+        let upper = self.cap_one.then(|| rng.gen_range(0..self.word_count));
+        let selected: Vec<String> = indices
+            .into_iter()
+            .enumerate()
+            .map(|(i, idx)| {
+                let word = words[idx].as_str();
 
                 // Apply mutation if enabled
                 let word = if self.mutate {
-                    Self::mutate_word(word, rng)
+                    Self::mutate_word(word, self.acrostic.is_some(), rng)
                 } else {
                     word.to_string()
                 };
 
-                if self.capitalize {
+                if upper == Some(i) {
+                    word.to_uppercase()
+                } else if self.capitalize {
                     Self::capitalize_word(&word)
                 } else {
                     word
@@ -128,25 +929,42 @@ impl PasswordGenerator for PassphraseGenerator {
             })
             .collect();
 
-        let passphrase = selected.join(&self.separator);
-
-        // Entropy calculation: base + mutation bonus if enabled
-        let mut entropy_bits = (self.word_count as f64) * Self::ENTROPY_PER_WORD;
-        if self.mutate {
-            entropy_bits += (self.word_count as f64) * Self::MUTATION_ENTROPY_BONUS;
-        }
+        let passphrase = match &self.random_separators {
+            Some(set) => {
+                let mut joined = String::new();
+                for (i, word) in selected.iter().enumerate() {
+                    if i > 0 {
+                        joined.push(set[rng.gen_range(0..set.len())]);
+                    }
+                    joined.push_str(word);
+                }
+                joined
+            }
+            None => selected.join(&self.separator),
+        };
+        let story = self.story.then(|| {
+            let words: Vec<&str> = selected.iter().map(String::as_str).collect();
+            Zeroizing::new(mnemonic::story(&words, rng))
+        });
 
         GeneratedPassword {
             value: Zeroizing::new(passphrase),
-            entropy: EntropyInfo::new(entropy_bits, "Diceware"),
+            entropy: EntropyInfo::new(self.entropy_bits(), "Diceware"),
+            mnemonic: story,
         }
     }
 
+    fn estimated_entropy(&self) -> Option<f64> {
+        Some(self.entropy_bits())
+    }
+
     fn description(&self) -> &'static str {
-        if self.mutate {
-            "EFF Diceware passphrase (mutated)"
-        } else {
-            "EFF Diceware passphrase"
+        let builtin = self.wordlist.name() == BUILTIN_NAME;
+        match (builtin, self.mutate) {
+            (true, true) => "EFF Diceware passphrase (mutated)",
+            (true, false) => "EFF Diceware passphrase",
+            (false, true) => "Diceware passphrase, custom wordlist (mutated)",
+            (false, false) => "Diceware passphrase, custom wordlist",
         }
     }
 }
@@ -154,12 +972,13 @@ impl PasswordGenerator for PassphraseGenerator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generators::PasswordGeneratorExt;
     use rand::SeedableRng;
     use rand_chacha::ChaCha8Rng;
 
     #[test]
     fn test_passphrase_word_count() {
-        let gen = PassphraseGenerator::new(6, "-".to_string(), false, false);
+        let gen = PassphraseGenerator::new(6, "-".to_string(), false, false).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
         assert_eq!(password.value.split('-').count(), 6);
@@ -167,15 +986,139 @@ mod tests {
 
     #[test]
     fn test_passphrase_custom_separator() {
-        let gen = PassphraseGenerator::new(4, ".".to_string(), false, false);
+        let gen = PassphraseGenerator::new(4, ".".to_string(), false, false).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
         assert_eq!(password.value.split('.').count(), 4);
     }
 
+    #[test]
+    fn test_passphrase_separator_validation() {
+        for bad in ["x", "-a-", "1", "\t"] {
+            assert_eq!(
+                PassphraseGenerator::new(4, bad.to_string(), false, false).err(),
+                Some(PwgenError::InvalidSeparator(bad.to_string()))
+            );
+        }
+        let gen = PassphraseGenerator::new(4, ", ".to_string(), false, false).unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let password = gen.generate(&mut rng);
+        assert_eq!(password.value.split(", ").count(), 4);
+        assert!((password.entropy.bits - 4.0 * 7776f64.log2()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_passphrase_wrap_safe() {
+        let generator = PassphraseGenerator::builder()
+            .separator(".")
+            .wrap_safe(true)
+            .build()
+            .unwrap();
+        assert_eq!(generator.wordlist().len(), Wordlist::builtin().len() - 4);
+        assert!(!generator.wordlist().words().iter().any(|w| w == "t-shirt"));
+
+        let generator = PassphraseGenerator::builder()
+            .random_separator("._")
+            .wrap_safe(true)
+            .build();
+        assert!(generator.is_ok());
+        for bad in ["-", " ", "/"] {
+            assert_eq!(
+                PassphraseGenerator::builder()
+                    .separator(bad)
+                    .wrap_safe(true)
+                    .build()
+                    .err(),
+                Some(PwgenError::WrapUnsafeSeparator(bad.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_passphrase_random_separator() {
+        let gen = PassphraseGenerator::builder()
+            .word_count(5)
+            .mutate(false)
+            .random_separator("-_.!!")
+            .build()
+            .unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let mut seen = std::collections::HashSet::new();
+        for _ in 0..20 {
+            let password = gen.generate(&mut rng);
+            let separators: Vec<char> = password
+                .value
+                .chars()
+                .filter(|c| "-_.!".contains(*c))
+                .collect();
+            assert!(separators.len() >= 4);
+            seen.extend(separators);
+            // Four distinct characters: 2 bits for each of the 4 gaps
+            assert!((password.entropy.bits - (5.0 * 7776f64.log2() + 8.0)).abs() < 1e-9);
+        }
+        assert_eq!(seen.len(), 4);
+
+        assert!(matches!(
+            PassphraseGenerator::builder().random_separator("").build(),
+            Err(PwgenError::EmptySeparatorSet)
+        ));
+        assert!(matches!(
+            PassphraseGenerator::builder()
+                .random_separator("-a")
+                .build(),
+            Err(PwgenError::InvalidSeparator(_))
+        ));
+    }
+
+    #[test]
+    fn test_passphrase_unique_words() {
+        let words: Vec<String> = ["ant", "bee", "cat", "dog"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        let gen = PassphraseGenerator::builder()
+            .word_count(4)
+            .mutate(false)
+            .wordlist(Wordlist::from_words("tiny", words).unwrap())
+            .unique_words(true)
+            .build()
+            .unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        for _ in 0..20 {
+            let password = gen.generate(&mut rng);
+            let mut parts: Vec<&str> = password.value.split('-').collect();
+            parts.sort_unstable();
+            parts.dedup();
+            assert_eq!(parts.len(), 4);
+            // 4! orderings of the four words
+            assert!((password.entropy.bits - 24f64.log2()).abs() < 1e-9);
+        }
+
+        // The EFF list loses only a sliver of a bit
+        let eff = PassphraseGenerator::builder()
+            .unique_words(true)
+            .build()
+            .unwrap();
+        assert!(eff.unique_words_cost_bits() > 0.0);
+        assert!(eff.unique_words_cost_bits() < 0.01);
+
+        let too_many = PassphraseGenerator::builder()
+            .word_count(5)
+            .wordlist(Wordlist::from_words("tiny", vec!["a".into(), "b".into()]).unwrap())
+            .unique_words(true)
+            .build();
+        assert!(matches!(
+            too_many,
+            Err(PwgenError::NotEnoughWords {
+                words: 5,
+                available: 2
+            })
+        ));
+    }
+
     #[test]
     fn test_passphrase_no_separator() {
-        let gen = PassphraseGenerator::new(3, "".to_string(), false, false);
+        let gen = PassphraseGenerator::new(3, "".to_string(), false, false).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
         // Without separator and mutation, should be all lowercase letters
@@ -184,7 +1127,7 @@ mod tests {
 
     #[test]
     fn test_passphrase_capitalize() {
-        let gen = PassphraseGenerator::new(4, "-".to_string(), true, false);
+        let gen = PassphraseGenerator::new(4, "-".to_string(), true, false).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
         // Each word should start with uppercase
@@ -194,9 +1137,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_passphrase_cap_one() {
+        let gen = PassphraseGenerator::builder()
+            .word_count(4)
+            .mutate(false)
+            .cap_one(true)
+            .build()
+            .unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        for password in gen.iter(&mut rng).take(20) {
+            let upper = password
+                .value
+                .split('-')
+                .filter(|w| w.chars().all(|c| c.is_ascii_uppercase()))
+                .count();
+            assert_eq!(upper, 1);
+            // log2(4) = 2 bits for the choice of word
+            assert!((password.entropy.bits - (4.0 * 7776f64.log2() + 2.0)).abs() < 1e-9);
+        }
+    }
+
     #[test]
     fn test_passphrase_entropy_no_mutate() {
-        let gen = PassphraseGenerator::new(6, "-".to_string(), false, false);
+        let gen = PassphraseGenerator::new(6, "-".to_string(), false, false).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
         // 6 words * 12.925 bits ≈ 77.55 bits (no mutation bonus)
@@ -205,7 +1169,7 @@ mod tests {
 
     #[test]
     fn test_passphrase_entropy_with_mutate() {
-        let gen = PassphraseGenerator::new(6, "-".to_string(), false, true);
+        let gen = PassphraseGenerator::new(6, "-".to_string(), false, true).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
         // 6 words * (12.925 + 2.0) bits ≈ 89.55 bits (with mutation bonus)
@@ -214,7 +1178,7 @@ mod tests {
 
     #[test]
     fn test_passphrase_words_from_eff_list() {
-        let gen = PassphraseGenerator::new(10, "-".to_string(), false, false);
+        let gen = PassphraseGenerator::new(10, "-".to_string(), false, false).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
         // All words should be lowercase alphabetic (no mutation)
@@ -226,13 +1190,268 @@ mod tests {
 
     #[test]
     fn test_passphrase_mutation_changes_words() {
-        let gen = PassphraseGenerator::new(6, "-".to_string(), false, true);
+        let gen = PassphraseGenerator::new(6, "-".to_string(), false, true).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
         // With mutation, at least some words should have non-alphabetic chars or be modified
         let has_mutation = password.value.chars().any(|c| c.is_ascii_digit());
         // Note: not guaranteed every time due to 15% no-mutation chance, but very likely with 6 words
-        assert!(has_mutation || password.value.len() > 0); // At minimum, generates something
+        assert!(has_mutation || !password.value.is_empty()); // At minimum, generates something
+    }
+
+    #[test]
+    fn test_passphrase_builder() {
+        let gen = PassphraseGenerator::builder()
+            .word_count(4)
+            .separator(".")
+            .mutate(false)
+            .build()
+            .unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let password = gen.generate(&mut rng);
+        assert_eq!(password.value.split('.').count(), 4);
+        assert_eq!(
+            PassphraseGenerator::builder().word_count(0).build().err(),
+            Some(PwgenError::ZeroWords)
+        );
+    }
+
+    #[test]
+    fn test_passphrase_custom_wordlist() {
+        let words = ["red", "green", "blue", "cyan"].map(String::from).to_vec();
+        let gen = PassphraseGenerator::builder()
+            .word_count(5)
+            .mutate(false)
+            .wordlist(Wordlist::from_words("colors", words.clone()).unwrap())
+            .build()
+            .unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let password = gen.generate(&mut rng);
+        assert!(password
+            .value
+            .split('-')
+            .all(|w| words.iter().any(|c| c == w)));
+        // 5 words * log2(4) = 10 bits
+        assert!((password.entropy.bits - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_passphrase_familiar_reduces_entropy() {
+        let gen = PassphraseGenerator::builder()
+            .mutate(false)
+            .familiar(true)
+            .build()
+            .unwrap();
+        let reduction = gen.entropy_reduction_per_word();
+        assert!(reduction > 0.0 && reduction < 12.925);
+
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let password = gen.generate(&mut rng);
+        assert!((password.entropy.bits - 6.0 * gen.entropy_per_word()).abs() < 1e-9);
+
+        // Short words should dominate the weighted sample
+        let sample: Vec<_> = gen.iter(&mut rng).take(200).collect();
+        let avg_len = sample
+            .iter()
+            .flat_map(|p| p.value.split('-').map(str::len).collect::<Vec<_>>())
+            .sum::<usize>() as f64
+            / 1200.0;
+        assert!(avg_len < 6.0, "average word length {}", avg_len);
+    }
+
+    #[test]
+    fn test_passphrase_max_length() {
+        // Of the 9 pairs from {a, bb, ccc}, the 6 that fit in "xx-yy" remain
+        let words = ["a", "bb", "ccc"].map(String::from).to_vec();
+        let gen = PassphraseGenerator::builder()
+            .word_count(2)
+            .mutate(false)
+            .wordlist(Wordlist::from_words("lengths", words).unwrap())
+            .max_length(5)
+            .build()
+            .unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let password = gen.generate(&mut rng);
+        assert!((password.entropy.bits - 6f64.log2()).abs() < 1e-9);
+        assert!((gen.max_length_cost() - (9f64.log2() - 6f64.log2())).abs() < 1e-9);
+        assert!(gen.iter(&mut rng).take(100).all(|p| p.value.len() <= 5));
+
+        let gen = PassphraseGenerator::builder()
+            .familiar(true)
+            .max_length(32)
+            .build()
+            .unwrap();
+        assert!(gen
+            .iter(&mut rng)
+            .take(100)
+            .all(|p| p.value.chars().count() <= 32));
+
+        assert_eq!(
+            PassphraseGenerator::builder().max_length(20).build().err(),
+            Some(PwgenError::PassphraseTooLong {
+                words: 6,
+                max_length: 20
+            })
+        );
+    }
+
+    #[test]
+    fn test_passphrase_max_length_non_ascii() {
+        // ß uppercases to SS and e + U+0301 doubles as two characters
+        assert_eq!(occupied_length("straße", false), 7);
+        assert_eq!(occupied_length("straße", true), 9);
+        assert_eq!(occupied_length("cafe\u{301}", true), 7);
+        assert_eq!(occupied_length("otter", true), 6);
+
+        let words = ["ßaß", "maße", "fuß", "groß", "weiß"]
+            .map(String::from)
+            .to_vec();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        for (capitalize, cap_one, mutate, max_length) in [
+            (true, false, false, 14),
+            (false, true, false, 14),
+            (false, true, true, 20),
+        ] {
+            let gen = PassphraseGenerator::builder()
+                .word_count(3)
+                .capitalize(capitalize)
+                .cap_one(cap_one)
+                .mutate(mutate)
+                .wordlist(Wordlist::from_words("german", words.clone()).unwrap())
+                .max_length(max_length)
+                .build()
+                .unwrap();
+            assert!(gen
+                .iter(&mut rng)
+                .take(500)
+                .all(|p| p.value.chars().count() <= max_length));
+        }
+    }
+
+    #[test]
+    fn test_passphrase_story() {
+        let gen = PassphraseGenerator::builder()
+            .word_count(5)
+            .mutate(false)
+            .story(true)
+            .build()
+            .unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let password = gen.generate(&mut rng);
+        let story = password.mnemonic.expect("story requested");
+        for word in password.value.split('-') {
+            assert!(story.contains(word));
+        }
+
+        let plain = PassphraseGenerator::new(5, "-".to_string(), false, false).unwrap();
+        assert!(plain.generate(&mut rng).mnemonic.is_none());
+    }
+
+    #[test]
+    fn test_passphrase_acrostic() {
+        let gen = PassphraseGenerator::builder()
+            .word_count(3)
+            .acrostic("Tulip")
+            .build()
+            .unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        for password in gen.iter(&mut rng).take(50) {
+            let initials: String = password
+                .value
+                .split('-')
+                .filter_map(|word| word.chars().next())
+                .collect();
+            assert_eq!(initials.to_lowercase(), "tulip");
+        }
+
+        // Each word comes from its letter's sublist of the 7776-word list
+        let words = Wordlist::builtin();
+        let expected: f64 = "tulip"
+            .chars()
+            .map(|letter| {
+                let n = words
+                    .words()
+                    .iter()
+                    .filter(|w| w.starts_with(letter))
+                    .count();
+                (n as f64).log2()
+            })
+            .sum();
+        let plain = PassphraseGenerator::builder()
+            .acrostic("tulip")
+            .mutate(false)
+            .build()
+            .unwrap();
+        assert!((plain.entropy_bits() - expected).abs() < 1e-9);
+        assert!((plain.acrostic_cost() - (5.0 * words.entropy_per_word() - expected)).abs() < 1e-9);
+        assert!(plain.acrostic_cost() > 0.0);
+
+        // Repeated letters share a sublist, so only they can repeat a word
+        let unique = PassphraseGenerator::builder()
+            .acrostic("tot")
+            .unique_words(true)
+            .build()
+            .unwrap();
+        let t = words.words().iter().filter(|w| w.starts_with('t')).count() as f64;
+        assert!((unique.unique_words_cost_bits() + ((t - 1.0) / t).log2()).abs() < 1e-9);
+
+        assert_eq!(
+            PassphraseGenerator::builder().acrostic("ab1").build().err(),
+            Some(PwgenError::InvalidAcrostic("ab1".to_string()))
+        );
+        assert_eq!(
+            PassphraseGenerator::builder()
+                .acrostic("tulip")
+                .max_length(40)
+                .build()
+                .err(),
+            Some(PwgenError::AcrosticWithMaxLength)
+        );
+        let list = Wordlist::from_words("fruit", vec!["apple".into(), "banana".into()]).unwrap();
+        assert_eq!(
+            PassphraseGenerator::builder()
+                .wordlist(list)
+                .acrostic("abz")
+                .build()
+                .err(),
+            Some(PwgenError::NoWordsWithInitial('z'))
+        );
+    }
+
+    #[test]
+    fn test_passphrase_non_ascii_words() {
+        assert_eq!(PassphraseGenerator::capitalize_word("über"), "Über");
+        assert_eq!(PassphraseGenerator::capitalize_word("ßuppe"), "SSuppe");
+
+        // "kaffeé" with a combining accent, and letters outside ASCII
+        let decomposed = "kaffe\u{301}";
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let mut doubled = false;
+        for _ in 0..500 {
+            let word = PassphraseGenerator::mutate_word(decomposed, false, &mut rng);
+            let accents = word.matches('\u{301}').count();
+            assert_eq!(accents, word.matches("e\u{301}").count(), "{:?}", word);
+            assert!(accents <= 2);
+            doubled |= PassphraseGenerator::mutate_word("größe", false, &mut rng).contains("öö");
+        }
+        assert!(doubled);
+
+        let words = ["größe", "niño", "çà", "été", "øre"]
+            .map(String::from)
+            .to_vec();
+        let gen = PassphraseGenerator::builder()
+            .word_count(4)
+            .capitalize(true)
+            .mutate(false)
+            .wordlist(Wordlist::from_words("mixed", words).unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(gen.wordlist().entropy_per_word(), 5f64.log2());
+        let password = gen.generate(&mut rng);
+        assert!(password
+            .value
+            .split('-')
+            .all(|w| w.starts_with(char::is_uppercase)));
     }
 
     #[test]
@@ -3,18 +3,53 @@ use rand::RngCore;
 use zeroize::Zeroizing;
 
 use crate::entropy::EntropyInfo;
+use crate::error::PwgenError;
 use crate::generators::{GeneratedPassword, PasswordGenerator};
 
 pub struct PinGenerator {
     length: usize,
 }
 
+/// Builder for [`PinGenerator`], validated at [`build`](Self::build) time
+#[derive(Debug, Clone)]
+pub struct PinGeneratorBuilder {
+    length: usize,
+}
+
+impl Default for PinGeneratorBuilder {
+    fn default() -> Self {
+        Self {
+            length: PinGenerator::DEFAULT_LENGTH,
+        }
+    }
+}
+
+impl PinGeneratorBuilder {
+    pub fn length(mut self, length: usize) -> Self {
+        self.length = length;
+        self
+    }
+
+    pub fn build(self) -> Result<PinGenerator, PwgenError> {
+        PinGenerator::new(self.length)
+    }
+}
+
 impl PinGenerator {
+    pub const DEFAULT_LENGTH: usize = 6;
+
     /// Bits of entropy per digit: log2(10)
     const ENTROPY_PER_DIGIT: f64 = std::f64::consts::LOG2_10;
 
-    pub fn new(length: usize) -> Self {
-        Self { length }
+    pub fn new(length: usize) -> Result<Self, PwgenError> {
+        if length == 0 {
+            return Err(PwgenError::ZeroLength);
+        }
+        Ok(Self { length })
+    }
+
+    pub fn builder() -> PinGeneratorBuilder {
+        PinGeneratorBuilder::default()
     }
 }
 
@@ -29,12 +64,25 @@ impl PasswordGenerator for PinGenerator {
         GeneratedPassword {
             value: Zeroizing::new(pin),
             entropy: EntropyInfo::new(entropy_bits, "Numeric"),
+            mnemonic: None,
         }
     }
 
     fn description(&self) -> &'static str {
         "Numeric PIN"
     }
+
+    fn uniform_alphabet(&self) -> Option<usize> {
+        Some(10)
+    }
+
+    fn length(&self) -> Option<usize> {
+        Some(self.length)
+    }
+
+    fn estimated_entropy(&self) -> Option<f64> {
+        Some(self.length as f64 * 10f64.log2())
+    }
 }
 
 #[cfg(test)]
@@ -45,7 +93,7 @@ mod tests {
 
     #[test]
     fn test_pin_length() {
-        let gen = PinGenerator::new(6);
+        let gen = PinGenerator::new(6).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
         assert_eq!(password.value.len(), 6);
@@ -53,7 +101,7 @@ mod tests {
 
     #[test]
     fn test_pin_only_digits() {
-        let gen = PinGenerator::new(10);
+        let gen = PinGenerator::new(10).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
         assert!(password.value.chars().all(|c| c.is_ascii_digit()));
@@ -61,16 +109,27 @@ mod tests {
 
     #[test]
     fn test_pin_entropy() {
-        let gen = PinGenerator::new(6);
+        let gen = PinGenerator::new(6).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
         // 6 digits = 6 * log2(10) ≈ 19.93 bits
         assert!((password.entropy.bits - 19.93).abs() < 0.1);
     }
 
+    #[test]
+    fn test_pin_builder() {
+        let gen = PinGenerator::builder().length(8).build().unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        assert_eq!(gen.generate(&mut rng).value.len(), 8);
+        assert_eq!(
+            PinGenerator::builder().length(0).build().err(),
+            Some(PwgenError::ZeroLength)
+        );
+    }
+
     #[test]
     fn test_pin_deterministic_with_seed() {
-        let gen = PinGenerator::new(6);
+        let gen = PinGenerator::new(6).unwrap();
         let mut rng1 = ChaCha8Rng::seed_from_u64(12345);
         let mut rng2 = ChaCha8Rng::seed_from_u64(12345);
         let p1 = gen.generate(&mut rng1);
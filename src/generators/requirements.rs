@@ -0,0 +1,280 @@
+use std::fmt;
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rand::RngCore;
+
+use crate::error::PwgenError;
+use crate::generators::CharClass;
+
+/// Least number of characters of each class a secure password must contain
+/// (`--require`, `--min-digits`, `--min-symbols`)
+///
+/// Passwords are drawn uniformly from those that meet every minimum, so no
+/// class is favoured beyond what the policy demands, and the entropy is
+/// reduced by exactly the share of passwords the policy rules out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClassRequirements {
+    /// Indexed like [`CharClass::ALL`]
+    minimums: [usize; 4],
+}
+
+impl ClassRequirements {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// At least `count` characters of `class`; a larger minimum already set is kept
+    pub fn at_least(mut self, class: CharClass, count: usize) -> Self {
+        let minimum = &mut self.minimums[class as usize];
+        *minimum = (*minimum).max(count);
+        self
+    }
+
+    pub fn minimum(&self, class: CharClass) -> usize {
+        self.minimums[class as usize]
+    }
+
+    /// Characters the minimums take up together
+    pub fn total(&self) -> usize {
+        self.minimums.iter().sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+}
+
+impl fmt::Display for ClassRequirements {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = CharClass::ALL
+            .into_iter()
+            .filter(|&class| self.minimum(class) > 0)
+            .map(|class| format!("{}:{}", class.name(), self.minimum(class)))
+            .collect();
+        f.write_str(&parts.join(","))
+    }
+}
+
+/// Uniform sampling of charset positions among passwords meeting [`ClassRequirements`]
+///
+/// First draws how many characters each required class gets, with the exact
+/// probability of that split among all conforming passwords, then shuffles
+/// the classes into positions and picks each character uniformly within its
+/// class. Classes without a minimum are pooled into one.
+pub(crate) struct ClassSampler {
+    /// Charset indices of each required class, with its minimum
+    required: Vec<(Vec<usize>, usize)>,
+    /// Charset indices of the classes without a minimum
+    free: Vec<usize>,
+    /// ln of each required class's share of the charset
+    ln_shares: Vec<f64>,
+    /// `fill[k][t]`: ln of the probability that `t` uniform draws land only in
+    /// required classes `k..` and the pool, meeting the minimums of `k..`
+    fill: Vec<Vec<f64>>,
+    ln_factorials: Vec<f64>,
+}
+
+impl ClassSampler {
+    /// Sampler for passwords of `length` symbols from `charset`, classified by
+    /// their first character
+    pub(crate) fn new(
+        charset: &[String],
+        requirements: ClassRequirements,
+        length: usize,
+    ) -> Result<Self, PwgenError> {
+        if requirements.total() > length {
+            return Err(PwgenError::RequirementsTooLong {
+                needed: requirements.total(),
+                length,
+            });
+        }
+        let mut members: [Vec<usize>; 4] = Default::default();
+        for (i, symbol) in charset.iter().enumerate() {
+            let first = symbol.chars().next().unwrap_or_default();
+            members[CharClass::of(first) as usize].push(i);
+        }
+        let mut required = Vec::new();
+        let mut free = Vec::new();
+        for (class, members) in CharClass::ALL.into_iter().zip(members) {
+            match requirements.minimum(class) {
+                0 => free.extend(members),
+                _ if members.is_empty() => {
+                    return Err(PwgenError::RequiredClassMissing(class.name()))
+                }
+                minimum => required.push((members, minimum)),
+            }
+        }
+
+        let n = charset.len() as f64;
+        let ln_shares: Vec<f64> = required
+            .iter()
+            .map(|(members, _)| (members.len() as f64 / n).ln())
+            .collect();
+        let ln_factorials: Vec<f64> = std::iter::once(0.0)
+            .chain((1..=length).scan(0.0, |sum, i| {
+                *sum += (i as f64).ln();
+                Some(*sum)
+            }))
+            .collect();
+        let ln_free = (free.len() as f64 / n).ln();
+        let mut fill = vec![Vec::new(); required.len() + 1];
+        fill[required.len()] = (0..=length)
+            .map(|t| if t == 0 { 0.0 } else { t as f64 * ln_free })
+            .collect();
+        let mut sampler = Self {
+            required,
+            free,
+            ln_shares,
+            fill,
+            ln_factorials,
+        };
+        for k in (0..sampler.required.len()).rev() {
+            sampler.fill[k] = (0..=length)
+                .map(|t| log_sum_exp(&sampler.split_weights(k, t)))
+                .collect();
+        }
+        Ok(sampler)
+    }
+
+    /// Bits ruled out by the requirements: -log2 of the share of passwords meeting them
+    pub(crate) fn penalty(&self) -> f64 {
+        let length = self.ln_factorials.len() - 1;
+        -self.fill[0][length] / std::f64::consts::LN_2
+    }
+
+    /// ln of the weight of giving required class `k` each count from its minimum
+    /// up to `t` of the remaining `t` positions
+    fn split_weights(&self, k: usize, t: usize) -> Vec<f64> {
+        let minimum = self.required[k].1;
+        (minimum..=t)
+            .map(|c| {
+                let choose =
+                    self.ln_factorials[t] - self.ln_factorials[c] - self.ln_factorials[t - c];
+                choose + c as f64 * self.ln_shares[k] + self.fill[k + 1][t - c]
+            })
+            .collect()
+    }
+
+    /// Charset indices of one conforming password of `length` symbols
+    pub(crate) fn sample(&self, rng: &mut dyn RngCore) -> Vec<usize> {
+        let length = self.ln_factorials.len() - 1;
+        let mut classes = Vec::with_capacity(length);
+        let mut remaining = length;
+        for k in 0..self.required.len() {
+            let weights = self.split_weights(k, remaining);
+            let max = weights.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let scaled: Vec<f64> = weights.iter().map(|w| (w - max).exp()).collect();
+            let count = self.required[k].1
+                + WeightedIndex::new(&scaled)
+                    .expect("a conforming split exists")
+                    .sample(rng);
+            classes.extend(std::iter::repeat_n(k, count));
+            remaining -= count;
+        }
+        classes.extend(std::iter::repeat_n(self.required.len(), remaining));
+        classes.shuffle(rng);
+        classes
+            .into_iter()
+            .map(|k| {
+                let members = self.required.get(k).map_or(&self.free, |(m, _)| m);
+                members[rng.gen_range(0..members.len())]
+            })
+            .collect()
+    }
+}
+
+/// ln of the sum of the exponentials of `values`, without overflow
+fn log_sum_exp(values: &[f64]) -> f64 {
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY {
+        return max;
+    }
+    max + values.iter().map(|v| (v - max).exp()).sum::<f64>().ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn charset(chars: &str) -> Vec<String> {
+        chars.chars().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_requirements() {
+        let requirements = ClassRequirements::new()
+            .at_least(CharClass::Digit, 2)
+            .at_least(CharClass::Digit, 1)
+            .at_least(CharClass::Upper, 1);
+        assert_eq!(requirements.minimum(CharClass::Digit), 2);
+        assert_eq!(requirements.total(), 3);
+        assert_eq!(requirements.to_string(), "upper:1,digit:2");
+        assert!(ClassRequirements::new().is_empty());
+    }
+
+    #[test]
+    fn test_penalty_matches_counting() {
+        // Over "aB1" at length 4, count the strings with an upper and two digits
+        let chars = charset("aB1");
+        let requirements = ClassRequirements::new()
+            .at_least(CharClass::Upper, 1)
+            .at_least(CharClass::Digit, 2);
+        let sampler = ClassSampler::new(&chars, requirements, 4).unwrap();
+        let conforming = (0..81)
+            .filter(|n| {
+                let digits: Vec<u32> = (0..4).map(|i| n / 3u32.pow(i) % 3).collect();
+                digits.contains(&1) && digits.iter().filter(|&&d| d == 2).count() >= 2
+            })
+            .count() as f64;
+        assert!((sampler.penalty() - (81.0 / conforming).log2()).abs() < 1e-9);
+
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        for _ in 0..50 {
+            let sample = sampler.sample(&mut rng);
+            assert_eq!(sample.len(), 4);
+            assert!(sample.contains(&1));
+            assert!(sample.iter().filter(|&&i| i == 2).count() >= 2);
+        }
+
+        // Every conforming password is about equally likely
+        let mut seen = std::collections::HashMap::new();
+        for _ in 0..20_000 {
+            *seen.entry(sampler.sample(&mut rng)).or_insert(0usize) += 1;
+        }
+        assert_eq!(seen.len() as f64, conforming);
+        let expected = 20_000.0 / conforming;
+        assert!(seen
+            .values()
+            .all(|&n| (n as f64 - expected).abs() < expected * 0.2));
+    }
+
+    #[test]
+    fn test_unsatisfiable_requirements() {
+        let chars = charset("abc123");
+        assert_eq!(
+            ClassSampler::new(
+                &chars,
+                ClassRequirements::new().at_least(CharClass::Symbol, 1),
+                8
+            )
+            .err(),
+            Some(PwgenError::RequiredClassMissing("symbol"))
+        );
+        assert_eq!(
+            ClassSampler::new(
+                &chars,
+                ClassRequirements::new().at_least(CharClass::Digit, 5),
+                4
+            )
+            .err(),
+            Some(PwgenError::RequirementsTooLong {
+                needed: 5,
+                length: 4
+            })
+        );
+    }
+}
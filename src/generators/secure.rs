@@ -1,24 +1,355 @@
+use rand::distributions::{Distribution, WeightedIndex};
 use rand::Rng;
 use rand::RngCore;
 use zeroize::Zeroizing;
 
-use crate::cli::CharSet;
-use crate::entropy::EntropyInfo;
-use crate::generators::{GeneratedPassword, PasswordGenerator};
+use crate::entropy::{weighted_entropy, EntropyInfo};
+use crate::error::PwgenError;
+use crate::generators::requirements::ClassSampler;
+use crate::generators::{
+    CharClass, ClassRequirements, Composition, GeneratedPassword, PasswordGenerator, SymbolSet,
+};
+use crate::graphemes;
+use crate::rules::PasswordRules;
+
+/// Preset alphabets of the secure generator (`--charset`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CharSet {
+    /// a-z, A-Z
+    Alpha,
+    /// a-z, A-Z, 0-9
+    Alphanumeric,
+    /// a-z, A-Z, 0-9, symbols
+    AlphanumericSymbols,
+    /// All printable ASCII
+    All,
+    /// Bitcoin base58 alphabet (alphanumeric without 0OIl)
+    Base58,
+    /// a-z, A-Z, 0-9, symbols that PowerShell and cmd.exe leave alone inside quotes
+    PowershellSafe,
+}
+
+/// What `length` counts in a generated password
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LengthMode {
+    /// User-perceived characters (grapheme clusters), as most sites count them
+    #[default]
+    Graphemes,
+    /// Unicode scalar values
+    Chars,
+    /// UTF-8 bytes, for systems with byte-limited fields
+    Bytes,
+}
+
+impl LengthMode {
+    fn unit(&self) -> &'static str {
+        match self {
+            LengthMode::Graphemes => "grapheme",
+            LengthMode::Chars => "char",
+            LengthMode::Bytes => "byte",
+        }
+    }
+
+    fn measure(&self, symbol: &str) -> usize {
+        match self {
+            LengthMode::Graphemes => 1,
+            LengthMode::Chars => symbol.chars().count(),
+            LengthMode::Bytes => symbol.len(),
+        }
+    }
+}
 
 pub struct SecureGenerator {
     length: usize,
-    charset: Vec<char>,
+    /// Alphabet as grapheme clusters; each entry is one user-perceived character
+    charset: Vec<String>,
+    length_mode: LengthMode,
+    /// Site rules every password must satisfy, with the entropy they remove (in bits)
+    rules: Option<(PasswordRules, f64)>,
+    /// Most times any one character may appear in a password
+    max_repeat: Option<usize>,
+    /// Classes drawn by weight for each position (`--compose`), instead of the flat charset
+    composed: Option<Composed>,
+    /// Minimum counts per class, with the entropy they remove (in bits)
+    required: Option<(ClassSampler, f64)>,
+}
+
+/// Per-position class draw of a composed generator
+struct Composed {
+    classes: Vec<Vec<String>>,
+    /// Weight of each class, as given
+    shares: Vec<f64>,
+    weights: WeightedIndex<f64>,
+    /// Entropy of one position: the class draw plus the character within the class
+    bits_per_char: f64,
+}
+
+impl Composed {
+    fn new(classes: Vec<Vec<String>>, shares: Vec<f64>) -> Result<Self, PwgenError> {
+        if classes.iter().any(Vec::is_empty) {
+            return Err(PwgenError::EmptyCharset);
+        }
+        let total: f64 = shares.iter().sum();
+        let bits_per_char = weighted_entropy(&shares)
+            + shares
+                .iter()
+                .zip(&classes)
+                .map(|(w, class)| w / total * (class.len() as f64).log2())
+                .sum::<f64>();
+        Ok(Self {
+            weights: WeightedIndex::new(&shares).map_err(|_| PwgenError::EmptyCharset)?,
+            classes,
+            shares,
+            bits_per_char,
+        })
+    }
+}
+
+/// Builder for [`SecureGenerator`], validated at [`build`](Self::build) time
+#[derive(Debug, Clone)]
+pub struct SecureGeneratorBuilder {
+    length: usize,
+    charset: CharSet,
+    symbol_set: Option<SymbolSet>,
+    alphabet: Option<String>,
+    excluded: String,
+    exclude_ambiguous: bool,
+    length_mode: LengthMode,
+    rules: Option<PasswordRules>,
+    max_repeat: Option<usize>,
+    composition: Option<Composition>,
+    requirements: ClassRequirements,
+    classes: Vec<CharClass>,
+    space: bool,
+}
+
+impl Default for SecureGeneratorBuilder {
+    fn default() -> Self {
+        Self {
+            length: SecureGenerator::DEFAULT_LENGTH,
+            charset: CharSet::AlphanumericSymbols,
+            symbol_set: None,
+            alphabet: None,
+            excluded: String::new(),
+            exclude_ambiguous: false,
+            length_mode: LengthMode::default(),
+            rules: None,
+            max_repeat: None,
+            composition: None,
+            requirements: ClassRequirements::default(),
+            classes: Vec::new(),
+            space: false,
+        }
+    }
+}
+
+impl SecureGeneratorBuilder {
+    pub fn length(mut self, length: usize) -> Self {
+        self.length = length;
+        self
+    }
+
+    pub fn charset(mut self, charset: CharSet) -> Self {
+        self.charset = charset;
+        self
+    }
+
+    /// Symbols of the `alphanumeric-symbols` charset (default: [`SymbolSet::Full`])
+    pub fn symbol_set(mut self, set: SymbolSet) -> Self {
+        self.symbol_set = Some(set);
+        self
+    }
+
+    /// Draw from this class too; classes chosen this way replace the charset,
+    /// and symbols come from the [`symbol_set`](Self::symbol_set)
+    pub fn include(mut self, class: CharClass) -> Self {
+        if !self.classes.contains(&class) {
+            self.classes.push(class);
+        }
+        self
+    }
+
+    /// Add the space character, with or without [`include`](Self::include)d classes
+    pub fn include_space(mut self, space: bool) -> Self {
+        self.space = space;
+        self
+    }
+
+    /// Use an explicit alphabet (any Unicode) instead of a preset charset
+    pub fn alphabet(mut self, alphabet: impl Into<String>) -> Self {
+        self.alphabet = Some(alphabet.into());
+        self
+    }
+
+    /// Leave these characters out of the charset or alphabet, e.g. quotes and
+    /// backslashes that break shell scripts; adds to earlier calls
+    pub fn exclude_chars(mut self, chars: &str) -> Self {
+        self.excluded.push_str(chars);
+        self
+    }
+
+    /// Exclude ambiguous characters (0O1lI), from an explicit alphabet too
+    pub fn exclude_ambiguous(mut self, exclude: bool) -> Self {
+        self.exclude_ambiguous = exclude;
+        self
+    }
+
+    pub fn length_mode(mut self, mode: LengthMode) -> Self {
+        self.length_mode = mode;
+        self
+    }
+
+    /// Conform to a site's password rules; replaces the charset and alphabet
+    pub fn rules(mut self, rules: PasswordRules) -> Self {
+        self.rules = Some(rules);
+        self
+    }
+
+    /// Let no character appear more than `max` times
+    pub fn max_repeat(mut self, max: usize) -> Self {
+        self.max_repeat = Some(max);
+        self
+    }
+
+    /// Draw each position's class by weight; replaces the charset, and symbols
+    /// come from the [`symbol_set`](Self::symbol_set)
+    pub fn compose(mut self, composition: Composition) -> Self {
+        self.composition = Some(composition);
+        self
+    }
+
+    /// Contain at least the given number of characters of each class
+    pub fn require(mut self, requirements: ClassRequirements) -> Self {
+        self.requirements = requirements;
+        self
+    }
+
+    pub fn build(self) -> Result<SecureGenerator, PwgenError> {
+        if self.composition.is_some() && self.max_repeat.is_some() {
+            return Err(PwgenError::ComposeWithMaxRepeat);
+        }
+        if self.rules.is_some() && !self.excluded.is_empty() {
+            return Err(PwgenError::ExcludedCharsWithRules);
+        }
+        let mut generator = self.build_unlimited()?;
+        let mut excluded = self.excluded.clone();
+        if self.alphabet.is_some() && self.exclude_ambiguous {
+            excluded.push_str(SecureGenerator::AMBIGUOUS);
+        }
+        if !excluded.is_empty() && self.rules.is_none() {
+            generator = generator.without_chars(&excluded)?;
+            crate::info!(
+                "secure: {} symbols left after exclusions",
+                generator.charset.len()
+            );
+        }
+        if let Some(max) = self.max_repeat {
+            generator = generator.with_max_repeat(max)?;
+        }
+        if self.requirements.is_empty() {
+            return Ok(generator);
+        }
+        crate::info!("secure: requiring {}", self.requirements);
+        generator.with_requirements(self.requirements)
+    }
+
+    fn build_unlimited(&self) -> Result<SecureGenerator, PwgenError> {
+        if let Some(rules) = self.rules.clone() {
+            let generator = SecureGenerator::from_rules(self.length, rules)?;
+            crate::info!(
+                "secure: {} symbols allowed by site rules, length {}",
+                generator.charset.len(),
+                generator.length
+            );
+            return Ok(generator);
+        }
+        if let Some(composition) = &self.composition {
+            let symbols = self.symbol_set.clone().unwrap_or(SymbolSet::Full);
+            let generator = SecureGenerator::composed(
+                self.length,
+                composition,
+                &symbols,
+                self.exclude_ambiguous,
+            )?
+            .with_length_mode(self.length_mode)?;
+            crate::info!(
+                "secure: composed {}, {:.2} bits per character",
+                composition,
+                generator.bits_per_char()
+            );
+            return Ok(generator);
+        }
+        if self.alphabet.is_none() && (!self.classes.is_empty() || self.space) {
+            let symbols = match &self.symbol_set {
+                Some(_) if !self.classes.contains(&CharClass::Symbol) => {
+                    return Err(PwgenError::SymbolSetWithoutSymbolClass)
+                }
+                Some(symbols) => symbols.clone(),
+                None => SymbolSet::Full,
+            };
+            let generator = SecureGenerator::from_classes(
+                self.length,
+                &self.classes,
+                &symbols,
+                self.space,
+                self.exclude_ambiguous,
+            )?
+            .with_length_mode(self.length_mode)?;
+            crate::info!(
+                "secure: {} symbols from the chosen classes, length {}",
+                generator.charset.len(),
+                generator.length
+            );
+            return Ok(generator);
+        }
+        let generator = match (&self.alphabet, &self.symbol_set) {
+            (Some(alphabet), _) => {
+                SecureGenerator::from_alphabet(self.length, alphabet, self.length_mode)?
+            }
+            (None, Some(symbols)) => {
+                if !matches!(self.charset, CharSet::AlphanumericSymbols) {
+                    return Err(PwgenError::SymbolSetWithoutSymbols);
+                }
+                SecureGenerator::with_symbols(self.length, symbols, self.exclude_ambiguous)?
+                    .with_length_mode(self.length_mode)?
+            }
+            (None, None) => {
+                SecureGenerator::new(self.length, &self.charset, self.exclude_ambiguous)?
+                    .with_length_mode(self.length_mode)?
+            }
+        };
+        crate::info!(
+            "secure: {} symbols, length {} ({:?})",
+            generator.charset.len(),
+            generator.length,
+            generator.length_mode
+        );
+        Ok(generator)
+    }
 }
 
 impl SecureGenerator {
+    pub const DEFAULT_LENGTH: usize = 16;
+
     const LOWERCASE: &'static str = "abcdefghijklmnopqrstuvwxyz";
     const UPPERCASE: &'static str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
     const DIGITS: &'static str = "0123456789";
-    const SYMBOLS: &'static str = "!@#$%^&*()-_=+[]{}|;:,.<>?";
+    /// [`SymbolSet::FULL`] without `$ % ^ & ( ) | < > !`, plus `~`: nothing PowerShell or cmd.exe
+    /// expands or escapes inside quotes, and no quote or backtick to end the string
+    const SHELL_SAFE_SYMBOLS: &'static str = "#*+,-.:;=?@[]_{}~";
     const AMBIGUOUS: &'static str = "0O1lI";
+    /// Bitcoin base58 alphabet, in its canonical order (no 0, O, I, l)
+    const BASE58: &'static str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+    pub fn new(
+        length: usize,
+        charset_type: &CharSet,
+        exclude_ambiguous: bool,
+    ) -> Result<Self, PwgenError> {
+        if length == 0 {
+            return Err(PwgenError::ZeroLength);
+        }
 
-    pub fn new(length: usize, charset_type: &CharSet, exclude_ambiguous: bool) -> Self {
         let mut charset = String::new();
 
         match charset_type {
@@ -35,59 +366,406 @@ impl SecureGenerator {
                 charset.push_str(Self::LOWERCASE);
                 charset.push_str(Self::UPPERCASE);
                 charset.push_str(Self::DIGITS);
-                charset.push_str(Self::SYMBOLS);
+                charset.push_str(SymbolSet::FULL);
+            }
+            CharSet::PowershellSafe => {
+                charset.push_str(Self::LOWERCASE);
+                charset.push_str(Self::UPPERCASE);
+                charset.push_str(Self::DIGITS);
+                charset.push_str(Self::SHELL_SAFE_SYMBOLS);
             }
+            CharSet::Base58 => charset.push_str(Self::BASE58),
             CharSet::All => {
                 // All printable ASCII (32-126)
                 charset = (32u8..=126).map(|b| b as char).collect();
             }
         }
 
-        let charset: Vec<char> = if exclude_ambiguous {
-            charset
-                .chars()
-                .filter(|c| !Self::AMBIGUOUS.contains(*c))
-                .collect()
+        let charset: Vec<String> = charset
+            .chars()
+            .filter(|c| !exclude_ambiguous || !Self::AMBIGUOUS.contains(*c))
+            .map(String::from)
+            .collect();
+
+        Self::from_symbols(length, charset, LengthMode::default())
+    }
+
+    /// Letters and digits plus the symbols of `symbols`
+    pub fn with_symbols(
+        length: usize,
+        symbols: &SymbolSet,
+        exclude_ambiguous: bool,
+    ) -> Result<Self, PwgenError> {
+        let mut generator = Self::new(length, &CharSet::Alphanumeric, exclude_ambiguous)?;
+        generator
+            .charset
+            .extend(symbols.chars().into_iter().map(String::from));
+        Ok(generator)
+    }
+
+    /// Generator over an arbitrary alphabet, split into grapheme clusters
+    ///
+    /// Duplicate clusters are removed, as are leading combining marks that
+    /// would merge into the preceding character of the password. Fewer than
+    /// two distinct clusters left is an error: such passwords have no entropy.
+    pub fn from_alphabet(
+        length: usize,
+        alphabet: &str,
+        length_mode: LengthMode,
+    ) -> Result<Self, PwgenError> {
+        let mut symbols: Vec<String> = Vec::new();
+        for cluster in graphemes::split(alphabet) {
+            if !graphemes::starts_with_extend(cluster) && !symbols.iter().any(|s| s == cluster) {
+                symbols.push(cluster.to_string());
+            }
+        }
+        if symbols.len() == 1 {
+            return Err(PwgenError::SingleSymbolAlphabet);
+        }
+        Self::from_symbols(length, symbols, length_mode)
+    }
+
+    fn from_symbols(
+        length: usize,
+        charset: Vec<String>,
+        length_mode: LengthMode,
+    ) -> Result<Self, PwgenError> {
+        if length == 0 {
+            return Err(PwgenError::ZeroLength);
+        }
+        if charset.is_empty() {
+            return Err(PwgenError::EmptyCharset);
+        }
+        Self {
+            length,
+            charset,
+            length_mode: LengthMode::default(),
+            rules: None,
+            max_repeat: None,
+            composed: None,
+            required: None,
+        }
+        .with_length_mode(length_mode)
+    }
+
+    /// Characters of `class`, with symbols from `symbols`
+    fn class_chars(class: CharClass, symbols: &SymbolSet) -> &str {
+        match class {
+            CharClass::Lower => Self::LOWERCASE,
+            CharClass::Upper => Self::UPPERCASE,
+            CharClass::Digit => Self::DIGITS,
+            CharClass::Symbol => symbols.as_str(),
+        }
+    }
+
+    /// Generator over the characters of `classes`, plus a space if `space`
+    pub fn from_classes(
+        length: usize,
+        classes: &[CharClass],
+        symbols: &SymbolSet,
+        space: bool,
+        exclude_ambiguous: bool,
+    ) -> Result<Self, PwgenError> {
+        let mut charset: Vec<String> = CharClass::ALL
+            .into_iter()
+            .filter(|class| classes.contains(class))
+            .flat_map(|class| Self::class_chars(class, symbols).chars())
+            .filter(|c| !exclude_ambiguous || !Self::AMBIGUOUS.contains(*c))
+            .map(String::from)
+            .collect();
+        if space && !charset.iter().any(|s| s == " ") {
+            charset.push(" ".to_string());
+        }
+        Self::from_symbols(length, charset, LengthMode::default())
+    }
+
+    /// Generator drawing each position's class by weight, then a character from the class
+    pub fn composed(
+        length: usize,
+        composition: &Composition,
+        symbols: &SymbolSet,
+        exclude_ambiguous: bool,
+    ) -> Result<Self, PwgenError> {
+        let classes: Vec<Vec<String>> = composition
+            .classes()
+            .iter()
+            .map(|(class, _)| {
+                Self::class_chars(*class, symbols)
+                    .chars()
+                    .filter(|c| !exclude_ambiguous || !Self::AMBIGUOUS.contains(*c))
+                    .map(String::from)
+                    .collect()
+            })
+            .collect();
+        let weights: Vec<f64> = composition.classes().iter().map(|(_, w)| *w).collect();
+        let composed = Composed::new(classes, weights)?;
+
+        let mut generator =
+            Self::from_symbols(length, composed.classes.concat(), LengthMode::default())?;
+        generator.composed = Some(composed);
+        Ok(generator)
+    }
+
+    /// Entropy of one character: log2 of the alphabet, or of the weighted class draw
+    fn bits_per_char(&self) -> f64 {
+        match &self.composed {
+            Some(composed) => composed.bits_per_char,
+            None => (self.charset.len() as f64).log2(),
+        }
+    }
+
+    /// The same generator without the characters of `chars` (`--exclude-chars`)
+    ///
+    /// Removed from the alphabet and from every class of a composition, so the
+    /// entropy follows the smaller set. Must come before a repeat limit or
+    /// class requirements, which are worked out from the alphabet.
+    fn without_chars(mut self, chars: &str) -> Result<Self, PwgenError> {
+        let excluded = graphemes::split(chars);
+        let kept = |symbol: &String| !excluded.contains(&symbol.as_str());
+        self.charset.retain(kept);
+        if self.charset.is_empty() {
+            return Err(PwgenError::AllCharsExcluded);
+        }
+        if let Some(composed) = self.composed.take() {
+            let classes = composed
+                .classes
+                .into_iter()
+                .map(|class| class.into_iter().filter(kept).collect())
+                .collect();
+            self.composed = Some(
+                Composed::new(classes, composed.shares)
+                    .map_err(|_| PwgenError::AllCharsExcluded)?,
+            );
+        }
+        let mode = self.length_mode;
+        self.with_length_mode(mode)
+    }
+
+    /// Generator producing only passwords that satisfy `rules`
+    ///
+    /// Candidates are drawn from the rules' alphabet and rejected until the
+    /// required character sets and repeat limit are met.
+    pub fn from_rules(length: usize, rules: PasswordRules) -> Result<Self, PwgenError> {
+        let min = rules.min_length.unwrap_or(1);
+        let max = rules.max_length.unwrap_or(usize::MAX);
+        if length != 0 && (length < min || length > max) {
+            return Err(PwgenError::LengthOutsideRules { length, min, max });
+        }
+        let alphabet: String = rules.alphabet().into_iter().collect();
+        let probability = rules.required_probability(length);
+        let repeats_possible = match rules.max_consecutive {
+            Some(0) => false,
+            Some(1) => alphabet.chars().count() > 1 || length <= 1,
+            _ => true,
+        };
+        if length != 0 && (probability <= 0.0 || !repeats_possible) {
+            return Err(PwgenError::RulesUnsatisfiable { length });
+        }
+        let mut generator = Self::from_alphabet(length, &alphabet, LengthMode::Graphemes)?;
+        crate::debug!(
+            "site rules: {:.1}% of candidates satisfy the required sets",
+            probability * 100.0
+        );
+        generator.rules = Some((rules, -probability.log2()));
+        Ok(generator)
+    }
+
+    /// Change what `length` counts
+    ///
+    /// Exact char/byte lengths can only always be reached if some symbol is a single unit.
+    pub fn with_length_mode(mut self, mode: LengthMode) -> Result<Self, PwgenError> {
+        if !self.charset.iter().any(|s| mode.measure(s) == 1) {
+            return Err(PwgenError::LengthModeUnsatisfiable { unit: mode.unit() });
+        }
+        self.length_mode = mode;
+        Ok(self)
+    }
+
+    /// Let no character appear more than `max` times
+    ///
+    /// Characters that reach the limit are dropped from the alphabet for the
+    /// rest of the password, so each password's entropy is the sum of log2 of
+    /// the alphabet size at every draw. The limit is only guaranteed to leave a
+    /// choice at every draw if the single-unit characters alone can fill the length.
+    pub fn with_max_repeat(mut self, max: usize) -> Result<Self, PwgenError> {
+        let single = self
+            .charset
+            .iter()
+            .filter(|s| self.length_mode.measure(s) == 1)
+            .count();
+        if single.saturating_mul(max) < self.length {
+            return Err(PwgenError::RepeatLimitUnsatisfiable {
+                length: self.length,
+                max_repeat: max,
+            });
+        }
+        self.max_repeat = Some(max);
+        Ok(self)
+    }
+
+    /// Only generate passwords with at least the required characters of each class
+    ///
+    /// Passwords are drawn uniformly from those that qualify, and the entropy
+    /// drops by -log2 of their share of all passwords.
+    pub fn with_requirements(
+        mut self,
+        requirements: ClassRequirements,
+    ) -> Result<Self, PwgenError> {
+        let conflict = if self.rules.is_some() {
+            Some("site rules")
+        } else if self.composed.is_some() {
+            Some("a weighted composition")
+        } else if self.max_repeat.is_some() {
+            Some("a repeat limit")
+        } else if self.length_mode != LengthMode::Graphemes {
+            Some("an exact char or byte length")
         } else {
-            charset.chars().collect()
+            None
         };
+        if let Some(conflict) = conflict {
+            return Err(PwgenError::RequirementsConflict(conflict));
+        }
+        let sampler = ClassSampler::new(&self.charset, requirements, self.length)?;
+        let penalty = sampler.penalty();
+        crate::debug!("class requirements remove {:.2} bits", penalty);
+        self.required = Some((sampler, penalty));
+        Ok(self)
+    }
 
-        Self { length, charset }
+    pub fn builder() -> SecureGeneratorBuilder {
+        SecureGeneratorBuilder::default()
     }
 }
 
 impl PasswordGenerator for SecureGenerator {
     fn generate(&self, rng: &mut dyn RngCore) -> GeneratedPassword {
-        let password: String = (0..self.length)
-            .map(|_| {
-                let idx = rng.gen_range(0..self.charset.len());
-                self.charset[idx]
-            })
-            .collect();
+        if let Some((sampler, penalty)) = &self.required {
+            let mut password = Zeroizing::new(String::with_capacity(self.length));
+            for i in sampler.sample(rng) {
+                password.push_str(&self.charset[i]);
+            }
+            let bits = self.length as f64 * self.bits_per_char() - penalty;
+            return GeneratedPassword::new(password, EntropyInfo::new(bits, "Random"));
+        }
+        if let Some((rules, penalty)) = &self.rules {
+            let mut rejected = 0usize;
+            loop {
+                let mut candidate = self.generate_unconstrained(rng);
+                if rules.accepts(&candidate.value) {
+                    crate::trace!("site rules rejected {} candidate(s)", rejected);
+                    candidate.entropy.bits -= penalty;
+                    return candidate;
+                }
+                rejected += 1;
+            }
+        }
+        self.generate_unconstrained(rng)
+    }
+
+    fn description(&self) -> &'static str {
+        if self.rules.is_some() {
+            "Secure random (site rules)"
+        } else {
+            "Secure random"
+        }
+    }
+
+    fn uniform_alphabet(&self) -> Option<usize> {
+        // Rejection sampling skews character frequencies slightly
+        let single_chars = self.charset.iter().all(|s| s.chars().count() == 1);
+        (single_chars
+            && self.length_mode == LengthMode::Graphemes
+            && self.rules.is_none()
+            && self.required.is_none()
+            && self.max_repeat.is_none()
+            && self.composed.is_none())
+        .then_some(self.charset.len())
+    }
+
+    fn length(&self) -> Option<usize> {
+        (self.length_mode == LengthMode::Graphemes).then_some(self.length)
+    }
 
-        // Entropy = log2(charset_size^length) = length * log2(charset_size)
-        let entropy_bits = (self.length as f64) * (self.charset.len() as f64).log2();
+    fn estimated_entropy(&self) -> Option<f64> {
+        // Char/byte budgets and repeat limits draw from a shrinking alphabet, so the total varies
+        if self.length_mode != LengthMode::Graphemes || self.max_repeat.is_some() {
+            return None;
+        }
+        let penalty = self.rules.as_ref().map_or(0.0, |(_, penalty)| *penalty)
+            + self.required.as_ref().map_or(0.0, |(_, penalty)| *penalty);
+        Some(self.length as f64 * self.bits_per_char() - penalty)
+    }
+}
+
+impl SecureGenerator {
+    fn generate_unconstrained(&self, rng: &mut dyn RngCore) -> GeneratedPassword {
+        let mut password = String::new();
+        let mut entropy_bits = 0.0;
+
+        if let Some(composed) = &self.composed {
+            // Only ASCII classes, so every length mode counts one per character
+            for _ in 0..self.length {
+                let class = &composed.classes[composed.weights.sample(rng)];
+                password.push_str(&class[rng.gen_range(0..class.len())]);
+            }
+            entropy_bits = self.length as f64 * composed.bits_per_char;
+        } else if let Some(max) = self.max_repeat {
+            // Draw from the characters still under the limit (and within the budget)
+            let mut uses = vec![0usize; self.charset.len()];
+            let mut remaining = self.length;
+            while remaining > 0 {
+                let available: Vec<usize> = (0..self.charset.len())
+                    .filter(|&i| {
+                        uses[i] < max && self.length_mode.measure(&self.charset[i]) <= remaining
+                    })
+                    .collect();
+                let idx = available[rng.gen_range(0..available.len())];
+                password.push_str(&self.charset[idx]);
+                uses[idx] += 1;
+                remaining -= self.length_mode.measure(&self.charset[idx]);
+                entropy_bits += (available.len() as f64).log2();
+            }
+        } else if self.length_mode == LengthMode::Graphemes {
+            for _ in 0..self.length {
+                let idx = rng.gen_range(0..self.charset.len());
+                password.push_str(&self.charset[idx]);
+            }
+            // Entropy = log2(charset_size^length) = length * log2(charset_size)
+            entropy_bits = (self.length as f64) * (self.charset.len() as f64).log2();
+        } else {
+            // Fill an exact char/byte budget, only drawing symbols that still fit
+            let mut remaining = self.length;
+            while remaining > 0 {
+                let fitting: Vec<&String> = self
+                    .charset
+                    .iter()
+                    .filter(|s| self.length_mode.measure(s) <= remaining)
+                    .collect();
+                let symbol = fitting[rng.gen_range(0..fitting.len())];
+                password.push_str(symbol);
+                remaining -= self.length_mode.measure(symbol);
+                entropy_bits += (fitting.len() as f64).log2();
+            }
+        }
 
         GeneratedPassword {
             value: Zeroizing::new(password),
             entropy: EntropyInfo::new(entropy_bits, "Random"),
+            mnemonic: None,
         }
     }
-
-    fn description(&self) -> &'static str {
-        "Secure random"
-    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generators::PasswordGeneratorExt;
     use rand::SeedableRng;
     use rand_chacha::ChaCha8Rng;
 
     #[test]
     fn test_secure_length() {
-        let gen = SecureGenerator::new(16, &CharSet::Alphanumeric, false);
+        let gen = SecureGenerator::new(16, &CharSet::Alphanumeric, false).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
         assert_eq!(password.value.len(), 16);
@@ -95,7 +773,7 @@ mod tests {
 
     #[test]
     fn test_secure_alphanumeric_charset() {
-        let gen = SecureGenerator::new(100, &CharSet::Alphanumeric, false);
+        let gen = SecureGenerator::new(100, &CharSet::Alphanumeric, false).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
         assert!(password.value.chars().all(|c| c.is_ascii_alphanumeric()));
@@ -103,7 +781,7 @@ mod tests {
 
     #[test]
     fn test_secure_alpha_only() {
-        let gen = SecureGenerator::new(100, &CharSet::Alpha, false);
+        let gen = SecureGenerator::new(100, &CharSet::Alpha, false).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
         assert!(password.value.chars().all(|c| c.is_ascii_alphabetic()));
@@ -111,28 +789,405 @@ mod tests {
 
     #[test]
     fn test_secure_no_ambiguous() {
-        let gen = SecureGenerator::new(1000, &CharSet::Alphanumeric, true);
+        let gen = SecureGenerator::new(1000, &CharSet::Alphanumeric, true).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
         let ambiguous = ['0', 'O', '1', 'l', 'I'];
         assert!(!password.value.chars().any(|c| ambiguous.contains(&c)));
     }
 
+    #[test]
+    fn test_secure_powershell_safe() {
+        let gen = SecureGenerator::new(1000, &CharSet::PowershellSafe, false).unwrap();
+        assert_eq!(gen.uniform_alphabet(), Some(62 + 17));
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let password = gen.generate(&mut rng);
+        assert!(!password.value.contains(|c| "`$'\"%!^&|<>()".contains(c)));
+    }
+
+    #[test]
+    fn test_secure_base58() {
+        let gen = SecureGenerator::new(22, &CharSet::Base58, false).unwrap();
+        assert_eq!(gen.uniform_alphabet(), Some(58));
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let password = gen.generate(&mut rng);
+        assert!(password
+            .value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() && !"0OIl".contains(c)));
+        // 22 * log2(58) ≈ 128.9 bits
+        assert!((password.entropy.bits - 128.87).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_secure_site_rules() {
+        let rules: PasswordRules =
+            "minlength: 8; maxlength: 12; max-consecutive: 1; required: upper; required: digit; allowed: lower;"
+                .parse()
+                .unwrap();
+        let gen = SecureGenerator::builder()
+            .length(10)
+            .rules(rules.clone())
+            .build()
+            .unwrap();
+        assert_eq!(gen.uniform_alphabet(), None);
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        for password in gen.iter(&mut rng).take(50) {
+            assert_eq!(password.value.len(), 10);
+            assert!(rules.accepts(&password.value));
+            // Below the unconstrained 10 * log2(62) ≈ 59.5 bits
+            assert!(password.entropy.bits < 59.55);
+        }
+
+        assert_eq!(
+            SecureGenerator::from_rules(20, rules.clone()).err(),
+            Some(PwgenError::LengthOutsideRules {
+                length: 20,
+                min: 8,
+                max: 12
+            })
+        );
+        let strict: PasswordRules = "required: upper; required: digit; required: lower;"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            SecureGenerator::from_rules(2, strict).err(),
+            Some(PwgenError::RulesUnsatisfiable { length: 2 })
+        );
+    }
+
     #[test]
     fn test_secure_entropy_alphanumeric() {
-        let gen = SecureGenerator::new(16, &CharSet::Alphanumeric, false);
+        let gen = SecureGenerator::new(16, &CharSet::Alphanumeric, false).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
         // 62 chars: 16 * log2(62) ≈ 95.27 bits
         assert!((password.entropy.bits - 95.27).abs() < 0.1);
     }
 
+    #[test]
+    fn test_secure_builder() {
+        let gen = SecureGenerator::builder()
+            .length(20)
+            .charset(CharSet::Alpha)
+            .exclude_ambiguous(true)
+            .build()
+            .unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let password = gen.generate(&mut rng);
+        assert_eq!(password.value.len(), 20);
+        assert!(password.value.chars().all(|c| c.is_ascii_alphabetic()));
+        assert!(!password.value.contains(['O', 'l', 'I']));
+    }
+
+    #[test]
+    fn test_secure_builder_rejects_zero_length() {
+        let result = SecureGenerator::builder().length(0).build();
+        assert_eq!(result.err(), Some(PwgenError::ZeroLength));
+    }
+
+    #[test]
+    fn test_grapheme_length_with_combining_characters() {
+        // Decomposed accented letters: each is two chars but one grapheme
+        let alphabet = "e\u{301}a\u{301}o\u{308}u\u{300}";
+        let gen = SecureGenerator::from_alphabet(12, alphabet, LengthMode::Graphemes).unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let password = gen.generate(&mut rng);
+
+        assert_eq!(graphemes::count(&password.value), 12);
+        assert_eq!(password.value.chars().count(), 24);
+        assert!((password.entropy.bits - 24.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_alphabet_drops_duplicates_and_dangling_marks() {
+        let gen = SecureGenerator::from_alphabet(4, "\u{301}abca", LengthMode::Graphemes).unwrap();
+        assert_eq!(gen.charset, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_char_and_byte_length_modes() {
+        let alphabet = "xe\u{301}ß";
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+
+        let chars = SecureGenerator::from_alphabet(9, alphabet, LengthMode::Chars).unwrap();
+        for _ in 0..20 {
+            assert_eq!(chars.generate(&mut rng).value.chars().count(), 9);
+        }
+
+        let bytes = SecureGenerator::from_alphabet(9, alphabet, LengthMode::Bytes).unwrap();
+        for _ in 0..20 {
+            assert_eq!(bytes.generate(&mut rng).value.len(), 9);
+        }
+    }
+
+    #[test]
+    fn test_single_symbol_alphabet() {
+        for alphabet in ["a", "aa", "a\u{301}a\u{301}"] {
+            let result = SecureGenerator::from_alphabet(8, alphabet, LengthMode::Graphemes);
+            assert_eq!(result.err(), Some(PwgenError::SingleSymbolAlphabet));
+        }
+        let result = SecureGenerator::from_alphabet(8, "", LengthMode::Graphemes);
+        assert_eq!(result.err(), Some(PwgenError::EmptyCharset));
+        assert!(SecureGenerator::from_alphabet(8, "ab", LengthMode::Graphemes).is_ok());
+    }
+
+    #[test]
+    fn test_byte_mode_needs_single_byte_symbol() {
+        let result = SecureGenerator::from_alphabet(8, "äöü", LengthMode::Bytes);
+        assert_eq!(
+            result.err(),
+            Some(PwgenError::LengthModeUnsatisfiable { unit: "byte" })
+        );
+    }
+
+    #[test]
+    fn test_max_repeat() {
+        let gen = SecureGenerator::builder()
+            .length(6)
+            .alphabet("abc")
+            .max_repeat(2)
+            .build()
+            .unwrap();
+        assert_eq!(gen.uniform_alphabet(), None);
+        assert_eq!(gen.estimated_entropy(), None);
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        for password in gen.iter(&mut rng).take(20) {
+            for c in ['a', 'b', 'c'] {
+                assert_eq!(password.value.matches(c).count(), 2);
+            }
+            // The last draw is forced, so at most 5 * log2(3) bits
+            assert!(password.entropy.bits <= 5.0 * 3f64.log2() + 1e-9);
+            assert!(password.entropy.bits >= 2.0 * 3f64.log2() + 2.0 - 1e-9);
+        }
+
+        assert_eq!(
+            SecureGenerator::builder()
+                .length(7)
+                .alphabet("abc")
+                .max_repeat(2)
+                .build()
+                .err(),
+            Some(PwgenError::RepeatLimitUnsatisfiable {
+                length: 7,
+                max_repeat: 2
+            })
+        );
+    }
+
     #[test]
     fn test_secure_with_symbols_has_special_chars() {
-        let gen = SecureGenerator::new(100, &CharSet::AlphanumericSymbols, false);
+        let gen = SecureGenerator::new(100, &CharSet::AlphanumericSymbols, false).unwrap();
         let mut rng = ChaCha8Rng::seed_from_u64(42);
         let password = gen.generate(&mut rng);
         // With 100 chars from a set including symbols, we should have some symbols
         assert!(password.value.chars().any(|c| !c.is_ascii_alphanumeric()));
     }
+
+    #[test]
+    fn test_secure_symbol_set() {
+        let set: SymbolSet = "custom:!#".parse().unwrap();
+        let gen = SecureGenerator::builder()
+            .length(200)
+            .symbol_set(set)
+            .build()
+            .unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let password = gen.generate(&mut rng);
+        let symbols: Vec<char> = password
+            .value
+            .chars()
+            .filter(|c| !c.is_ascii_alphanumeric())
+            .collect();
+        assert!(!symbols.is_empty());
+        assert!(symbols.iter().all(|c| "!#".contains(*c)));
+        assert_eq!(gen.charset.len(), 64);
+
+        assert!(matches!(
+            SecureGenerator::builder()
+                .charset(CharSet::Alphanumeric)
+                .symbol_set(SymbolSet::Readable)
+                .build(),
+            Err(PwgenError::SymbolSetWithoutSymbols)
+        ));
+    }
+
+    #[test]
+    fn test_secure_exclude_chars() {
+        let gen = SecureGenerator::builder()
+            .length(500)
+            .charset(CharSet::All)
+            .exclude_chars("'\"")
+            .exclude_chars("\\`")
+            .build()
+            .unwrap();
+        assert_eq!(gen.charset.len(), 91);
+        assert_eq!(gen.estimated_entropy(), Some(500.0 * 91f64.log2()));
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let password = gen.generate(&mut rng);
+        assert!(!password.value.contains(['\'', '"', '\\', '`']));
+
+        // An explicit alphabet, with --no-ambiguous applying to it as well
+        let gen = SecureGenerator::builder()
+            .alphabet("abc01O")
+            .exclude_ambiguous(true)
+            .exclude_chars("c")
+            .build()
+            .unwrap();
+        assert_eq!(gen.charset, ["a", "b"]);
+
+        let composed = SecureGenerator::builder()
+            .compose("lower:1,digit:1".parse().unwrap())
+            .exclude_chars("0123456789")
+            .build();
+        assert_eq!(composed.err(), Some(PwgenError::AllCharsExcluded));
+        assert_eq!(
+            SecureGenerator::builder()
+                .alphabet("ab")
+                .exclude_chars("ba")
+                .build()
+                .err(),
+            Some(PwgenError::AllCharsExcluded)
+        );
+        assert_eq!(
+            SecureGenerator::builder()
+                .rules(PasswordRules::default())
+                .exclude_chars("'")
+                .build()
+                .err(),
+            Some(PwgenError::ExcludedCharsWithRules)
+        );
+    }
+
+    #[test]
+    fn test_secure_included_classes() {
+        let gen = SecureGenerator::builder()
+            .length(200)
+            .include(CharClass::Digit)
+            .include(CharClass::Upper)
+            .include(CharClass::Digit)
+            .include_space(true)
+            .build()
+            .unwrap();
+        assert_eq!(gen.charset.len(), 26 + 10 + 1);
+        assert_eq!(gen.charset[0], "A");
+        assert_eq!(gen.charset.last().map(String::as_str), Some(" "));
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let password = gen.generate(&mut rng);
+        assert!(password
+            .value
+            .chars()
+            .all(|c| c == ' ' || c.is_ascii_uppercase() || c.is_ascii_digit()));
+
+        let gen = SecureGenerator::builder()
+            .include(CharClass::Lower)
+            .include(CharClass::Symbol)
+            .symbol_set(SymbolSet::Readable)
+            .exclude_ambiguous(true)
+            .exclude_chars("x")
+            .build()
+            .unwrap();
+        assert_eq!(
+            gen.charset.len(),
+            26 - 2 + SymbolSet::Readable.as_str().len()
+        );
+
+        let err = SecureGenerator::builder()
+            .include(CharClass::Lower)
+            .symbol_set(SymbolSet::Readable)
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(err, PwgenError::SymbolSetWithoutSymbolClass);
+        assert!(err.to_string().contains("--with-symbols"));
+    }
+
+    #[test]
+    fn test_secure_composed() {
+        let composition: Composition = "lower:3,digit:1".parse().unwrap();
+        let gen = SecureGenerator::builder()
+            .length(4000)
+            .compose(composition.clone())
+            .build()
+            .unwrap();
+        // 0.811 bits for the class, then 3/4 * log2(26) + 1/4 * log2(10)
+        let bits_per_char =
+            weighted_entropy(&[3.0, 1.0]) + 0.75 * 26f64.log2() + 0.25 * 10f64.log2();
+        assert_eq!(gen.uniform_alphabet(), None);
+        assert!((gen.estimated_entropy().unwrap() - 4000.0 * bits_per_char).abs() < 1e-6);
+
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let password = gen.generate(&mut rng);
+        assert!(password
+            .value
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()));
+        let digits = password.value.chars().filter(char::is_ascii_digit).count();
+        assert!((800..1200).contains(&digits), "{} digits", digits);
+        assert!((password.entropy.bits - 4000.0 * bits_per_char).abs() < 1e-6);
+
+        assert_eq!(
+            SecureGenerator::builder()
+                .compose(composition)
+                .max_repeat(3)
+                .build()
+                .err(),
+            Some(PwgenError::ComposeWithMaxRepeat)
+        );
+    }
+
+    #[test]
+    fn test_secure_class_requirements() {
+        let requirements = ClassRequirements::new()
+            .at_least(CharClass::Upper, 1)
+            .at_least(CharClass::Lower, 1)
+            .at_least(CharClass::Digit, 3)
+            .at_least(CharClass::Symbol, 2);
+        let gen = SecureGenerator::builder()
+            .length(12)
+            .require(requirements)
+            .build()
+            .unwrap();
+        let unconstrained = 12.0 * 88f64.log2();
+        let bits = gen.estimated_entropy().unwrap();
+        assert!(
+            bits < unconstrained - 2.0 && bits > unconstrained - 5.0,
+            "{}",
+            bits
+        );
+        assert_eq!(gen.uniform_alphabet(), None);
+
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        for password in gen.iter(&mut rng).take(50) {
+            let count = |class| {
+                password
+                    .value
+                    .chars()
+                    .filter(|&c| CharClass::of(c) == class)
+                    .count()
+            };
+            assert_eq!(password.value.len(), 12);
+            assert!(count(CharClass::Upper) >= 1 && count(CharClass::Lower) >= 1);
+            assert!(count(CharClass::Digit) >= 3 && count(CharClass::Symbol) >= 2);
+            assert_eq!(password.entropy.bits, bits);
+        }
+
+        let digits = ClassRequirements::new().at_least(CharClass::Digit, 1);
+        assert_eq!(
+            SecureGenerator::builder()
+                .max_repeat(2)
+                .require(digits)
+                .build()
+                .err(),
+            Some(PwgenError::RequirementsConflict("a repeat limit"))
+        );
+        assert_eq!(
+            SecureGenerator::builder()
+                .charset(CharSet::Alpha)
+                .require(digits)
+                .build()
+                .err(),
+            Some(PwgenError::RequiredClassMissing("digit"))
+        );
+    }
 }
@@ -0,0 +1,94 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Symbols the Markov and secure generators draw from (`--symbol-set`)
+///
+/// Sites that ban particular symbols can still be given passwords with
+/// symbols by naming the allowed ones with `custom:<chars>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SymbolSet {
+    /// Symbols that are easy to read and type: `!@#$%&*-_+`
+    Readable,
+    /// Common punctuation: `!@#$%^&*()-_=+[]{}|;:,.<>?`
+    Full,
+    /// Exactly these symbols, without duplicates
+    Custom(String),
+}
+
+impl SymbolSet {
+    pub const READABLE: &'static str = "!@#$%&*-_+";
+    pub const FULL: &'static str = "!@#$%^&*()-_=+[]{}|;:,.<>?";
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            SymbolSet::Readable => Self::READABLE,
+            SymbolSet::Full => Self::FULL,
+            SymbolSet::Custom(chars) => chars,
+        }
+    }
+
+    pub fn chars(&self) -> Vec<char> {
+        self.as_str().chars().collect()
+    }
+}
+
+impl FromStr for SymbolSet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "readable" => return Ok(SymbolSet::Readable),
+            "full" => return Ok(SymbolSet::Full),
+            _ => {}
+        }
+        let Some(chars) = s.strip_prefix("custom:") else {
+            return Err(format!(
+                "unknown symbol set '{}' (readable, full or custom:<chars>)",
+                s
+            ));
+        };
+        let mut custom = String::new();
+        for c in chars.chars() {
+            if c.is_alphanumeric() || c.is_whitespace() || c.is_control() {
+                return Err(format!("'{}' is not a symbol", c.escape_debug()));
+            }
+            if !custom.contains(c) {
+                custom.push(c);
+            }
+        }
+        if custom.is_empty() {
+            return Err("custom symbol set is empty".to_string());
+        }
+        Ok(SymbolSet::Custom(custom))
+    }
+}
+
+impl fmt::Display for SymbolSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SymbolSet::Readable => f.write_str("readable"),
+            SymbolSet::Full => f.write_str("full"),
+            SymbolSet::Custom(chars) => write!(f, "custom:{}", chars),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_symbol_set() {
+        assert_eq!("readable".parse(), Ok(SymbolSet::Readable));
+        assert_eq!("full".parse::<SymbolSet>().unwrap().chars().len(), 26);
+        assert_eq!(
+            "custom:!#!-".parse(),
+            Ok(SymbolSet::Custom("!#-".to_string()))
+        );
+        assert!("custom:".parse::<SymbolSet>().is_err());
+        assert!("custom:!a".parse::<SymbolSet>().is_err());
+        assert!("custom:! ".parse::<SymbolSet>().is_err());
+        assert!("fancy".parse::<SymbolSet>().is_err());
+        assert_eq!(SymbolSet::Custom("!#".into()).to_string(), "custom:!#");
+    }
+}
@@ -0,0 +1,294 @@
+use rand::Rng;
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+use crate::entropy::EntropyInfo;
+use crate::error::PwgenError;
+use crate::generators::{GeneratedPassword, PasswordGenerator, SymbolSet};
+
+/// One position of a template
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Slot {
+    /// Copied as is
+    Literal(char),
+    /// Drawn uniformly from these characters
+    Class(Vec<char>),
+}
+
+/// Passwords shaped by a mask, e.g. `Cvcvc-####-%%` or `?u?l?l?l?d?d?s`
+///
+/// For sites with structural rules no other generator fits. Two syntaxes can
+/// be mixed in one mask:
+///
+/// - hashcat classes: `?l` lowercase, `?u` uppercase, `?d` digit, `?h` and
+///   `?H` lower- and uppercase hex, `?s` the 33 ASCII symbols including
+///   space, `?a` all of those (95), and `??` for a literal `?`
+/// - friendly placeholders: `c`/`C` a lower- or uppercase consonant, `v`/`V`
+///   a vowel, `#` a digit and `%` a symbol from the symbol set
+///
+/// Any other character is kept as is, and `\` keeps the next one too (`\#`).
+/// Every slot is drawn independently and uniformly, so the entropy is exactly
+/// the sum of log2 of each slot's alphabet size.
+pub struct TemplateGenerator {
+    slots: Vec<Slot>,
+    bits: f64,
+}
+
+/// Builder for [`TemplateGenerator`], validated at [`build`](Self::build) time
+#[derive(Debug, Clone, Default)]
+pub struct TemplateGeneratorBuilder {
+    mask: String,
+    symbol_set: Option<SymbolSet>,
+}
+
+impl TemplateGeneratorBuilder {
+    /// The mask to fill
+    pub fn mask(mut self, mask: impl Into<String>) -> Self {
+        self.mask = mask.into();
+        self
+    }
+
+    /// Symbols for `%` slots (default: readable); `?s` always uses hashcat's set
+    pub fn symbol_set(mut self, symbol_set: SymbolSet) -> Self {
+        self.symbol_set = Some(symbol_set);
+        self
+    }
+
+    pub fn build(self) -> Result<TemplateGenerator, PwgenError> {
+        let symbol_set = self.symbol_set.unwrap_or(SymbolSet::Readable);
+        TemplateGenerator::new(&self.mask, &symbol_set)
+    }
+}
+
+impl TemplateGenerator {
+    const LOWERCASE: &'static str = "abcdefghijklmnopqrstuvwxyz";
+    const UPPERCASE: &'static str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    const DIGITS: &'static str = "0123456789";
+    const VOWELS: &'static str = "aeiou";
+    const CONSONANTS: &'static str = "bcdfghjklmnpqrstvwxyz";
+
+    /// hashcat's `?s`: every printable ASCII symbol, space included
+    const HASHCAT_SYMBOLS: &'static str = " !\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+    pub fn new(mask: &str, symbol_set: &SymbolSet) -> Result<Self, PwgenError> {
+        let slots = parse(mask, symbol_set)?;
+        if !slots.iter().any(|slot| matches!(slot, Slot::Class(_))) {
+            return Err(PwgenError::TemplateWithoutSlots(mask.to_string()));
+        }
+        let bits = slots
+            .iter()
+            .map(|slot| match slot {
+                Slot::Class(chars) => (chars.len() as f64).log2(),
+                Slot::Literal(_) => 0.0,
+            })
+            .sum();
+        Ok(Self { slots, bits })
+    }
+
+    pub fn builder() -> TemplateGeneratorBuilder {
+        TemplateGeneratorBuilder::default()
+    }
+
+    /// Characters in every generated password
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+}
+
+/// Split `mask` into slots, rejecting unknown `?` classes and dangling escapes
+fn parse(mask: &str, symbol_set: &SymbolSet) -> Result<Vec<Slot>, PwgenError> {
+    let class = |sets: &[&str]| Slot::Class(sets.concat().chars().collect());
+    let invalid = |reason: String| PwgenError::InvalidTemplate {
+        template: mask.to_string(),
+        reason,
+    };
+    let mut slots = Vec::with_capacity(mask.len());
+    let mut chars = mask.chars().enumerate();
+    while let Some((position, c)) = chars.next() {
+        let slot = match c {
+            '?' => match chars.next().map(|(_, c)| c) {
+                Some('l') => class(&[TemplateGenerator::LOWERCASE]),
+                Some('u') => class(&[TemplateGenerator::UPPERCASE]),
+                Some('d') => class(&[TemplateGenerator::DIGITS]),
+                Some('h') => class(&[TemplateGenerator::DIGITS, "abcdef"]),
+                Some('H') => class(&[TemplateGenerator::DIGITS, "ABCDEF"]),
+                Some('s') => class(&[TemplateGenerator::HASHCAT_SYMBOLS]),
+                Some('a') => class(&[
+                    TemplateGenerator::LOWERCASE,
+                    TemplateGenerator::UPPERCASE,
+                    TemplateGenerator::DIGITS,
+                    TemplateGenerator::HASHCAT_SYMBOLS,
+                ]),
+                Some('?') => Slot::Literal('?'),
+                Some(other) => {
+                    return Err(invalid(format!(
+                        "unknown class ?{} at position {} (use ?l ?u ?d ?h ?H ?s ?a or ??)",
+                        other.escape_debug(),
+                        position + 1
+                    )))
+                }
+                None => {
+                    return Err(invalid(
+                        "ends with a lone ?; write ?? for a literal one".into(),
+                    ))
+                }
+            },
+            '\\' => match chars.next() {
+                Some((_, escaped)) => Slot::Literal(escaped),
+                None => {
+                    return Err(invalid(
+                        "ends with a lone \\; write \\\\ for a literal one".into(),
+                    ))
+                }
+            },
+            'c' => class(&[TemplateGenerator::CONSONANTS]),
+            'C' => Slot::Class(
+                TemplateGenerator::CONSONANTS
+                    .to_uppercase()
+                    .chars()
+                    .collect(),
+            ),
+            'v' => class(&[TemplateGenerator::VOWELS]),
+            'V' => Slot::Class(TemplateGenerator::VOWELS.to_uppercase().chars().collect()),
+            '#' => class(&[TemplateGenerator::DIGITS]),
+            '%' => Slot::Class(symbol_set.chars()),
+            literal => Slot::Literal(literal),
+        };
+        slots.push(slot);
+    }
+    Ok(slots)
+}
+
+impl PasswordGenerator for TemplateGenerator {
+    fn generate(&self, rng: &mut dyn RngCore) -> GeneratedPassword {
+        let mut value = Zeroizing::new(String::with_capacity(self.slots.len()));
+        for slot in &self.slots {
+            value.push(match slot {
+                Slot::Literal(c) => *c,
+                Slot::Class(chars) => chars[rng.gen_range(0..chars.len())],
+            });
+        }
+
+        GeneratedPassword {
+            value,
+            entropy: EntropyInfo::new(self.bits, "Template"),
+            mnemonic: None,
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        "Template-shaped password"
+    }
+
+    /// Only a mask of one repeated class and no literals, such as `?a?a?a?a`
+    fn uniform_alphabet(&self) -> Option<usize> {
+        let first = match self.slots.first()? {
+            Slot::Class(chars) => chars,
+            Slot::Literal(_) => return None,
+        };
+        self.slots
+            .iter()
+            .all(|slot| matches!(slot, Slot::Class(chars) if chars == first))
+            .then_some(first.len())
+    }
+
+    fn length(&self) -> Option<usize> {
+        Some(self.slots.len())
+    }
+
+    fn estimated_entropy(&self) -> Option<f64> {
+        Some(self.bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_hashcat_mask() {
+        let gen = TemplateGenerator::builder()
+            .mask("?u?l?l?l?d?d?s")
+            .build()
+            .unwrap();
+        let expected = 26f64.log2() * 4.0 + 10f64.log2() * 2.0 + 33f64.log2();
+        assert!((gen.estimated_entropy().unwrap() - expected).abs() < 1e-9);
+        assert_eq!(gen.uniform_alphabet(), None);
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        for _ in 0..50 {
+            let password = gen.generate(&mut rng);
+            let value: Vec<char> = password.value.chars().collect();
+            assert_eq!(value.len(), 7);
+            assert!(value[0].is_ascii_uppercase());
+            assert!(value[1..4].iter().all(char::is_ascii_lowercase));
+            assert!(value[4..6].iter().all(char::is_ascii_digit));
+            assert!(TemplateGenerator::HASHCAT_SYMBOLS.contains(value[6]));
+            assert_eq!(password.entropy.bits, expected);
+        }
+
+        let all = TemplateGenerator::builder()
+            .mask("?a?a?a?a")
+            .build()
+            .unwrap();
+        assert_eq!(all.uniform_alphabet(), Some(95));
+    }
+
+    #[test]
+    fn test_friendly_mask() {
+        let gen = TemplateGenerator::builder()
+            .mask("Cvcvc-####-%%\\#")
+            .build()
+            .unwrap();
+        assert_eq!(gen.len(), 14);
+        let expected = 21f64.log2() * 3.0
+            + 5f64.log2() * 2.0
+            + 10f64.log2() * 4.0
+            + (SymbolSet::READABLE.len() as f64).log2() * 2.0;
+        assert!((gen.estimated_entropy().unwrap() - expected).abs() < 1e-9);
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        for _ in 0..50 {
+            let password = gen.generate(&mut rng);
+            let value: Vec<char> = password.value.chars().collect();
+            assert!(value[0].is_ascii_uppercase() && !"AEIOU".contains(value[0]));
+            assert!("aeiou".contains(value[1]) && "aeiou".contains(value[3]));
+            assert_eq!((value[5], value[10], value[13]), ('-', '-', '#'));
+            assert!(value[6..10].iter().all(char::is_ascii_digit));
+            assert!(value[11..13]
+                .iter()
+                .all(|&c| SymbolSet::READABLE.contains(c)));
+        }
+
+        let custom = TemplateGenerator::builder()
+            .mask("%%")
+            .symbol_set(SymbolSet::Custom("!?".into()))
+            .build()
+            .unwrap();
+        assert_eq!(custom.estimated_entropy(), Some(2.0));
+    }
+
+    #[test]
+    fn test_invalid_masks() {
+        for mask in ["?x", "ab?", "?1?d", "#\\"] {
+            assert!(
+                matches!(
+                    TemplateGenerator::builder().mask(mask).build(),
+                    Err(PwgenError::InvalidTemplate { .. })
+                ),
+                "{}",
+                mask
+            );
+        }
+        for mask in ["", "??-\\c", "xyz"] {
+            assert_eq!(
+                TemplateGenerator::builder().mask(mask).build().err(),
+                Some(PwgenError::TemplateWithoutSlots(mask.to_string()))
+            );
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use rand::RngCore;
+
+use super::{GeneratedPassword, MarkovGenerator, PasswordGenerator};
+use crate::error::PwgenError;
+
+/// Short pronounceable first-login passwords: capitalized, with exactly two digits
+///
+/// Meant to be read out or typed from a letter once and then replaced, so they
+/// trade strength for ease of communication and are flagged as temporary
+/// wherever they are exported.
+pub struct TemporaryGenerator {
+    inner: MarkovGenerator,
+}
+
+/// Builder for [`TemporaryGenerator`], validated at [`build`](Self::build) time
+#[derive(Debug, Clone)]
+pub struct TemporaryGeneratorBuilder {
+    length: usize,
+}
+
+impl Default for TemporaryGeneratorBuilder {
+    fn default() -> Self {
+        Self {
+            length: TemporaryGenerator::DEFAULT_LENGTH,
+        }
+    }
+}
+
+impl TemporaryGeneratorBuilder {
+    pub fn length(mut self, length: usize) -> Self {
+        self.length = length;
+        self
+    }
+
+    pub fn build(self) -> Result<TemporaryGenerator, PwgenError> {
+        let inner = MarkovGenerator::builder()
+            .length(self.length)
+            .digit_count(TemporaryGenerator::DIGITS)
+            .symbols(false)
+            .capitalize(true)
+            .build()?;
+        Ok(TemporaryGenerator { inner })
+    }
+}
+
+impl TemporaryGenerator {
+    pub const DEFAULT_LENGTH: usize = 10;
+    /// Digits inserted into every password
+    pub const DIGITS: usize = 2;
+
+    pub fn builder() -> TemporaryGeneratorBuilder {
+        TemporaryGeneratorBuilder::default()
+    }
+}
+
+impl PasswordGenerator for TemporaryGenerator {
+    fn generate(&self, rng: &mut dyn RngCore) -> GeneratedPassword {
+        self.inner.generate(rng)
+    }
+
+    fn description(&self) -> &'static str {
+        "Temporary (pronounceable, change at first login)"
+    }
+
+    fn estimated_entropy(&self) -> Option<f64> {
+        self.inner.estimated_entropy()
+    }
+
+    fn is_temporary(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_temporary_passwords() {
+        let generator = TemporaryGenerator::builder().build().unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        for _ in 0..20 {
+            let password = generator.generate(&mut rng);
+            assert_eq!(password.value.len(), TemporaryGenerator::DEFAULT_LENGTH);
+            assert_eq!(
+                password.value.chars().filter(char::is_ascii_digit).count(),
+                TemporaryGenerator::DIGITS
+            );
+            assert!(password.value.chars().all(|c| c.is_ascii_alphanumeric()));
+            assert!(password.value.starts_with(|c: char| c.is_ascii_uppercase()));
+        }
+        assert!(generator.is_temporary());
+        assert!(TemporaryGenerator::builder().length(2).build().is_err());
+    }
+}
@@ -0,0 +1,119 @@
+//! Approximate extended grapheme cluster segmentation
+//!
+//! Covers what matters for password alphabets: combining marks, variation
+//! selectors, emoji modifiers, zero-width-joiner sequences and regional
+//! indicator (flag) pairs. It is not a full UAX #29 implementation.
+
+const ZWJ: char = '\u{200D}';
+
+/// Whether `c` extends the preceding cluster instead of starting a new one
+fn is_extend(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'     // combining diacritical marks
+        | '\u{0483}'..='\u{0489}'   // combining Cyrillic
+        | '\u{0591}'..='\u{05BD}'   // Hebrew points
+        | '\u{064B}'..='\u{065F}'   // Arabic harakat
+        | '\u{0900}'..='\u{0903}'   // Devanagari signs
+        | '\u{093A}'..='\u{094F}'
+        | '\u{1AB0}'..='\u{1AFF}'   // combining diacritical marks extended
+        | '\u{1DC0}'..='\u{1DFF}'   // combining diacritical marks supplement
+        | '\u{200C}'                // zero width non-joiner
+        | ZWJ
+        | '\u{20D0}'..='\u{20FF}'   // combining marks for symbols
+        | '\u{FE00}'..='\u{FE0F}'   // variation selectors
+        | '\u{FE20}'..='\u{FE2F}'   // combining half marks
+        | '\u{1F3FB}'..='\u{1F3FF}' // emoji skin tone modifiers
+        | '\u{E0020}'..='\u{E007F}' // tag characters
+    )
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    matches!(c, '\u{1F1E6}'..='\u{1F1FF}')
+}
+
+/// Whether a cluster starts with a character that would merge into whatever precedes it
+pub fn starts_with_extend(s: &str) -> bool {
+    s.chars().next().is_some_and(is_extend)
+}
+
+/// Split `s` into grapheme clusters
+pub fn split(s: &str) -> Vec<&str> {
+    let mut clusters = Vec::new();
+    let mut start = 0;
+    let mut prev: Option<char> = None;
+    let mut regional_run = 0;
+
+    for (i, c) in s.char_indices() {
+        let joins = match prev {
+            None => false,
+            Some(p) => {
+                is_extend(c)
+                    || p == ZWJ
+                    || (is_regional_indicator(c)
+                        && is_regional_indicator(p)
+                        && regional_run % 2 == 1)
+            }
+        };
+
+        if !joins && i > 0 {
+            clusters.push(&s[start..i]);
+            start = i;
+        }
+
+        regional_run = if is_regional_indicator(c) {
+            regional_run + 1
+        } else {
+            0
+        };
+        prev = Some(c);
+    }
+
+    if start < s.len() {
+        clusters.push(&s[start..]);
+    }
+    clusters
+}
+
+/// Number of grapheme clusters in `s`
+pub fn count(s: &str) -> usize {
+    split(s).len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ascii_is_one_cluster_per_char() {
+        assert_eq!(split("ab1!"), vec!["a", "b", "1", "!"]);
+    }
+
+    #[test]
+    fn test_combining_marks_join() {
+        // "e" + COMBINING ACUTE, "a" + COMBINING RING ABOVE + COMBINING ACUTE
+        let s = "e\u{301}a\u{30A}\u{301}x";
+        assert_eq!(split(s), vec!["e\u{301}", "a\u{30A}\u{301}", "x"]);
+        assert_eq!(count(s), 3);
+        assert_eq!(s.chars().count(), 6);
+    }
+
+    #[test]
+    fn test_zwj_and_modifiers() {
+        // woman + skin tone + ZWJ + laptop
+        let s = "\u{1F469}\u{1F3FD}\u{200D}\u{1F4BB}a";
+        assert_eq!(count(s), 2);
+    }
+
+    #[test]
+    fn test_flag_pairs() {
+        // Two flags back to back: DE + FR
+        let s = "\u{1F1E9}\u{1F1EA}\u{1F1EB}\u{1F1F7}";
+        assert_eq!(count(s), 2);
+    }
+
+    #[test]
+    fn test_starts_with_extend() {
+        assert!(starts_with_extend("\u{301}"));
+        assert!(!starts_with_extend("e\u{301}"));
+    }
+}
@@ -0,0 +1,279 @@
+//! Opt-in history of generated passwords for reuse detection (`--history`,
+//! `[history]` config section)
+//!
+//! Each generated password is recorded as an scrypt hash under a random salt
+//! kept in the file's first line, with the time it was generated. The salt
+//! makes entries useless for comparing against other machines' histories or
+//! precomputed tables, and scrypt's cost (also in the header) makes guessing
+//! the passwords behind them slow even for whoever has the file, though a
+//! short PIN still falls to a patient search. The plaintext is never written.
+//! A later password with the same hash was generated before, on this machine.
+
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rand::RngCore;
+
+use crate::audit::expand_home;
+use crate::config::Config;
+use crate::digest::{hex, scrypt};
+
+/// First word of the header line, followed by the hex salt and the scrypt cost
+const HEADER: &str = "pwgen-rs-history-v2";
+
+/// Header of the earlier format, whose HMAC entries were fast to brute-force
+const HEADER_V1: &str = "pwgen-rs-history-v1";
+
+/// Bytes of salt generated for a new history file
+const SALT_BYTES: usize = 32;
+
+/// scrypt parameters of an entry, written as `log_n:r:p`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Cost {
+    log_n: u8,
+    r: usize,
+    p: usize,
+}
+
+impl Cost {
+    /// 32 MiB and about a tenth of a second per password
+    const DEFAULT: Cost = Cost {
+        log_n: 15,
+        r: 8,
+        p: 1,
+    };
+
+    fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.split(':').map(str::parse::<usize>);
+        let (Some(Ok(log_n)), Some(Ok(r)), Some(Ok(p)), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return None;
+        };
+        // Bounds keep a tampered header from asking for absurd amounts of memory
+        ((1..=20).contains(&log_n) && (1..=32).contains(&r) && (1..=16).contains(&p)).then_some(
+            Self {
+                log_n: log_n as u8,
+                r,
+                p,
+            },
+        )
+    }
+}
+
+/// Salted hashes of previously generated passwords
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct History {
+    path: PathBuf,
+    /// Cost of a new file; an existing one keeps the cost in its header
+    cost: Cost,
+}
+
+impl History {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            cost: Cost::DEFAULT,
+        }
+    }
+
+    /// The history configured by `path` (or the `[history]` section), if any
+    ///
+    /// ```toml
+    /// [history]
+    /// path = "~/.local/state/pwgen-rs/history"
+    /// ```
+    pub fn from_config(path: Option<&Path>, config: &Config) -> Option<Self> {
+        match path {
+            Some(path) => Some(Self::new(path)),
+            None => Some(Self::new(expand_home(config.get_str("history.path")?))),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// When each of `values` was generated before, or `None` if it is new
+    ///
+    /// A missing file is an empty history.
+    pub fn lookup(&self, values: &[&str]) -> io::Result<Vec<Option<SystemTime>>> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![None; values.len()]),
+            Err(e) => return Err(e),
+        };
+        let mut lines = text.lines();
+        let Some(header) = lines.next() else {
+            return Ok(vec![None; values.len()]);
+        };
+        let (salt, cost) = parse_header(header)?;
+        let hashes: Vec<String> = values.iter().map(|v| hash(&salt, cost, v)).collect();
+        Ok(sightings(&text, &hashes))
+    }
+
+    /// Append `values`, generated at `time`, returning when each was generated
+    /// before like [`lookup`](Self::lookup)
+    ///
+    /// Creates the file (0600) with a fresh salt if needed. Each value is
+    /// hashed once for both.
+    pub fn record(&self, values: &[&str], time: SystemTime) -> io::Result<Vec<Option<SystemTime>>> {
+        let existing = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        let (salt, cost, header) = match existing.lines().next() {
+            Some(line) => {
+                let (salt, cost) = parse_header(line)?;
+                (salt, cost, None)
+            }
+            None => {
+                let mut salt = vec![0u8; SALT_BYTES];
+                rand::rngs::OsRng.fill_bytes(&mut salt);
+                let cost = self.cost;
+                let header = format!(
+                    "{} {} {}:{}:{}\n",
+                    HEADER,
+                    hex(&salt),
+                    cost.log_n,
+                    cost.r,
+                    cost.p
+                );
+                (salt, cost, Some(header))
+            }
+        };
+        let hashes: Vec<String> = values.iter().map(|v| hash(&salt, cost, v)).collect();
+        let seen = sightings(&existing, &hashes);
+
+        let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let mut text = header.unwrap_or_default();
+        for hash in &hashes {
+            text.push_str(&format!("{} {}\n", secs, hash));
+        }
+
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let mut options = OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options.open(&self.path)?.write_all(text.as_bytes())?;
+        Ok(seen)
+    }
+}
+
+fn hash(salt: &[u8], cost: Cost, value: &str) -> String {
+    let mut out = [0u8; 32];
+    scrypt(value.as_bytes(), salt, cost.log_n, cost.r, cost.p, &mut out);
+    hex(&out)
+}
+
+/// When each of `hashes` was recorded in the history `text`
+fn sightings(text: &str, hashes: &[String]) -> Vec<Option<SystemTime>> {
+    let seen: HashMap<&str, SystemTime> = text
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let (secs, hash) = line.split_once(' ')?;
+            let secs = secs.parse().ok()?;
+            Some((hash, UNIX_EPOCH + Duration::from_secs(secs)))
+        })
+        .collect();
+    hashes
+        .iter()
+        .map(|hash| seen.get(hash.as_str()).copied())
+        .collect()
+}
+
+/// The salt and scrypt cost from a header line
+fn parse_header(line: &str) -> io::Result<(Vec<u8>, Cost)> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "not a pwgen-rs history file");
+    let mut fields = line.split_whitespace();
+    match fields.next() {
+        Some(HEADER) => {}
+        Some(HEADER_V1) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "a v1 history file, whose fast hashes give away short passwords; \
+                 delete it to start a new one",
+            ))
+        }
+        _ => return Err(invalid()),
+    }
+    let (Some(salt), Some(cost), None) = (fields.next(), fields.next(), fields.next()) else {
+        return Err(invalid());
+    };
+    let cost = Cost::parse(cost).ok_or_else(invalid)?;
+    if salt.len() != SALT_BYTES * 2 {
+        return Err(invalid());
+    }
+    let salt = (0..salt.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&salt[i..i + 2], 16).map_err(|_| invalid()))
+        .collect::<io::Result<_>>()?;
+    Ok((salt, cost))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_lookup() {
+        let dir = std::env::temp_dir().join(format!("pwgen-history-{}", std::process::id()));
+        let history = History {
+            cost: Cost {
+                log_n: 4,
+                r: 1,
+                p: 1,
+            },
+            ..History::new(dir.join("history"))
+        };
+        assert_eq!(history.lookup(&["first"]).unwrap(), vec![None]);
+
+        let then = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!(
+            history.record(&["first", "second"], then).unwrap(),
+            vec![None, None]
+        );
+        assert_eq!(
+            history
+                .record(&["third", "first"], SystemTime::now())
+                .unwrap(),
+            vec![None, Some(then)]
+        );
+        assert_eq!(
+            history.lookup(&["second", "fourth"]).unwrap(),
+            vec![Some(then), None]
+        );
+
+        let text = fs::read_to_string(history.path()).unwrap();
+        assert_eq!(text.lines().count(), 5);
+        assert!(text.lines().next().unwrap().ends_with(" 4:1:1"));
+        assert!(!text.contains("first"));
+        assert!(text
+            .lines()
+            .all(|line| !line.ends_with(&hex(&crate::digest::sha256(b"second")))));
+
+        fs::write(history.path(), "something else\n").unwrap();
+        assert!(history.lookup(&["first"]).is_err());
+
+        let v1 = format!("{} {}\n", HEADER_V1, "00".repeat(SALT_BYTES));
+        fs::write(history.path(), v1).unwrap();
+        let err = history.record(&["first"], then).unwrap_err();
+        assert!(err.to_string().contains("delete it"));
+        let huge = format!("{} {} 40:8:1\n", HEADER, "00".repeat(SALT_BYTES));
+        fs::write(history.path(), huge).unwrap();
+        assert!(history.lookup(&["first"]).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,340 @@
+//! Minimal HTTPS GET built on the system `curl`
+//!
+//! Network access is rare and optional in pwgen-rs, so it shells out to
+//! `curl` rather than linking a TLS stack into every build. Every online
+//! feature goes through this module, which makes it the one place that
+//! enforces `--offline`, applies `--proxy`, backs off from rate limits and
+//! caches responses under `~/.cache/pwgen-rs`.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+
+use rand::Rng;
+use thiserror::Error;
+
+/// Seconds before a request is abandoned
+pub const TIMEOUT_SECS: u32 = 10;
+
+/// Largest response body accepted, in bytes
+pub const MAX_BODY_BYTES: u32 = 2 * 1024 * 1024;
+
+/// Longest wait before a retry; a host asking for more is not retried
+pub const MAX_RETRY_WAIT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Error)]
+pub enum HttpError {
+    #[error("could not run curl: {0}")]
+    Spawn(#[from] std::io::Error),
+    #[error("{url}: {message}")]
+    Failed { url: String, message: String },
+    #[error("{url}: HTTP {status}")]
+    Status { url: String, status: u16 },
+    #[error("{url}: rate limited, retry in {}s", .retry_after.as_secs())]
+    RateLimited { url: String, retry_after: Duration },
+    #[error("{url}: not fetched because of --offline")]
+    Offline { url: String },
+}
+
+/// How the process may use the network, fixed once at startup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    /// Refuse every request; cached responses are still served
+    pub offline: bool,
+    /// Proxy URL passed to curl, which otherwise honors `https_proxy` and friends
+    pub proxy: Option<String>,
+    /// Further attempts after a timeout, a connection failure, 429 or 5xx
+    pub retries: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            offline: false,
+            proxy: None,
+            retries: 3,
+        }
+    }
+}
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+/// Set the network settings; only the first call has an effect
+pub fn configure(settings: Settings) {
+    let _ = SETTINGS.set(settings);
+}
+
+fn settings() -> &'static Settings {
+    SETTINGS.get_or_init(Settings::default)
+}
+
+pub fn is_offline() -> bool {
+    settings().offline
+}
+
+/// Base cache directory, honoring `XDG_CACHE_HOME`
+pub fn cache_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))?;
+    Some(base.join("pwgen-rs"))
+}
+
+/// Fetch `url` and return its body; non-2xx responses are errors
+pub fn get(url: &str) -> Result<String, HttpError> {
+    let settings = settings();
+    if settings.offline {
+        return Err(HttpError::Offline {
+            url: url.to_string(),
+        });
+    }
+
+    let mut attempt = 0;
+    loop {
+        let (error, retry) = match fetch_once(url, settings.proxy.as_deref())? {
+            (Ok(body), _) => return Ok(body),
+            (Err(error), retry) => (error, retry),
+        };
+        let wait = match retry {
+            _ if attempt >= settings.retries => return Err(error),
+            Retry::Never => return Err(error),
+            Retry::Backoff => backoff(attempt),
+            Retry::After(wait) => wait,
+        };
+        if wait > MAX_RETRY_WAIT {
+            return Err(HttpError::RateLimited {
+                url: url.to_string(),
+                retry_after: wait,
+            });
+        }
+        crate::debug!("http: {}; retrying in {:.1}s", error, wait.as_secs_f64());
+        std::thread::sleep(wait);
+        attempt += 1;
+    }
+}
+
+/// [`get`] through a cache that is trusted for `ttl`
+///
+/// Returns the body and whether it came from the cache. With `--offline` a
+/// cached body is returned however old it is.
+pub fn get_cached(url: &str, ttl: Duration) -> Result<(String, bool), HttpError> {
+    let path = cache_dir().map(|dir| dir.join("http").join(cache_key(url)));
+    let max_age = if is_offline() { None } else { Some(ttl) };
+    if let Some(body) = path.as_deref().and_then(|p| read_cache(p, url, max_age)) {
+        return Ok((body, true));
+    }
+    let body = get(url)?;
+    if let Some(path) = &path {
+        write_cache(path, url, &body);
+    }
+    Ok((body, false))
+}
+
+/// Whether and when a failed request may be tried again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Retry {
+    Never,
+    Backoff,
+    /// The host's `Retry-After`
+    After(Duration),
+}
+
+/// One curl run, and whether its failure is worth retrying
+///
+/// Only a failure to start curl is an outer error; everything else is left
+/// to the retry loop.
+fn fetch_once(
+    url: &str,
+    proxy: Option<&str>,
+) -> Result<(Result<String, HttpError>, Retry), HttpError> {
+    let mut command = Command::new("curl");
+    command
+        .args([
+            "--silent",
+            "--show-error",
+            "--location",
+            "--dump-header",
+            "-",
+        ])
+        .args(["--proto", "=https", "--proto-redir", "=https"])
+        .args(["--max-time", &TIMEOUT_SECS.to_string()])
+        .args(["--max-filesize", &MAX_BODY_BYTES.to_string()]);
+    if let Some(proxy) = proxy {
+        command.args(["--proxy", proxy]);
+    }
+    let output = command.arg("--").arg(url).output()?;
+    if !output.status.success() {
+        let error = HttpError::Failed {
+            url: url.to_string(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        };
+        // Connection refused or reset, timeouts and TLS handshake failures
+        let retry = match output.status.code() {
+            Some(7 | 28 | 35 | 52 | 55 | 56) => Retry::Backoff,
+            _ => Retry::Never,
+        };
+        return Ok((Err(error), retry));
+    }
+
+    let (headers, body) = split_response(&output.stdout);
+    let status = status_code(headers).unwrap_or(0);
+    if (200..300).contains(&status) {
+        return Ok((Ok(String::from_utf8_lossy(body).into_owned()), Retry::Never));
+    }
+    let error = HttpError::Status {
+        url: url.to_string(),
+        status,
+    };
+    Ok((Err(error), retry_for(status, headers)))
+}
+
+/// 408, 429 and 5xx are retried, after `Retry-After` when the host sends one
+fn retry_for(status: u16, headers: &str) -> Retry {
+    match (status, retry_after(headers)) {
+        (408 | 429 | 500..=599, Some(wait)) => Retry::After(wait),
+        (408 | 429 | 500..=599, None) => Retry::Backoff,
+        _ => Retry::Never,
+    }
+}
+
+/// Exponential backoff with jitter: about 0.5s, 1s, 2s, ...
+fn backoff(attempt: u32) -> Duration {
+    let base = 500u64 << attempt.min(6);
+    Duration::from_millis(base + rand::thread_rng().gen_range(0..base / 2))
+}
+
+/// Split curl's `--dump-header -` output into the last header block and the body
+///
+/// Redirects and `100 Continue` each add a header block before the final one.
+fn split_response(output: &[u8]) -> (&str, &[u8]) {
+    let mut rest = output;
+    let mut headers = "";
+    while rest.starts_with(b"HTTP/") {
+        let Some(end) = rest.windows(4).position(|w| w == b"\r\n\r\n") else {
+            break;
+        };
+        headers = std::str::from_utf8(&rest[..end]).unwrap_or("");
+        rest = &rest[end + 4..];
+    }
+    (headers, rest)
+}
+
+fn status_code(headers: &str) -> Option<u16> {
+    headers
+        .lines()
+        .next()?
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()
+}
+
+/// `Retry-After` in seconds; the HTTP-date form falls back to backoff
+fn retry_after(headers: &str) -> Option<Duration> {
+    headers.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        if !name.trim().eq_ignore_ascii_case("retry-after") {
+            return None;
+        }
+        value.trim().parse().ok().map(Duration::from_secs)
+    })
+}
+
+/// File name for a cached URL (FNV-1a; the URL is stored inside to rule out collisions)
+fn cache_key(url: &str) -> String {
+    let hash = url.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// The body cached at `path` for `key`, if younger than `max_age` (or any age for `None`)
+///
+/// The key (the URL, for responses) is the entry's first line, so a hash
+/// collision or a file from another format reads as a miss.
+pub(crate) fn read_cache(path: &Path, key: &str, max_age: Option<Duration>) -> Option<String> {
+    if let Some(max_age) = max_age {
+        let age = fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())?;
+        if age > max_age {
+            return None;
+        }
+    }
+    let text = fs::read_to_string(path).ok()?;
+    let (cached_key, body) = text.split_once('\n')?;
+    (cached_key == key).then(|| body.to_string())
+}
+
+/// Cache `body` for `key` at `path`, readable only by the user
+///
+/// Best effort: a failed cache write only costs a refetch next time.
+pub(crate) fn write_cache(path: &Path, key: &str, body: &str) {
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    // A new file, so entries written before with wider permissions don't keep them
+    let _ = fs::remove_file(path);
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let _ = options
+        .open(path)
+        .and_then(|mut file| write!(file, "{}\n{}", key, body));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_response() {
+        let output = b"HTTP/1.1 301 Moved\r\nLocation: /x\r\n\r\n\
+            HTTP/2 429\r\nretry-after: 7\r\n\r\nslow down";
+        let (headers, body) = split_response(output);
+        assert_eq!(status_code(headers), Some(429));
+        assert_eq!(
+            retry_for(429, headers),
+            Retry::After(Duration::from_secs(7))
+        );
+        assert_eq!(body, b"slow down");
+
+        let (headers, body) = split_response(b"HTTP/2 200\r\n\r\n");
+        assert_eq!(status_code(headers), Some(200));
+        assert!(body.is_empty());
+    }
+
+    #[test]
+    fn test_retry_policy() {
+        assert_eq!(retry_for(503, "HTTP/2 503"), Retry::Backoff);
+        assert_eq!(retry_for(404, "HTTP/2 404\r\nRetry-After: 5"), Retry::Never);
+        assert!(backoff(0) < backoff(3));
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let url = "https://example.com/range/ABCDE";
+        let path = std::env::temp_dir().join(format!("pwgen-http-{}", cache_key(url)));
+        write_cache(&path, url, "line one\nline two");
+        assert_eq!(
+            read_cache(&path, url, Some(Duration::from_secs(60))).as_deref(),
+            Some("line one\nline two")
+        );
+        assert_eq!(read_cache(&path, "https://example.com/other", None), None);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        fs::remove_file(&path).unwrap();
+    }
+}
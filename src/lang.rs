@@ -0,0 +1,243 @@
+//! Language selection shared by wordlists and display text
+
+use crate::entropy::StrengthLevel;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    German,
+    French,
+    Spanish,
+    Italian,
+}
+
+/// Localized display text
+pub struct UiStrings {
+    pub password: &'static str,
+    pub strength: &'static str,
+    pub bits: &'static str,
+    /// Label for a passphrase's story mnemonic
+    pub story: &'static str,
+    /// Label for a transcription fingerprint
+    pub fingerprint: &'static str,
+    /// Label for the phone keys that enter a keypad code
+    pub keypad: &'static str,
+    /// Label for the time-to-crack estimate
+    pub crack_time: &'static str,
+    /// Label for the line comparing strength estimators
+    pub estimates: &'static str,
+    /// Label for the per-class character counts shown with --verbose
+    pub classes: &'static str,
+    /// Header template with `{count}` and `{description}` placeholders
+    pub header: &'static str,
+    /// Strength labels from Very Weak to Very Strong
+    pub levels: [&'static str; 5],
+}
+
+impl UiStrings {
+    pub fn header(&self, count: usize, description: &str) -> String {
+        self.header
+            .replace("{count}", &count.to_string())
+            .replace("{description}", description)
+    }
+
+    pub fn strength_label(&self, level: StrengthLevel) -> &'static str {
+        let idx = match level {
+            StrengthLevel::VeryWeak => 0,
+            StrengthLevel::Weak => 1,
+            StrengthLevel::Moderate => 2,
+            StrengthLevel::Strong => 3,
+            StrengthLevel::VeryStrong => 4,
+        };
+        self.levels[idx]
+    }
+}
+
+const ENGLISH: UiStrings = UiStrings {
+    password: "Password:",
+    strength: "Strength:",
+    bits: "bits",
+    story: "Story:",
+    fingerprint: "Fingerprint:",
+    keypad: "Keys:",
+    crack_time: "Time to crack:",
+    estimates: "Estimates:",
+    classes: "Classes:",
+    header: "Generating {count} {description} password(s):",
+    levels: ["Very Weak", "Weak", "Moderate", "Strong", "Very Strong"],
+};
+
+const GERMAN: UiStrings = UiStrings {
+    password: "Passwort:",
+    strength: "Stärke:",
+    bits: "Bit",
+    story: "Merksatz:",
+    fingerprint: "Prüfwörter:",
+    keypad: "Tasten:",
+    crack_time: "Knackzeit:",
+    estimates: "Schätzungen:",
+    classes: "Zeichenklassen:",
+    header: "Erzeuge {count} Passwort/Passwörter ({description}):",
+    levels: ["Sehr schwach", "Schwach", "Mittel", "Stark", "Sehr stark"],
+};
+
+const FRENCH: UiStrings = UiStrings {
+    password: "Mot de passe :",
+    strength: "Robustesse :",
+    bits: "bits",
+    story: "Histoire :",
+    fingerprint: "Empreinte :",
+    keypad: "Touches :",
+    crack_time: "Temps de cassage :",
+    estimates: "Estimations :",
+    classes: "Classes :",
+    header: "Génération de {count} mot(s) de passe ({description}) :",
+    levels: ["Très faible", "Faible", "Moyen", "Fort", "Très fort"],
+};
+
+const SPANISH: UiStrings = UiStrings {
+    password: "Contraseña:",
+    strength: "Fortaleza:",
+    bits: "bits",
+    story: "Historia:",
+    fingerprint: "Huella:",
+    keypad: "Teclas:",
+    crack_time: "Tiempo de descifrado:",
+    estimates: "Estimaciones:",
+    classes: "Clases:",
+    header: "Generando {count} contraseña(s) ({description}):",
+    levels: ["Muy débil", "Débil", "Moderada", "Fuerte", "Muy fuerte"],
+};
+
+const ITALIAN: UiStrings = UiStrings {
+    password: "Password:",
+    strength: "Robustezza:",
+    bits: "bit",
+    story: "Storia:",
+    fingerprint: "Impronta:",
+    keypad: "Tasti:",
+    crack_time: "Tempo di decifrazione:",
+    estimates: "Stime:",
+    classes: "Classi:",
+    header: "Generazione di {count} password ({description}):",
+    levels: ["Molto debole", "Debole", "Moderata", "Forte", "Molto forte"],
+};
+
+impl Language {
+    /// Languages with an embedded diceware wordlist
+    const WORDLIST_LANGUAGES: &'static [Language] = &[Language::English];
+
+    /// Parse a language code such as `de`, `de-AT` or a locale like `de_DE.UTF-8`
+    pub fn from_code(code: &str) -> Option<Self> {
+        let primary = code
+            .split(['_', '-', '.', '@'])
+            .next()?
+            .to_ascii_lowercase();
+        match primary.as_str() {
+            "en" => Some(Language::English),
+            "de" => Some(Language::German),
+            "fr" => Some(Language::French),
+            "es" => Some(Language::Spanish),
+            "it" => Some(Language::Italian),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::German => "de",
+            Language::French => "fr",
+            Language::Spanish => "es",
+            Language::Italian => "it",
+        }
+    }
+
+    /// Resolve the language from `--lang`, then the locale environment, defaulting to English
+    pub fn resolve(requested: Option<&str>) -> Self {
+        Self::resolve_with(requested, |name| std::env::var(name).ok())
+    }
+
+    fn resolve_with(requested: Option<&str>, env: impl Fn(&str) -> Option<String>) -> Self {
+        if let Some(code) = requested {
+            return Self::from_code(code).unwrap_or(Language::English);
+        }
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| env(name))
+            .find(|value| !value.is_empty())
+            .and_then(|value| Self::from_code(&value))
+            .unwrap_or(Language::English)
+    }
+
+    /// Language of the wordlist to use, falling back to English when none is embedded
+    pub fn wordlist_language(&self) -> Language {
+        if Self::WORDLIST_LANGUAGES.contains(self) {
+            *self
+        } else {
+            Language::English
+        }
+    }
+
+    pub fn strings(&self) -> &'static UiStrings {
+        match self {
+            Language::English => &ENGLISH,
+            Language::German => &GERMAN,
+            Language::French => &FRENCH,
+            Language::Spanish => &SPANISH,
+            Language::Italian => &ITALIAN,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_code_variants() {
+        assert_eq!(Language::from_code("de"), Some(Language::German));
+        assert_eq!(Language::from_code("fr-CA"), Some(Language::French));
+        assert_eq!(Language::from_code("es_ES.UTF-8"), Some(Language::Spanish));
+        assert_eq!(Language::from_code("C"), None);
+    }
+
+    #[test]
+    fn test_resolve_precedence() {
+        let env = |name: &str| match name {
+            "LANG" => Some("it_IT.UTF-8".to_string()),
+            _ => None,
+        };
+        assert_eq!(Language::resolve_with(Some("de"), env), Language::German);
+        assert_eq!(Language::resolve_with(None, env), Language::Italian);
+        assert_eq!(Language::resolve_with(Some("xx"), env), Language::English);
+        assert_eq!(Language::resolve_with(None, |_| None), Language::English);
+    }
+
+    #[test]
+    fn test_wordlist_falls_back_to_english() {
+        assert_eq!(Language::English.wordlist_language(), Language::English);
+        assert_eq!(Language::German.wordlist_language(), Language::English);
+    }
+
+    #[test]
+    fn test_header_template() {
+        let header = Language::English.strings().header(3, "Secure random");
+        assert_eq!(header, "Generating 3 Secure random password(s):");
+    }
+
+    #[test]
+    fn test_strength_labels_localized() {
+        let strings = Language::German.strings();
+        assert_eq!(
+            strings.strength_label(StrengthLevel::VeryStrong),
+            "Sehr stark"
+        );
+        assert_eq!(
+            Language::English
+                .strings()
+                .strength_label(StrengthLevel::Weak),
+            StrengthLevel::Weak.label()
+        );
+    }
+}
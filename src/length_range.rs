@@ -0,0 +1,114 @@
+//! Length limits for any generator (`--min-length`, `--max-length`)
+//!
+//! Candidates outside the range are drawn again, so one mechanism enforces a
+//! site's length cap whatever the generator. Rejecting candidates removes them
+//! from the keyspace; the entropy this costs is estimated from the acceptance
+//! rate over a calibration sample and subtracted from every accepted password.
+//! The estimate is exact for generators whose outputs are equally likely.
+
+use rand::RngCore;
+
+use crate::error::PwgenError;
+use crate::generators::{PasswordGenerator, PasswordGeneratorExt};
+use crate::graphemes;
+
+/// Allowed length of a password in characters (grapheme clusters)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LengthRange {
+    pub min: Option<usize>,
+    pub max: Option<usize>,
+}
+
+impl LengthRange {
+    /// Candidates drawn to estimate how many fit
+    pub const CALIBRATION_SAMPLES: usize = 256;
+
+    pub fn new(min: Option<usize>, max: Option<usize>) -> Result<Self, PwgenError> {
+        match (min, max) {
+            (_, Some(0)) => Err(PwgenError::ZeroLength),
+            (Some(min), Some(max)) if min > max => Err(PwgenError::InvalidLengthRange { min, max }),
+            _ => Ok(Self { min, max }),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.is_none() && self.max.is_none()
+    }
+
+    pub fn accepts(&self, value: &str) -> bool {
+        let len = graphemes::count(value);
+        self.min.is_none_or(|min| len >= min) && self.max.is_none_or(|max| len <= max)
+    }
+
+    /// Human-readable form, e.g. "between 8 and 16 characters"
+    pub fn describe(&self) -> String {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) if min == max => format!("exactly {} characters", min),
+            (Some(min), Some(max)) => format!("between {} and {} characters", min, max),
+            (Some(min), None) => format!("at least {} characters", min),
+            (None, Some(max)) => format!("at most {} characters", max),
+            (None, None) => "any length".to_string(),
+        }
+    }
+
+    /// Bits lost by rejecting out-of-range candidates, or None if none of the sample fit
+    pub fn entropy_cost(
+        &self,
+        generator: &dyn PasswordGenerator,
+        rng: &mut dyn RngCore,
+        samples: usize,
+    ) -> Option<f64> {
+        if self.is_empty() {
+            return Some(0.0);
+        }
+        let accepted = generator
+            .iter(rng)
+            .take(samples)
+            .filter(|p| self.accepts(&p.value))
+            .count();
+        crate::debug!(
+            "length: {} of {} calibration candidates are {}",
+            accepted,
+            samples,
+            self.describe()
+        );
+        (accepted > 0).then(|| (samples as f64 / accepted as f64).log2())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::MarkovGenerator;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_accepts_and_describe() {
+        let range = LengthRange::new(Some(3), Some(5)).unwrap();
+        assert!(!range.accepts("ab"));
+        assert!(range.accepts("abc"));
+        assert!(range.accepts("äöüßé"));
+        assert!(!range.accepts("abcdef"));
+        assert_eq!(range.describe(), "between 3 and 5 characters");
+        assert_eq!(
+            LengthRange::new(None, Some(8)).unwrap().describe(),
+            "at most 8 characters"
+        );
+        assert_eq!(
+            LengthRange::new(Some(9), Some(8)),
+            Err(PwgenError::InvalidLengthRange { min: 9, max: 8 })
+        );
+    }
+
+    #[test]
+    fn test_entropy_cost() {
+        let generator = MarkovGenerator::builder().build().unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let open = LengthRange::default();
+        assert_eq!(open.entropy_cost(&generator, &mut rng, 16), Some(0.0));
+
+        let impossible = LengthRange::new(None, Some(1)).unwrap();
+        assert_eq!(impossible.entropy_cost(&generator, &mut rng, 16), None);
+    }
+}
@@ -1,4 +1,61 @@
+//! Password generators, entropy estimates and wordlists behind the `pwgen-x` CLI
+//!
+//! Start from [`PasswordSpec`] and import [`prelude`] for the common types;
+//! the `pwgen-x` binary is built on the same API.
+
+pub mod audit;
+pub mod avoid;
+#[cfg(feature = "breach")]
+pub mod breach;
+pub mod capture;
+pub mod check;
 pub mod cli;
+pub mod clipboard;
+pub mod compare;
+pub mod compat;
+pub mod config;
+pub mod crack_time;
+pub mod data;
+pub mod decoy;
+pub mod digest;
+pub mod diversity;
+pub mod dry_run;
 pub mod entropy;
+pub mod error;
+pub mod estimators;
+pub mod exit;
+pub mod fingerprint;
 pub mod generators;
+pub mod graphemes;
+pub mod history;
+pub mod http;
+pub mod lang;
+pub mod length_range;
+pub mod metadata;
+pub mod mnemonic;
+pub mod native_host;
 pub mod output;
+pub mod prelude;
+pub mod prompt;
+pub mod provision;
+pub mod qr;
+pub mod redacted;
+pub mod rejections;
+pub mod remind;
+pub mod rng;
+pub mod rules;
+pub mod scrub;
+pub mod shamir;
+pub mod spec;
+pub mod threshold;
+pub mod training;
+pub mod validation;
+pub mod verbose;
+pub mod verify;
+pub mod version;
+pub mod wizard;
+pub mod wordlist;
+
+pub use error::PwgenError;
+pub use generators::{GeneratedPassword, PasswordGenerator, PasswordGeneratorExt};
+pub use spec::PasswordSpec;
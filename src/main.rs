@@ -1,81 +1,1576 @@
-mod cli;
-mod entropy;
-mod generators;
-mod output;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{BufRead, BufReader, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{anyhow, bail, Context, Result};
+use console::{style, Term};
+use zeroize::Zeroizing;
 
-use anyhow::Result;
 use clap::Parser;
-use console::Term;
-use rand::thread_rng;
+use pwgen_x::audit::{AuditLog, AuditRecord, Destination};
+use pwgen_x::avoid::AvoidList;
+#[cfg(feature = "breach")]
+use pwgen_x::breach;
+use pwgen_x::capture;
+use pwgen_x::check::CheckReport;
+use pwgen_x::cli::{
+    negatable, Cli, ColorChoice, Command, DataCommand, DecoyCommand, GeneratorCommand,
+    OutputFormat, ProfileCommand, Separator, WordlistCommand,
+};
+use pwgen_x::clipboard;
+use pwgen_x::compare::{self, Comparison};
+use pwgen_x::compat::{pwgen::PwgenOptions, xkcdpass::XkcdpassOptions, Compat};
+use pwgen_x::config::{Config, Value as ConfigValue};
+use pwgen_x::data;
+use pwgen_x::decoy::DecoyKey;
+use pwgen_x::diversity::Diversity;
+use pwgen_x::dry_run::DryRunReport;
+use pwgen_x::entropy::{EntropyInfo, Scale};
+use pwgen_x::exit::{self, ErrorFormat, Failure};
+use pwgen_x::fingerprint;
+use pwgen_x::generators::{
+    AppleGenerator, CharClass, ClassRequirements, GroupedGenerator, KeypadGenerator,
+    MarkovGenerator, MarkovModel, PasscodeGenerator, PassphraseGenerator, PasswordGenerator,
+    PasswordGeneratorExt, PinGenerator, SecureGenerator, TemplateGenerator, TemporaryGenerator,
+    MAX_ATTEMPTS_PER_PASSWORD,
+};
+use pwgen_x::history::History;
+use pwgen_x::http;
+use pwgen_x::lang::Language;
+use pwgen_x::length_range::LengthRange;
+use pwgen_x::metadata::{format_utc, CreationMetadata};
+use pwgen_x::native_host;
+use pwgen_x::output::files::FileWriter;
+use pwgen_x::output::share::Sharer;
+use pwgen_x::output::PasswordDisplay;
+use pwgen_x::prompt::{self, Secret};
+use pwgen_x::provision::{self, Account};
+use pwgen_x::qr::QrContent;
+use pwgen_x::rejections::Rejections;
+use pwgen_x::remind::{self, Reminder};
+use pwgen_x::rng::PwgenRng;
+use pwgen_x::rules::{self, online::RulesSource};
+use pwgen_x::scrub;
+use pwgen_x::shamir::{self, Share};
+use pwgen_x::threshold::{check_danger, EntropyFloor, Threshold};
+use pwgen_x::training::{self, Stop, TrainingLimits};
+use pwgen_x::validation::Limits;
+use pwgen_x::verbose;
+use pwgen_x::verify;
+use pwgen_x::version;
+use pwgen_x::wizard;
+use pwgen_x::wordlist::{self, Source};
+
+/// Policy recorded in the audit log for `temp` passwords
+const TEMPORARY_POLICY: &str = "temporary";
+const TEMPORARY_NOTE: &str = "temporary password(s): must be changed at first login";
+
+/// Generator for the visible part of a decoy
+///
+/// Alphanumeric like the tag, so nothing marks where the tag starts; the tag
+/// brings it to 16 characters.
+const DECOY_POLICY: &str = "secure 10 -S alphanumeric";
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(code) => code,
+        Err(err) => {
+            match exit::error_format() {
+                ErrorFormat::Text => eprintln!("error: {:#}", err),
+                ErrorFormat::Json => eprintln!("{}", exit::to_json(&err)),
+            }
+            ExitCode::from(Failure::of(&err).code())
+        }
+    }
+}
+
+fn run() -> Result<ExitCode> {
+    let args: Vec<_> = std::env::args_os().collect();
+    match Compat::detect(&args) {
+        Some((Compat::Pwgen, args)) => return run_pwgen_compat(args),
+        Some((Compat::Xkcdpass, args)) => return run_xkcdpass_compat(args),
+        None => {}
+    }
+
+    let config = Config::load()?;
+    let cli = Cli::parse_with_config(&config);
+    verbose::set_verbosity(cli.verbose);
+    exit::set_error_format(cli.error_format);
+    http::configure(network_settings(&cli, &config));
+    if cli.version {
+        match cli.format {
+            OutputFormat::Text | OutputFormat::Printable => println!("{}", version::text()),
+            OutputFormat::Json => println!("{:#}", version::to_json()),
+            OutputFormat::Jsonl => println!("{}", version::to_json()),
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+    let Some(command) = &cli.command else {
+        unreachable!("parse_with_config always resolves a subcommand or --version");
+    };
 
-use cli::{Cli, Command};
-use generators::{MarkovGenerator, PassphraseGenerator, PasswordGenerator, PinGenerator, SecureGenerator};
-use output::PasswordDisplay;
+    let limits = Limits::from_config(&config);
+    if let Command::NativeHost { .. } = command {
+        // stdout belongs to the protocol; nothing else may be printed
+        native_host::run(
+            &mut std::io::stdin().lock(),
+            &mut std::io::stdout().lock(),
+            &limits,
+            &mut PwgenRng::thread(),
+        )?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    if let Command::ClipboardClear { after } = command {
+        // Detached from the terminal, with nowhere to report failures
+        let _ = clipboard::clear_if_unchanged(
+            &mut std::io::stdin().lock(),
+            Duration::from_secs(*after),
+        );
+        return Ok(ExitCode::SUCCESS);
+    }
+    limits.check_count(cli.count)?;
+    let file_writer = cli
+        .output_dir
+        .as_ref()
+        .map(|dir| FileWriter::new(dir, &cli.filename_template))
+        .transpose()?;
+    let sharer = (!cli.share_with.is_empty())
+        .then(|| Sharer::new(&cli.share_dir, &cli.share_with))
+        .transpose()?;
+    let to_files = file_writer.is_some() || sharer.is_some();
+    let remind_file = match (cli.remind, &cli.remind_file, &file_writer) {
+        (None, _, _) => None,
+        (Some(_), Some(path), _) => Some(path.clone()),
+        (Some(_), None, Some(writer)) => Some(writer.dir().join(remind::DEFAULT_CALENDAR)),
+        (Some(_), None, None) => bail!("--remind needs --remind-file or --output-dir"),
+    };
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let length_range = LengthRange::new(cli.min_length, cli.max_length)?;
+    let threshold = Threshold {
+        min_bits: cli.min_bits,
+        min_strength: cli.min_strength,
+        attacker: cli.attacker,
+    };
+    let avoid = AvoidList::load(&cli.avoid, cli.avoid_file.as_deref())?;
+    if cli.check_only && threshold.is_empty() {
+        bail!("--check-only needs a requirement: pass --min-bits or --min-strength");
+    }
 
     // Auto-enable quiet mode when stdout is not a TTY (e.g., piped to pbcopy)
     let is_tty = Term::stdout().is_term();
-    let quiet = cli.quiet || !is_tty;
+    // With --split-streams the decoration goes to stderr, so that is the terminal that matters
+    let display_term = if cli.split_streams && !cli.quiet {
+        Term::stderr()
+    } else {
+        Term::stdout()
+    };
+    let quiet = cli.quiet || !display_term.is_term();
 
     // Determine color support
-    let use_colors = is_tty && !cli.no_color;
-    let display = PasswordDisplay::new(use_colors, quiet);
+    let color = if cli.no_color {
+        ColorChoice::Never
+    } else {
+        cli.color
+    };
+    let use_colors = color.enabled(display_term.is_term(), |name| std::env::var_os(name));
+    let language = Language::resolve(cli.lang.as_deref());
+
+    let mut display = PasswordDisplay::new(use_colors, quiet)
+        .with_language(language)
+        .with_accessible(cli.accessible)
+        .with_oneline(cli.oneline)
+        .with_fingerprints(cli.fingerprint)
+        .with_scale(display_scale(&cli, &config)?)
+        .with_attacker(cli.attacker)
+        .with_crack_time(cli.crack_time)
+        .with_escape(cli.escape)
+        .with_split_streams(cli.split_streams)
+        .with_record_separator(cli.record_sep)
+        .with_estimators(&cli.estimator)
+        .with_class_counts(cli.verbose > 0)
+        .with_print_layout(matches!(
+            command,
+            Command::Generate(GeneratorCommand::Phrase {
+                print_layout: true,
+                ..
+            })
+        ))
+        .with_qr(match (&cli.qr_wifi, cli.qr) {
+            (Some(ssid), _) => Some(QrContent::Wifi(ssid.clone())),
+            (None, true) => Some(QrContent::Password),
+            (None, false) => None,
+        });
+    if display_term.is_term() {
+        let (_, width) = display_term.size();
+        display = display.with_width(width as usize);
+        if !cli.no_grid {
+            display = display.with_grid(width as usize);
+        }
+    }
+
+    let (generator, target) = match command {
+        Command::Generate(target) => build_generator(target, &cli, &limits, language)?,
+        Command::NativeHost { .. } | Command::ClipboardClear { .. } => {
+            unreachable!("handled before any output")
+        }
+        Command::Wizard => {
+            if !Term::stderr().is_term() {
+                bail!("the wizard needs an interactive terminal");
+            }
+            let answers = wizard::run(&mut std::io::stdin().lock(), &mut std::io::stderr())?;
+            let args = answers.command_line();
+            eprintln!("Next time, run: pwgen-x {}\n", args.join(" "));
+            let parsed = Cli::try_parse_from(std::iter::once("pwgen-x".to_string()).chain(args))?;
+            let Some(Command::Generate(target)) = parsed.command else {
+                unreachable!("wizard answers always map to a generator command");
+            };
+            build_generator(&target, &cli, &limits, language)?
+        }
+        Command::Fingerprint => {
+            let value = read_password(&cli)?;
+            println!("{}", fingerprint::fingerprint(&value));
+            return Ok(ExitCode::SUCCESS);
+        }
+        Command::Compare { target } => {
+            warn_if_captured(is_tty, &cli);
+            let comparison = Comparison::new(*target, &mut PwgenRng::thread());
+            match cli.format {
+                OutputFormat::Text | OutputFormat::Printable => print!("{}", comparison),
+                OutputFormat::Json => println!("{:#}", comparison.to_json()),
+                OutputFormat::Jsonl => println!("{}", comparison.to_json()),
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+        Command::Check => {
+            let value = read_password(&cli)?;
+            if let Some(history) = History::from_config(cli.history.as_deref(), &config) {
+                let context = || format!("reading password history {}", history.path().display());
+                let seen = history
+                    .lookup(&[&value])
+                    .with_context(|| Failure::Storage.because(context()))?;
+                if let [Some(time)] = seen[..] {
+                    if !cli.quiet {
+                        eprintln!(
+                            "warning: this password was generated on {}",
+                            format_utc(time)
+                        );
+                    }
+                }
+            }
+            let mut report = CheckReport::new(&value, cli.attacker);
+            if cli.check_breach {
+                report.breaches = breach_counts(&[&value])?.first().copied();
+            }
+            match cli.format {
+                OutputFormat::Text | OutputFormat::Printable => println!("{}", report),
+                OutputFormat::Json => println!("{:#}", report.to_json()),
+                OutputFormat::Jsonl => println!("{}", report.to_json()),
+            }
+            if report.breaches.is_some_and(|n| n > 0) {
+                return Ok(ExitCode::FAILURE);
+            }
+            if threshold.accepts(&report.entropy()) {
+                return Ok(ExitCode::SUCCESS);
+            }
+            if !cli.quiet {
+                eprintln!("note: the password does not meet {}", threshold.describe());
+            }
+            return Ok(ExitCode::from(Failure::TooWeak.code()));
+        }
+        Command::Decoy { key_file, action } => {
+            let Some(key_file) = key_file else {
+                bail!("decoy needs a key: pass --key-file or set PWGEN_RS_DECOY_KEY");
+            };
+            let key = DecoyKey::load(key_file)?;
+            let target = match action {
+                Some(DecoyCommand::Verify { value }) => {
+                    let value = match value {
+                        Some(value) => Zeroizing::new(value.clone()),
+                        None => read_password(&cli)?,
+                    };
+                    let ours = key.is_decoy(&value);
+                    if !cli.quiet {
+                        println!(
+                            "{}",
+                            if ours {
+                                "decoy: tagged with this key"
+                            } else {
+                                "not a decoy from this key"
+                            }
+                        );
+                    }
+                    return Ok(if ours {
+                        ExitCode::SUCCESS
+                    } else {
+                        ExitCode::FAILURE
+                    });
+                }
+                Some(DecoyCommand::Generate(target)) => (**target).clone(),
+                None => GeneratorCommand::parse_policy(DECOY_POLICY).map_err(|e| anyhow!(e))?,
+            };
+            let (generator, _) = build_generator(&target, &cli, &limits, language)?;
+            warn_if_captured(is_tty, &cli);
+            for password in generator.iter(&mut PwgenRng::thread()).take(cli.count) {
+                println!("{}", key.mark(password.expose()).as_str());
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+        Command::Split {
+            shares,
+            threshold,
+            length,
+            encoding,
+        } => {
+            limits.check_length(*length)?;
+            let secret = SecureGenerator::builder()
+                .length(*length)
+                .build()?
+                .generate(&mut PwgenRng::thread());
+            let parts = shamir::split(
+                secret.expose().as_bytes(),
+                *shares,
+                *threshold,
+                &mut PwgenRng::thread(),
+            )?;
+            warn_if_captured(is_tty, &cli);
+            if quiet {
+                println!("{}", secret.expose());
+                for part in &parts {
+                    println!("{}", part.encode(*encoding).as_str());
+                }
+            } else {
+                println!(
+                    "Secret ({:.1} bits):\n  {}\n",
+                    secret.entropy.bits,
+                    secret.expose()
+                );
+                println!(
+                    "Shares (any {} of {} recover the secret):",
+                    threshold, shares
+                );
+                for part in &parts {
+                    println!("  {}", part.encode(*encoding).as_str());
+                }
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+        Command::Recover { shares } => {
+            let lines: Vec<Zeroizing<String>> = if !shares.is_empty() {
+                if !cli.quiet {
+                    eprintln!(
+                        "warning: shares given as arguments are visible in process listings \
+                         and shell history; run `pwgen-x recover` without them to be prompted"
+                    );
+                }
+                shares.iter().cloned().map(Zeroizing::new).collect()
+            } else if std::io::stdin().is_terminal() {
+                eprintln!("Enter one share per prompt; leave it empty to finish.");
+                let mut lines = Vec::new();
+                loop {
+                    let prompt = format!("Share {}: ", lines.len() + 1);
+                    let secret = prompt::read_secret(&Term::stderr(), &prompt)?;
+                    if secret.value.trim().is_empty() {
+                        break;
+                    }
+                    note_if_pasted(&secret, &cli);
+                    lines.push(secret.value);
+                }
+                lines
+            } else {
+                std::io::stdin()
+                    .lines()
+                    .map(|line| line.map(Zeroizing::new))
+                    .collect::<Result<_, _>>()?
+            };
+            let parts = lines
+                .iter()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| Share::decode(line))
+                .collect::<Result<Vec<_>, _>>()?;
+            let secret = shamir::recover(&parts)?;
+            let text = std::str::from_utf8(&secret).map_err(|_| {
+                anyhow!("recovered secret is not text; too few or mismatched shares?")
+            })?;
+            warn_if_captured(is_tty, &cli);
+            println!("{}", text);
+            return Ok(ExitCode::SUCCESS);
+        }
+        Command::Wordlist {
+            action: WordlistCommand::List,
+        } => {
+            list_wordlists();
+            return Ok(ExitCode::SUCCESS);
+        }
+        Command::Profile {
+            action: ProfileCommand::List,
+        } => {
+            list_profiles(&config);
+            return Ok(ExitCode::SUCCESS);
+        }
+        Command::Data {
+            action:
+                DataCommand::Export {
+                    what,
+                    output,
+                    force,
+                },
+        } => {
+            let path = output
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(what.file_name()));
+            if path.as_os_str() == "-" {
+                print!("{}", what.contents());
+                return Ok(ExitCode::SUCCESS);
+            }
+            data::export(*what, &path, *force)?;
+            if !cli.quiet {
+                eprintln!("wrote {}", path.display());
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+        Command::Provision {
+            csv,
+            column,
+            target,
+        } => {
+            let text = if csv.as_os_str() == "-" {
+                std::io::read_to_string(std::io::stdin())?
+            } else {
+                std::fs::read_to_string(csv)
+                    .with_context(|| format!("reading {}", csv.display()))?
+            };
+            let users = provision::read_users(&text, column)?;
+            limits.check_count(users.len())?;
+            let default = match target {
+                Some(target) => target.clone(),
+                None => GeneratorCommand::parse_policy("secure").map_err(|e| anyhow!(e))?,
+            };
+            let mut rng = PwgenRng::thread();
+            let mut accounts = Vec::with_capacity(users.len());
+            for row in users {
+                let command = match &row.policy {
+                    Some(policy) => GeneratorCommand::parse_policy(policy)
+                        .map_err(|e| anyhow!("line {}: {}", row.line, e))?,
+                    None => default.clone(),
+                };
+                let command = match row.length {
+                    Some(n) => command.with_length(n),
+                    None => command,
+                };
+                let (generator, _) = build_generator(&command, &cli, &limits, language)
+                    .with_context(|| format!("line {} ({})", row.line, row.user))?;
+                let password = generator
+                    .iter(&mut rng)
+                    .take(MAX_ATTEMPTS_PER_PASSWORD)
+                    .find(|p| {
+                        length_range.accepts(p.expose())
+                            && avoid.accepts(p.expose())
+                            && threshold.accepts(&p.entropy)
+                    })
+                    .ok_or_else(|| {
+                        Failure::Unsatisfiable.because(format!(
+                            "line {}: could not generate a password for {} that meets \
+                             the length range and strength requirement",
+                            row.line, row.user
+                        ))
+                    })?;
+                accounts.push(Account {
+                    user: row.user,
+                    password,
+                    must_change: generator.is_temporary(),
+                });
+            }
+            warn_if_captured(is_tty, &cli);
+            print!(
+                "{}",
+                provision::render(&accounts, column, cli.format).as_str()
+            );
+            return Ok(ExitCode::SUCCESS);
+        }
+        Command::Verify { samples, target } => {
+            let (generator, _) = build_generator(target, &cli, &limits, language)?;
+            let report = verify::verify(generator.as_ref(), &mut PwgenRng::thread(), *samples);
+            println!("{}", report);
+            if !report.passed() {
+                return Err(anyhow!("distribution check failed"));
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+    };
+    // A keypad code is only half-delivered without the keys to press
+    let display = display
+        .with_keypad(matches!(target, GeneratorCommand::Keypad { .. }))
+        .with_format(cli.format, target.name());
+
+    let generator: Box<dyn PasswordGenerator> = match cli.group_size {
+        Some(_) if matches!(command, Command::Generate(GeneratorCommand::Phrase { .. })) => {
+            bail!("--group-size applies to character passwords; passphrases use --separator")
+        }
+        Some(size) => Box::new(GroupedGenerator::new(generator, size, &cli.group_char)?),
+        None => generator,
+    };
+
+    pwgen_x::info!(
+        "generator: {}, estimated {}",
+        generator.description(),
+        generator
+            .estimated_entropy()
+            .map_or("entropy unknown until generated".to_string(), |bits| {
+                format!("{:.1} bits", bits)
+            })
+    );
+    if cli.check_only {
+        let report = threshold.check(generator.description(), generator.estimated_entropy());
+        eprintln!("{}", report.to_json());
+        return Ok(if report.pass {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::from(Failure::TooWeak.code())
+        });
+    }
+    if cli.dry_run {
+        let report = DryRunReport::new(
+            &*generator,
+            &length_range,
+            &threshold,
+            cli.attacker,
+            &mut PwgenRng::thread(),
+            LengthRange::CALIBRATION_SAMPLES,
+        );
+        println!("{}", report);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // Refuse trivially guessable configurations typed at a terminal; scripts and pipes are left alone
+    let gate_weak = !quiet && !cli.allow_weak;
+    if let (true, Some(bits)) = (gate_weak, generator.estimated_entropy()) {
+        check_danger(&target, bits)?;
+    }
+
+    if !to_files && !cli.copy {
+        warn_if_captured(is_tty || display_term.is_term(), &cli);
+    }
+
+    let scrub = cli.scrub && !to_files && display_term.is_term() && Term::stderr().is_term();
+    if cli.scrub && !to_files && !scrub {
+        eprintln!(
+            "warning: --scrub needs stdout and stderr on a terminal; nothing will be cleared"
+        );
+    }
+    if scrub {
+        scrub::save_title(&display_term)?;
+    }
+
+    // Show header
+    if !to_files && !cli.copy {
+        display.show_header(generator.description(), cli.count);
+    }
+
+    // Generate passwords using CSPRNG (the thread RNG is ChaCha12, reseeded from the OS)
+    let mut rng = PwgenRng::thread();
+    let Some(length_cost) =
+        length_range.entropy_cost(&*generator, &mut rng, LengthRange::CALIBRATION_SAMPLES)
+    else {
+        bail!(
+            "none of {} candidates had {}; adjust the length or word count",
+            LengthRange::CALIBRATION_SAMPLES,
+            length_range.describe()
+        );
+    };
+    if length_cost > 0.0 && !cli.quiet {
+        eprintln!(
+            "note: keeping only passwords of {} costs about {:.1} bits",
+            length_range.describe(),
+            length_cost
+        );
+    }
+    let deadline = cli.timeout.map(|timeout| Instant::now() + timeout);
+    let mut timed_out = false;
+    let mut rejections = Rejections::default();
+    let mut diversity = Diversity::new();
+    // Candidates that failed only --diverse, in case it has to be relaxed
+    let mut lookalikes = Vec::new();
+    let mut passwords = Vec::with_capacity(cli.count);
+    for mut p in generator
+        .iter(&mut rng)
+        .take(cli.count.saturating_mul(MAX_ATTEMPTS_PER_PASSWORD))
+    {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            timed_out = true;
+            break;
+        }
+        rejections.drawn += 1;
+        if !length_range.accepts(p.expose()) {
+            rejections.length += 1;
+            continue;
+        }
+        if !avoid.accepts(p.expose()) {
+            rejections.avoided += 1;
+            continue;
+        }
+        p.entropy = EntropyInfo::new(p.entropy.bits - length_cost, p.entropy.source);
+        if !threshold.accepts(&p.entropy) {
+            rejections.weak += 1;
+            continue;
+        }
+        if cli.diverse && !diversity.admit(p.expose()) {
+            rejections.similar += 1;
+            if lookalikes.len() < cli.count {
+                lookalikes.push(p);
+            }
+            continue;
+        }
+        passwords.push(p);
+        if passwords.len() == cli.count {
+            break;
+        }
+    }
+    pwgen_x::debug!(
+        "generation: {}{}",
+        rejections.summary(),
+        if timed_out { " before the timeout" } else { "" }
+    );
+
+    // --diverse is a preference: rather than fail, fill up with look-alikes
+    let missing = cli.count - passwords.len();
+    if missing > 0 && cli.diverse && lookalikes.len() >= missing {
+        if !cli.quiet {
+            eprintln!(
+                "warning: only {} of {} passwords look different from the rest{}; \
+                 keeping {} that resemble others",
+                passwords.len(),
+                cli.count,
+                match cli.timeout {
+                    Some(timeout) if timed_out => format!(" after {:?}", timeout),
+                    _ => String::new(),
+                },
+                missing
+            );
+        }
+        passwords.extend(lookalikes.drain(..missing));
+    }
+    if passwords.len() < cli.count {
+        let mut requirements = Vec::new();
+        if !threshold.is_empty() {
+            requirements.push(format!("at least {}", threshold.describe()));
+        }
+        if !length_range.is_empty() {
+            requirements.push(length_range.describe());
+        }
+        if !avoid.is_empty() {
+            requirements.push(format!("without the {} avoided term(s)", avoid.len()));
+        }
+        if cli.diverse {
+            requirements.push("all looking different".to_string());
+        }
+        // Falling short of a strength requirement alone is a weak configuration
+        let failure = if requirements.len() == 1 && !threshold.is_empty() {
+            Failure::TooWeak
+        } else {
+            Failure::Unsatisfiable
+        };
+        let requirement = requirements.join(", ");
+        let within = match cli.timeout {
+            Some(timeout) if timed_out => format!(" within {:?}", timeout),
+            _ => String::new(),
+        };
+        bail!(failure.because(format!(
+            "could not generate {} password(s) ({}){}: {}; \
+             try a different length or word count",
+            cli.count,
+            requirement,
+            within,
+            rejections.summary()
+        )));
+    }
+
+    if cli.sort_entropy {
+        passwords.sort_by(|a, b| b.entropy.bits.total_cmp(&a.entropy.bits));
+    }
+
+    let lowest = passwords
+        .iter()
+        .map(|p| p.entropy.bits)
+        .min_by(f64::total_cmp);
+    if let (true, None, Some(lowest)) = (gate_weak, generator.estimated_entropy(), lowest) {
+        check_danger(&target, lowest)?;
+    }
+    if let (Some(lowest), false) = (lowest, cli.quiet || generator.is_temporary()) {
+        if let Some(warning) = entropy_floor(&cli, &config).warning(&target, lowest) {
+            let label = if color.enabled(Term::stderr().is_term(), |name| std::env::var_os(name)) {
+                style("warning:").yellow().bold().to_string()
+            } else {
+                "warning:".to_string()
+            };
+            eprintln!("{} {}", label, warning);
+        }
+    }
+
+    if let Some(history) = History::from_config(cli.history.as_deref(), &config) {
+        let values: Vec<&str> = passwords.iter().map(|p| p.expose()).collect();
+        let context = || format!("updating password history {}", history.path().display());
+        let seen = history
+            .record(&values, SystemTime::now())
+            .with_context(|| Failure::Storage.because(context()))?;
+        if !cli.quiet {
+            for (index, time) in seen.iter().enumerate() {
+                if let Some(time) = time {
+                    eprintln!(
+                        "warning: password {} was already generated on {}",
+                        index + 1,
+                        format_utc(*time)
+                    );
+                }
+            }
+        }
+    }
+
+    if cli.check_breach {
+        let values: Vec<&str> = passwords.iter().map(|p| p.expose()).collect();
+        let counts = breach_counts(&values)?;
+        if !cli.quiet {
+            for (index, count) in counts.iter().enumerate().filter(|(_, &n)| n > 0) {
+                eprintln!(
+                    "warning: password {} appears {} times in known breaches",
+                    index + 1,
+                    count
+                );
+            }
+        }
+    }
+
+    // Record the run before anything is shown, so unlogged passwords are never handed out
+    if let Some(log) = AuditLog::from_config(cli.audit_log.as_deref(), &config) {
+        let record = AuditRecord {
+            metadata: if generator.is_temporary() {
+                CreationMetadata::now(generator.description()).with_policy(TEMPORARY_POLICY)
+            } else {
+                CreationMetadata::now(generator.description())
+            },
+            entropy_bits: passwords.iter().map(|p| p.entropy.bits).collect(),
+            destination: if file_writer.is_some() {
+                Destination::Files
+            } else if sharer.is_some() {
+                Destination::Recipients
+            } else if cli.copy {
+                Destination::Clipboard
+            } else if is_tty {
+                Destination::Terminal
+            } else {
+                Destination::Stdout
+            },
+        };
+        log.append(&record).with_context(|| {
+            Failure::Storage.because(format!("writing audit log {}", log.path().display()))
+        })?;
+    }
+
+    if let Some(writer) = &file_writer {
+        let paths = writer.write_all(&passwords)?;
+        if let (Some(interval), Some(path)) = (cli.remind, &remind_file) {
+            let names: Vec<_> = paths
+                .iter()
+                .filter_map(|p| p.file_name())
+                .map(|n| n.to_string_lossy())
+                .collect();
+            let subject = format!("{} in {}", names.join(", "), writer.dir().display());
+            write_reminder(Reminder::new(SystemTime::now(), interval, subject), path)?;
+        }
+        if !cli.quiet {
+            eprintln!(
+                "note: wrote {} password(s) to {}",
+                paths.len(),
+                writer.dir().display()
+            );
+            if generator.is_temporary() {
+                eprintln!("note: {}", TEMPORARY_NOTE);
+            }
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(sharer) = &sharer {
+        let values: Vec<&str> = passwords.iter().map(|p| p.expose()).collect();
+        let batch = Zeroizing::new(values.join("\n"));
+        let paths = sharer.share(&batch)?;
+        let names: Vec<_> = paths
+            .iter()
+            .filter_map(|p| p.file_name())
+            .map(|n| n.to_string_lossy())
+            .collect();
+        if let (Some(interval), Some(path)) = (cli.remind, &remind_file) {
+            let subject = format!("{} in {}", names.join(", "), sharer.dir().display());
+            write_reminder(Reminder::new(SystemTime::now(), interval, subject), path)?;
+        }
+        if !cli.quiet {
+            eprintln!(
+                "note: encrypted {} password(s) to {} recipient(s): {} in {}",
+                passwords.len(),
+                paths.len(),
+                names.join(", "),
+                sharer.dir().display()
+            );
+            if generator.is_temporary() {
+                eprintln!("note: {}", TEMPORARY_NOTE);
+            }
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let (Some(interval), Some(path)) = (cli.remind, &remind_file) {
+        let subject = format!(
+            "{} password(s) from {}",
+            passwords.len(),
+            generator.description()
+        );
+        write_reminder(Reminder::new(SystemTime::now(), interval, subject), path)?;
+    }
+
+    let shown = if cli.copy {
+        let first = &passwords[0];
+        let tool = clipboard::copy(first.expose())?;
+        if cli.clear_after > 0 {
+            clipboard::clear_later(first.expose(), Duration::from_secs(cli.clear_after))?;
+        }
+        if !cli.quiet {
+            eprintln!(
+                "note: copied the password ({:.1} bits, {}) with {}{}",
+                first.entropy.bits,
+                first.entropy.strength_against(cli.attacker).label(),
+                tool.name(),
+                match cli.clear_after {
+                    0 => String::new(),
+                    secs => format!("; clearing the clipboard in {}s", secs),
+                }
+            );
+        }
+        &passwords[1..]
+    } else {
+        &passwords[..]
+    };
+    display.show_all(shown);
+    if generator.is_temporary() && !cli.quiet {
+        eprintln!("note: {}", TEMPORARY_NOTE);
+    }
+    if scrub {
+        scrub::wait_and_scrub(&display_term, &Term::stderr(), display.rows_printed())?;
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn write_reminder(reminder: Reminder, path: &Path) -> Result<()> {
+    reminder
+        .write(path)
+        .with_context(|| Failure::Storage.because(format!("writing reminder {}", path.display())))
+}
+
+/// Strength bar scale from the flags, falling back to the `[display]` config section
+fn display_scale(cli: &Cli, config: &Config) -> Result<Scale> {
+    let max_bits = match (cli.scale_max, config.get("display.scale_max")) {
+        (Some(bits), _) => bits,
+        (None, Some(ConfigValue::Integer(bits))) => *bits as f64,
+        (None, _) => Scale::DEFAULT_MAX_BITS,
+    };
+    let logarithmic = cli.log_scale
+        || matches!(
+            config.get("display.log_scale"),
+            Some(ConfigValue::Boolean(true))
+        );
+    Scale::new(max_bits, logarithmic)
+        .ok_or_else(|| anyhow!("--scale-max must be a positive number of bits"))
+}
+
+/// Network settings from the flags, falling back to the `[network]` config section
+fn network_settings(cli: &Cli, config: &Config) -> http::Settings {
+    let defaults = http::Settings::default();
+    http::Settings {
+        offline: cli.offline
+            || matches!(
+                config.get("network.offline"),
+                Some(ConfigValue::Boolean(true))
+            ),
+        proxy: cli
+            .proxy
+            .clone()
+            .or_else(|| config.get_str("network.proxy").map(str::to_string)),
+        retries: match config.get("network.retries") {
+            Some(ConfigValue::Integer(n)) => (*n).max(0) as u32,
+            _ => defaults.retries,
+        },
+    }
+}
+
+/// Warning floor from the flag, falling back to the `[warnings]` config section
+fn entropy_floor(cli: &Cli, config: &Config) -> EntropyFloor {
+    match (cli.entropy_floor, config.get("warnings.entropy_floor")) {
+        (Some(bits), _) => EntropyFloor { bits },
+        (None, Some(ConfigValue::Integer(bits))) => EntropyFloor { bits: *bits as f64 },
+        (None, _) => EntropyFloor::default(),
+    }
+}
+
+/// Drop-in `pwgen`: bare passwords, in columns on a terminal
+fn run_pwgen_compat(args: &[OsString]) -> Result<ExitCode> {
+    let options = PwgenOptions::parse(args).context("pwgen compatibility mode")?;
+    if options.help {
+        print!("{}", pwgen_x::compat::pwgen::USAGE);
+        return Ok(ExitCode::SUCCESS);
+    }
+    let term = Term::stdout();
+    let is_tty = term.is_term();
+    let columns = options.columns.unwrap_or(is_tty);
+    let width = if is_tty { term.size().1 as usize } else { 80 };
+    let count = options
+        .count
+        .unwrap_or_else(|| options.default_count(columns, width));
+    let passwords = options.generate(count, &mut PwgenRng::thread())?;
+    for line in options.layout(&passwords, columns, width) {
+        println!("{}", *line);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Drop-in `xkcdpass`: passphrases joined by its separator, nothing else on stdout
+fn run_xkcdpass_compat(args: &[OsString]) -> Result<ExitCode> {
+    let options = XkcdpassOptions::parse(args).context("xkcdpass compatibility mode")?;
+    if options.help {
+        print!("{}", pwgen_x::compat::xkcdpass::USAGE);
+        return Ok(ExitCode::SUCCESS);
+    }
+    let list = options.wordlist().with_context(|| {
+        format!(
+            "keeping words of {} to {} letters",
+            options.min_length, options.max_length
+        )
+    })?;
+    if options.verbose {
+        let per_word = list.entropy_per_word();
+        eprintln!(
+            "With the current options, your word list contains {} words.\n\
+             A {} word password from this list will have roughly {:.0} ({:.2} * {}) bits of entropy.",
+            list.len(),
+            options.numwords,
+            options.entropy_bits(&list),
+            per_word,
+            options.numwords
+        );
+    }
+    let mut rng = PwgenRng::thread();
+    let passphrases: Vec<_> = (0..options.count)
+        .map(|_| options.generate(&list, &mut rng))
+        .collect();
+    let joined = Zeroizing::new(
+        passphrases
+            .iter()
+            .map(|p| p.as_str())
+            .collect::<Vec<_>>()
+            .join(&options.separator),
+    );
+    println!("{}", *joined);
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Secrets printed to a recorded terminal end up in the recording
+fn warn_if_captured(is_tty: bool, cli: &Cli) {
+    if !is_tty || cli.no_warn_capture {
+        return;
+    }
+    if let Some(context) = capture::detect() {
+        eprintln!(
+            "warning: this terminal appears to be captured by {}; \
+             consider --copy to put the password on the clipboard instead \
+             (silence with --no-warn-capture)",
+            context.description()
+        );
+    }
+}
+
+/// A password typed at a hidden prompt, or the first line of piped stdin
+fn read_password(cli: &Cli) -> Result<Zeroizing<String>> {
+    let mut line = Zeroizing::new(String::new());
+    if std::io::stdin().is_terminal() {
+        let secret = prompt::read_secret(&Term::stderr(), "Password: ")?;
+        note_if_pasted(&secret, cli);
+        line = secret.value;
+    } else {
+        std::io::stdin().read_line(&mut line)?;
+    }
+    let value = Zeroizing::new(line.trim_end_matches(['\n', '\r']).to_string());
+    if value.is_empty() {
+        bail!("no password given on stdin");
+    }
+    Ok(value)
+}
+
+/// Times each of `values` appears in Have I Been Pwned (--check-breach)
+#[cfg(feature = "breach")]
+fn breach_counts(values: &[&str]) -> Result<Vec<u64>> {
+    values
+        .iter()
+        .map(|value| breach::breach_count(value).context("checking against Have I Been Pwned"))
+        .collect()
+}
+
+#[cfg(not(feature = "breach"))]
+fn breach_counts(_values: &[&str]) -> Result<Vec<u64>> {
+    bail!(
+        "--check-breach needs a build with the `breach` feature \
+         (cargo install pwgen-x --features breach)"
+    )
+}
+
+/// Train the Markov model on a corpus file (or stdin), showing progress on an interactive stderr
+fn train_corpus(path: &Path, limits: TrainingLimits, quiet: bool) -> Result<MarkovModel> {
+    let context = || format!("training on {}", path.display());
+    let (corpus, total): (Box<dyn BufRead>, Option<u64>) = if path == Path::new("-") {
+        (Box::new(std::io::stdin().lock()), None)
+    } else {
+        let file = File::open(path).with_context(context)?;
+        let total = file.metadata().ok().map(|m| m.len());
+        (Box::new(BufReader::new(file)), total)
+    };
 
-    // Get the appropriate generator
-    let generator: Box<dyn PasswordGenerator> = match &cli.command {
-        Command::Normal {
+    let stderr = Term::stderr();
+    let show_progress = !quiet && stderr.is_term();
+    let mib = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+    let trained = training::train(corpus, total, limits, &mut |progress| {
+        if !show_progress {
+            return;
+        }
+        let read = match progress.total {
+            Some(total) if total > 0 => format!(
+                "{:.0} of {:.0} MiB ({}%)",
+                mib(progress.bytes),
+                mib(total),
+                progress.bytes * 100 / total
+            ),
+            _ => format!("{:.0} MiB", mib(progress.bytes)),
+        };
+        let _ = stderr.clear_line();
+        let _ = stderr.write_str(&format!(
+            "training: {}, {} words, model {:.1} MiB",
+            read,
+            progress.words,
+            mib(progress.memory as u64)
+        ));
+    });
+    if show_progress {
+        let _ = stderr.clear_line();
+    }
+    let trained = trained.with_context(context)?;
+
+    let progress = &trained.progress;
+    if let (Some(stop), false) = (trained.stopped, quiet) {
+        let reason = match stop {
+            Stop::MemoryLimit => format!(
+                "the model reached --corpus-max-memory ({:.0} MiB)",
+                mib(limits.max_memory as u64)
+            ),
+            Stop::Timeout => "--corpus-timeout ran out".to_string(),
+        };
+        eprintln!(
+            "warning: stopped training after {:.1} MiB of the corpus because {}; \
+             the model has only what was read so far",
+            mib(progress.bytes),
+            reason
+        );
+    }
+    pwgen_x::info!(
+        "training: {} words in {:.1} MiB, {} bigrams, {:.2} successors each, {:.1}s",
+        progress.words,
+        mib(progress.bytes),
+        trained.model.bigrams(),
+        trained.model.avg_branching_factor(),
+        progress.elapsed.as_secs_f64()
+    );
+    Ok(trained.model)
+}
+
+/// Remind the user that a pasted secret is still on the clipboard
+fn note_if_pasted(secret: &Secret, cli: &Cli) {
+    if secret.pasted && !cli.quiet {
+        eprintln!("note: that looked pasted; clear your clipboard if it still holds the secret");
+    }
+}
+
+/// The generator for `command` and the command it was built from, sized by `--bits` when given
+///
+/// Advice about the passwords (danger zone, entropy floor) must use the
+/// returned command, which has the length that was actually generated.
+fn build_generator(
+    command: &GeneratorCommand,
+    cli: &Cli,
+    limits: &Limits,
+    language: Language,
+) -> Result<(Box<dyn PasswordGenerator>, GeneratorCommand)> {
+    let command = match cli.bits {
+        Some(bits) => size_for_bits(command, bits, cli, limits, language)?,
+        None => command.clone(),
+    };
+    let generator = construct_generator(&command, cli, limits, language, cli.quiet)?;
+    Ok((generator, command))
+}
+
+/// `command` with the smallest length, or word count, whose estimated entropy reaches `bits`
+///
+/// Candidates are built silently and only their estimates are compared, so
+/// every option of the command is taken into account.
+fn size_for_bits(
+    command: &GeneratorCommand,
+    bits: f64,
+    cli: &Cli,
+    limits: &Limits,
+    language: Language,
+) -> Result<GeneratorCommand> {
+    match command {
+        GeneratorCommand::Template { .. } => {
+            bail!(Failure::Usage.because("--bits cannot size a template; its mask fixes the length"))
+        }
+        GeneratorCommand::Apple => bail!(Failure::Usage
+            .because("--bits cannot size an Apple-style password; its format is fixed")),
+        GeneratorCommand::Normal {
+            corpus: Some(_), ..
+        } => bail!(Failure::Usage.because(
+            "--bits cannot be combined with --corpus, which would be trained again for every \
+             length tried; give --length instead"
+        )),
+        _ => {}
+    }
+    let unit = match command {
+        GeneratorCommand::Phrase { .. } => "word count",
+        _ => "length",
+    };
+    let build = |n| {
+        let candidate = command.clone().with_length(n);
+        construct_generator(&candidate, cli, limits, language, true)
+    };
+    let Some((size, _, reached)) = compare::smallest(bits, build) else {
+        // Report why the command itself cannot be built, if it cannot
+        let generator = construct_generator(command, cli, limits, language, true)?;
+        if generator.estimated_entropy().is_none() {
+            bail!(Failure::Usage.because(format!(
+                "--bits needs an entropy estimate, which {} cannot give without generating",
+                command.name()
+            )));
+        }
+        bail!(Failure::TooWeak.because(format!(
+            "no {} up to {} reaches {} bits",
+            unit,
+            compare::MAX_SIZE,
+            bits
+        )));
+    };
+    if !cli.quiet {
+        eprintln!(
+            "note: {} bits needs a {} of {} ({:.1} bits)",
+            bits, unit, size, reached
+        );
+    }
+    Ok(command.clone().with_length(size))
+}
+
+fn construct_generator(
+    command: &GeneratorCommand,
+    cli: &Cli,
+    limits: &Limits,
+    language: Language,
+    quiet: bool,
+) -> Result<Box<dyn PasswordGenerator>> {
+    let generator: Box<dyn PasswordGenerator> = match command {
+        GeneratorCommand::Normal {
             length_pos,
             length,
             digits,
+            no_digits,
             symbols,
+            no_symbols,
+            digit_count,
+            symbol_count,
+            symbol_set,
             capitalize,
+            no_capitalize,
+            min_guess_percentile,
+            corpus,
+            corpus_max_memory,
+            corpus_timeout,
         } => {
-            let len = length_pos.or(*length).unwrap_or(12);
-            Box::new(MarkovGenerator::new(len, *digits, *symbols, *capitalize))
+            let mut builder = MarkovGenerator::builder()
+                .digits(negatable(*digits, *no_digits))
+                .symbols(negatable(*symbols, *no_symbols))
+                .capitalize(negatable(*capitalize, *no_capitalize))
+                .min_guess_percentile(*min_guess_percentile);
+            if let Some(count) = digit_count {
+                builder = builder.digit_count(*count);
+            }
+            if let Some(count) = symbol_count {
+                builder = builder.symbol_count(*count);
+            }
+            if let Some(set) = symbol_set {
+                builder = builder.symbol_set(set.clone());
+            }
+            if let Some(len) = length_pos.or(*length) {
+                limits.check_length(len)?;
+                builder = builder.length(len);
+            }
+            if let Some(path) = corpus {
+                let limits = TrainingLimits {
+                    max_memory: *corpus_max_memory,
+                    timeout: *corpus_timeout,
+                };
+                builder = builder.model(train_corpus(path, limits, quiet)?);
+            }
+            Box::new(builder.build()?)
         }
 
-        Command::Secure {
+        GeneratorCommand::Secure {
             length_pos,
             length,
             charset,
+            chars,
+            exclude_chars,
+            with_lower,
+            with_upper,
+            with_digits,
+            with_symbols,
+            with_space,
             no_ambiguous,
+            symbol_set,
+            site,
+            online,
+            max_char_repeat,
+            compose,
+            require,
+            min_digits,
+            min_symbols,
         } => {
-            let len = length_pos.or(*length).unwrap_or(16);
-            Box::new(SecureGenerator::new(len, charset, *no_ambiguous))
+            let mut builder = SecureGenerator::builder()
+                .charset(*charset)
+                .exclude_ambiguous(*no_ambiguous);
+            let mut requirements = require
+                .iter()
+                .fold(ClassRequirements::new(), |r, &class| r.at_least(class, 1));
+            if let Some(n) = min_digits {
+                requirements = requirements.at_least(CharClass::Digit, *n);
+            }
+            if let Some(n) = min_symbols {
+                requirements = requirements.at_least(CharClass::Symbol, *n);
+            }
+            builder = builder.require(requirements);
+            if let Some(chars) = chars {
+                builder = builder.alphabet(chars.as_str());
+            }
+            if let Some(excluded) = exclude_chars {
+                builder = builder.exclude_chars(excluded);
+            }
+            let with = [
+                (*with_lower, CharClass::Lower),
+                (*with_upper, CharClass::Upper),
+                (*with_digits, CharClass::Digit),
+                (*with_symbols, CharClass::Symbol),
+            ];
+            for (_, class) in with.into_iter().filter(|(on, _)| *on) {
+                builder = builder.include(class);
+            }
+            builder = builder.include_space(*with_space);
+            if let Some(max) = max_char_repeat {
+                builder = builder.max_repeat(*max);
+            }
+            if let Some(composition) = compose {
+                builder = builder.compose(composition.clone());
+            }
+            if let Some(set) = symbol_set {
+                builder = builder.symbol_set(set.clone());
+            }
+            if let Some(site) = site {
+                let rules = if *online {
+                    let (rules, source) = rules::online::for_site(site)?;
+                    if !quiet {
+                        match source {
+                            RulesSource::Online { url, cached: true } => {
+                                eprintln!("note: using password rules from {} (cached)", url)
+                            }
+                            RulesSource::Online { url, cached: false } => {
+                                eprintln!("note: using password rules from {}", url)
+                            }
+                            RulesSource::Embedded => eprintln!(
+                                "note: no published password rules found, using built-in rules"
+                            ),
+                            RulesSource::Offline => eprintln!(
+                                "note: --offline and no cached rules for {}, using built-in rules",
+                                site
+                            ),
+                            RulesSource::Unreachable(err) => {
+                                eprintln!("warning: {}; using built-in rules", err)
+                            }
+                        }
+                    }
+                    rules
+                } else {
+                    rules::for_site(site)?
+                };
+                builder = builder.length(rules.clamp_length(SecureGenerator::DEFAULT_LENGTH));
+                builder = builder.rules(rules);
+            }
+            if let Some(len) = length_pos.or(*length) {
+                limits.check_length(len)?;
+                builder = builder.length(len);
+            }
+            Box::new(builder.build()?)
         }
 
-        Command::Phrase {
+        GeneratorCommand::Phrase {
             words_pos,
             words,
             separator,
             custom_sep,
+            random_sep,
             capitalize,
+            cap_one,
             no_mutate,
+            wordlist,
+            familiar,
+            acrostic,
+            unique_words,
+            story,
+            wrap_safe,
+            print_layout,
         } => {
-            let word_count = words_pos.or(*words).unwrap_or(6);
-            let sep = custom_sep
-                .clone()
-                .unwrap_or_else(|| separator.as_str().to_string());
-            Box::new(PassphraseGenerator::new(word_count, sep, *capitalize, !*no_mutate))
+            let list = match wordlist {
+                Some(name) => Some(wordlist::resolve(name)?),
+                // The display language picks the list too, when one is installed
+                None if cli.lang.is_some() && language != Language::English => {
+                    match wordlist::for_language(language) {
+                        Ok(list) => Some(list),
+                        Err(_) => {
+                            if !quiet {
+                                eprintln!(
+                                    "warning: no '{}' wordlist is available, using '{}'",
+                                    language.code(),
+                                    language.wordlist_language().code()
+                                );
+                            }
+                            None
+                        }
+                    }
+                }
+                None => None,
+            };
+            let wrap_safe = *wrap_safe || *print_layout;
+            let sep = match (custom_sep, separator) {
+                (Some(sep), _) => sep.clone(),
+                // Lines break after a dash; a dot holds the words together
+                (None, Separator::Dash) if wrap_safe => Separator::Dot.as_str().to_string(),
+                (None, separator) => separator.as_str().to_string(),
+            };
+            if *print_layout && sep.is_empty() && random_sep.is_none() {
+                bail!("--print-layout needs a separator between the words");
+            }
+            let mut builder = PassphraseGenerator::builder()
+                .separator(sep)
+                .capitalize(*capitalize)
+                .cap_one(*cap_one)
+                .mutate(!*no_mutate)
+                .unique_words(*unique_words)
+                .story(*story)
+                .wrap_safe(wrap_safe);
+            if let Some(set) = random_sep {
+                builder = builder.random_separator(set.as_str());
+            }
+            if let Some(list) = list {
+                builder = builder.wordlist(list);
+            }
+            if let Some(count) = words_pos.or(*words) {
+                limits.check_words(count)?;
+                builder = builder.word_count(count);
+            }
+            // Sampling only from passphrases that fit is exact and never needs a redraw
+            if let Some(max_length) = cli.max_length {
+                builder = builder.max_length(max_length);
+            }
+            if let Some(word) = acrostic {
+                builder = builder.acrostic(word.as_str());
+            }
+            let generator = builder.familiar(*familiar).build()?;
+            if *familiar && !quiet {
+                let uniform = generator.wordlist().entropy_per_word();
+                let reduction = generator.entropy_reduction_per_word();
+                eprintln!(
+                    "note: familiar words give {:.2} bits/word instead of {:.2} \
+                     ({:.1} bits less per passphrase)",
+                    uniform - reduction,
+                    uniform,
+                    reduction * generator.word_count() as f64
+                );
+            }
+            if let (Some(word), false) = (acrostic, quiet) {
+                eprintln!(
+                    "note: spelling '{}' costs {:.1} bits; the word itself adds none",
+                    word,
+                    generator.acrostic_cost()
+                );
+            }
+            if *unique_words && !quiet {
+                eprintln!(
+                    "note: never repeating a word costs {:.3} bits",
+                    generator.unique_words_cost_bits()
+                );
+            }
+            if let (Some(max_length), Some(bits)) = (cli.max_length, generator.estimated_entropy())
+            {
+                if !quiet {
+                    eprintln!(
+                        "note: fitting in {} characters leaves {:.1} bits ({:.1} bits less)",
+                        max_length,
+                        bits,
+                        generator.max_length_cost()
+                    );
+                }
+            }
+            Box::new(generator)
+        }
+
+        GeneratorCommand::Temp { length_pos, length } => {
+            let mut builder = TemporaryGenerator::builder();
+            if let Some(len) = length_pos.or(*length) {
+                limits.check_length(len)?;
+                builder = builder.length(len);
+            }
+            Box::new(builder.build()?)
         }
 
-        Command::Pin { length_pos, length } => {
-            let len = length_pos.or(*length).unwrap_or(6);
-            Box::new(PinGenerator::new(len))
+        GeneratorCommand::Pin { length_pos, length } => {
+            let mut builder = PinGenerator::builder();
+            if let Some(len) = length_pos.or(*length) {
+                limits.check_length(len)?;
+                builder = builder.length(len);
+            }
+            Box::new(builder.build()?)
         }
+
+        GeneratorCommand::Passcode {
+            length_pos,
+            length,
+            alphanumeric,
+        } => {
+            let mut builder = PasscodeGenerator::builder().alphanumeric(*alphanumeric);
+            if let Some(len) = length_pos.or(*length) {
+                limits.check_length(len)?;
+                builder = builder.length(len);
+            }
+            let generator = builder.build()?;
+            if !quiet {
+                for line in PasscodeGenerator::GUIDANCE {
+                    eprintln!("note: {}", line);
+                }
+            }
+            Box::new(generator)
+        }
+
+        GeneratorCommand::Keypad {
+            length_pos,
+            length,
+            digit_count,
+        } => {
+            let mut builder = KeypadGenerator::builder();
+            if let Some(letters) = length_pos.or(*length) {
+                limits.check_length(letters)?;
+                builder = builder.letters(letters);
+            }
+            if let Some(digits) = digit_count {
+                // No digits is a bare word, so only the upper bound applies
+                if *digits > 0 {
+                    limits.check_length(*digits)?;
+                }
+                builder = builder.digits(*digits);
+            }
+            Box::new(builder.build()?)
+        }
+
+        GeneratorCommand::Template { mask, symbol_set } => {
+            limits.check_length(mask.chars().count())?;
+            let mut builder = TemplateGenerator::builder().mask(mask.as_str());
+            if let Some(set) = symbol_set {
+                builder = builder.symbol_set(set.clone());
+            }
+            Box::new(builder.build()?)
+        }
+
+        GeneratorCommand::Apple => Box::new(AppleGenerator::new()),
     };
+    Ok(generator)
+}
 
-    // Show header
-    display.show_header(generator.description(), cli.count);
+/// Print the `[defaults]` and each `[profile.NAME]` with its settings
+fn list_profiles(config: &Config) {
+    let settings = |section: &str| {
+        config
+            .entries(section)
+            .map(|(key, value)| format!("{}={}", key, value.to_arg()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let defaults = settings("defaults");
+    if !defaults.is_empty() {
+        println!("{:<16} {}", "(defaults)", defaults);
+    }
+    for name in config.profiles() {
+        println!("{:<16} {}", name, settings(&format!("profile.{}", name)));
+    }
+}
 
-    // Generate passwords using CSPRNG (thread_rng uses ChaCha12-based StdRng)
-    let mut rng = thread_rng();
-    for _ in 0..cli.count {
-        let password = generator.generate(&mut rng);
-        display.show(&password);
+/// Print every discoverable wordlist with its size and per-word entropy
+fn list_wordlists() {
+    for entry in wordlist::discover() {
+        let location = match &entry.source {
+            Source::Builtin => "built-in".to_string(),
+            Source::File(path) => path.display().to_string(),
+        };
+        match entry.load() {
+            Ok(list) => println!(
+                "{:<16} {:>6} words  {:>5.2} bits/word  {}",
+                entry.name,
+                list.len(),
+                list.entropy_per_word(),
+                location
+            ),
+            Err(err) => println!("{:<16} unusable: {}", entry.name, err),
+        }
     }
+}
 
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advice_follows_bits_sizing() {
+        let cli = Cli::try_parse_from(["pwgen-x", "--bits", "40", "--quiet", "pin"]).unwrap();
+        let Some(Command::Generate(command)) = &cli.command else {
+            panic!("expected a generator command");
+        };
+        let (generator, sized) =
+            build_generator(command, &cli, &Limits::default(), Language::English).unwrap();
+        assert_eq!(generator.length(), Some(13));
+        assert!(
+            matches!(
+                sized,
+                GeneratorCommand::Pin {
+                    length: Some(13),
+                    ..
+                }
+            ),
+            "{:?}",
+            sized
+        );
+
+        // 13 digits are 43.2 bits; the advice must count from 13, not the default 4
+        let bits = generator.estimated_entropy().unwrap();
+        let warning = EntropyFloor::default().warning(&sized, bits).unwrap();
+        assert!(warning.contains("16 digits"), "{}", warning);
+        check_danger(&sized, bits).unwrap();
+    }
 }
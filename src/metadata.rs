@@ -0,0 +1,129 @@
+//! Creation metadata attached to exported passwords
+//!
+//! Records which version and parameters produced a credential, when, and under
+//! which policy, so exports can be re-audited long after the fact. Holds no
+//! secret material.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Value};
+
+#[cfg(feature = "serde")]
+use crate::generators::GeneratedPassword;
+
+/// How and when a batch of passwords was generated
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreationMetadata {
+    pub created: SystemTime,
+    pub generator: String,
+    /// Command-line arguments, without the program name
+    pub parameters: Vec<String>,
+    /// Name of the policy the passwords were generated under, if any
+    pub policy: Option<String>,
+}
+
+impl CreationMetadata {
+    /// Metadata stamped with the current time and the process arguments
+    pub fn now(generator: &str) -> Self {
+        Self {
+            created: SystemTime::now(),
+            generator: generator.to_string(),
+            parameters: std::env::args().skip(1).collect(),
+            policy: None,
+        }
+    }
+
+    pub fn with_policy(mut self, policy: impl Into<String>) -> Self {
+        self.policy = Some(policy.into());
+        self
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "generator_version": env!("CARGO_PKG_VERSION"),
+            "generator": self.generator,
+            "parameters": self.parameters,
+            "created": format_utc(self.created),
+            "policy": self.policy,
+        })
+    }
+}
+
+/// `{"metadata": {...}, "passwords": [...]}` document for exports
+#[cfg(feature = "serde")]
+pub fn export(passwords: &[GeneratedPassword], metadata: &CreationMetadata) -> Value {
+    json!({
+        "metadata": metadata.to_json(),
+        "passwords": passwords,
+    })
+}
+
+/// RFC 3339 UTC timestamp with second precision, e.g. `2024-03-01T12:00:00Z`
+pub fn format_utc(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+    let (days, rem) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+
+    // Civil-from-days (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_format_utc() {
+        assert_eq!(format_utc(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        let leap_day = UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+        assert_eq!(format_utc(leap_day), "2024-02-29T12:34:56Z");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_export_includes_metadata() {
+        use crate::entropy::EntropyInfo;
+        use zeroize::Zeroizing;
+
+        let metadata = CreationMetadata {
+            created: UNIX_EPOCH,
+            generator: "Numeric PIN".to_string(),
+            parameters: vec!["pin".to_string()],
+            policy: None,
+        }
+        .with_policy("corp-2024");
+        let password = GeneratedPassword {
+            value: Zeroizing::new("1234".to_string()),
+            entropy: EntropyInfo::new(13.3, "Random digits"),
+            mnemonic: None,
+        };
+        let document = export(&[password], &metadata);
+        assert_eq!(document["metadata"]["created"], "1970-01-01T00:00:00Z");
+        assert_eq!(document["metadata"]["policy"], "corp-2024");
+        assert_eq!(
+            document["metadata"]["generator_version"],
+            env!("CARGO_PKG_VERSION")
+        );
+        assert_eq!(document["passwords"][0]["value"], "1234");
+    }
+}
@@ -0,0 +1,106 @@
+//! Story mnemonics that weave passphrase words into a sentence
+//!
+//! Each frame is a short clause with `{}` slots. Frames are picked at random
+//! and chained until every word has a slot, keeping the words in passphrase
+//! order so the story can be read back into the passphrase.
+
+use rand::seq::SliceRandom;
+use rand::RngCore;
+
+const FRAMES: &[&str] = &[
+    "the {} hid a {} under the {} {}",
+    "a {} and a {} argued about the {} {}",
+    "the {} gave a {} to the {}",
+    "a {} chased the {} into a {}",
+    "the {} painted a {} on the {}",
+    "the {} met a {}",
+    "a {} sat beside the {}",
+    "then came the {}",
+    "and a {}",
+];
+
+fn slots(frame: &str) -> usize {
+    frame.matches("{}").count()
+}
+
+fn fill(frame: &str, words: &[&str]) -> String {
+    let mut out = String::new();
+    let mut words = words.iter();
+    let mut parts = frame.split("{}").peekable();
+    while let Some(part) = parts.next() {
+        out.push_str(part);
+        if parts.peek().is_some() {
+            let word = words.next().copied().unwrap_or_default();
+            let vowel = word.starts_with(|c: char| "aeiouAEIOU".contains(c));
+            if vowel && (out == "a " || out.ends_with(" a ")) {
+                out.insert(out.len() - 1, 'n');
+            }
+            out.push_str(word);
+        }
+    }
+    out
+}
+
+/// Build a sentence containing every word, in order
+///
+/// Frame choice uses `rng` but adds nothing to the passphrase's entropy; the
+/// story is derived from the words, not the other way around.
+pub fn story(words: &[&str], rng: &mut dyn RngCore) -> String {
+    let mut clauses = Vec::new();
+    let mut rest = words;
+    while !rest.is_empty() {
+        let fitting: Vec<&str> = FRAMES
+            .iter()
+            .copied()
+            .filter(|f| slots(f) <= rest.len())
+            .collect();
+        let frame = fitting.choose(rng).copied().unwrap_or("{}");
+        let (used, remaining) = rest.split_at(slots(frame));
+        clauses.push(fill(frame, used));
+        rest = remaining;
+    }
+
+    let sentence = clauses.join(", ");
+    let mut chars = sentence.chars();
+    match chars.next() {
+        Some(first) => format!("{}{}.", first.to_uppercase(), chars.as_str()),
+        None => sentence,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_fill() {
+        assert_eq!(
+            fill(FRAMES[0], &["otter", "lantern", "canyon", "piano"]),
+            "the otter hid a lantern under the canyon piano"
+        );
+        assert_eq!(
+            fill("a {} met a {}", &["otter", "ibis"]),
+            "an otter met an ibis"
+        );
+    }
+
+    #[test]
+    fn test_story_keeps_words_in_order() {
+        let words = [
+            "otter", "lantern", "canyon", "piano", "dust", "ivory", "yo-yo",
+        ];
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        for _ in 0..50 {
+            let story = story(&words, &mut rng);
+            assert!(story.ends_with('.'));
+            assert!(story.starts_with(char::is_uppercase));
+            let mut offset = 0;
+            for word in words {
+                let found = story[offset..].find(word).expect(word);
+                offset += found + word.len();
+            }
+        }
+    }
+}
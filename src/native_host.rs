@@ -0,0 +1,268 @@
+//! Browser native-messaging host (`native-host` subcommand)
+//!
+//! Chrome and Firefox talk to native hosts over stdio: every message is a
+//! 32-bit length in native byte order followed by that many bytes of UTF-8
+//! JSON. Each request gets exactly one response.
+//!
+//! Requests:
+//!
+//! ```json
+//! {"action": "ping"}
+//! {"action": "generate", "origin": "https://github.com/login", "count": 1}
+//! {"action": "generate", "kind": "phrase", "words": 5}
+//! ```
+//!
+//! `kind` is `secure` (default), `phrase` or `pin`. For `secure`, a known
+//! `origin` applies that site's rules from the embedded database. Responses are
+//! `{"passwords": [{"value", "entropy"}], "rules": "..."}`, `{"version": "..."}`
+//! or `{"error": "..."}`.
+
+use std::io::{self, Read, Write};
+
+use rand::RngCore;
+use serde_json::{json, Value};
+use zeroize::Zeroizing;
+
+use crate::generators::{
+    PassphraseGenerator, PasswordGenerator, PasswordGeneratorExt, PinGenerator, SecureGenerator,
+};
+use crate::rules::{self, RulesError};
+use crate::validation::Limits;
+
+/// Largest request accepted; requests are tiny, so anything bigger is a protocol error
+pub const MAX_REQUEST_BYTES: u32 = 64 * 1024;
+
+/// Browsers reject host messages above 1 MiB
+pub const MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+
+/// Most passwords returned for one request
+pub const MAX_COUNT: usize = 100;
+
+/// Read one length-prefixed message; `Ok(None)` on a clean end of input
+pub fn read_message(reader: &mut impl Read) -> io::Result<Option<Zeroizing<Vec<u8>>>> {
+    let mut len = [0u8; 4];
+    match reader.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let len = u32::from_ne_bytes(len);
+    if len > MAX_REQUEST_BYTES {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("message of {} bytes exceeds {}", len, MAX_REQUEST_BYTES),
+        ));
+    }
+    let mut body = Zeroizing::new(vec![0u8; len as usize]);
+    reader.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+pub fn write_message(writer: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = Zeroizing::new(message.to_string().into_bytes());
+    if body.len() > MAX_RESPONSE_BYTES {
+        return write_message(writer, &json!({"error": "response too large"}));
+    }
+    writer.write_all(&(body.len() as u32).to_ne_bytes())?;
+    writer.write_all(&body)?;
+    writer.flush()
+}
+
+/// Serve requests until the browser closes stdin
+pub fn run(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    limits: &Limits,
+    rng: &mut dyn RngCore,
+) -> io::Result<()> {
+    while let Some(body) = read_message(reader)? {
+        let response = match serde_json::from_slice::<Value>(&body) {
+            Ok(request) => handle(&request, limits, rng),
+            Err(e) => error(format!("invalid JSON: {}", e)),
+        };
+        write_message(writer, &response)?;
+    }
+    Ok(())
+}
+
+fn error(message: impl Into<String>) -> Value {
+    json!({ "error": message.into() })
+}
+
+/// Respond to a single decoded request
+pub fn handle(request: &Value, limits: &Limits, rng: &mut dyn RngCore) -> Value {
+    match request.get("action").and_then(Value::as_str) {
+        Some("ping") => json!({ "version": env!("CARGO_PKG_VERSION") }),
+        Some("generate") => generate(request, limits, rng).unwrap_or_else(error),
+        Some(other) => error(format!("unknown action '{}'", other)),
+        None => error("missing 'action'"),
+    }
+}
+
+fn field(request: &Value, name: &str) -> Result<Option<usize>, String> {
+    match request.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => value
+            .as_u64()
+            .map(|n| Some(n as usize))
+            .ok_or_else(|| format!("'{}' must be a non-negative integer", name)),
+    }
+}
+
+fn generate(request: &Value, limits: &Limits, rng: &mut dyn RngCore) -> Result<Value, String> {
+    let count = field(request, "count")?.unwrap_or(1);
+    if count > MAX_COUNT {
+        return Err(format!("'count' must be at most {}", MAX_COUNT));
+    }
+    limits.check_count(count).map_err(|e| e.to_string())?;
+    let length = field(request, "length")?;
+    if let Some(length) = length {
+        limits.check_length(length).map_err(|e| e.to_string())?;
+    }
+    let origin = request.get("origin").and_then(Value::as_str);
+
+    let mut applied_rules = None;
+    let generator: Box<dyn PasswordGenerator> = match request
+        .get("kind")
+        .and_then(Value::as_str)
+        .unwrap_or("secure")
+    {
+        "secure" => {
+            let mut builder = SecureGenerator::builder();
+            match origin.map(rules::for_site) {
+                Some(Ok(site_rules)) => {
+                    let default_length = site_rules.clamp_length(SecureGenerator::DEFAULT_LENGTH);
+                    builder = builder
+                        .length(length.unwrap_or(default_length))
+                        .rules(site_rules.clone());
+                    applied_rules = Some(site_rules.to_string());
+                }
+                Some(Err(RulesError::UnknownSite(_))) | None => {
+                    if let Some(length) = length {
+                        builder = builder.length(length);
+                    }
+                }
+                Some(Err(e)) => return Err(e.to_string()),
+            }
+            Box::new(builder.build().map_err(|e| e.to_string())?)
+        }
+        "phrase" => {
+            let mut builder = PassphraseGenerator::builder();
+            if let Some(words) = field(request, "words")? {
+                limits.check_words(words).map_err(|e| e.to_string())?;
+                builder = builder.word_count(words);
+            }
+            Box::new(builder.build().map_err(|e| e.to_string())?)
+        }
+        "pin" => {
+            let mut builder = PinGenerator::builder();
+            if let Some(length) = length {
+                builder = builder.length(length);
+            }
+            Box::new(builder.build().map_err(|e| e.to_string())?)
+        }
+        other => return Err(format!("unknown kind '{}'", other)),
+    };
+
+    let passwords: Vec<Value> = generator
+        .iter(rng)
+        .take(count)
+        .map(|p| {
+            json!({
+                "value": p.value.as_str(),
+                "entropy": { "bits": p.entropy.bits, "strength": p.entropy.strength().label() },
+            })
+        })
+        .collect();
+    Ok(json!({ "passwords": passwords, "rules": applied_rules }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn frame(message: &str) -> Vec<u8> {
+        let mut bytes = (message.len() as u32).to_ne_bytes().to_vec();
+        bytes.extend_from_slice(message.as_bytes());
+        bytes
+    }
+
+    fn responses(mut output: &[u8]) -> Vec<Value> {
+        let mut values = Vec::new();
+        while let Some(body) = read_message(&mut output).unwrap() {
+            values.push(serde_json::from_slice(&body).unwrap());
+        }
+        values
+    }
+
+    #[test]
+    fn test_protocol_round_trip() {
+        let mut input = frame(r#"{"action":"ping"}"#);
+        input.extend(frame(
+            r#"{"action":"generate","origin":"https://www.chase.com/","count":2}"#,
+        ));
+        input.extend(frame("not json"));
+        let mut output = Vec::new();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        run(
+            &mut input.as_slice(),
+            &mut output,
+            &Limits::default(),
+            &mut rng,
+        )
+        .unwrap();
+
+        let replies = responses(&output);
+        assert_eq!(replies.len(), 3);
+        assert_eq!(replies[0]["version"], env!("CARGO_PKG_VERSION"));
+
+        let site_rules = rules::for_site("chase.com").unwrap();
+        let passwords = replies[1]["passwords"].as_array().unwrap();
+        assert_eq!(passwords.len(), 2);
+        for password in passwords {
+            assert!(site_rules.accepts(password["value"].as_str().unwrap()));
+        }
+        assert!(replies[1]["rules"].is_string());
+        assert!(replies[2]["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("invalid JSON"));
+    }
+
+    #[test]
+    fn test_request_errors() {
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let limits = Limits::default();
+        let reply = handle(
+            &json!({"action": "generate", "kind": "pin", "length": 0}),
+            &limits,
+            &mut rng,
+        );
+        assert!(reply["error"].is_string());
+        let reply = handle(
+            &json!({"action": "generate", "count": -1}),
+            &limits,
+            &mut rng,
+        );
+        assert!(reply["error"].is_string());
+        let reply = handle(&json!({"action": "steal"}), &limits, &mut rng);
+        assert_eq!(reply["error"], "unknown action 'steal'");
+
+        // Unknown origins fall back to the default secure generator
+        let reply = handle(
+            &json!({"action": "generate", "origin": "https://example.invalid"}),
+            &limits,
+            &mut rng,
+        );
+        assert_eq!(reply["passwords"][0]["value"].as_str().unwrap().len(), 16);
+        assert!(reply["rules"].is_null());
+    }
+
+    #[test]
+    fn test_oversized_message_rejected() {
+        let input = (MAX_REQUEST_BYTES + 1).to_ne_bytes();
+        assert!(read_message(&mut input.as_slice()).is_err());
+    }
+}
@@ -0,0 +1,95 @@
+//! Screen-reader friendly rendering of password values
+
+/// Spoken name for a non-alphanumeric character
+pub fn symbol_name(c: char) -> Option<&'static str> {
+    let name = match c {
+        ' ' => "space",
+        '!' => "exclamation mark",
+        '"' => "double quote",
+        '#' => "hash",
+        '$' => "dollar sign",
+        '%' => "percent sign",
+        '&' => "ampersand",
+        '\'' => "apostrophe",
+        '(' => "left parenthesis",
+        ')' => "right parenthesis",
+        '*' => "asterisk",
+        '+' => "plus sign",
+        ',' => "comma",
+        '-' => "hyphen",
+        '.' => "period",
+        '/' => "slash",
+        ':' => "colon",
+        ';' => "semicolon",
+        '<' => "less-than sign",
+        '=' => "equals sign",
+        '>' => "greater-than sign",
+        '?' => "question mark",
+        '@' => "at sign",
+        '[' => "left bracket",
+        '\\' => "backslash",
+        ']' => "right bracket",
+        '^' => "caret",
+        '_' => "underscore",
+        '`' => "backtick",
+        '{' => "left brace",
+        '|' => "vertical bar",
+        '}' => "right brace",
+        '~' => "tilde",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Spell a value character by character, e.g. "capital K, lowercase x, digit 7, exclamation mark"
+pub fn spell(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| {
+            if c.is_uppercase() {
+                format!("capital {}", c)
+            } else if c.is_lowercase() {
+                format!("lowercase {}", c)
+            } else if c.is_ascii_digit() {
+                format!("digit {}", c)
+            } else if let Some(name) = symbol_name(c) {
+                name.to_string()
+            } else {
+                format!("character {}", c)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spell_mixed() {
+        assert_eq!(
+            spell("Kx7!"),
+            "capital K, lowercase x, digit 7, exclamation mark"
+        );
+    }
+
+    #[test]
+    fn test_symbol_names_cover_printable_ascii() {
+        for c in (32u8..=126).map(|b| b as char) {
+            if !c.is_ascii_alphanumeric() {
+                assert!(symbol_name(c).is_some(), "missing name for {:?}", c);
+            }
+        }
+    }
+
+    #[test]
+    fn test_spell_has_no_block_characters_or_emoji() {
+        let spoken = spell("a-b_c");
+        assert!(spoken.is_ascii());
+        assert_eq!(
+            spoken,
+            "lowercase a, hyphen, lowercase b, underscore, lowercase c"
+        );
+    }
+}
@@ -0,0 +1,77 @@
+//! Character class counts of a value, for checking it against a site's form validation
+
+use std::fmt;
+
+/// How many characters of each class a value holds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClassCounts {
+    pub lower: usize,
+    pub upper: usize,
+    pub digits: usize,
+    /// ASCII punctuation and spaces
+    pub symbols: usize,
+    /// Everything else, e.g. accented letters or emoji
+    pub other: usize,
+}
+
+impl ClassCounts {
+    pub fn of(value: &str) -> Self {
+        let mut counts = Self::default();
+        for c in value.chars() {
+            match c {
+                'a'..='z' => counts.lower += 1,
+                'A'..='Z' => counts.upper += 1,
+                '0'..='9' => counts.digits += 1,
+                c if c.is_ascii_punctuation() || c == ' ' => counts.symbols += 1,
+                _ => counts.other += 1,
+            }
+        }
+        counts
+    }
+}
+
+impl fmt::Display for ClassCounts {
+    /// e.g. "4 lower, 3 upper, 2 digits, 1 symbol"; `other` only when present
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = |n: usize, one: &'static str, many: &'static str| {
+            format!("{} {}", n, if n == 1 { one } else { many })
+        };
+        write!(
+            f,
+            "{} lower, {} upper, {}, {}",
+            self.lower,
+            self.upper,
+            plural(self.digits, "digit", "digits"),
+            plural(self.symbols, "symbol", "symbols")
+        )?;
+        if self.other > 0 {
+            write!(f, ", {} other", self.other)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_class_counts() {
+        let counts = ClassCounts::of("abCD12!x y");
+        assert_eq!(
+            counts,
+            ClassCounts {
+                lower: 4,
+                upper: 2,
+                digits: 2,
+                symbols: 2,
+                other: 0
+            }
+        );
+        assert_eq!(counts.to_string(), "4 lower, 2 upper, 2 digits, 2 symbols");
+        assert_eq!(
+            ClassCounts::of("é1-").to_string(),
+            "0 lower, 0 upper, 1 digit, 1 symbol, 1 other"
+        );
+    }
+}
@@ -1,45 +1,815 @@
+use std::cell::Cell;
+use std::fmt;
+use std::io::Write;
+
+use console::{measure_text_width, truncate_str, Term};
 use owo_colors::OwoColorize;
+use serde_json::{json, Value};
+use zeroize::Zeroizing;
 
-use crate::entropy::StrengthLevel;
+use crate::cli::{OutputFormat, RecordSeparator, Shell};
+use crate::crack_time::CrackTime;
+use crate::entropy::{AttackerModel, EntropyInfo, Scale, StrengthLevel};
+use crate::estimators::{self, EstimatorKind, StrengthEstimator};
+use crate::fingerprint;
+use crate::generators::keypad::keypad_digits;
 use crate::generators::GeneratedPassword;
+use crate::lang::{Language, UiStrings};
+use crate::metadata::{format_utc, CreationMetadata};
+use crate::output::classes::ClassCounts;
+use crate::output::{accessible, escape, print_layout, printable};
+use crate::qr::{QrCode, QrContent};
+
+/// `println!` to the display's stream that also counts the terminal rows used, for `--scrub`
+macro_rules! out {
+    ($display:expr) => {
+        $display.emit(format_args!(""))
+    };
+    ($display:expr, $($arg:tt)*) => {
+        $display.emit(format_args!($($arg)*))
+    };
+}
+
+/// How each password entry is laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layout {
+    /// Password line followed by a strength bar line
+    Block,
+    /// `password  [92.4 bits, Strong]` on a single line
+    OneLine,
+    /// Plain, screen-reader friendly paragraphs (English only)
+    Accessible,
+}
 
 pub struct PasswordDisplay {
     use_colors: bool,
     use_emoji: bool,
     quiet: bool,
+    /// Terminal width available for the column grid (None disables the grid)
+    grid_width: Option<usize>,
+    /// Terminal width that bars, headers and spelled-out values are fitted to (None: unlimited)
+    width: Option<usize>,
+    layout: Layout,
+    strings: &'static UiStrings,
+    /// Show a transcription fingerprint under each password
+    fingerprints: bool,
+    /// Show the phone keys that enter each password
+    keypad: bool,
+    scale: Scale,
+    /// Threat model the strength labels are judged against
+    attacker: AttackerModel,
+    /// Show how long the attacker needs on average
+    crack_time: bool,
+    /// Print values as literals for this shell
+    escape: Option<Shell>,
+    /// Decoration goes to stderr and only bare values to stdout
+    split_streams: bool,
+    /// Framing of bare values
+    record_sep: RecordSeparator,
+    /// Estimators compared on an extra line (empty for the keyspace alone)
+    estimators: Vec<Box<dyn StrengthEstimator>>,
+    /// Count the characters of each class under each password
+    class_counts: bool,
+    /// Text, or structured records instead of everything else
+    format: OutputFormat,
+    /// Subcommand named in structured records
+    generator: &'static str,
+    /// Draw a QR code of each password, or of a Wi-Fi join string carrying it
+    qr: Option<QrContent>,
+    /// Print each passphrase as a numbered word block for documents
+    print_layout: bool,
+    /// Terminal rows printed to the display's stream so far
+    rows: Cell<usize>,
 }
 
 impl PasswordDisplay {
+    /// Minimum batch size before switching to a column grid
+    const GRID_MIN_COUNT: usize = 10;
+    /// Spaces between grid columns
+    const GRID_GAP: usize = 2;
+    /// Left indent matching the block layout
+    const INDENT: usize = 2;
+    /// Strength bar cells on a wide enough terminal
+    const BAR_WIDTH: usize = 20;
+    /// Fewest bar cells kept on a narrow terminal
+    const MIN_BAR_WIDTH: usize = 5;
+
     pub fn new(use_colors: bool, quiet: bool) -> Self {
         Self {
             use_colors,
             use_emoji: use_colors,
             quiet,
+            grid_width: None,
+            width: None,
+            layout: Layout::Block,
+            strings: Language::English.strings(),
+            fingerprints: false,
+            keypad: false,
+            scale: Scale::default(),
+            attacker: AttackerModel::default(),
+            crack_time: false,
+            escape: None,
+            split_streams: false,
+            record_sep: RecordSeparator::default(),
+            estimators: Vec::new(),
+            class_counts: false,
+            format: OutputFormat::Text,
+            generator: "",
+            qr: None,
+            print_layout: false,
+            rows: Cell::new(0),
+        }
+    }
+
+    /// Localize labels and the header
+    pub fn with_language(mut self, language: Language) -> Self {
+        self.strings = language.strings();
+        self
+    }
+
+    /// Screen-reader friendly output: no bars or emoji, strength in words, symbols spelled out
+    pub fn with_accessible(mut self, accessible: bool) -> Self {
+        if accessible {
+            self.layout = Layout::Accessible;
+            self.use_colors = false;
+            self.use_emoji = false;
+            self.grid_width = None;
+        }
+        self
+    }
+
+    /// Compact `password  [bits, label]` line per entry
+    pub fn with_oneline(mut self, oneline: bool) -> Self {
+        if oneline && self.layout == Layout::Block {
+            self.layout = Layout::OneLine;
+            self.grid_width = None;
+        }
+        self
+    }
+
+    /// Show a two-word fingerprint for checking a transcription
+    pub fn with_fingerprints(mut self, fingerprints: bool) -> Self {
+        self.fingerprints = fingerprints;
+        self
+    }
+
+    /// Show the phone keypad keys for each password (ITU E.161)
+    pub fn with_keypad(mut self, keypad: bool) -> Self {
+        self.keypad = keypad;
+        self
+    }
+
+    /// Scale of the strength bar
+    pub fn with_scale(mut self, scale: Scale) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Judge strength labels against this attacker
+    pub fn with_attacker(mut self, attacker: AttackerModel) -> Self {
+        self.attacker = attacker;
+        self
+    }
+
+    /// Show a time-to-crack estimate with each strength line
+    pub fn with_crack_time(mut self, crack_time: bool) -> Self {
+        self.crack_time = crack_time;
+        self
+    }
+
+    /// Print each value quoted or escaped for pasting into `shell`
+    pub fn with_escape(mut self, shell: Option<Shell>) -> Self {
+        self.escape = shell;
+        self
+    }
+
+    /// Show the full display on stderr and print bare values to stdout (`--split-streams`)
+    ///
+    /// Has no effect in quiet mode, where stdout already holds only the values.
+    pub fn with_split_streams(mut self, split: bool) -> Self {
+        self.split_streams = split;
+        self
+    }
+
+    /// Frame bare values with `record_sep`
+    pub fn with_record_separator(mut self, record_sep: RecordSeparator) -> Self {
+        self.record_sep = record_sep;
+        self
+    }
+
+    /// Compare these estimators side by side (nothing extra for the keyspace alone)
+    pub fn with_estimators(mut self, kinds: &[EstimatorKind]) -> Self {
+        self.estimators = if kinds == [EstimatorKind::Keyspace] {
+            Vec::new()
+        } else {
+            kinds.iter().map(|kind| kind.build()).collect()
+        };
+        self
+    }
+
+    /// Show how many lowercase, uppercase, digit and symbol characters each password has
+    pub fn with_class_counts(mut self, class_counts: bool) -> Self {
+        self.class_counts = class_counts;
+        self
+    }
+
+    /// Print a JSON array (`Json`) or one object per line (`Jsonl`) instead of text
+    ///
+    /// Each record has the value, entropy bits, strength label and `generator`;
+    /// escaping, framing and decoration options don't apply.
+    pub fn with_format(mut self, format: OutputFormat, generator: &'static str) -> Self {
+        self.format = format;
+        self.generator = generator;
+        self
+    }
+
+    /// Draw a QR code under each password (text output only)
+    pub fn with_qr(mut self, qr: Option<QrContent>) -> Self {
+        self.qr = qr;
+        self
+    }
+
+    /// Print each passphrase as a wrap-safe block of numbered words instead (text output only)
+    ///
+    /// The block goes to stdout in quiet mode too, since it is meant to be pasted.
+    pub fn with_print_layout(mut self, print_layout: bool) -> Self {
+        self.print_layout = print_layout;
+        if print_layout {
+            self.grid_width = None;
+        }
+        self
+    }
+
+    fn class_counts(&self, password: &GeneratedPassword) -> Option<ClassCounts> {
+        self.class_counts.then(|| ClassCounts::of(&password.value))
+    }
+
+    /// e.g. "keyspace 64.0 · pattern 38.2 bits (worst: pattern)"
+    fn render_estimates(&self, password: &GeneratedPassword) -> Option<String> {
+        let estimates = estimators::estimate_all(&self.estimators, password);
+        let worst = estimates.iter().min_by(|a, b| a.bits.total_cmp(&b.bits))?;
+        let list = estimates
+            .iter()
+            .map(|e| format!("{} {:.1}", e.name, e.bits))
+            .collect::<Vec<_>>()
+            .join(" · ");
+        if estimates.len() == 1 {
+            return Some(format!("{} {}", list, self.strings.bits));
+        }
+        Some(format!(
+            "{} {} (worst: {})",
+            list, self.strings.bits, worst.name
+        ))
+    }
+
+    /// Whether the decorated display goes to stderr
+    fn decorating_stderr(&self) -> bool {
+        self.split_streams && !self.quiet
+    }
+
+    /// Print one line to the display's stream, counting the terminal rows it wraps to
+    fn emit(&self, args: fmt::Arguments) {
+        let line = Zeroizing::new(args.to_string());
+        let term = if self.decorating_stderr() {
+            eprintln!("{}", line.as_str());
+            Term::stderr()
+        } else {
+            println!("{}", line.as_str());
+            Term::stdout()
+        };
+        self.count_rows(&line, &term);
+    }
+
+    fn count_rows(&self, line: &str, term: &Term) {
+        let width = term.size().1.max(1) as usize;
+        let rows: usize = line
+            .split('\n')
+            .map(|l| measure_text_width(l).div_ceil(width).max(1))
+            .sum();
+        self.rows.set(self.rows.get() + rows);
+    }
+
+    /// Terminal rows printed so far, for clearing them again
+    pub fn rows_printed(&self) -> usize {
+        self.rows.get()
+    }
+
+    /// The value as printed, escaped if requested
+    fn shown(&self, password: &GeneratedPassword) -> Zeroizing<String> {
+        match self.escape {
+            Some(shell) => escape::escape(&password.value, shell),
+            None => password.value.clone(),
+        }
+    }
+
+    /// Fit strength bars, headers and spelled-out values into `term_width` columns
+    pub fn with_width(mut self, term_width: usize) -> Self {
+        self.width = Some(term_width);
+        self
+    }
+
+    /// Enable pwgen-style column grid for large batches on a terminal of the given width
+    pub fn with_grid(mut self, term_width: usize) -> Self {
+        if self.layout == Layout::Block {
+            self.grid_width = Some(term_width);
+        }
+        self
+    }
+
+    /// Display a batch of passwords, using a column grid when it fits
+    pub fn show_all(&self, passwords: &[GeneratedPassword]) {
+        if self.format == OutputFormat::Printable {
+            self.show_printable(passwords);
+            return;
+        }
+        if self.format != OutputFormat::Text {
+            self.show_structured(passwords);
+            return;
+        }
+        if self.print_layout {
+            self.show_print_layouts(passwords);
+            return;
+        }
+        if self.quiet {
+            self.show_records(passwords);
+            // Keep stdout to bare values; memory aids are for the person at the terminal
+            for password in passwords {
+                if let Some(story) = &password.mnemonic {
+                    eprintln!("{}", story.as_str());
+                }
+                if let Some(fingerprint) = self.fingerprint(password) {
+                    eprintln!("{} {}", self.strings.fingerprint, fingerprint);
+                }
+                if let Some(keys) = self.keys(password) {
+                    eprintln!("{} {}", self.strings.keypad, keys);
+                }
+                if let Some(counts) = self.class_counts(password) {
+                    eprintln!("{} {}", self.strings.classes, counts);
+                }
+                for line in self.qr_lines(password) {
+                    eprintln!("{}", line);
+                }
+            }
+            return;
+        }
+        self.show_decorated(passwords);
+        if self.decorating_stderr() {
+            self.show_records(passwords);
+        }
+    }
+
+    /// Numbered word blocks separated by blank lines (values without separators print as they are)
+    fn show_print_layouts(&self, passwords: &[GeneratedPassword]) {
+        for (i, password) in passwords.iter().enumerate() {
+            if i > 0 {
+                out!(self);
+            }
+            match print_layout::render(&password.value) {
+                Some(lines) => lines.iter().for_each(|line| out!(self, "{}", line)),
+                None => out!(self, "{}", password.value.as_str()),
+            }
+        }
+    }
+
+    /// Print the bare values to stdout, framed by the record separator
+    fn show_records(&self, passwords: &[GeneratedPassword]) {
+        let values: Vec<Zeroizing<String>> = passwords.iter().map(|p| self.shown(p)).collect();
+        let records = frame_records(&values, self.record_sep);
+        print!("{}", records.as_str());
+        let _ = std::io::stdout().flush();
+        // On a terminal the values take rows that --scrub has to clear
+        let stdout = Term::stdout();
+        if stdout.is_term() {
+            self.count_rows(records.trim_end_matches(['\n', '\0']), &stdout);
+        }
+    }
+
+    fn show_structured(&self, passwords: &[GeneratedPassword]) {
+        let records: Vec<Value> = passwords.iter().map(|p| self.record(p)).collect();
+        let text = Zeroizing::new(match self.format {
+            OutputFormat::Jsonl => records.iter().map(|r| format!("{}\n", r)).collect(),
+            _ => format!("{:#}\n", Value::Array(records)),
+        });
+        print!("{}", text.as_str());
+        let _ = std::io::stdout().flush();
+        let stdout = Term::stdout();
+        if stdout.is_term() {
+            self.count_rows(text.trim_end(), &stdout);
+        }
+    }
+
+    /// One sheet per password on stdout, separated by page breaks
+    fn show_printable(&self, passwords: &[GeneratedPassword]) {
+        let metadata = CreationMetadata::now(self.generator);
+        let mut command = vec!["pwgen-x".to_string()];
+        command.extend(metadata.parameters.iter().cloned());
+        let content = self.qr.clone().unwrap_or(QrContent::Password);
+        for (i, password) in passwords.iter().enumerate() {
+            let title = match passwords.len() {
+                1 => "Password".to_string(),
+                n => format!("Password {} of {}", i + 1, n),
+            };
+            let strength = password.entropy.strength_against(self.attacker);
+            let mut details = vec![
+                ("Generated", format_utc(metadata.created)),
+                (
+                    "Generator",
+                    format!("{} (pwgen-x {})", self.generator, env!("CARGO_PKG_VERSION")),
+                ),
+                ("Command", command.join(" ")),
+                (
+                    "Entropy",
+                    format!("{:.1} bits ({})", password.entropy.bits, strength.label()),
+                ),
+                (
+                    "Length",
+                    format!("{} characters", password.value.chars().count()),
+                ),
+            ];
+            if let Some(story) = &password.mnemonic {
+                details.push(("Story", story.to_string()));
+            }
+            if let Some(keys) = self.keys(password) {
+                details.push(("Keys", keys));
+            }
+            let code = QrCode::encode(content.payload(&password.value).as_bytes()).ok();
+            let sheet = printable::render(&title, &password.value, code.as_ref(), &details);
+            if i > 0 {
+                println!("{}", printable::PAGE_BREAK);
+            }
+            print!("{}", sheet.as_str());
+        }
+        let _ = std::io::stdout().flush();
+    }
+
+    /// One password as a JSON object
+    fn record(&self, password: &GeneratedPassword) -> Value {
+        let mut record = json!({
+            "value": password.value.as_str(),
+            "entropy_bits": password.entropy.bits,
+            "strength": password.entropy.strength_against(self.attacker).label(),
+            "generator": self.generator,
+        });
+        if let Some(story) = &password.mnemonic {
+            record["mnemonic"] = json!(story.as_str());
+        }
+        if self.keypad {
+            record["keys"] = json!(keypad_digits(&password.value));
+        }
+        record
+    }
+
+    fn show_decorated(&self, passwords: &[GeneratedPassword]) {
+        if self.layout == Layout::Accessible && !self.quiet {
+            for (i, password) in passwords.iter().enumerate() {
+                out!(
+                    self,
+                    "{}",
+                    self.render_accessible(password, i + 1, passwords.len())
+                );
+            }
+            return;
+        }
+
+        match self.grid_columns(passwords) {
+            Some(columns) => self.show_grid(passwords, columns),
+            None => passwords.iter().for_each(|p| self.show(p)),
+        }
+    }
+
+    /// Number of grid columns for this batch, or None if the grid should not be used
+    fn grid_columns(&self, passwords: &[GeneratedPassword]) -> Option<usize> {
+        let width = self.grid_width?;
+        if self.quiet
+            || self.fingerprints
+            || self.keypad
+            || self.qr.is_some()
+            || passwords.len() < Self::GRID_MIN_COUNT
+            || passwords.iter().any(|p| p.mnemonic.is_some())
+            // Spaces inside values would read as column gaps
+            || passwords.iter().any(|p| p.value.contains(char::is_whitespace))
+        {
+            return None;
+        }
+
+        let cell = passwords
+            .iter()
+            .map(|p| measure_text_width(&self.shown(p)))
+            .max()?;
+        let usable = width.saturating_sub(Self::INDENT) + Self::GRID_GAP;
+        let columns = usable / (cell + Self::GRID_GAP);
+
+        (columns >= 2).then_some(columns)
+    }
+
+    fn show_grid(&self, passwords: &[GeneratedPassword], columns: usize) {
+        let cell = passwords
+            .iter()
+            .map(|p| measure_text_width(&self.shown(p)))
+            .max()
+            .unwrap_or(0);
+
+        for row in passwords.chunks(columns) {
+            let line = row
+                .iter()
+                .map(|p| {
+                    let value = self.shown(p);
+                    let pad = cell - measure_text_width(&value);
+                    format!("{}{}", value.as_str(), " ".repeat(pad))
+                })
+                .collect::<Vec<_>>()
+                .join(&" ".repeat(Self::GRID_GAP));
+            out!(self, "{}{}", " ".repeat(Self::INDENT), line.trim_end());
+        }
+        out!(self);
+
+        // A single strength line for the batch, based on its weakest member
+        if let Some(weakest) = passwords
+            .iter()
+            .min_by(|a, b| a.entropy.bits.total_cmp(&b.entropy.bits))
+        {
+            self.show_strength(&weakest.entropy);
+            out!(self);
+        }
+    }
+
+    fn render_oneline(&self, password: &GeneratedPassword) -> String {
+        let entropy = &password.entropy;
+        let strength = entropy.strength_against(self.attacker);
+        let crack_time = self
+            .crack_time(entropy)
+            .map(|t| format!(", {}", t.duration()))
+            .unwrap_or_default();
+        if self.use_colors {
+            format!(
+                "{}  [{:.1} {}, {}{}]",
+                self.shown(password).as_str().green().bold(),
+                entropy.bits,
+                self.strings.bits,
+                self.colored_strength_label(strength),
+                crack_time
+            )
+        } else {
+            format!(
+                "{}  [{:.1} {}, {}{}]",
+                self.shown(password).as_str(),
+                entropy.bits,
+                self.strings.bits,
+                self.strings.strength_label(strength),
+                crack_time
+            )
+        }
+    }
+
+    /// One self-contained paragraph per password, each line starting with what it describes
+    fn render_accessible(
+        &self,
+        password: &GeneratedPassword,
+        index: usize,
+        total: usize,
+    ) -> String {
+        let entropy = &password.entropy;
+        let story = password
+            .mnemonic
+            .as_ref()
+            .map(|m| format!("Story: {}\n", m.as_str()))
+            .unwrap_or_default();
+        let fingerprint = self
+            .fingerprint(password)
+            .map(|f| format!("Fingerprint: {}\n", f))
+            .unwrap_or_default();
+        let keys = self
+            .keys(password)
+            .map(|k| format!("Keys: {}.\n", k))
+            .unwrap_or_default();
+        let crack_time = self
+            .crack_time(entropy)
+            .map(|t| format!("Time to crack: {}.\n", t))
+            .unwrap_or_default();
+        let estimates = self
+            .render_estimates(password)
+            .map(|e| format!("Estimates: {}.\n", e))
+            .unwrap_or_default();
+        let classes = self
+            .class_counts(password)
+            .map(|c| format!("Character classes: {}.\n", c))
+            .unwrap_or_default();
+        format!(
+            "Password {} of {}: {}\nSpelled: {}.\n{}{}{}Strength: {}, {:.0} bits of entropy.\n{}{}{}",
+            index,
+            total,
+            self.shown(password).as_str(),
+            self.wrap_list(&accessible::spell(&password.value), "Spelled: ".len()),
+            story,
+            fingerprint,
+            keys,
+            entropy.strength_against(self.attacker).label(),
+            entropy.bits,
+            crack_time,
+            estimates,
+            classes
+        )
+    }
+
+    /// Break a comma-separated list after its commas so lines fit the terminal
+    ///
+    /// `first_indent` is the width of the label in front of the first line.
+    fn wrap_list(&self, list: &str, first_indent: usize) -> String {
+        let Some(width) = self.width else {
+            return list.to_string();
+        };
+        let mut out = String::new();
+        let mut column = first_indent;
+        for (i, item) in list.split(", ").enumerate() {
+            let item_width = measure_text_width(item) + 1;
+            if i > 0 {
+                if column + 1 + item_width > width {
+                    out.push_str(",\n");
+                    column = 0;
+                } else {
+                    out.push_str(", ");
+                    column += 2;
+                }
+            }
+            out.push_str(item);
+            column += item_width - 1;
+        }
+        out
+    }
+
+    fn keys(&self, password: &GeneratedPassword) -> Option<String> {
+        self.keypad.then(|| keypad_digits(&password.value))
+    }
+
+    /// Lines of the QR code for `password`, forced to white on black when colored
+    fn qr_lines(&self, password: &GeneratedPassword) -> Vec<String> {
+        let Some(content) = &self.qr else {
+            return Vec::new();
+        };
+        let payload = content.payload(&password.value);
+        match QrCode::encode(payload.as_bytes()) {
+            Ok(code) if self.use_colors => code
+                .render()
+                .iter()
+                .map(|line| format!("  {}", line.bright_white().on_black()))
+                .collect(),
+            Ok(code) => code
+                .render()
+                .iter()
+                .map(|line| format!("  {}", line))
+                .collect(),
+            Err(err) => {
+                eprintln!("warning: {}", err);
+                Vec::new()
+            }
         }
     }
 
+    fn fingerprint(&self, password: &GeneratedPassword) -> Option<String> {
+        self.fingerprints
+            .then(|| fingerprint::fingerprint(&password.value))
+    }
+
     /// Display a generated password with strength indicator
     pub fn show(&self, password: &GeneratedPassword) {
-        // Dereference Zeroizing<String> to get the inner String
-        let value = password.value.as_str();
+        let value = self.shown(password);
+        let value = value.as_str();
 
         if self.quiet {
-            println!("{}", value);
+            out!(self, "{}", value);
+            // Keep stdout to bare values; the memory aid is for the person at the terminal
+            if let Some(story) = &password.mnemonic {
+                eprintln!("{}", story.as_str());
+            }
+            if let Some(fingerprint) = self.fingerprint(password) {
+                eprintln!("{} {}", self.strings.fingerprint, fingerprint);
+            }
+            if let Some(keys) = self.keys(password) {
+                eprintln!("{} {}", self.strings.keypad, keys);
+            }
+            if let Some(counts) = self.class_counts(password) {
+                eprintln!("{} {}", self.strings.classes, counts);
+            }
+            for line in self.qr_lines(password) {
+                eprintln!("{}", line);
+            }
             return;
         }
 
         let entropy = &password.entropy;
-        let strength = entropy.strength();
+
+        if self.layout == Layout::OneLine {
+            out!(self, "{}", self.render_oneline(password));
+            if let Some(story) = &password.mnemonic {
+                out!(self, "  {}", story.as_str());
+            }
+            if let Some(fingerprint) = self.fingerprint(password) {
+                out!(self, "  {} {}", self.strings.fingerprint, fingerprint);
+            }
+            if let Some(keys) = self.keys(password) {
+                out!(self, "  {} {}", self.strings.keypad, keys);
+            }
+            if let Some(counts) = self.class_counts(password) {
+                out!(self, "  {} {}", self.strings.classes, counts);
+            }
+            for line in self.qr_lines(password) {
+                out!(self, "{}", line);
+            }
+            return;
+        }
 
         // Password value
         if self.use_colors {
-            println!("  {} {}", "Password:".bold(), value.green().bold());
+            out!(
+                self,
+                "  {} {}",
+                self.strings.password.bold(),
+                value.green().bold()
+            );
         } else {
-            println!("  Password: {}", value);
+            out!(self, "  {} {}", self.strings.password, value);
         }
 
-        // Progress bar
-        let bar = self.render_progress_bar(entropy.percentage(), 20, strength);
+        if let Some(story) = &password.mnemonic {
+            if self.use_colors {
+                out!(
+                    self,
+                    "  {} {}",
+                    self.strings.story.bold(),
+                    story.as_str().italic()
+                );
+            } else {
+                out!(self, "  {} {}", self.strings.story, story.as_str());
+            }
+        }
+
+        if let Some(fingerprint) = self.fingerprint(password) {
+            if self.use_colors {
+                out!(
+                    self,
+                    "  {} {}",
+                    self.strings.fingerprint.bold(),
+                    fingerprint.cyan()
+                );
+            } else {
+                out!(self, "  {} {}", self.strings.fingerprint, fingerprint);
+            }
+        }
+
+        if let Some(keys) = self.keys(password) {
+            if self.use_colors {
+                out!(self, "  {} {}", self.strings.keypad.bold(), keys.cyan());
+            } else {
+                out!(self, "  {} {}", self.strings.keypad, keys);
+            }
+        }
+
+        self.show_strength(entropy);
+        if let Some(estimates) = self.render_estimates(password) {
+            if self.use_colors {
+                out!(self, "  {} {}", self.strings.estimates.bold(), estimates);
+            } else {
+                out!(self, "  {} {}", self.strings.estimates, estimates);
+            }
+        }
+        if let Some(counts) = self.class_counts(password) {
+            if self.use_colors {
+                out!(self, "  {} {}", self.strings.classes.bold(), counts);
+            } else {
+                out!(self, "  {} {}", self.strings.classes, counts);
+            }
+        }
+        for line in self.qr_lines(password) {
+            out!(self, "{}", line);
+        }
+        out!(self);
+    }
+
+    /// Strength line with progress bar, bits and label
+    fn show_strength(&self, entropy: &EntropyInfo) {
+        out!(self, "{}", self.render_strength(entropy));
+        if let Some(time) = self.crack_time(entropy) {
+            out!(self, "  {} {}", self.strings.crack_time, time);
+        }
+    }
+
+    /// The strength line, with the bar shrunk to fit the terminal
+    fn render_strength(&self, entropy: &EntropyInfo) -> String {
+        let bar_width = match self.width {
+            Some(width) => {
+                let rest = measure_text_width(&self.render_strength_with_bar(entropy, 0));
+                width
+                    .saturating_sub(rest)
+                    .clamp(Self::MIN_BAR_WIDTH, Self::BAR_WIDTH)
+            }
+            None => Self::BAR_WIDTH,
+        };
+        self.render_strength_with_bar(entropy, bar_width)
+    }
+
+    fn render_strength_with_bar(&self, entropy: &EntropyInfo, bar_width: usize) -> String {
+        let strength = entropy.strength_against(self.attacker);
+        let bar = self.render_progress_bar(entropy.percentage_on(&self.scale), bar_width, strength);
 
         if self.use_colors {
             let emoji = if self.use_emoji {
@@ -47,24 +817,30 @@ impl PasswordDisplay {
             } else {
                 String::new()
             };
-            println!(
-                "  {} {} {:.1} bits {}{}",
-                "Strength:".bold(),
+            format!(
+                "  {} {} {:.1} {} {}{}",
+                self.strings.strength.bold(),
                 bar,
                 entropy.bits,
+                self.strings.bits,
                 self.colored_strength_label(strength),
                 emoji
-            );
+            )
         } else {
-            println!(
-                "  Strength: {} {:.1} bits ({})",
+            format!(
+                "  {} {} {:.1} {} ({})",
+                self.strings.strength,
                 bar,
                 entropy.bits,
-                strength.label()
-            );
+                self.strings.bits,
+                self.strings.strength_label(strength)
+            )
         }
+    }
 
-        println!();
+    fn crack_time(&self, entropy: &EntropyInfo) -> Option<CrackTime> {
+        self.crack_time
+            .then(|| CrackTime::new(entropy.bits, self.attacker))
     }
 
     fn render_progress_bar(&self, percentage: u8, width: usize, strength: StrengthLevel) -> String {
@@ -90,7 +866,7 @@ impl PasswordDisplay {
     }
 
     fn colored_strength_label(&self, strength: StrengthLevel) -> String {
-        let label = strength.label();
+        let label = self.strings.strength_label(strength);
         match strength {
             StrengthLevel::VeryWeak => label.red().to_string(),
             StrengthLevel::Weak => label.yellow().to_string(),
@@ -102,24 +878,312 @@ impl PasswordDisplay {
 
     /// Show header with generator type
     pub fn show_header(&self, description: &str, count: usize) {
-        if self.quiet {
+        if self.quiet || self.format != OutputFormat::Text {
+            return;
+        }
+
+        if self.layout == Layout::Accessible {
+            out!(self, "Generating {} {} password(s).\n", count, description);
             return;
         }
 
         let emoji = if self.use_emoji { "🔑 " } else { "" };
+        let header = self.fit_header(description, count, measure_text_width(emoji));
 
         if self.use_colors {
-            println!(
-                "\n{}{}",
-                emoji,
-                format!("Generating {} {} password(s):", count, description)
-                    .cyan()
-                    .bold()
-            );
+            out!(self, "\n{}{}", emoji, header.cyan().bold());
         } else {
-            println!("\nGenerating {} {} password(s):", count, description);
+            out!(self, "\n{}", header);
+        }
+        out!(self);
+    }
+
+    /// The header text, shortening the description rather than letting it wrap
+    fn fit_header(&self, description: &str, count: usize, prefix: usize) -> String {
+        let description = match self.width {
+            Some(width) => {
+                let fixed = prefix + measure_text_width(&self.strings.header(count, ""));
+                truncate_str(description, width.saturating_sub(fixed).max(1), "…")
+            }
+            None => description.into(),
+        };
+        self.strings.header(count, &description)
+    }
+}
+
+/// Join bare values into one batch with no stray whitespace
+///
+/// Newline and comma framing end with a single `\n`; NUL framing terminates
+/// every value with `\0` and adds nothing else.
+fn frame_records(values: &[Zeroizing<String>], record_sep: RecordSeparator) -> Zeroizing<String> {
+    let mut out = Zeroizing::new(String::new());
+    match record_sep {
+        RecordSeparator::Newline | RecordSeparator::Nul => {
+            let terminator = if record_sep == RecordSeparator::Nul {
+                '\0'
+            } else {
+                '\n'
+            };
+            for value in values {
+                out.push_str(value);
+                out.push(terminator);
+            }
         }
-        println!();
+        RecordSeparator::Comma => {
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                push_csv_field(&mut out, value);
+            }
+            if !values.is_empty() {
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+/// Append `value` as one CSV field, quoted if it holds a comma, quote or line break
+pub(crate) fn push_csv_field(out: &mut String, value: &str) {
+    if value.contains([',', '"', '\n', '\r']) {
+        out.push('"');
+        out.push_str(&value.replace('"', "\"\""));
+        out.push('"');
+    } else {
+        out.push_str(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zeroize::Zeroizing;
+
+    fn batch(value: &str, count: usize) -> Vec<GeneratedPassword> {
+        (0..count)
+            .map(|_| GeneratedPassword {
+                value: Zeroizing::new(value.to_string()),
+                entropy: EntropyInfo::new(40.0, "test"),
+                mnemonic: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_structured_record() {
+        let display = PasswordDisplay::new(true, false)
+            .with_format(OutputFormat::Jsonl, "keypad")
+            .with_keypad(true);
+        let password = GeneratedPassword::new(
+            Zeroizing::new("tavo-7319".to_string()),
+            EntropyInfo::new(77.5, "Keypad"),
+        );
+        assert_eq!(
+            display.record(&password),
+            json!({
+                "value": "tavo-7319",
+                "entropy_bits": 77.5,
+                "strength": "Strong",
+                "generator": "keypad",
+                "keys": "8286-7319",
+            })
+        );
+    }
+
+    #[test]
+    fn test_frame_records() {
+        let values: Vec<Zeroizing<String>> = ["ab", "c,d", "e\"f"]
+            .iter()
+            .map(|v| Zeroizing::new(v.to_string()))
+            .collect();
+        assert_eq!(
+            frame_records(&values, RecordSeparator::Newline).as_str(),
+            "ab\nc,d\ne\"f\n"
+        );
+        assert_eq!(
+            frame_records(&values, RecordSeparator::Nul).as_str(),
+            "ab\0c,d\0e\"f\0"
+        );
+        assert_eq!(
+            frame_records(&values, RecordSeparator::Comma).as_str(),
+            "ab,\"c,d\",\"e\"\"f\"\n"
+        );
+        assert_eq!(frame_records(&[], RecordSeparator::Comma).as_str(), "");
+    }
+
+    #[test]
+    fn test_grid_columns_fit_width() {
+        let display = PasswordDisplay::new(false, false).with_grid(80);
+        // 2 indent + n * 8 + (n - 1) * 2 <= 80 -> n = 8
+        assert_eq!(display.grid_columns(&batch("abcdefgh", 60)), Some(8));
+    }
+
+    #[test]
+    fn test_grid_disabled_for_small_batches_and_quiet() {
+        let display = PasswordDisplay::new(false, false).with_grid(80);
+        assert_eq!(display.grid_columns(&batch("abcdefgh", 3)), None);
+
+        let quiet = PasswordDisplay::new(false, true).with_grid(80);
+        assert_eq!(quiet.grid_columns(&batch("abcdefgh", 60)), None);
+
+        let no_tty = PasswordDisplay::new(false, false);
+        assert_eq!(no_tty.grid_columns(&batch("abcdefgh", 60)), None);
+    }
+
+    #[test]
+    fn test_accessible_rendering() {
+        let display = PasswordDisplay::new(true, false).with_accessible(true);
+        let password = GeneratedPassword {
+            value: Zeroizing::new("Ab1!".to_string()),
+            entropy: EntropyInfo::new(80.4, "test"),
+            mnemonic: None,
+        };
+        let text = display.render_accessible(&password, 2, 3);
+
+        assert_eq!(
+            text,
+            "Password 2 of 3: Ab1!\n\
+             Spelled: capital A, lowercase b, digit 1, exclamation mark.\n\
+             Strength: Strong, 80 bits of entropy.\n"
+        );
+        assert!(!text.contains('█') && !text.contains('░'));
+        assert!(!text.contains(StrengthLevel::Strong.emoji()));
+
+        let verbose = display.with_class_counts(true);
+        assert!(verbose
+            .render_accessible(&password, 2, 3)
+            .ends_with("Character classes: 1 lower, 1 upper, 1 digit, 1 symbol.\n"));
+    }
+
+    #[test]
+    fn test_story_disables_grid() {
+        let display = PasswordDisplay::new(false, false).with_grid(200);
+        let mut passwords = batch("otter-lantern", 12);
+        assert!(display.grid_columns(&passwords).is_some());
+
+        passwords[0].mnemonic = Some(Zeroizing::new("The otter met a lantern.".to_string()));
+        assert_eq!(display.grid_columns(&passwords), None);
+    }
+
+    #[test]
+    fn test_oneline_rendering() {
+        let display = PasswordDisplay::new(false, false).with_oneline(true);
+        let password = GeneratedPassword {
+            value: Zeroizing::new("Kx7!ab".to_string()),
+            entropy: EntropyInfo::new(92.4, "test"),
+            mnemonic: None,
+        };
+        assert_eq!(
+            display.render_oneline(&password),
+            "Kx7!ab  [92.4 bits, Strong]"
+        );
+
+        let display = display
+            .with_attacker(AttackerModel::OfflineFastHash)
+            .with_crack_time(true);
+        assert_eq!(
+            display.render_oneline(&password),
+            "Kx7!ab  [92.4 bits, Strong, ≈ 100 million years]"
+        );
+    }
+
+    #[test]
+    fn test_oneline_localized() {
+        let display = PasswordDisplay::new(false, false)
+            .with_oneline(true)
+            .with_language(Language::German);
+        let password = GeneratedPassword {
+            value: Zeroizing::new("Kx7!ab".to_string()),
+            entropy: EntropyInfo::new(92.4, "test"),
+            mnemonic: None,
+        };
+        assert_eq!(
+            display.render_oneline(&password),
+            "Kx7!ab  [92.4 Bit, Stark]"
+        );
     }
 
+    #[test]
+    fn test_oneline_disables_grid() {
+        let display = PasswordDisplay::new(false, false)
+            .with_oneline(true)
+            .with_grid(80);
+        assert_eq!(display.grid_columns(&batch("abcdefgh", 60)), None);
+    }
+
+    #[test]
+    fn test_accessible_disables_grid() {
+        let display = PasswordDisplay::new(true, false)
+            .with_accessible(true)
+            .with_grid(80);
+        assert_eq!(display.grid_columns(&batch("abcdefgh", 60)), None);
+    }
+
+    #[test]
+    fn test_strength_bar_fits_width() {
+        let entropy = EntropyInfo::new(80.4, "test");
+        let wide = PasswordDisplay::new(false, false);
+        let full = wide.render_strength(&entropy);
+        assert_eq!(full.matches(['█', '░']).count(), PasswordDisplay::BAR_WIDTH);
+
+        let narrow = PasswordDisplay::new(false, false).with_width(40);
+        let line = narrow.render_strength(&entropy);
+        assert!(measure_text_width(&line) <= 40, "{line:?}");
+        assert!(line.matches(['█', '░']).count() < PasswordDisplay::BAR_WIDTH);
+
+        // The bar never shrinks below a readable minimum
+        let tiny = PasswordDisplay::new(false, false).with_width(10);
+        assert_eq!(
+            tiny.render_strength(&entropy).matches(['█', '░']).count(),
+            PasswordDisplay::MIN_BAR_WIDTH
+        );
+    }
+
+    #[test]
+    fn test_header_truncated_to_width() {
+        let description = "Passphrase (6 words from the EFF large list, separated by '-')";
+        let wide = PasswordDisplay::new(false, false);
+        assert!(wide.fit_header(description, 5, 0).contains(description));
+
+        let narrow = PasswordDisplay::new(false, false).with_width(40);
+        let header = narrow.fit_header(description, 5, 3);
+        assert!(measure_text_width(&header) + 3 <= 40, "{header:?}");
+        assert!(header.contains('…'));
+        assert!(header.contains("Passphrase"));
+    }
+
+    #[test]
+    fn test_spelled_value_wraps() {
+        let display = PasswordDisplay::new(true, false)
+            .with_accessible(true)
+            .with_width(32);
+        let password = GeneratedPassword {
+            value: Zeroizing::new("Ab1!Cd2?".to_string()),
+            entropy: EntropyInfo::new(50.0, "test"),
+            mnemonic: None,
+        };
+        let text = display.render_accessible(&password, 1, 1);
+        let spelled: Vec<&str> = text
+            .lines()
+            .skip_while(|line| !line.starts_with("Spelled:"))
+            .take_while(|line| !line.starts_with("Strength:"))
+            .collect();
+        assert!(spelled.len() > 1, "{text}");
+        assert!(spelled.iter().all(|line| measure_text_width(line) <= 32));
+        assert!(spelled[0].starts_with("Spelled: capital A, lowercase b,"));
+        assert!(spelled.last().unwrap().ends_with("question mark."));
+    }
+
+    #[test]
+    fn test_grid_disabled_for_values_with_spaces() {
+        let display = PasswordDisplay::new(false, false).with_grid(200);
+        assert_eq!(display.grid_columns(&batch("otter, lantern", 12)), None);
+    }
+
+    #[test]
+    fn test_grid_disabled_when_values_too_wide() {
+        let display = PasswordDisplay::new(false, false).with_grid(40);
+        assert_eq!(display.grid_columns(&batch(&"x".repeat(30), 20)), None);
+    }
 }
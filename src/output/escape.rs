@@ -0,0 +1,68 @@
+//! Quoted literals for pasting a password into a shell (`--escape`)
+//!
+//! The value itself is unchanged; only what is printed gains quotes or escapes
+//! so the shell passes the password through literally.
+
+use zeroize::Zeroizing;
+
+use crate::cli::Shell;
+
+/// Characters cmd.exe treats specially outside quotes, each escaped with `^`
+const CMD_SPECIAL: &str = "^&|<>()%!\" \t";
+
+/// Quotes PowerShell accepts as a single quote, including the typographic ones
+const POWERSHELL_QUOTES: &str = "'\u{2018}\u{2019}\u{201A}\u{201B}";
+
+/// `value` as a literal for `shell`
+pub fn escape(value: &str, shell: Shell) -> Zeroizing<String> {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    match shell {
+        Shell::Posix => {
+            escaped.push('\'');
+            for c in value.chars() {
+                match c {
+                    '\'' => escaped.push_str("'\\''"),
+                    c => escaped.push(c),
+                }
+            }
+            escaped.push('\'');
+        }
+        Shell::Powershell => {
+            escaped.push('\'');
+            for c in value.chars() {
+                // Inside single quotes a quote is written twice; nothing else is special
+                if POWERSHELL_QUOTES.contains(c) {
+                    escaped.push(c);
+                }
+                escaped.push(c);
+            }
+            escaped.push('\'');
+        }
+        Shell::Cmd => {
+            for c in value.chars() {
+                if CMD_SPECIAL.contains(c) {
+                    escaped.push('^');
+                }
+                escaped.push(c);
+            }
+        }
+    }
+    Zeroizing::new(escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("it's", Shell::Posix).as_str(), "'it'\\''s'");
+        assert_eq!(escape("a$b`c'd", Shell::Powershell).as_str(), "'a$b`c''d'");
+        assert_eq!(
+            escape("x\u{2019}y", Shell::Powershell).as_str(),
+            "'x\u{2019}\u{2019}y'"
+        );
+        assert_eq!(escape("50%&a|b", Shell::Cmd).as_str(), "50^%^&a^|b");
+        assert_eq!(escape("plain", Shell::Cmd).as_str(), "plain");
+    }
+}
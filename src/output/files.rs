@@ -0,0 +1,153 @@
+//! One file per password (`--output-dir`, `--filename-template`)
+//!
+//! The layout Docker/Podman secrets and many config-management tools expect:
+//! each value alone in its own file, without a trailing newline, readable only
+//! by the owner.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::generators::GeneratedPassword;
+
+/// Placeholder replaced by the 1-based position in the batch
+pub const INDEX_PLACEHOLDER: &str = "{index}";
+
+pub const DEFAULT_TEMPLATE: &str = "password{index}.secret";
+
+#[derive(Debug, Error)]
+pub enum FilesError {
+    #[error("filename template '{0}' must be a plain file name without path separators")]
+    NotAFileName(String),
+    #[error("filename template '{0}' needs {INDEX_PLACEHOLDER} to name more than one file")]
+    MissingIndex(String),
+    #[error("cannot write {path}")]
+    Io { path: PathBuf, source: io::Error },
+}
+
+/// Writes each password of a batch to `dir/<template>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileWriter {
+    dir: PathBuf,
+    template: String,
+}
+
+impl FileWriter {
+    pub fn new(dir: impl Into<PathBuf>, template: &str) -> Result<Self, FilesError> {
+        let name = Path::new(template);
+        if template.is_empty()
+            || name.components().count() != 1
+            || name.file_name().is_none_or(|n| n != template)
+        {
+            return Err(FilesError::NotAFileName(template.to_string()));
+        }
+        Ok(Self {
+            dir: dir.into(),
+            template: template.to_string(),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path for the password at 1-based `index`
+    pub fn path_for(&self, index: usize) -> PathBuf {
+        self.dir
+            .join(self.template.replace(INDEX_PLACEHOLDER, &index.to_string()))
+    }
+
+    /// Write every password to its own new 0600 file; existing files are never overwritten
+    pub fn write_all(&self, passwords: &[GeneratedPassword]) -> Result<Vec<PathBuf>, FilesError> {
+        if passwords.len() > 1 && !self.template.contains(INDEX_PLACEHOLDER) {
+            return Err(FilesError::MissingIndex(self.template.clone()));
+        }
+        fs::create_dir_all(&self.dir).map_err(|source| FilesError::Io {
+            path: self.dir.clone(),
+            source,
+        })?;
+        passwords
+            .iter()
+            .enumerate()
+            .map(|(i, password)| {
+                let path = self.path_for(i + 1);
+                write_secret(&path, password.value.as_bytes())
+                    .map_err(|source| FilesError::Io {
+                        path: path.clone(),
+                        source,
+                    })
+                    .map(|()| path)
+            })
+            .collect()
+    }
+}
+
+/// Create `path` as a new 0600 file holding `secret`
+pub(crate) fn write_secret(path: &Path, secret: &[u8]) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(secret)?;
+    file.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entropy::EntropyInfo;
+    use zeroize::Zeroizing;
+
+    fn password(value: &str) -> GeneratedPassword {
+        GeneratedPassword {
+            value: Zeroizing::new(value.to_string()),
+            entropy: EntropyInfo::new(10.0, "test"),
+            mnemonic: None,
+        }
+    }
+
+    #[test]
+    fn test_template_validation() {
+        assert!(FileWriter::new("out", "../escape{index}").is_err());
+        assert!(FileWriter::new("out", "a/b").is_err());
+        assert!(FileWriter::new("out", "").is_err());
+        let writer = FileWriter::new("out", "user{index}.secret").unwrap();
+        assert_eq!(writer.path_for(3), Path::new("out/user3.secret"));
+    }
+
+    #[test]
+    fn test_write_all() {
+        let dir = std::env::temp_dir().join(format!("pwgen-files-{}", std::process::id()));
+        let writer = FileWriter::new(&dir, "user{index}.secret").unwrap();
+        let paths = writer
+            .write_all(&[password("first"), password("second")])
+            .unwrap();
+        assert_eq!(paths.len(), 2);
+        assert_eq!(fs::read_to_string(&paths[1]).unwrap(), "second");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&paths[0]).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // Existing secrets are never replaced
+        assert!(matches!(
+            writer.write_all(&[password("again")]),
+            Err(FilesError::Io { .. })
+        ));
+        let fixed = FileWriter::new(&dir, "db.secret").unwrap();
+        assert!(matches!(
+            fixed.write_all(&[password("a"), password("b")]),
+            Err(FilesError::MissingIndex(_))
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
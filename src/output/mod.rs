@@ -1,3 +1,10 @@
+pub mod accessible;
+pub mod classes;
 pub mod display;
+pub mod escape;
+pub mod files;
+pub mod print_layout;
+pub mod printable;
+pub mod share;
 
 pub use display::PasswordDisplay;
@@ -0,0 +1,92 @@
+//! Wrap-safe block layout of a passphrase for documents (`--print-layout`)
+//!
+//! The words are numbered in short rows and the separators are named once
+//! underneath, so however a word processor or mail client reflows the text,
+//! nothing can be read back as part of the passphrase that isn't.
+
+use zeroize::Zeroizing;
+
+/// Words per row; three of the longest list words fit a 72-column email
+pub const COLUMNS: usize = 3;
+
+/// Spaces between cells in a row
+const GAP: usize = 3;
+
+/// The block's lines, or None if `value` has no separators to split it at
+pub fn render(value: &str) -> Option<Zeroizing<Vec<String>>> {
+    let (words, separators) = split(value);
+    if separators.is_empty() {
+        return None;
+    }
+    let width = words.iter().map(|w| w.chars().count()).max().unwrap_or(0);
+    let number_width = words.len().to_string().len();
+    let mut lines = Zeroizing::new(Vec::new());
+    for (row, chunk) in words.chunks(COLUMNS).enumerate() {
+        let cells: Vec<String> = chunk
+            .iter()
+            .enumerate()
+            .map(|(col, word)| {
+                format!(
+                    "{:>nw$}. {:<w$}",
+                    row * COLUMNS + col + 1,
+                    word,
+                    nw = number_width,
+                    w = width
+                )
+            })
+            .collect();
+        lines.push(cells.join(&" ".repeat(GAP)).trim_end().to_string());
+    }
+    let quoted: Vec<String> = separators.iter().map(|s| format!("\"{}\"", s)).collect();
+    if separators.iter().all(|s| *s == separators[0]) {
+        lines.push(format!("Separator: {} between words", quoted[0]));
+    } else {
+        lines.push(format!("Separators, in order: {}", quoted.join(" ")));
+    }
+    Some(lines)
+}
+
+/// The words, and the runs of other characters between them
+fn split(value: &str) -> (Zeroizing<Vec<String>>, Vec<String>) {
+    let mut words = Zeroizing::new(vec![String::new()]);
+    let mut separators: Vec<String> = Vec::new();
+    let mut in_separator = false;
+    for c in value.chars() {
+        let is_word = c.is_alphanumeric();
+        if is_word && in_separator {
+            words.push(String::new());
+        } else if !is_word && !in_separator {
+            separators.push(String::new());
+        }
+        in_separator = !is_word;
+        let current = if in_separator {
+            separators.last_mut()
+        } else {
+            words.last_mut()
+        };
+        current.expect("a word or separator was started").push(c);
+    }
+    (words, separators)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_layout() {
+        let lines = render("Correct.horse.b4ttery.staple").unwrap();
+        assert_eq!(
+            *lines,
+            [
+                "1. Correct   2. horse     3. b4ttery",
+                "4. staple",
+                "Separator: \".\" between words",
+            ]
+        );
+
+        let lines = render("aa.bb_cc").unwrap();
+        assert_eq!(lines[1], "Separators, in order: \".\" \"_\"");
+        assert!(render("correcthorse").is_none());
+    }
+}
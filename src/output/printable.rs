@@ -0,0 +1,161 @@
+//! Password sheets to print and keep in a safe (`--format printable`)
+//!
+//! Master passwords and recovery keys often end up on paper. Each sheet is
+//! plain text that also reads as Markdown: the value spaced out in groups with
+//! a position ruler, spelled with the NATO alphabet, drawn as a QR code for
+//! paper, and followed by how and when it was made and a blank line for what
+//! it is used for.
+
+use zeroize::Zeroizing;
+
+use crate::output::accessible::symbol_name;
+use crate::qr::QrCode;
+
+/// Characters per group of the spaced-out value
+const GROUP: usize = 4;
+
+/// Groups per line of the spaced-out value
+const GROUPS_PER_LINE: usize = 4;
+
+/// Indent that makes a block monospaced in Markdown
+const CODE: &str = "    ";
+
+/// Page break between sheets
+pub const PAGE_BREAK: &str = "\u{c}";
+
+const NATO: [&str; 26] = [
+    "Alfa", "Bravo", "Charlie", "Delta", "Echo", "Foxtrot", "Golf", "Hotel", "India", "Juliett",
+    "Kilo", "Lima", "Mike", "November", "Oscar", "Papa", "Quebec", "Romeo", "Sierra", "Tango",
+    "Uniform", "Victor", "Whiskey", "X-ray", "Yankee", "Zulu",
+];
+
+const DIGITS: [&str; 10] = [
+    "Zero", "One", "Two", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine",
+];
+
+/// How `c` is read out, e.g. "capital Kilo", "lowercase x-ray" or "digit Seven"
+pub fn nato(c: char) -> String {
+    if c.is_ascii_uppercase() {
+        format!("capital {}", NATO[(c as u8 - b'A') as usize])
+    } else if c.is_ascii_lowercase() {
+        format!(
+            "lowercase {}",
+            NATO[(c as u8 - b'a') as usize].to_lowercase()
+        )
+    } else if c.is_ascii_digit() {
+        format!("digit {}", DIGITS[(c as u8 - b'0') as usize])
+    } else if let Some(name) = symbol_name(c) {
+        name.to_string()
+    } else if c.is_uppercase() {
+        format!("capital {}", c)
+    } else if c.is_lowercase() {
+        format!("lowercase {}", c)
+    } else {
+        format!("character U+{:04X}", c as u32)
+    }
+}
+
+/// One sheet for `value`
+///
+/// `details` are `(label, text)` lines for the Details section; without a
+/// `qr` code (the value was too long to encode) the section says so.
+pub fn render(
+    title: &str,
+    value: &str,
+    qr: Option<&QrCode>,
+    details: &[(&str, String)],
+) -> Zeroizing<String> {
+    let mut sheet = Zeroizing::new(format!("# {}\n\n", title));
+    sheet.push_str("Used for: ________________________________________\n\n");
+
+    let chars: Vec<char> = value.chars().collect();
+    for (row, line) in chars.chunks(GROUP * GROUPS_PER_LINE).enumerate() {
+        let start = row * GROUP * GROUPS_PER_LINE;
+        let groups: Vec<String> = line
+            .chunks(GROUP)
+            .map(|group| {
+                let spaced: Vec<String> = group
+                    .iter()
+                    .map(|&c| if c == ' ' { '␣' } else { c }.to_string())
+                    .collect();
+                spaced.join(" ")
+            })
+            .collect();
+        let ruler: Vec<String> = (0..groups.len())
+            .map(|i| format!("{:<w$}", start + i * GROUP + 1, w = GROUP * 2 - 1))
+            .collect();
+        sheet.push_str(&format!("{}{}\n", CODE, groups.join("   ")));
+        sheet.push_str(&format!("{}{}\n", CODE, ruler.join("   ").trim_end()));
+    }
+
+    sheet.push_str("\n## Spelled out\n\n");
+    let number_width = chars.len().to_string().len();
+    for (i, &c) in chars.iter().enumerate() {
+        let shown = if c == ' ' { '␣' } else { c };
+        sheet.push_str(&format!(
+            "{}{:>nw$}  {}  {}\n",
+            CODE,
+            i + 1,
+            shown,
+            nato(c),
+            nw = number_width
+        ));
+    }
+
+    sheet.push_str("\n## QR code\n\n");
+    match qr {
+        Some(code) => {
+            for line in code.render_for_paper() {
+                sheet.push_str(&format!("{}{}\n", CODE, line));
+            }
+        }
+        None => sheet.push_str("Too long to encode as a QR code.\n"),
+    }
+
+    sheet.push_str("\n## Details\n\n");
+    for (label, text) in details {
+        sheet.push_str(&format!("- {}: {}\n", label, text));
+    }
+    sheet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nato() {
+        assert_eq!(nato('K'), "capital Kilo");
+        assert_eq!(nato('x'), "lowercase x-ray");
+        assert_eq!(nato('7'), "digit Seven");
+        assert_eq!(nato('!'), "exclamation mark");
+        assert_eq!(nato('É'), "capital É");
+        assert_eq!(nato('\u{3000}'), "character U+3000");
+    }
+
+    #[test]
+    fn test_render_sheet() {
+        let value = "Kx7!a9Qz D<(=j(|G2";
+        let code = QrCode::encode(value.as_bytes()).unwrap();
+        let details = [("Entropy", "103.4 bits (Very Strong)".to_string())];
+        let sheet = render("Password", value, Some(&code), &details);
+        let lines: Vec<&str> = sheet.lines().collect();
+        assert_eq!(lines[0], "# Password");
+        assert_eq!(
+            lines[2],
+            "Used for: ________________________________________"
+        );
+        assert_eq!(lines[4], "    K x 7 !   a 9 Q z   ␣ D < (   = j ( |");
+        assert_eq!(lines[5], "    1         5         9         13");
+        assert_eq!(lines[6], "    G 2");
+        assert_eq!(lines[7], "    17");
+        assert!(sheet.contains("\n     1  K  capital Kilo\n"));
+        assert!(sheet.contains("\n     9  ␣  space\n"));
+        assert!(sheet.contains("\n    18  2  digit Two\n"));
+        assert!(sheet.contains("█"));
+        assert!(sheet.ends_with("## Details\n\n- Entropy: 103.4 bits (Very Strong)\n"));
+
+        let sheet = render("Password", value, None, &[]);
+        assert!(sheet.contains("## QR code\n\nToo long to encode as a QR code.\n"));
+    }
+}
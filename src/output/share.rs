@@ -0,0 +1,284 @@
+//! One encrypted copy per recipient (`--share-with`, `--share-dir`)
+//!
+//! Handing a new credential to several admins shouldn't mean pasting it into
+//! a chat. Each recipient gets their own `<name>.age` file, encrypted with the
+//! system `age` (or a compatible tool such as `rage`, via `PWGEN_RS_AGE`) to
+//! their age or SSH public key, so only they can open it and the files can go
+//! through any channel.
+
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use thiserror::Error;
+
+use crate::audit::expand_home;
+use crate::output::files::write_secret;
+
+/// Program run to encrypt, unless `PWGEN_RS_AGE` names another
+pub const DEFAULT_PROGRAM: &str = "age";
+
+/// Extension of the encrypted files
+pub const EXTENSION: &str = "age";
+
+#[derive(Debug, Error)]
+pub enum ShareError {
+    #[error("could not run {program}; install age or point PWGEN_RS_AGE at it")]
+    Spawn { program: String, source: io::Error },
+    #[error("encrypting to {recipient} failed: {message}")]
+    Failed { recipient: String, message: String },
+    #[error("public key file {0} not found")]
+    MissingKeyFile(PathBuf),
+    #[error("two recipients would share {0}; rename one of the key files")]
+    DuplicateName(PathBuf),
+    #[error("{0} already exists")]
+    Exists(PathBuf),
+    #[error("cannot write {path}")]
+    Io { path: PathBuf, source: io::Error },
+}
+
+/// Who a copy is encrypted to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Recipient {
+    /// A key given inline: `age1...` or `ssh-ed25519 AAAA...`
+    Key(String),
+    /// A file of public keys, such as `alice.pub` or `~/.ssh/id_ed25519.pub`
+    File(PathBuf),
+}
+
+impl Recipient {
+    /// Inline keys start like age and OpenSSH public keys; anything else is a
+    /// path, where a leading `~/` is the home directory
+    pub fn parse(spec: &str) -> Self {
+        let spec = spec.trim();
+        if spec.starts_with("age1") || spec.starts_with("ssh-") {
+            Recipient::Key(spec.to_string())
+        } else {
+            Recipient::File(expand_home(spec))
+        }
+    }
+
+    /// File name stem: the key file's own (`alice.pub` → `alice`), or
+    /// `recipient<position>` for an inline key
+    fn name(&self, position: usize) -> String {
+        match self {
+            Recipient::File(path) => path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| format!("recipient{}", position)),
+            Recipient::Key(_) => format!("recipient{}", position),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Recipient::File(path) => path.display().to_string(),
+            Recipient::Key(key) => {
+                let mut words = key.split_whitespace();
+                let kind = words.next().unwrap_or_default();
+                match words.nth(1) {
+                    Some(comment) => format!("{} key {}", kind, comment),
+                    None if kind.len() > 16 => format!("{}…", &kind[..16]),
+                    None => kind.to_string(),
+                }
+            }
+        }
+    }
+}
+
+/// Encrypts one secret to each recipient, into `dir/<name>.age`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sharer {
+    dir: PathBuf,
+    recipients: Vec<(Recipient, PathBuf)>,
+    program: OsString,
+}
+
+impl Sharer {
+    pub fn new(dir: impl Into<PathBuf>, specs: &[String]) -> Result<Self, ShareError> {
+        let dir = dir.into();
+        let mut recipients: Vec<(Recipient, PathBuf)> = Vec::with_capacity(specs.len());
+        for (i, spec) in specs.iter().enumerate() {
+            let recipient = Recipient::parse(spec);
+            if let Recipient::File(path) = &recipient {
+                if !path.is_file() {
+                    return Err(ShareError::MissingKeyFile(path.clone()));
+                }
+            }
+            let path = dir.join(format!("{}.{}", recipient.name(i + 1), EXTENSION));
+            if recipients.iter().any(|(_, taken)| *taken == path) {
+                return Err(ShareError::DuplicateName(path));
+            }
+            recipients.push((recipient, path));
+        }
+        Ok(Self {
+            dir,
+            recipients,
+            program: std::env::var_os("PWGEN_RS_AGE").unwrap_or_else(|| DEFAULT_PROGRAM.into()),
+        })
+    }
+
+    /// Encrypt with `program` instead of `age`
+    pub fn with_program(mut self, program: impl Into<OsString>) -> Self {
+        self.program = program.into();
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Write `secret` encrypted to every recipient
+    ///
+    /// Every copy is encrypted before any is written, so nothing is written if
+    /// a recipient fails or one of the files already exists.
+    pub fn share(&self, secret: &str) -> Result<Vec<PathBuf>, ShareError> {
+        if let Some((_, path)) = self.recipients.iter().find(|(_, path)| path.exists()) {
+            return Err(ShareError::Exists(path.clone()));
+        }
+        let encrypted = self
+            .recipients
+            .iter()
+            .map(|(recipient, _)| self.encrypt(recipient, secret))
+            .collect::<Result<Vec<_>, _>>()?;
+        fs::create_dir_all(&self.dir).map_err(|source| ShareError::Io {
+            path: self.dir.clone(),
+            source,
+        })?;
+        self.recipients
+            .iter()
+            .zip(encrypted)
+            .map(|((_, path), copy)| {
+                write_secret(path, &copy)
+                    .map_err(|source| ShareError::Io {
+                        path: path.clone(),
+                        source,
+                    })
+                    .map(|()| path.clone())
+            })
+            .collect()
+    }
+
+    fn encrypt(&self, recipient: &Recipient, secret: &str) -> Result<Vec<u8>, ShareError> {
+        let spawn_error = |source| ShareError::Spawn {
+            program: self.program.to_string_lossy().into_owned(),
+            source,
+        };
+        let mut command = Command::new(&self.program);
+        command.arg("--encrypt");
+        match recipient {
+            Recipient::Key(key) => command.arg("-r").arg(key),
+            Recipient::File(path) => command.arg("-R").arg(path),
+        };
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(spawn_error)?;
+        // A tool that rejects the key may exit before reading; its status says why
+        let written = match child.stdin.take() {
+            Some(mut stdin) => stdin.write_all(secret.as_bytes()),
+            None => Ok(()),
+        };
+        let output = child.wait_with_output().map_err(spawn_error)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(ShareError::Failed {
+                recipient: recipient.describe(),
+                message: stderr
+                    .lines()
+                    .map(str::trim)
+                    .find(|line| !line.is_empty())
+                    .map_or_else(|| output.status.to_string(), str::to_string),
+            });
+        }
+        written.map_err(spawn_error)?;
+        Ok(output.stdout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recipients() {
+        assert_eq!(
+            Recipient::parse("age1qyqszqgpqyqszqgp"),
+            Recipient::Key("age1qyqszqgpqyqszqgp".into())
+        );
+        assert_eq!(
+            Recipient::parse(" keys/bob.pub"),
+            Recipient::File("keys/bob.pub".into())
+        );
+        let ssh = Recipient::parse("ssh-ed25519 AAAAC3NzaC1lZDI1NTE5 carol@laptop");
+        assert_eq!(ssh.describe(), "ssh-ed25519 key carol@laptop");
+        assert_eq!(ssh.name(2), "recipient2");
+        assert_eq!(Recipient::File("keys/bob.pub".into()).name(1), "bob");
+
+        assert!(matches!(
+            Sharer::new("out", &["missing/alice.pub".to_string()]),
+            Err(ShareError::MissingKeyFile(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_share() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("pwgen-share-{}", std::process::id()));
+        let keys = dir.join("keys");
+        fs::create_dir_all(keys.join("other")).unwrap();
+        fs::write(keys.join("alice.pub"), "age1alice\n").unwrap();
+        fs::write(keys.join("other/alice.pub"), "age1alice2\n").unwrap();
+        // Stands in for age: prints its arguments, then the plaintext
+        let program = dir.join("fake-age");
+        fs::write(&program, "#!/bin/sh\necho \"$@\"\ncat\n").unwrap();
+        fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let out = dir.join("out");
+        let specs = [
+            keys.join("alice.pub").display().to_string(),
+            "age1bob".to_string(),
+        ];
+        let sharer = Sharer::new(&out, &specs).unwrap().with_program(&program);
+        let paths = sharer.share("s3cret").unwrap();
+        assert_eq!(paths, [out.join("alice.age"), out.join("recipient2.age")]);
+        assert_eq!(
+            fs::read_to_string(&paths[0]).unwrap(),
+            format!("--encrypt -R {}\ns3cret", specs[0])
+        );
+        assert_eq!(
+            fs::read_to_string(&paths[1]).unwrap(),
+            "--encrypt -r age1bob\ns3cret"
+        );
+        let mode = fs::metadata(&paths[0]).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        // Existing copies are never replaced, and clashing names are refused
+        assert!(matches!(sharer.share("again"), Err(ShareError::Exists(_))));
+        let clash = [
+            keys.join("alice.pub").display().to_string(),
+            keys.join("other/alice.pub").display().to_string(),
+        ];
+        assert!(matches!(
+            Sharer::new(&out, &clash),
+            Err(ShareError::DuplicateName(_))
+        ));
+
+        let failing = Sharer::new(dir.join("failed"), &["age1bob".to_string()])
+            .unwrap()
+            .with_program("false");
+        assert!(matches!(
+            failing.share("s3cret"),
+            Err(ShareError::Failed { .. })
+        ));
+        assert!(!dir.join("failed").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
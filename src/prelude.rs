@@ -0,0 +1,10 @@
+//! The types most programs embedding the generators need: `use pwgen_x::prelude::*;`
+
+pub use crate::entropy::{EntropyInfo, StrengthLevel};
+pub use crate::error::PwgenError;
+pub use crate::generators::{
+    CharSet, GeneratedPassword, LengthMode, PasswordGenerator, PasswordGeneratorExt, SymbolSet,
+};
+pub use crate::rng::PwgenRng;
+pub use crate::spec::PasswordSpec;
+pub use crate::wordlist::Wordlist;
@@ -0,0 +1,128 @@
+//! Hidden terminal prompts for secrets
+//!
+//! Secrets given as arguments show up in process listings and shell history,
+//! so commands that need one read it here instead: without echo, into a
+//! zeroized buffer, optionally entered twice. Keystrokes arriving faster than
+//! anyone types mark the input as pasted, so the caller can remind the user
+//! that the clipboard still holds it.
+
+use std::io;
+use std::time::{Duration, Instant};
+
+use console::{Key, Term};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use crate::redacted::Redacted;
+
+/// Keystrokes closer together than this are too fast to be typed
+const PASTE_GAP: Duration = Duration::from_millis(5);
+/// Consecutive fast keystrokes that count as a paste
+const PASTE_RUN: usize = 4;
+
+#[derive(Debug, Error)]
+pub enum PromptError {
+    #[error("reading a secret needs an interactive terminal")]
+    NotATerminal,
+    #[error("interrupted")]
+    Interrupted,
+    #[error("the two entries did not match")]
+    Mismatch,
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// A secret read from the terminal
+pub struct Secret {
+    pub value: Zeroizing<String>,
+    /// Whether the input looked pasted rather than typed
+    pub pasted: bool,
+}
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Secret")
+            .field("value", &Redacted::of(&self.value))
+            .field("pasted", &self.pasted)
+            .finish()
+    }
+}
+
+/// Show `prompt` on `term` and read a line without echoing it
+pub fn read_secret(term: &Term, prompt: &str) -> Result<Secret, PromptError> {
+    if !term.is_term() {
+        return Err(PromptError::NotATerminal);
+    }
+    term.write_str(prompt)?;
+    let mut value = Zeroizing::new(String::new());
+    let mut gaps = Vec::new();
+    let mut last = None;
+    let result = loop {
+        let key = match term.read_key() {
+            Ok(key) => key,
+            Err(e) => break Err(e.into()),
+        };
+        let now = Instant::now();
+        if let Some(previous) = last.replace(now) {
+            gaps.push(now - previous);
+        }
+        match key {
+            Key::Enter => break Ok(()),
+            Key::CtrlC => break Err(PromptError::Interrupted),
+            Key::Backspace => {
+                value.pop();
+            }
+            Key::Char(c) if !c.is_control() => value.push(c),
+            _ => {}
+        }
+    };
+    term.write_line("")?;
+    result.map(|()| Secret {
+        value,
+        pasted: looks_pasted(&gaps),
+    })
+}
+
+/// Read a secret twice and fail unless both entries match
+pub fn read_secret_confirmed(
+    term: &Term,
+    prompt: &str,
+    confirm_prompt: &str,
+) -> Result<Secret, PromptError> {
+    let first = read_secret(term, prompt)?;
+    let second = read_secret(term, confirm_prompt)?;
+    if first.value != second.value {
+        return Err(PromptError::Mismatch);
+    }
+    Ok(Secret {
+        value: first.value,
+        pasted: first.pasted || second.pasted,
+    })
+}
+
+/// Whether the gaps between keystrokes contain a run too fast to be typed
+fn looks_pasted(gaps: &[Duration]) -> bool {
+    gaps.split(|gap| *gap >= PASTE_GAP)
+        .any(|run| run.len() + 1 >= PASTE_RUN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paste_detection() {
+        let typed = vec![Duration::from_millis(120); 12];
+        assert!(!looks_pasted(&typed));
+        assert!(!looks_pasted(&[]));
+
+        let mut pasted = typed.clone();
+        pasted.splice(4..4, vec![Duration::from_micros(50); 3]);
+        assert!(looks_pasted(&pasted));
+
+        // Two quick keys in a row happen when typing too
+        let mut double = typed;
+        double.insert(3, Duration::from_millis(1));
+        assert!(!looks_pasted(&double));
+    }
+}
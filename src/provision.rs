@@ -0,0 +1,265 @@
+//! Passwords for a list of users (`provision`)
+//!
+//! Reads a CSV file with a header row, takes the user names from one column and
+//! pairs each with a fresh password, written as CSV or JSON for an identity
+//! management import. Two optional columns override the policy per row:
+//! `length` (characters, or words for passphrases) and `policy`, a generator
+//! command line such as `phrase 6` or `secure -S alphanumeric`. Empty cells
+//! fall back to the command's own generator.
+
+use serde_json::{json, Value};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use crate::cli::OutputFormat;
+use crate::generators::GeneratedPassword;
+use crate::output::display::push_csv_field;
+
+/// Optional column overriding the length or word count
+pub const LENGTH_COLUMN: &str = "length";
+/// Optional column holding a generator command line
+pub const POLICY_COLUMN: &str = "policy";
+/// Output flag set for temporary passwords
+pub const MUST_CHANGE_COLUMN: &str = "must_change_at_first_login";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ProvisionError {
+    #[error("the CSV has no header row")]
+    Empty,
+    #[error("no '{column}' column; the header has: {available}")]
+    MissingColumn { column: String, available: String },
+    #[error("line {line}: unterminated quoted field")]
+    UnterminatedQuote { line: usize },
+    #[error("line {line}: empty user name")]
+    EmptyUser { line: usize },
+    #[error("line {line}: '{value}' is not a length")]
+    BadLength { line: usize, value: String },
+}
+
+/// One user to provision, with the row's policy overrides
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserRow {
+    /// Line in the CSV file where the row starts, for error messages
+    pub line: usize,
+    pub user: String,
+    pub length: Option<usize>,
+    pub policy: Option<String>,
+}
+
+/// A user paired with their new password
+#[derive(Debug)]
+pub struct Account {
+    pub user: String,
+    pub password: GeneratedPassword,
+    /// Temporary password the user must change at first login
+    pub must_change: bool,
+}
+
+/// The users in `column` of a CSV file, skipping blank lines
+pub fn read_users(text: &str, column: &str) -> Result<Vec<UserRow>, ProvisionError> {
+    let mut records = parse_csv(text)?.into_iter();
+    let (_, header) = records.next().ok_or(ProvisionError::Empty)?;
+    let position = |name: &str| header.iter().position(|h| h.trim() == name);
+    let user_index = position(column).ok_or_else(|| ProvisionError::MissingColumn {
+        column: column.to_string(),
+        available: header.join(", "),
+    })?;
+    let length_index = position(LENGTH_COLUMN);
+    let policy_index = position(POLICY_COLUMN);
+    let cell = |record: &[String], index: Option<usize>| {
+        index
+            .and_then(|i| record.get(i))
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    records
+        .filter(|(_, record)| record.iter().any(|field| !field.trim().is_empty()))
+        .map(|(line, record)| {
+            let user = cell(&record, Some(user_index)).ok_or(ProvisionError::EmptyUser { line })?;
+            let length = cell(&record, length_index)
+                .map(|value| {
+                    value
+                        .parse()
+                        .map_err(|_| ProvisionError::BadLength { line, value })
+                })
+                .transpose()?;
+            Ok(UserRow {
+                line,
+                user,
+                length,
+                policy: cell(&record, policy_index),
+            })
+        })
+        .collect()
+}
+
+/// Records of an RFC 4180 CSV file, each with the line it starts on
+fn parse_csv(text: &str) -> Result<Vec<(usize, Vec<String>)>, ProvisionError> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            '\n' if quoted => {
+                line += 1;
+                field.push(c);
+            }
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push((start, std::mem::take(&mut record)));
+                line += 1;
+                start = line;
+            }
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(ProvisionError::UnterminatedQuote { line: start });
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((start, record));
+    }
+    Ok(records)
+}
+
+/// Accounts as a `username,password` CSV (for text and printable), a JSON array
+/// of objects, or JSON lines
+///
+/// When any password is temporary, the CSV gets a `must_change_at_first_login`
+/// column; JSON always carries the flag.
+pub fn render(accounts: &[Account], column: &str, format: OutputFormat) -> Zeroizing<String> {
+    let mut out = Zeroizing::new(String::new());
+    match format {
+        OutputFormat::Text | OutputFormat::Printable => {
+            let flag = accounts.iter().any(|account| account.must_change);
+            push_csv_field(&mut out, column);
+            out.push_str(",password");
+            if flag {
+                out.push_str(&format!(",{}", MUST_CHANGE_COLUMN));
+            }
+            out.push('\n');
+            for account in accounts {
+                push_csv_field(&mut out, &account.user);
+                out.push(',');
+                push_csv_field(&mut out, &account.password.value);
+                if flag {
+                    out.push_str(if account.must_change {
+                        ",true"
+                    } else {
+                        ",false"
+                    });
+                }
+                out.push('\n');
+            }
+        }
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let entries: Vec<Value> = accounts
+                .iter()
+                .map(|account| {
+                    json!({
+                        column: account.user,
+                        "password": account.password.value.as_str(),
+                        "entropy_bits": account.password.entropy.bits,
+                        MUST_CHANGE_COLUMN: account.must_change,
+                    })
+                })
+                .collect();
+            if format == OutputFormat::Jsonl {
+                for entry in &entries {
+                    out.push_str(&format!("{}\n", entry));
+                }
+            } else {
+                out.push_str(&Value::Array(entries).to_string());
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entropy::EntropyInfo;
+
+    #[test]
+    fn test_read_users() {
+        let text = "id,username,length,policy\r\n\
+                    1,alice,,\r\n\
+                    \r\n\
+                    2,\"bob, jr\",24,\r\n\
+                    3,carol,,phrase 5\r\n";
+        let users = read_users(text, "username").unwrap();
+        assert_eq!(users.len(), 3);
+        assert_eq!(users[0].user, "alice");
+        assert_eq!(users[0].length, None);
+        assert_eq!(users[1].user, "bob, jr");
+        assert_eq!(users[1].length, Some(24));
+        assert_eq!(users[1].line, 4);
+        assert_eq!(users[2].policy.as_deref(), Some("phrase 5"));
+
+        assert!(matches!(
+            read_users(text, "email"),
+            Err(ProvisionError::MissingColumn { .. })
+        ));
+        assert_eq!(
+            read_users("username,length\nalice,long\n", "username"),
+            Err(ProvisionError::BadLength {
+                line: 2,
+                value: "long".to_string()
+            })
+        );
+        assert_eq!(
+            read_users("username\n\"alice\n", "username"),
+            Err(ProvisionError::UnterminatedQuote { line: 2 })
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let account = |user: &str, value: &str, must_change| Account {
+            user: user.to_string(),
+            password: GeneratedPassword {
+                value: Zeroizing::new(value.to_string()),
+                entropy: EntropyInfo::new(40.0, "test"),
+                mnemonic: None,
+            },
+            must_change,
+        };
+        let mut accounts = vec![account("bob, jr", "a\"b", false)];
+        assert_eq!(
+            render(&accounts, "username", OutputFormat::Text).as_str(),
+            "username,password\n\"bob, jr\",\"a\"\"b\"\n"
+        );
+        let json: Value =
+            serde_json::from_str(&render(&accounts, "login", OutputFormat::Json)).unwrap();
+        assert_eq!(json[0]["login"], "bob, jr");
+        assert_eq!(json[0]["password"], "a\"b");
+        assert_eq!(json[0][MUST_CHANGE_COLUMN], false);
+        let line: Value =
+            serde_json::from_str(&render(&accounts, "login", OutputFormat::Jsonl)).unwrap();
+        assert_eq!(line["login"], "bob, jr");
+
+        accounts.push(account("carol", "Tamuvo42ra", true));
+        assert_eq!(
+            render(&accounts, "username", OutputFormat::Text).as_str(),
+            "username,password,must_change_at_first_login\n\
+             \"bob, jr\",\"a\"\"b\",false\n\
+             carol,Tamuvo42ra,true\n"
+        );
+    }
+}
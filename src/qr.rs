@@ -0,0 +1,558 @@
+//! QR codes drawn in the terminal with Unicode half blocks (`--qr`)
+//!
+//! A freshly generated password, or a Wi-Fi join string carrying it, can be
+//! scanned straight off the screen with a phone. Encoding is byte mode at error
+//! correction level M (about 15% damage tolerated), in the smallest of the 40
+//! versions that fits; like [`digest`](crate::digest), the encoder is small
+//! enough to carry here rather than pull in a dependency.
+//!
+//! Each printed line covers two module rows. Light modules are drawn with
+//! block characters and dark ones left as background, which reads correctly on
+//! a dark terminal; the display forces white on black when colors are on.
+
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum QrError {
+    #[error("{len} bytes is too long for a QR code (at most {max})")]
+    TooLong { len: usize, max: usize },
+}
+
+/// Light modules around the code; the standard asks for 4, but 2 scans fine off a screen
+pub const QUIET_ZONE: usize = 2;
+
+/// Error correction codewords per block at level M, by version
+const ECC_CODEWORDS_PER_BLOCK: [usize; 41] = [
+    0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28, 28,
+    28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28,
+];
+
+/// Error correction blocks at level M, by version
+const NUM_BLOCKS: [usize; 41] = [
+    0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21, 23,
+    25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49,
+];
+
+/// Format bits of error correction level M
+const LEVEL_M: u32 = 0;
+
+/// What `--qr` encodes for each password
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QrContent {
+    /// The password itself
+    Password,
+    /// A Wi-Fi join string (WPA) for the network with this SSID
+    Wifi(String),
+}
+
+impl QrContent {
+    /// The text to encode for `password`
+    pub fn payload(&self, password: &str) -> Zeroizing<String> {
+        match self {
+            QrContent::Password => Zeroizing::new(password.to_string()),
+            QrContent::Wifi(ssid) => Zeroizing::new(format!(
+                "WIFI:T:WPA;S:{};P:{};;",
+                escape_wifi(ssid).as_str(),
+                escape_wifi(password).as_str()
+            )),
+        }
+    }
+}
+
+/// Backslash-escape the characters that delimit Wi-Fi join string fields
+fn escape_wifi(value: &str) -> Zeroizing<String> {
+    let mut escaped = Zeroizing::new(String::with_capacity(value.len()));
+    for c in value.chars() {
+        if matches!(c, '\\' | ';' | ',' | ':' | '"') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// An encoded QR symbol
+pub struct QrCode {
+    size: usize,
+    /// Dark modules, row by row
+    modules: Vec<bool>,
+    /// Finder, timing, alignment, format and version modules, which masks skip
+    function: Vec<bool>,
+}
+
+impl QrCode {
+    /// Encode `data` in byte mode in the smallest version that holds it
+    pub fn encode(data: &[u8]) -> Result<Self, QrError> {
+        let version = (1..=40)
+            .find(|&v| 4 + count_bits(v) + data.len() * 8 <= data_codewords(v) * 8)
+            .ok_or(QrError::TooLong {
+                len: data.len(),
+                max: data_codewords(40) - 3,
+            })?;
+
+        let mut bits = Bits::default();
+        bits.push(0b0100, 4);
+        bits.push(data.len() as u32, count_bits(version));
+        for &byte in data {
+            bits.push(byte as u32, 8);
+        }
+        let capacity = data_codewords(version) * 8;
+        bits.push(0, (capacity - bits.len()).min(4));
+        bits.push(0, (8 - bits.len() % 8) % 8);
+        let mut codewords = bits.into_bytes();
+        for pad in [0xEC, 0x11].iter().cycle() {
+            if codewords.len() == data_codewords(version) {
+                break;
+            }
+            codewords.push(*pad);
+        }
+
+        let size = version * 4 + 17;
+        let mut qr = Self {
+            size,
+            modules: vec![false; size * size],
+            function: vec![false; size * size],
+        };
+        qr.draw_function_patterns(version);
+        qr.draw_codewords(&add_ecc_and_interleave(version, &codewords));
+
+        let mask = (0..8)
+            .min_by_key(|&mask| {
+                qr.apply_mask(mask);
+                qr.draw_format_bits(mask);
+                let penalty = qr.penalty();
+                qr.apply_mask(mask);
+                penalty
+            })
+            .unwrap_or(0);
+        qr.apply_mask(mask);
+        qr.draw_format_bits(mask);
+        Ok(qr)
+    }
+
+    /// Modules per side
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Whether the module at column `x`, row `y` is dark; outside the symbol is light
+    pub fn is_dark(&self, x: isize, y: isize) -> bool {
+        let size = self.size as isize;
+        (0..size).contains(&x) && (0..size).contains(&y) && self.get(x as usize, y as usize)
+    }
+
+    /// Lines of half-block characters, quiet zone included, light modules drawn
+    pub fn render(&self) -> Vec<String> {
+        self.half_blocks(false)
+    }
+
+    /// Like [`render`](Self::render), but with the dark modules drawn, for paper
+    pub fn render_for_paper(&self) -> Vec<String> {
+        self.half_blocks(true)
+    }
+
+    /// Two module rows per line, drawing the modules whose darkness is `drawn`
+    fn half_blocks(&self, drawn: bool) -> Vec<String> {
+        let quiet = QUIET_ZONE as isize;
+        let end = self.size as isize + quiet;
+        let ink = |x: isize, y: isize| y < end && self.is_dark(x, y) == drawn;
+        (-quiet..end)
+            .step_by(2)
+            .map(|y| {
+                (-quiet..end)
+                    .map(|x| match (ink(x, y), ink(x, y + 1)) {
+                        (true, true) => '█',
+                        (true, false) => '▀',
+                        (false, true) => '▄',
+                        (false, false) => ' ',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+        self.function[y * self.size + x] = true;
+    }
+
+    fn draw_function_patterns(&mut self, version: usize) {
+        let size = self.size;
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+
+        // Finders with their separators
+        for (cx, cy) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            for dy in -4isize..=4 {
+                for dx in -4isize..=4 {
+                    let (x, y) = (cx as isize + dx, cy as isize + dy);
+                    if (0..size as isize).contains(&x) && (0..size as isize).contains(&y) {
+                        let dist = dx.abs().max(dy.abs());
+                        self.set_function(x as usize, y as usize, dist != 2 && dist != 4);
+                    }
+                }
+            }
+        }
+
+        let positions = alignment_positions(version);
+        let last = positions.len().saturating_sub(1);
+        for (i, &cy) in positions.iter().enumerate() {
+            for (j, &cx) in positions.iter().enumerate() {
+                // The three corners already hold finders
+                if (i == 0 && (j == 0 || j == last)) || (i == last && j == 0) {
+                    continue;
+                }
+                for dy in -2isize..=2 {
+                    for dx in -2isize..=2 {
+                        let dark = dx.abs().max(dy.abs()) != 1;
+                        self.set_function(
+                            (cx as isize + dx) as usize,
+                            (cy as isize + dy) as usize,
+                            dark,
+                        );
+                    }
+                }
+            }
+        }
+
+        // Reserve the format areas; the real bits are drawn once the mask is known
+        self.draw_format_bits(0);
+
+        if version >= 7 {
+            let mut rem = version as u32;
+            for _ in 0..12 {
+                rem = (rem << 1) ^ ((rem >> 11) * 0x1F25);
+            }
+            let bits = (version as u32) << 12 | rem;
+            for i in 0..18 {
+                let dark = (bits >> i) & 1 != 0;
+                let (a, b) = (size - 11 + i % 3, i / 3);
+                self.set_function(a, b, dark);
+                self.set_function(b, a, dark);
+            }
+        }
+    }
+
+    fn draw_format_bits(&mut self, mask: u32) {
+        let data = LEVEL_M << 3 | mask;
+        let mut rem = data;
+        for _ in 0..10 {
+            rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+        }
+        let bits = (data << 10 | rem) ^ 0x5412;
+        let bit = |i: usize| (bits >> i) & 1 != 0;
+        let size = self.size;
+
+        for i in 0..=5 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    /// Place the codewords in the two-column zigzag, skipping function modules
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let total = codewords.len() * 8;
+        let mut i = 0;
+        let mut right = size as isize - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            for vert in 0..size {
+                for j in 0..2 {
+                    let x = (right - j) as usize;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward { size - 1 - vert } else { vert };
+                    if !self.function[y * size + x] && i < total {
+                        self.modules[y * size + x] = (codewords[i >> 3] >> (7 - (i & 7))) & 1 != 0;
+                        i += 1;
+                    }
+                }
+            }
+            right -= 2;
+        }
+    }
+
+    /// XOR a mask pattern over the data modules; applying it twice undoes it
+    fn apply_mask(&mut self, mask: u32) {
+        let size = self.size;
+        for y in 0..size {
+            for x in 0..size {
+                let flip = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                if flip && !self.function[y * size + x] {
+                    self.modules[y * size + x] ^= true;
+                }
+            }
+        }
+    }
+
+    /// The standard's penalty score; the mask with the lowest one is used
+    fn penalty(&self) -> usize {
+        let size = self.size;
+        let mut penalty = 0;
+        let lines = (0..size).flat_map(|i| {
+            [
+                (0..size).map(|j| self.get(j, i)).collect::<Vec<_>>(),
+                (0..size).map(|j| self.get(i, j)).collect::<Vec<_>>(),
+            ]
+        });
+        for line in lines {
+            // Runs of five or more
+            let mut run = 1;
+            for k in 1..=size {
+                if k < size && line[k] == line[k - 1] {
+                    run += 1;
+                    continue;
+                }
+                if run >= 5 {
+                    penalty += run - 2;
+                }
+                run = 1;
+            }
+            // Finder look-alikes with four light modules on either side
+            const PATTERN: [bool; 7] = [true, false, true, true, true, false, true];
+            for k in 0..size.saturating_sub(6) {
+                if line[k..k + 7] != PATTERN {
+                    continue;
+                }
+                let light_before = k >= 4 && line[k - 4..k].iter().all(|&d| !d);
+                let light_after = k + 11 <= size && line[k + 7..k + 11].iter().all(|&d| !d);
+                if light_before || light_after {
+                    penalty += 40;
+                }
+            }
+        }
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let dark = self.get(x, y);
+                if dark == self.get(x + 1, y)
+                    && dark == self.get(x, y + 1)
+                    && dark == self.get(x + 1, y + 1)
+                {
+                    penalty += 3;
+                }
+            }
+        }
+        let dark = self.modules.iter().filter(|&&d| d).count();
+        let percent = dark * 100 / self.modules.len();
+        penalty + percent.abs_diff(50) / 5 * 10
+    }
+}
+
+/// Bits of the character count field in byte mode
+fn count_bits(version: usize) -> usize {
+    if version <= 9 {
+        8
+    } else {
+        16
+    }
+}
+
+/// Modules left for data and error correction once the function patterns are drawn
+fn raw_data_modules(version: usize) -> usize {
+    let mut modules = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let align = version / 7 + 2;
+        modules -= (25 * align - 10) * align - 55;
+        if version >= 7 {
+            modules -= 36;
+        }
+    }
+    modules
+}
+
+fn data_codewords(version: usize) -> usize {
+    raw_data_modules(version) / 8 - ECC_CODEWORDS_PER_BLOCK[version] * NUM_BLOCKS[version]
+}
+
+/// Centers of the alignment patterns along each axis
+fn alignment_positions(version: usize) -> Vec<usize> {
+    if version == 1 {
+        return Vec::new();
+    }
+    let align = version / 7 + 2;
+    let step = (version * 8 + align * 3 + 5) / (align * 4 - 4) * 2;
+    let size = version * 4 + 17;
+    let mut positions: Vec<usize> = (0..align - 1).map(|i| size - 7 - i * step).collect();
+    positions.push(6);
+    positions.reverse();
+    positions
+}
+
+/// Split into blocks, append each block's Reed-Solomon codewords, then interleave
+fn add_ecc_and_interleave(version: usize, data: &[u8]) -> Vec<u8> {
+    let blocks = NUM_BLOCKS[version];
+    let ecc_len = ECC_CODEWORDS_PER_BLOCK[version];
+    let raw = raw_data_modules(version) / 8;
+    let short_blocks = blocks - raw % blocks;
+    let short_len = raw / blocks;
+    let divisor = rs_divisor(ecc_len);
+
+    let mut split = Vec::with_capacity(blocks);
+    let mut start = 0;
+    for i in 0..blocks {
+        let len = short_len - ecc_len + usize::from(i >= short_blocks);
+        let block = &data[start..start + len];
+        start += len;
+        split.push((block, rs_remainder(block, &divisor)));
+    }
+
+    let mut result = Vec::with_capacity(raw);
+    for i in 0..=short_len - ecc_len {
+        for (block, _) in &split {
+            if let Some(&byte) = block.get(i) {
+                result.push(byte);
+            }
+        }
+    }
+    for i in 0..ecc_len {
+        for (_, ecc) in &split {
+            result.push(ecc[i]);
+        }
+    }
+    result
+}
+
+/// Generator polynomial of degree `degree`, without its leading term
+fn rs_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0u8; degree];
+    result[degree - 1] = 1;
+    let mut root = 1u8;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = gf_multiply(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = gf_multiply(root, 0x02);
+    }
+    result
+}
+
+fn rs_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0u8; divisor.len()];
+    for &byte in data {
+        let factor = byte ^ result.remove(0);
+        result.push(0);
+        for (r, &d) in result.iter_mut().zip(divisor) {
+            *r ^= gf_multiply(d, factor);
+        }
+    }
+    result
+}
+
+/// Multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x^2 + 1
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut z = 0u8;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x1D);
+        z ^= ((y >> i) & 1) * x;
+    }
+    z
+}
+
+/// Big-endian bit buffer for the data codewords
+#[derive(Default)]
+struct Bits(Vec<bool>);
+
+impl Bits {
+    fn push(&mut self, value: u32, len: usize) {
+        self.0.extend((0..len).rev().map(|i| (value >> i) & 1 != 0));
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.0
+            .chunks(8)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .fold(0u8, |byte, &bit| byte << 1 | u8::from(bit))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capacity_and_structure() {
+        // Byte-mode capacities at level M from the standard
+        for (version, bytes) in [(1, 14), (2, 26), (7, 122), (10, 213), (40, 2331)] {
+            assert_eq!(data_codewords(version) - 1 - count_bits(version) / 8, bytes);
+        }
+        assert_eq!(alignment_positions(7), vec![6, 22, 38]);
+        assert_eq!(alignment_positions(32), vec![6, 34, 60, 86, 112, 138]);
+
+        let qr = QrCode::encode(b"correct horse battery staple").unwrap();
+        assert_eq!(qr.size(), 29);
+        // Finder corners are dark, their separators light
+        assert!(qr.is_dark(0, 0) && qr.is_dark(qr.size() as isize - 1, 0));
+        assert!(!qr.is_dark(7, 7) && !qr.is_dark(-1, 0));
+        assert_eq!(qr.render().len(), (qr.size() + 2 * QUIET_ZONE).div_ceil(2));
+
+        assert!(matches!(
+            QrCode::encode(&[b'x'; 3000]),
+            Err(QrError::TooLong { len: 3000, .. })
+        ));
+    }
+
+    #[test]
+    fn test_reed_solomon() {
+        // The "01234567" 1-M codewords worked through in the standard's annex
+        let data = [
+            0x10, 0x20, 0x0C, 0x56, 0x61, 0x80, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11,
+            0xEC, 0x11,
+        ];
+        assert_eq!(
+            rs_remainder(&data, &rs_divisor(10)),
+            vec![0xA5, 0x24, 0xD4, 0xC1, 0xED, 0x36, 0xC7, 0x87, 0x2C, 0x55]
+        );
+    }
+
+    #[test]
+    fn test_wifi_payload() {
+        let content = QrContent::Wifi("cafe;net".to_string());
+        assert_eq!(
+            content.payload("a:b\\c").as_str(),
+            "WIFI:T:WPA;S:cafe\\;net;P:a\\:b\\\\c;;"
+        );
+        assert_eq!(QrContent::Password.payload("pw").as_str(), "pw");
+    }
+}
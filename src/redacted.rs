@@ -0,0 +1,40 @@
+//! Placeholder printed instead of secret values
+//!
+//! Secret types format as `[REDACTED len=20]` in both `Debug` and `Display`,
+//! so a value can't reach a log line or panic message by accident. The length
+//! is kept because it is what a reader debugging a policy usually needs.
+
+use std::fmt;
+
+/// Formats as `[REDACTED len=<n>]`, `n` counting characters (or bytes for binary secrets)
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Redacted(pub usize);
+
+impl Redacted {
+    pub fn of(value: &str) -> Self {
+        Self(value.chars().count())
+    }
+}
+
+impl fmt::Display for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[REDACTED len={}]", self.0)
+    }
+}
+
+impl fmt::Debug for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacted() {
+        assert_eq!(Redacted::of("héllo").to_string(), "[REDACTED len=5]");
+        assert_eq!(format!("{:?}", Redacted(32)), "[REDACTED len=32]");
+    }
+}
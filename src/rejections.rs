@@ -0,0 +1,81 @@
+//! Tally of rejected candidates, for explaining a batch that could not be filled
+//!
+//! Length ranges, avoided terms, strength thresholds and `--diverse` all work
+//! by drawing candidates and discarding those that fail. When too few survive,
+//! within the attempt limit or before `--timeout`, the counts show which
+//! requirement was responsible.
+
+/// How many candidates were drawn and why each rejected one failed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rejections {
+    pub drawn: usize,
+    /// Outside `--min-length`/`--max-length`
+    pub length: usize,
+    /// Containing an `--avoid` term
+    pub avoided: usize,
+    /// Below `--min-bits`/`--min-strength`
+    pub weak: usize,
+    /// Too much like an earlier one (`--diverse`)
+    pub similar: usize,
+}
+
+impl Rejections {
+    /// e.g. "of 1200 candidates, 900 had the wrong length and 300 were too weak"
+    pub fn summary(&self) -> String {
+        let reasons: Vec<String> = [
+            (self.length, "had the wrong length"),
+            (self.avoided, "contained an avoided term"),
+            (self.weak, "were too weak"),
+            (self.similar, "looked like an earlier one"),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, reason)| format!("{} {}", count, reason))
+        .collect();
+        let plural = if self.drawn == 1 { "" } else { "s" };
+        match reasons.as_slice() {
+            [] => format!("{} candidate{} drawn", self.drawn, plural),
+            [only] => format!("of {} candidate{}, {}", self.drawn, plural, only),
+            [rest @ .., last] => format!(
+                "of {} candidate{}, {} and {}",
+                self.drawn,
+                plural,
+                rest.join(", "),
+                last
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let mut rejections = Rejections {
+            drawn: 1200,
+            length: 900,
+            ..Rejections::default()
+        };
+        assert_eq!(
+            rejections.summary(),
+            "of 1200 candidates, 900 had the wrong length"
+        );
+        rejections.weak = 250;
+        rejections.similar = 40;
+        assert_eq!(
+            rejections.summary(),
+            "of 1200 candidates, 900 had the wrong length, 250 were too weak \
+             and 40 looked like an earlier one"
+        );
+        assert_eq!(
+            Rejections {
+                drawn: 1,
+                ..Rejections::default()
+            }
+            .summary(),
+            "1 candidate drawn"
+        );
+    }
+}
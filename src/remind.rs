@@ -0,0 +1,216 @@
+//! Rotation reminders (`--remind`, `--remind-file`)
+//!
+//! Records when a freshly generated credential should be replaced, either as
+//! an all-day event in an iCalendar file (imported by any calendar app) or as a
+//! `YYYY-MM-DD<TAB>subject` line appended to a plain reminders file. Reminders
+//! name the credential, never its value.
+
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use crate::metadata::format_utc;
+
+const DAY: u64 = 86_400;
+/// Calendar written in --output-dir when no --remind-file is given
+pub const DEFAULT_CALENDAR: &str = "rotation.ics";
+
+/// How long until a credential is due for rotation, e.g. `90d`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interval {
+    pub days: u64,
+}
+
+impl FromStr for Interval {
+    type Err = String;
+
+    /// Days (`90d`), weeks (`12w`), months of 30 days (`3m`) or years of 365 days (`1y`)
+    fn from_str(text: &str) -> Result<Self, String> {
+        let invalid = || format!("'{}' is not an interval like 90d, 12w, 3m or 1y", text);
+        let split = text.len().checked_sub(1).ok_or_else(invalid)?;
+        if !text.is_char_boundary(split) {
+            return Err(invalid());
+        }
+        let (count, unit) = text.split_at(split);
+        let count: u64 = count.parse().map_err(|_| invalid())?;
+        let per_unit = match unit {
+            "d" => 1,
+            "w" => 7,
+            "m" => 30,
+            "y" => 365,
+            _ => return Err(invalid()),
+        };
+        match count.checked_mul(per_unit) {
+            Some(days) if days > 0 => Ok(Self { days }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} days", self.days)
+    }
+}
+
+/// A note that `subject` should be rotated on `due`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reminder {
+    pub created: SystemTime,
+    pub due: SystemTime,
+    /// What to rotate, e.g. the files the passwords were written to
+    pub subject: String,
+}
+
+impl Reminder {
+    pub fn new(created: SystemTime, interval: Interval, subject: impl Into<String>) -> Self {
+        Self {
+            created,
+            due: created + Duration::from_secs(interval.days * DAY),
+            subject: subject.into(),
+        }
+    }
+
+    /// Line for a plain reminders file
+    pub fn line(&self) -> String {
+        format!(
+            "{}\tRotate {} (generated {})\n",
+            date(self.due),
+            self.subject,
+            date(self.created)
+        )
+    }
+
+    /// An all-day VEVENT on the due date
+    pub fn event(&self) -> String {
+        let stamp = format_utc(self.created).replace(['-', ':'], "");
+        let due = date(self.due).replace('-', "");
+        let uid = format!("{}-{:08x}@pwgen-x", stamp, fnv1a(self.subject.as_bytes()));
+        [
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}", uid),
+            format!("DTSTAMP:{}", stamp),
+            format!("DTSTART;VALUE=DATE:{}", due),
+            format!(
+                "SUMMARY:{}",
+                escape_text(&format!("Rotate {}", self.subject))
+            ),
+            format!(
+                "DESCRIPTION:{}",
+                escape_text(&format!(
+                    "Generated by pwgen-x on {}; due for rotation.",
+                    date(self.created)
+                ))
+            ),
+            "END:VEVENT".to_string(),
+        ]
+        .join("\r\n")
+            + "\r\n"
+    }
+
+    /// Add the reminder to `path`: an event in a `.ics` calendar, otherwise a line
+    ///
+    /// A new calendar is created if needed; an existing one gets the event
+    /// before its closing `END:VCALENDAR`.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let is_calendar = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("ics"));
+        if !is_calendar {
+            return OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?
+                .write_all(self.line().as_bytes());
+        }
+
+        let calendar = match fs::read_to_string(path) {
+            Ok(existing) => {
+                let end = existing.rfind("END:VCALENDAR").ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "not an iCalendar file (no END:VCALENDAR)",
+                    )
+                })?;
+                format!("{}{}{}", &existing[..end], self.event(), &existing[end..])
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => format!(
+                "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//pwgen-x//rotation reminders//EN\r\n{}END:VCALENDAR\r\n",
+                self.event()
+            ),
+            Err(e) => return Err(e),
+        };
+        fs::write(path, calendar)
+    }
+}
+
+/// `YYYY-MM-DD` in UTC
+fn date(time: SystemTime) -> String {
+    format_utc(time)[..10].to_string()
+}
+
+/// Escape TEXT values per RFC 5545
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Stable hash keeping UIDs of reminders created in the same second apart
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash, &b| {
+        (hash ^ u32::from(b)).wrapping_mul(0x0100_0193)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    #[test]
+    fn test_interval() {
+        assert_eq!("90d".parse(), Ok(Interval { days: 90 }));
+        assert_eq!("12w".parse(), Ok(Interval { days: 84 }));
+        assert_eq!("1y".parse(), Ok(Interval { days: 365 }));
+        assert!("0d".parse::<Interval>().is_err());
+        assert!("90".parse::<Interval>().is_err());
+        assert!("d".parse::<Interval>().is_err());
+        assert!("9é".parse::<Interval>().is_err());
+    }
+
+    #[test]
+    fn test_reminder_files() {
+        // 2024-02-29
+        let created = UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+        let reminder = Reminder::new(created, "90d".parse().unwrap(), "db, prod");
+        assert_eq!(
+            reminder.line(),
+            "2024-05-29\tRotate db, prod (generated 2024-02-29)\n"
+        );
+        assert!(reminder.event().contains("DTSTART;VALUE=DATE:20240529\r\n"));
+        assert!(reminder.event().contains("SUMMARY:Rotate db\\, prod\r\n"));
+
+        let dir = std::env::temp_dir().join(format!("pwgen-remind-{}", std::process::id()));
+        let calendar = dir.join("rotation.ics");
+        reminder.write(&calendar).unwrap();
+        reminder.write(&calendar).unwrap();
+        let text = fs::read_to_string(&calendar).unwrap();
+        assert_eq!(text.matches("BEGIN:VEVENT").count(), 2);
+        assert!(text.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(text.ends_with("END:VCALENDAR\r\n"));
+
+        let list = dir.join("reminders.txt");
+        reminder.write(&list).unwrap();
+        reminder.write(&list).unwrap();
+        assert_eq!(fs::read_to_string(&list).unwrap().lines().count(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
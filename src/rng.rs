@@ -0,0 +1,182 @@
+//! Randomness for embedders (`PwgenRng`)
+//!
+//! Every generator takes its randomness as an explicit `&mut dyn RngCore`, so
+//! nothing in the library draws password material from a hidden global.
+//! `PwgenRng` is the supported way to choose where it comes from: the thread
+//! RNG (what the CLI uses), the operating system, a ChaCha20 stream from a
+//! 32-byte seed, or a source of the embedder's own such as an HSM-backed DRBG:
+//!
+//! ```
+//! use pwgen_x::prelude::*;
+//!
+//! let generator = PasswordSpec::passphrase().word_count(5).build()?;
+//! let mut rng = PwgenRng::os();
+//! let password = generator.generate(&mut rng);
+//! assert_eq!(password.expose().split('-').count(), 5);
+//! # Ok::<(), PwgenError>(())
+//! ```
+//!
+//! A `PwgenRng` is `Send`, so each worker thread can own one; none of them is
+//! shared behind a lock.
+
+use std::fmt;
+
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/// A cryptographically secure random source for the generators
+pub struct PwgenRng {
+    source: Source,
+}
+
+enum Source {
+    /// `rand::thread_rng()`, looked up on each call so the wrapper stays `Send`
+    Thread,
+    Os,
+    Seeded(Box<ChaCha20Rng>),
+    Custom(Box<dyn CryptoRngCore + Send>),
+}
+
+/// A `RngCore` that is also marked `CryptoRng`, usable as a trait object
+pub trait CryptoRngCore: RngCore + CryptoRng {}
+
+impl<T: RngCore + CryptoRng> CryptoRngCore for T {}
+
+impl PwgenRng {
+    /// The calling thread's ChaCha12 RNG, reseeded from the operating system
+    pub fn thread() -> Self {
+        Self {
+            source: Source::Thread,
+        }
+    }
+
+    /// Every byte straight from the operating system (`getrandom`)
+    pub fn os() -> Self {
+        Self { source: Source::Os }
+    }
+
+    /// ChaCha20 keyed with `seed`: the same seed always gives the same passwords
+    ///
+    /// For known-answer tests, or to expand a secret seed from elsewhere; the
+    /// passwords are only as secret as the seed.
+    pub fn seeded(seed: [u8; 32]) -> Self {
+        Self {
+            source: Source::Seeded(Box::new(ChaCha20Rng::from_seed(seed))),
+        }
+    }
+
+    /// The embedder's own source, such as a DRBG backed by an HSM
+    ///
+    /// It must be a cryptographically secure generator, which the `CryptoRng`
+    /// marker asserts.
+    pub fn custom(rng: impl RngCore + CryptoRng + Send + 'static) -> Self {
+        Self {
+            source: Source::Custom(Box::new(rng)),
+        }
+    }
+
+    /// Which kind of source this is, for logs; never the state
+    pub fn kind(&self) -> &'static str {
+        match self.source {
+            Source::Thread => "thread",
+            Source::Os => "os",
+            Source::Seeded(_) => "seeded",
+            Source::Custom(_) => "custom",
+        }
+    }
+}
+
+impl Default for PwgenRng {
+    fn default() -> Self {
+        Self::thread()
+    }
+}
+
+impl fmt::Debug for PwgenRng {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PwgenRng")
+            .field("kind", &self.kind())
+            .finish()
+    }
+}
+
+impl RngCore for PwgenRng {
+    fn next_u32(&mut self) -> u32 {
+        match &mut self.source {
+            Source::Thread => rand::thread_rng().next_u32(),
+            Source::Os => OsRng.next_u32(),
+            Source::Seeded(rng) => rng.next_u32(),
+            Source::Custom(rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match &mut self.source {
+            Source::Thread => rand::thread_rng().next_u64(),
+            Source::Os => OsRng.next_u64(),
+            Source::Seeded(rng) => rng.next_u64(),
+            Source::Custom(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match &mut self.source {
+            Source::Thread => rand::thread_rng().fill_bytes(dest),
+            Source::Os => OsRng.fill_bytes(dest),
+            Source::Seeded(rng) => rng.fill_bytes(dest),
+            Source::Custom(rng) => rng.fill_bytes(dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        match &mut self.source {
+            Source::Thread => rand::thread_rng().try_fill_bytes(dest),
+            Source::Os => OsRng.try_fill_bytes(dest),
+            Source::Seeded(rng) => rng.try_fill_bytes(dest),
+            Source::Custom(rng) => rng.try_fill_bytes(dest),
+        }
+    }
+}
+
+impl CryptoRng for PwgenRng {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::{PasswordGenerator, SecureGenerator};
+
+    #[test]
+    fn test_seeded_is_reproducible() {
+        let generator = SecureGenerator::builder().length(20).build().unwrap();
+        let first = generator.generate(&mut PwgenRng::seeded([7; 32]));
+        let again = generator.generate(&mut PwgenRng::seeded([7; 32]));
+        let other = generator.generate(&mut PwgenRng::seeded([8; 32]));
+        assert_eq!(first.expose(), again.expose());
+        assert_ne!(first.expose(), other.expose());
+    }
+
+    #[test]
+    fn test_sources_generate_across_threads() {
+        let rngs = [
+            PwgenRng::thread(),
+            PwgenRng::os(),
+            PwgenRng::custom(ChaCha20Rng::seed_from_u64(1)),
+        ];
+        let workers: Vec<_> = rngs
+            .into_iter()
+            .map(|mut rng| {
+                std::thread::spawn(move || {
+                    let generator = SecureGenerator::builder().length(16).build().unwrap();
+                    (rng.kind(), generator.generate(&mut rng).expose().len())
+                })
+            })
+            .collect();
+        let results: Vec<_> = workers.into_iter().map(|w| w.join().unwrap()).collect();
+        assert_eq!(results, [("thread", 16), ("os", 16), ("custom", 16)]);
+        assert_eq!(
+            format!("{:?}", PwgenRng::default()),
+            "PwgenRng { kind: \"thread\" }"
+        );
+    }
+}
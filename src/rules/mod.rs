@@ -0,0 +1,346 @@
+//! Site password rules in Apple's password-rules syntax
+//!
+//! A rule string such as `minlength: 8; maxlength: 20; required: lower;
+//! required: digit; allowed: [-_!];` describes which passwords a site accepts.
+//! Rules for well-known sites are embedded; `--for <site>` looks them up and
+//! configures the secure generator to match; with `--online` the site's own
+//! published rules take precedence (see [`online`]).
+
+pub mod online;
+
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+/// Embedded `domain rules` table
+pub(crate) const SITE_RULES: &str = include_str!("../../data/password_rules.txt");
+
+/// Apple's `special` class, minus the space character, which is never generated
+const SPECIAL: &str = "-~!@#$%^&*_+=`|(){}[:;\"'<>,.?]/\\";
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RulesError {
+    #[error("invalid password rule '{0}'")]
+    Syntax(String),
+    #[error("no password rules known for '{0}'")]
+    UnknownSite(String),
+}
+
+/// Constraints a site places on passwords
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PasswordRules {
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    /// Longest run of one repeated character
+    pub max_consecutive: Option<usize>,
+    /// Each set must contribute at least one character
+    pub required: Vec<Vec<char>>,
+    /// Characters permitted in addition to the required sets
+    pub allowed: Vec<char>,
+}
+
+impl PasswordRules {
+    /// Every character a conforming password may contain
+    ///
+    /// With neither `required` nor `allowed` rules, any printable ASCII is allowed.
+    pub fn alphabet(&self) -> Vec<char> {
+        let mut alphabet: Vec<char> = self
+            .required
+            .iter()
+            .flatten()
+            .chain(&self.allowed)
+            .copied()
+            .collect();
+        if alphabet.is_empty() {
+            alphabet = class("ascii-printable").unwrap_or_default();
+        }
+        alphabet.sort_unstable();
+        alphabet.dedup();
+        alphabet
+    }
+
+    /// `preferred` clamped into the allowed length range
+    pub fn clamp_length(&self, preferred: usize) -> usize {
+        let length = self.max_length.map_or(preferred, |max| preferred.min(max));
+        self.min_length.map_or(length, |min| length.max(min))
+    }
+
+    /// Whether `password` satisfies the required sets and repeat limit
+    pub fn accepts(&self, password: &str) -> bool {
+        let required_met = self
+            .required
+            .iter()
+            .all(|set| password.chars().any(|c| set.contains(&c)));
+        required_met
+            && self
+                .max_consecutive
+                .is_none_or(|max| longest_run(password) <= max)
+    }
+
+    /// Probability that a uniform random password over [`alphabet`](Self::alphabet)
+    /// of `length` characters contains every required set (inclusion-exclusion)
+    pub fn required_probability(&self, length: usize) -> f64 {
+        let alphabet = self.alphabet();
+        let n = alphabet.len() as f64;
+        let sets = &self.required;
+        (0u32..(1 << sets.len()))
+            .map(|mask| {
+                let mut union: Vec<char> = sets
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| mask & (1 << i) != 0)
+                    .flat_map(|(_, set)| set.iter().copied())
+                    .collect();
+                union.sort_unstable();
+                union.dedup();
+                let sign = if mask.count_ones() % 2 == 0 {
+                    1.0
+                } else {
+                    -1.0
+                };
+                sign * ((n - union.len() as f64) / n).powi(length as i32)
+            })
+            .sum()
+    }
+}
+
+fn longest_run(password: &str) -> usize {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut prev = None;
+    for c in password.chars() {
+        run = if prev == Some(c) { run + 1 } else { 1 };
+        longest = longest.max(run);
+        prev = Some(c);
+    }
+    longest
+}
+
+/// Characters of a named class
+fn class(name: &str) -> Option<Vec<char>> {
+    let chars = match name {
+        "lower" => ('a'..='z').collect(),
+        "upper" => ('A'..='Z').collect(),
+        "digit" => ('0'..='9').collect(),
+        "special" => SPECIAL.chars().collect(),
+        "ascii-printable" | "unicode" => (33u8..=126).map(char::from).collect(),
+        _ => return None,
+    };
+    Some(chars)
+}
+
+/// Parse a comma-separated list of classes and `[...]` custom sets
+fn parse_classes(value: &str) -> Option<Vec<char>> {
+    let mut chars = Vec::new();
+    let mut rest = value.trim();
+    while !rest.is_empty() {
+        if let Some(custom) = rest.strip_prefix('[') {
+            // `]` may itself be a member when it is the last character of the set
+            let end = custom
+                .char_indices()
+                .find(|&(i, c)| c == ']' && !custom[i + 1..].starts_with(']'))?
+                .0;
+            chars.extend(custom[..end].chars().filter(|c| *c != ' '));
+            rest = &custom[end + 1..];
+        } else {
+            let end = rest.find(',').unwrap_or(rest.len());
+            chars.extend(class(rest[..end].trim())?);
+            rest = &rest[end..];
+        }
+        rest = rest.trim_start().trim_start_matches(',').trim_start();
+    }
+    Some(chars)
+}
+
+impl FromStr for PasswordRules {
+    type Err = RulesError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut rules = PasswordRules::default();
+        for rule in split_rules(text) {
+            let rule = rule.trim();
+            if rule.is_empty() {
+                continue;
+            }
+            let syntax = || RulesError::Syntax(rule.to_string());
+            let (name, value) = rule.split_once(':').ok_or_else(syntax)?;
+            let number = || value.trim().parse::<usize>().map_err(|_| syntax());
+            match name.trim() {
+                "minlength" => rules.min_length = Some(number()?),
+                "maxlength" => rules.max_length = Some(number()?),
+                "max-consecutive" => rules.max_consecutive = Some(number()?),
+                "required" => rules
+                    .required
+                    .push(parse_classes(value).ok_or_else(syntax)?),
+                "allowed" => rules
+                    .allowed
+                    .extend(parse_classes(value).ok_or_else(syntax)?),
+                _ => return Err(syntax()),
+            }
+        }
+        Ok(rules)
+    }
+}
+
+/// Split on `;` outside of `[...]` custom sets
+fn split_rules(text: &str) -> Vec<&str> {
+    let mut rules = Vec::new();
+    let mut start = 0;
+    let mut in_set = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            '[' if !in_set => in_set = true,
+            ']' if in_set && !text[i + 1..].starts_with(']') => in_set = false,
+            ';' if !in_set => {
+                rules.push(&text[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    rules.push(&text[start..]);
+    rules
+}
+
+/// A `[...]` custom set, with `-` first and `]` last as the syntax requires
+fn format_set(set: &[char]) -> String {
+    let dash = if set.contains(&'-') { "-" } else { "" };
+    let bracket = if set.contains(&']') { "]" } else { "" };
+    let middle: String = set.iter().filter(|c| !matches!(c, '-' | ']')).collect();
+    format!("[{}{}{}]", dash, middle, bracket)
+}
+
+impl fmt::Display for PasswordRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(min) = self.min_length {
+            parts.push(format!("minlength: {}", min));
+        }
+        if let Some(max) = self.max_length {
+            parts.push(format!("maxlength: {}", max));
+        }
+        if let Some(max) = self.max_consecutive {
+            parts.push(format!("max-consecutive: {}", max));
+        }
+        for set in &self.required {
+            parts.push(format!("required: {}", format_set(set)));
+        }
+        if !self.allowed.is_empty() {
+            parts.push(format!("allowed: {}", format_set(&self.allowed)));
+        }
+        write!(f, "{};", parts.join("; "))
+    }
+}
+
+/// Reduce a URL or host name to a lowercase domain (`https://www.GitHub.com/x` -> `github.com`)
+pub fn normalize_site(site: &str) -> String {
+    let site = site.trim();
+    let host = site.split_once("://").map_or(site, |(_, rest)| rest);
+    let host = host.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = host.rsplit_once('@').map_or(host, |(_, h)| h);
+    let host = host.split(':').next().unwrap_or_default();
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    host.strip_prefix("www.")
+        .map(str::to_string)
+        .unwrap_or(host)
+}
+
+/// Embedded rules for `site`, falling back to parent domains (`login.chase.com` -> `chase.com`)
+pub fn for_site(site: &str) -> Result<PasswordRules, RulesError> {
+    let domain = normalize_site(site);
+    let mut candidate = domain.as_str();
+    loop {
+        if let Some(rules) = lookup(candidate) {
+            return rules;
+        }
+        match candidate.split_once('.') {
+            Some((_, parent)) if parent.contains('.') => candidate = parent,
+            _ => return Err(RulesError::UnknownSite(domain)),
+        }
+    }
+}
+
+fn lookup(domain: &str) -> Option<Result<PasswordRules, RulesError>> {
+    SITE_RULES
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .find_map(|line| {
+            let (site, rules) = line.split_once(char::is_whitespace)?;
+            (site == domain).then(|| rules.parse())
+        })
+}
+
+/// Domains with embedded rules
+pub fn known_sites() -> impl Iterator<Item = &'static str> {
+    SITE_RULES
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_whitespace().next())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rules() {
+        let rules: PasswordRules =
+            "minlength: 8; maxlength: 20; max-consecutive: 3; required: lower, upper; required: [-_!]]; allowed: digit;"
+                .parse()
+                .unwrap();
+        assert_eq!(rules.min_length, Some(8));
+        assert_eq!(rules.max_length, Some(20));
+        assert_eq!(rules.max_consecutive, Some(3));
+        assert_eq!(rules.required.len(), 2);
+        assert_eq!(rules.required[0].len(), 52);
+        assert_eq!(rules.required[1], vec!['-', '_', '!', ']']);
+        assert_eq!(rules.alphabet().len(), 52 + 4 + 10);
+
+        let round_trip: PasswordRules = rules.to_string().parse().unwrap();
+        assert_eq!(round_trip.alphabet(), rules.alphabet());
+        assert_eq!(round_trip.required.len(), 2);
+
+        assert!("minlength: eight".parse::<PasswordRules>().is_err());
+        assert!("required: emoji".parse::<PasswordRules>().is_err());
+    }
+
+    #[test]
+    fn test_accepts_and_probability() {
+        let rules: PasswordRules = "required: lower; required: digit; max-consecutive: 2;"
+            .parse()
+            .unwrap();
+        assert!(rules.accepts("ab1"));
+        assert!(!rules.accepts("abc"));
+        assert!(!rules.accepts("aaa1"));
+
+        // Alphabet of 36; P(no lower) = (10/36)^2, P(no digit) = (26/36)^2
+        let p = rules.required_probability(2);
+        let expected = 1.0 - (10.0f64 / 36.0).powi(2) - (26.0f64 / 36.0).powi(2);
+        assert!((p - expected).abs() < 1e-12);
+        assert_eq!(rules.clamp_length(16), 16);
+    }
+
+    #[test]
+    fn test_site_lookup() {
+        assert_eq!(
+            normalize_site("https://www.GitHub.com/login?x=1"),
+            "github.com"
+        );
+        assert_eq!(normalize_site("user@chase.com:443"), "chase.com");
+
+        let rules = for_site("secure.chase.com").unwrap();
+        assert_eq!(rules.max_length, Some(32));
+        assert_eq!(rules.clamp_length(64), 32);
+        assert!(matches!(
+            for_site("example.invalid"),
+            Err(RulesError::UnknownSite(_))
+        ));
+
+        // Every embedded entry must parse
+        for site in known_sites() {
+            for_site(site).unwrap();
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! Fetching a site's own password rules (`--online`)
+//!
+//! Sites can publish rules as a plain-text `/.well-known/passwordrules` file,
+//! or in their HTML as a `passwordrules` attribute on password inputs or a
+//! `<meta name="passwordrules">` tag. Results, including "nothing published",
+//! are cached per domain for [`CACHE_TTL`] so generation stays fast and works
+//! offline afterwards. With `--offline` the cache is used whatever its age and
+//! the site is never contacted.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::http;
+use crate::rules::{self, normalize_site, PasswordRules, RulesError};
+
+/// How long fetched rules are trusted before the site is asked again
+pub const CACHE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Where the rules used for a site came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RulesSource {
+    /// Fetched from the site (this run or a cached earlier run)
+    Online { url: String, cached: bool },
+    /// The embedded table, because the site publishes nothing usable
+    Embedded,
+    /// The embedded table, because `--offline` is set and nothing is cached
+    Offline,
+    /// The embedded table, because the site could not be reached
+    Unreachable(String),
+}
+
+/// Directory holding cached rules, honoring `XDG_CACHE_HOME`
+pub fn cache_dir() -> Option<PathBuf> {
+    http::cache_dir().map(|dir| dir.join("rules"))
+}
+
+/// Rules published by `site`, falling back to the embedded table
+pub fn for_site(site: &str) -> Result<(PasswordRules, RulesSource), RulesError> {
+    let domain = normalize_site(site);
+    let cache = cache_dir().map(|dir| dir.join(&domain));
+
+    let max_age = if http::is_offline() {
+        None
+    } else {
+        Some(CACHE_TTL)
+    };
+    let embedded = |source| rules::for_site(site).map(|rules| (rules, source));
+
+    let (found, cached) = match cache
+        .as_deref()
+        .and_then(|path| read_cache(path, &domain, max_age))
+    {
+        Some(entry) => (entry, true),
+        None if http::is_offline() => return embedded(RulesSource::Offline),
+        None => match fetch(site, &domain) {
+            Ok(found) => {
+                if let Some(path) = &cache {
+                    write_cache(path, &domain, found.as_ref());
+                }
+                (found, false)
+            }
+            // Not cached, so the next run asks again
+            Err(err) => return embedded(RulesSource::Unreachable(err.to_string())),
+        },
+    };
+
+    if let Some((url, text)) = found {
+        if let Ok(rules) = text.parse() {
+            return Ok((rules, RulesSource::Online { url, cached }));
+        }
+    }
+    embedded(RulesSource::Embedded)
+}
+
+/// Try the well-known file, then the page itself, returning `(url, rules)`
+///
+/// An error means the site itself could not be reached.
+fn fetch(site: &str, domain: &str) -> Result<Option<(String, String)>, http::HttpError> {
+    let well_known = format!("https://{}/.well-known/passwordrules", domain);
+    crate::debug!("rules: fetching {}", well_known);
+    match http::get(&well_known) {
+        Ok(body) => {
+            let text = body.trim();
+            if !text.is_empty() && !text.starts_with('<') && text.parse::<PasswordRules>().is_ok() {
+                return Ok(Some((well_known, text.to_string())));
+            }
+        }
+        // The page is on the same host, so don't wait for it to fail too
+        Err(err @ http::HttpError::Failed { .. }) => return Err(err),
+        Err(_) => {}
+    }
+
+    let page = if site.contains("://") {
+        site.to_string()
+    } else {
+        format!("https://{}/", domain)
+    };
+    crate::debug!("rules: fetching {}", page);
+    let html = http::get(&page)?;
+    Ok(extract_from_html(&html).map(|text| (page, text)))
+}
+
+/// Cached `(url, rules)` for `domain` younger than `max_age` (any age for
+/// `None`); `Some(None)` records that the site published nothing
+fn read_cache(
+    path: &Path,
+    domain: &str,
+    max_age: Option<Duration>,
+) -> Option<Option<(String, String)>> {
+    let body = http::read_cache(path, domain, max_age)?;
+    Some(
+        body.split_once('\n')
+            .map(|(url, rules)| (url.to_string(), rules.trim().to_string())),
+    )
+}
+
+/// An empty entry is the negative result, so the site isn't asked again
+/// until it expires
+fn write_cache(path: &Path, domain: &str, found: Option<&(String, String)>) {
+    let body = found
+        .map(|(url, rules)| format!("{}\n{}\n", url, rules))
+        .unwrap_or_default();
+    http::write_cache(path, domain, &body);
+}
+
+/// Rules from a `passwordrules` attribute or `<meta name="passwordrules">` tag
+pub fn extract_from_html(html: &str) -> Option<String> {
+    // ASCII lowercasing keeps byte offsets aligned with the original
+    let lower = html.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(pos) = lower[from..].find("passwordrules") {
+        let start = from + pos;
+        let after = start + "passwordrules".len();
+        from = after;
+
+        // passwordrules="..." on an input element
+        let rest = lower[after..].trim_start();
+        if let Some(value) = rest.strip_prefix('=') {
+            let offset = html.len() - value.len();
+            if let Some(rules) = quoted_value(&html[offset..]) {
+                return Some(decode_entities(&rules));
+            }
+        }
+
+        // <meta name="passwordrules" content="...">
+        let (Some(tag_start), Some(tag_len)) =
+            (lower[..start].rfind('<'), lower[after..].find('>'))
+        else {
+            continue;
+        };
+        let tag_end = after + tag_len;
+        let tag = &lower[tag_start..tag_end];
+        if tag.starts_with("<meta") {
+            if let Some(content) = tag.find("content") {
+                let value = tag[content + "content".len()..].trim_start();
+                if let Some(value) = value.strip_prefix('=') {
+                    let offset = tag_start + (tag.len() - value.len());
+                    if let Some(rules) = quoted_value(&html[offset..tag_end]) {
+                        return Some(decode_entities(&rules));
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// The contents of a leading `"..."` or `'...'` string
+fn quoted_value(text: &str) -> Option<String> {
+    let text = text.trim_start();
+    let quote = text.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let body = &text[1..];
+    body.find(quote).map(|end| body[..end].to_string())
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&#34;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_attribute() {
+        let html = r#"<form><INPUT type="password" PasswordRules = "minlength: 10; required: digit; allowed: [&amp;-];"></form>"#;
+        assert_eq!(
+            extract_from_html(html).as_deref(),
+            Some("minlength: 10; required: digit; allowed: [&-];")
+        );
+    }
+
+    #[test]
+    fn test_extract_meta() {
+        let html = r#"<head><meta name="passwordrules" content='maxlength: 20;'></head>"#;
+        assert_eq!(extract_from_html(html).as_deref(), Some("maxlength: 20;"));
+        assert_eq!(extract_from_html("<p>passwordrules are nice</p>"), None);
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let path = std::env::temp_dir().join(format!("pwgen-rules-{}", std::process::id()));
+        let entry = (
+            "https://example.com/".to_string(),
+            "minlength: 9;".to_string(),
+        );
+        write_cache(&path, "example.com", Some(&entry));
+        assert_eq!(
+            read_cache(&path, "example.com", Some(CACHE_TTL)),
+            Some(Some(entry))
+        );
+        write_cache(&path, "example.com", None);
+        assert_eq!(read_cache(&path, "example.com", None), Some(None));
+        assert_eq!(read_cache(&path, "example.org", None), None);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,59 @@
+//! Clearing passwords from the terminal once they have been read (`--scrub`)
+//!
+//! After a key press the rows holding the passwords are erased, the scrollback
+//! is cleared where the terminal supports it (xterm, kitty, VTE and iTerm2
+//! honour `CSI 3 J`), and the window title is reset. Terminals that persist
+//! scrollback to disk then have nothing left to save.
+
+use std::io;
+
+use console::Term;
+
+/// Push the current window title onto the terminal's title stack (xterm)
+const SAVE_TITLE: &str = "\x1b[22;0t";
+
+/// Empty the title, then pop the saved one where the title stack is supported
+const RESET_TITLE: &str = "\x1b]0;\x07\x1b[23;0t";
+
+const CLEAR_SCROLLBACK: &str = "\x1b[3J";
+
+/// Remember the window title so it can be restored after scrubbing
+pub fn save_title(term: &Term) -> io::Result<()> {
+    term.write_str(SAVE_TITLE)
+}
+
+/// Escape sequence erasing the last `rows` rows above the cursor, the scrollback and the title
+pub fn sequence(rows: usize) -> String {
+    let mut sequence = String::from("\r");
+    if rows > 0 {
+        sequence.push_str(&format!("\x1b[{}A", rows));
+    }
+    // Clear from the cursor to the end of the screen
+    sequence.push_str("\x1b[0J");
+    sequence.push_str(CLEAR_SCROLLBACK);
+    sequence.push_str(RESET_TITLE);
+    sequence
+}
+
+/// Wait for a key on `prompt`, then erase the `rows` rows printed to `output`
+pub fn wait_and_scrub(output: &Term, prompt: &Term, rows: usize) -> io::Result<()> {
+    prompt.write_str("Press any key to clear the password(s) from the terminal...")?;
+    prompt.read_key()?;
+    prompt.clear_line()?;
+    output.write_str(&sequence(rows))?;
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequence() {
+        let sequence = sequence(3);
+        assert!(sequence.starts_with("\r\x1b[3A\x1b[0J"));
+        assert!(sequence.contains(CLEAR_SCROLLBACK));
+        assert!(sequence.ends_with("\x1b[23;0t"));
+        assert!(!super::sequence(0).contains('A'));
+    }
+}
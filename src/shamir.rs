@@ -0,0 +1,279 @@
+//! Shamir secret sharing over GF(256) (`split` and `recover` subcommands)
+//!
+//! Each byte of the secret is the constant term of its own random polynomial
+//! of degree `threshold - 1`; share `x` holds every polynomial evaluated at
+//! `x`. Any `threshold` shares recover the secret by Lagrange interpolation at
+//! zero, fewer reveal nothing about it.
+//!
+//! Shares are written as `<index>-<hex>` or as words, one word per byte with
+//! the share index first, drawn from the first 256 words of the EFF list.
+
+use rand::RngCore;
+use thiserror::Error;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::cli::ShareEncoding;
+use crate::redacted::Redacted;
+use crate::wordlist::Wordlist;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ShamirError {
+    #[error("threshold must be between 2 and the number of shares ({shares}), got {threshold}")]
+    InvalidThreshold { threshold: u8, shares: u8 },
+    #[error("secret is empty")]
+    EmptySecret,
+    #[error("invalid share '{0}'")]
+    InvalidShare(String),
+    #[error("share {0} was given more than once")]
+    DuplicateShare(u8),
+    #[error("shares have different lengths")]
+    LengthMismatch,
+    #[error("at least 2 shares are needed")]
+    NotEnoughShares,
+}
+
+/// One share: its x coordinate and the polynomial values for each secret byte
+#[derive(Clone, PartialEq, Eq)]
+pub struct Share {
+    pub index: u8,
+    pub data: Zeroizing<Vec<u8>>,
+}
+
+impl std::fmt::Debug for Share {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Share")
+            .field("index", &self.index)
+            .field("data", &Redacted(self.data.len()))
+            .finish()
+    }
+}
+
+impl Share {
+    pub fn encode(&self, encoding: ShareEncoding) -> Zeroizing<String> {
+        match encoding {
+            ShareEncoding::Hex => {
+                let mut text = format!("{}-", self.index);
+                for byte in self.data.iter() {
+                    text.push_str(&format!("{:02x}", byte));
+                }
+                Zeroizing::new(text)
+            }
+            ShareEncoding::Words => {
+                let list = Wordlist::builtin();
+                let words = list.words();
+                let encoded: Vec<&str> = std::iter::once(self.index)
+                    .chain(self.data.iter().copied())
+                    .map(|byte| words[byte as usize].as_str())
+                    .collect();
+                Zeroizing::new(encoded.join(" "))
+            }
+        }
+    }
+
+    /// Parse either encoding
+    pub fn decode(text: &str) -> Result<Self, ShamirError> {
+        let text = text.trim();
+        let invalid = || ShamirError::InvalidShare(text.to_string());
+        let (index, data) = match text.split_once('-') {
+            Some((index, hex)) if !hex.contains(char::is_whitespace) => {
+                let index = index.parse::<u8>().map_err(|_| invalid())?;
+                if hex.len() % 2 != 0 || !hex.is_ascii() {
+                    return Err(invalid());
+                }
+                let data = (0..hex.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+                    .collect::<Result<Vec<u8>, _>>()
+                    .map_err(|_| invalid())?;
+                (index, data)
+            }
+            _ => {
+                let list = Wordlist::builtin();
+                let words = &list.words()[..256];
+                let mut bytes = text
+                    .split_whitespace()
+                    .map(|w| {
+                        let w = w.to_lowercase();
+                        words.iter().position(|known| *known == w).map(|i| i as u8)
+                    })
+                    .collect::<Option<Vec<u8>>>()
+                    .ok_or_else(invalid)?;
+                if bytes.is_empty() {
+                    return Err(invalid());
+                }
+                let index = bytes.remove(0);
+                (index, bytes)
+            }
+        };
+        if index == 0 || data.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Share {
+            index,
+            data: Zeroizing::new(data),
+        })
+    }
+}
+
+/// Multiply in GF(2^8) with the AES polynomial x^8 + x^4 + x^3 + x + 1
+fn mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        let carry = a & 0x80;
+        a <<= 1;
+        if carry != 0 {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+/// Multiplicative inverse (a^254); `a` must be non-zero
+fn inverse(a: u8) -> u8 {
+    let mut result = 1;
+    let mut base = a;
+    let mut exp = 254u8;
+    while exp != 0 {
+        if exp & 1 != 0 {
+            result = mul(result, base);
+        }
+        base = mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Split `secret` into `shares` shares, any `threshold` of which recover it
+pub fn split(
+    secret: &[u8],
+    shares: u8,
+    threshold: u8,
+    rng: &mut dyn RngCore,
+) -> Result<Vec<Share>, ShamirError> {
+    if threshold < 2 || threshold > shares {
+        return Err(ShamirError::InvalidThreshold { threshold, shares });
+    }
+    if secret.is_empty() {
+        return Err(ShamirError::EmptySecret);
+    }
+    let mut result: Vec<Share> = (1..=shares)
+        .map(|index| Share {
+            index,
+            data: Zeroizing::new(Vec::with_capacity(secret.len())),
+        })
+        .collect();
+    let mut coefficients = vec![0u8; threshold as usize];
+    for &byte in secret {
+        coefficients[0] = byte;
+        rng.fill_bytes(&mut coefficients[1..]);
+        for share in &mut result {
+            // Horner's rule, highest degree first
+            let y = coefficients
+                .iter()
+                .rev()
+                .fold(0, |acc, &c| mul(acc, share.index) ^ c);
+            share.data.push(y);
+        }
+    }
+    coefficients.zeroize();
+    Ok(result)
+}
+
+/// Recover the secret from at least `threshold` distinct shares
+///
+/// With fewer shares than the threshold the result is unrelated random bytes;
+/// the shares themselves cannot tell.
+pub fn recover(shares: &[Share]) -> Result<Zeroizing<Vec<u8>>, ShamirError> {
+    if shares.len() < 2 {
+        return Err(ShamirError::NotEnoughShares);
+    }
+    let len = shares[0].data.len();
+    for (i, share) in shares.iter().enumerate() {
+        if share.data.len() != len {
+            return Err(ShamirError::LengthMismatch);
+        }
+        if shares[..i].iter().any(|s| s.index == share.index) {
+            return Err(ShamirError::DuplicateShare(share.index));
+        }
+    }
+    // Lagrange basis polynomials evaluated at zero
+    let basis: Vec<u8> = shares
+        .iter()
+        .map(|share| {
+            shares
+                .iter()
+                .filter(|other| other.index != share.index)
+                .fold(1, |acc, other| {
+                    mul(acc, mul(other.index, inverse(other.index ^ share.index)))
+                })
+        })
+        .collect();
+    let secret = (0..len)
+        .map(|i| {
+            shares
+                .iter()
+                .zip(&basis)
+                .fold(0, |acc, (share, &b)| acc ^ mul(share.data[i], b))
+        })
+        .collect();
+    Ok(Zeroizing::new(secret))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_field_arithmetic() {
+        assert_eq!(mul(0x57, 0x83), 0xc1);
+        for a in 1..=255u8 {
+            assert_eq!(mul(a, inverse(a)), 1);
+        }
+    }
+
+    #[test]
+    fn test_split_and_recover() {
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let secret = b"correct horse battery staple";
+        let shares = split(secret, 5, 3, &mut rng).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        let subset = [shares[4].clone(), shares[0].clone(), shares[2].clone()];
+        assert_eq!(recover(&subset).unwrap().as_slice(), secret);
+        assert_eq!(recover(&shares).unwrap().as_slice(), secret);
+        // Below the threshold the secret does not come back
+        assert_ne!(recover(&shares[..2]).unwrap().as_slice(), secret);
+
+        assert!(matches!(
+            split(secret, 3, 4, &mut rng),
+            Err(ShamirError::InvalidThreshold { .. })
+        ));
+        assert_eq!(
+            recover(&[shares[1].clone(), shares[1].clone()]),
+            Err(ShamirError::DuplicateShare(2))
+        );
+    }
+
+    #[test]
+    fn test_share_encodings() {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let shares = split(b"vault", 3, 2, &mut rng).unwrap();
+        for encoding in [ShareEncoding::Hex, ShareEncoding::Words] {
+            let decoded: Vec<Share> = shares
+                .iter()
+                .map(|s| Share::decode(&s.encode(encoding)).unwrap())
+                .collect();
+            assert_eq!(decoded, shares);
+        }
+        assert!(shares[0].encode(ShareEncoding::Hex).starts_with("1-"));
+        assert!(Share::decode("1-xyz").is_err());
+        assert!(Share::decode("0-abcd").is_err());
+        assert!(Share::decode("notaword abacus").is_err());
+    }
+}
@@ -0,0 +1,108 @@
+//! Entry point for embedding the generators (`PasswordSpec`)
+//!
+//! Each kind of password starts from a named constructor that returns the
+//! generator's builder, so a whole configuration reads as one expression and
+//! is validated once, at `build()`:
+//!
+//! ```
+//! use pwgen_x::prelude::*;
+//!
+//! let generator = PasswordSpec::secure()
+//!     .length(20)
+//!     .charset(CharSet::Alphanumeric)
+//!     .build()?;
+//! let password = generator.generate(&mut rand::thread_rng());
+//! assert_eq!(password.expose().len(), 20);
+//! # Ok::<(), PwgenError>(())
+//! ```
+
+use crate::generators::{
+    AppleGenerator, KeypadGenerator, KeypadGeneratorBuilder, MarkovGenerator,
+    MarkovGeneratorBuilder, PasscodeGenerator, PasscodeGeneratorBuilder, PassphraseGenerator,
+    PassphraseGeneratorBuilder, PinGenerator, PinGeneratorBuilder, SecureGenerator,
+    SecureGeneratorBuilder, TemplateGenerator, TemplateGeneratorBuilder, TemporaryGenerator,
+    TemporaryGeneratorBuilder,
+};
+
+/// Starting points for every kind of password, matching the CLI subcommands
+pub struct PasswordSpec;
+
+impl PasswordSpec {
+    /// Uniformly random characters from a charset or alphabet (`secure`)
+    pub fn secure() -> SecureGeneratorBuilder {
+        SecureGenerator::builder()
+    }
+
+    /// Pronounceable passwords from the Markov model (`normal`)
+    pub fn pronounceable() -> MarkovGeneratorBuilder {
+        MarkovGenerator::builder()
+    }
+
+    /// Diceware passphrases (`phrase`)
+    pub fn passphrase() -> PassphraseGeneratorBuilder {
+        PassphraseGenerator::builder()
+    }
+
+    /// Numeric PINs (`pin`)
+    pub fn pin() -> PinGeneratorBuilder {
+        PinGenerator::builder()
+    }
+
+    /// Phone and tablet unlock codes (`passcode`)
+    pub fn passcode() -> PasscodeGeneratorBuilder {
+        PasscodeGenerator::builder()
+    }
+
+    /// Short first-login passwords (`temp`)
+    pub fn temporary() -> TemporaryGeneratorBuilder {
+        TemporaryGenerator::builder()
+    }
+
+    /// A pronounceable word plus digits for phone keypads (`keypad`)
+    pub fn keypad() -> KeypadGeneratorBuilder {
+        KeypadGenerator::builder()
+    }
+
+    /// Passwords shaped by a mask such as `Cvcvc-####-%%` (`template`)
+    pub fn template() -> TemplateGeneratorBuilder {
+        TemplateGenerator::builder()
+    }
+
+    /// iCloud Keychain-style passwords (`apple`); the format has no options
+    pub fn apple() -> AppleGenerator {
+        AppleGenerator::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::PasswordGenerator;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_spec_builds_each_kind() {
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let generators: Vec<Box<dyn PasswordGenerator>> = vec![
+            Box::new(PasswordSpec::secure().length(20).build().unwrap()),
+            Box::new(PasswordSpec::pronounceable().length(12).build().unwrap()),
+            Box::new(PasswordSpec::passphrase().word_count(5).build().unwrap()),
+            Box::new(PasswordSpec::pin().length(8).build().unwrap()),
+            Box::new(PasswordSpec::passcode().alphanumeric(true).build().unwrap()),
+            Box::new(PasswordSpec::temporary().build().unwrap()),
+            Box::new(PasswordSpec::keypad().build().unwrap()),
+            Box::new(
+                PasswordSpec::template()
+                    .mask("?u?l?l?d?d%")
+                    .build()
+                    .unwrap(),
+            ),
+            Box::new(PasswordSpec::apple()),
+        ];
+        for generator in &generators {
+            assert!(!generator.generate(&mut rng).expose().is_empty());
+        }
+        assert!(PasswordSpec::secure().length(0).build().is_err());
+    }
+}
@@ -0,0 +1,373 @@
+//! Entropy and strength requirements (`--min-bits`, `--min-strength`, `--check-only`)
+//! and the warning floor (`--entropy-floor`)
+
+use serde_json::{json, Value};
+
+use crate::cli::GeneratorCommand;
+use crate::entropy::{AttackerModel, EntropyInfo, StrengthLevel};
+use crate::error::PwgenError;
+use crate::generators::{
+    KeypadGenerator, MarkovGenerator, PasscodeGenerator, PassphraseGenerator, PinGenerator,
+    SecureGenerator, TemporaryGenerator,
+};
+
+/// Minimum entropy and/or strength a password must reach
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Threshold {
+    pub min_bits: Option<f64>,
+    pub min_strength: Option<StrengthLevel>,
+    /// Model `min_strength` is judged against
+    pub attacker: AttackerModel,
+}
+
+impl Threshold {
+    pub fn is_empty(&self) -> bool {
+        self.min_bits.is_none() && self.min_strength.is_none()
+    }
+
+    pub fn accepts(&self, entropy: &EntropyInfo) -> bool {
+        self.min_bits.is_none_or(|min| entropy.bits >= min)
+            && self
+                .min_strength
+                .is_none_or(|min| entropy.strength_against(self.attacker) >= min)
+    }
+
+    /// Human-readable form, e.g. "96 bits and Strong strength"
+    pub fn describe(&self) -> String {
+        let bits = self.min_bits.map(|b| format!("{} bits", b));
+        let strength = self.min_strength.map(|s| format!("{} strength", s.label()));
+        match (bits, strength) {
+            (Some(b), Some(s)) => format!("{} and {}", b, s),
+            (Some(x), None) | (None, Some(x)) => x,
+            (None, None) => "no requirement".to_string(),
+        }
+    }
+
+    /// Decide, without generating, whether a generator with this `estimate` meets the threshold
+    pub fn check(&self, generator: &str, estimate: Option<f64>) -> CheckReport {
+        let (pass, reason) = match estimate {
+            None => (
+                false,
+                "entropy of this generator cannot be determined without generating".to_string(),
+            ),
+            Some(bits) => {
+                let entropy = EntropyInfo::new(bits, "estimate");
+                if self.accepts(&entropy) {
+                    (
+                        true,
+                        format!("{:.1} bits meets the required {}", bits, self.describe()),
+                    )
+                } else {
+                    (
+                        false,
+                        format!(
+                            "{:.1} bits ({}) is below the required {}",
+                            bits,
+                            entropy.strength_against(self.attacker).label(),
+                            self.describe()
+                        ),
+                    )
+                }
+            }
+        };
+        CheckReport {
+            pass,
+            generator: generator.to_string(),
+            estimated_bits: estimate,
+            threshold: *self,
+            reason,
+        }
+    }
+}
+
+/// Outcome of `--check-only`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckReport {
+    pub pass: bool,
+    pub generator: String,
+    pub estimated_bits: Option<f64>,
+    pub threshold: Threshold,
+    pub reason: String,
+}
+
+impl CheckReport {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "pass": self.pass,
+            "generator": self.generator,
+            "estimated_bits": self.estimated_bits,
+            "estimated_strength": self
+                .estimated_bits
+                .map(|bits| {
+                    EntropyInfo::new(bits, "estimate")
+                        .strength_against(self.threshold.attacker)
+                        .label()
+                }),
+            "required_bits": self.threshold.min_bits,
+            "required_strength": self.threshold.min_strength.map(|s| s.label()),
+            "reason": self.reason,
+        })
+    }
+}
+
+/// Entropy below which generating on a terminal needs `--i-know-this-is-weak`
+pub const DANGER_BITS: f64 = 28.0;
+
+/// The danger zone for `command`
+///
+/// PINs, device passcodes and keypad codes rely on attempt limits rather than
+/// entropy, so only those weaker than the default configuration count as
+/// dangerous.
+pub fn danger_bits(command: &GeneratorCommand) -> f64 {
+    match command {
+        GeneratorCommand::Pin { .. } => PinGenerator::DEFAULT_LENGTH as f64 * 10f64.log2(),
+        GeneratorCommand::Passcode { .. } => {
+            PasscodeGenerator::entropy_bits(PasscodeGenerator::DEFAULT_DIGITS, false)
+        }
+        GeneratorCommand::Keypad { .. } => KeypadGenerator::entropy_bits(
+            KeypadGenerator::DEFAULT_LETTERS,
+            KeypadGenerator::DEFAULT_DIGITS,
+        ),
+        _ => DANGER_BITS,
+    }
+}
+
+/// Fail when `bits` falls in the danger zone of `command`
+pub fn check_danger(command: &GeneratorCommand, bits: f64) -> Result<(), PwgenError> {
+    let minimum = danger_bits(command);
+    // Tolerate rounding in estimates that sit exactly on the limit
+    if bits + 1e-9 >= minimum {
+        return Ok(());
+    }
+    Err(PwgenError::TooWeak {
+        bits: bits.floor() as u32,
+        minimum: minimum.floor() as u32,
+    })
+}
+
+/// Entropy below which a generated password earns a warning rather than a rejection
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntropyFloor {
+    /// Zero disables the warning
+    pub bits: f64,
+}
+
+impl Default for EntropyFloor {
+    fn default() -> Self {
+        Self {
+            bits: Self::DEFAULT_BITS,
+        }
+    }
+}
+
+impl EntropyFloor {
+    pub const DEFAULT_BITS: f64 = 50.0;
+
+    /// Warning for a batch whose weakest password has `lowest` bits, or None above the floor
+    ///
+    /// Suggests the length or word count that would reach the floor, assuming
+    /// the entropy grows linearly with it.
+    pub fn warning(&self, command: &GeneratorCommand, lowest: f64) -> Option<String> {
+        if self.bits <= 0.0 || lowest >= self.bits {
+            return None;
+        }
+        let below = format!(
+            "only {:.1} bits of entropy, below the {}-bit floor",
+            lowest, self.bits
+        );
+        if lowest <= 0.0 {
+            // Every character is fixed, so no length reaches the floor
+            let hint = match command {
+                GeneratorCommand::Secure { .. } => " (--charset, or more --chars)",
+                _ => "",
+            };
+            return Some(format!(
+                "the weakest password has {}; it is drawn from a single symbol, so no length \
+                 helps. Use a larger alphabet{}",
+                below, hint
+            ));
+        }
+        let needed = |units: usize| (self.bits / (lowest / units.max(1) as f64)).ceil() as usize;
+        let suggestion = match command {
+            GeneratorCommand::Pin { length_pos, length } => {
+                let digits = length_pos
+                    .or(*length)
+                    .unwrap_or(PinGenerator::DEFAULT_LENGTH);
+                return Some(format!(
+                    "this PIN has {}; each digit adds just {:.1} bits, so {} digits would be \
+                     needed. Use PINs only where guesses are rate-limited, or a password instead",
+                    below,
+                    10f64.log2(),
+                    needed(digits)
+                ));
+            }
+            GeneratorCommand::Passcode { .. } => {
+                let length = (PasscodeGenerator::MIN_LENGTH..)
+                    .find(|&n| PasscodeGenerator::entropy_bits(n, true) >= self.bits)
+                    .unwrap_or(PasscodeGenerator::MIN_LENGTH);
+                return Some(format!(
+                    "this passcode has {}; it relies on the device locking out after failed \
+                     attempts, or try --alphanumeric --length {}",
+                    below, length
+                ));
+            }
+            GeneratorCommand::Keypad { digit_count, .. } => {
+                let digits = digit_count.unwrap_or(KeypadGenerator::DEFAULT_DIGITS);
+                let more = ((self.bits - lowest) / 10f64.log2()).ceil() as usize;
+                return Some(format!(
+                    "this keypad code has {}; use it only where guesses are rate-limited, \
+                     or try --digit-count {}",
+                    below,
+                    digits + more
+                ));
+            }
+            GeneratorCommand::Template { .. } => {
+                // One ?a slot, the broadest class
+                let per_slot = 95f64.log2();
+                return Some(format!(
+                    "this template has {}; add {} more ?a slots ({:.1} bits each) or make \
+                     existing ones broader",
+                    below,
+                    ((self.bits - lowest) / per_slot).ceil() as usize,
+                    per_slot
+                ));
+            }
+            GeneratorCommand::Apple => {
+                return Some(format!(
+                    "this Apple-style password has {}; its format is fixed, so use secure \
+                     or phrase for more",
+                    below
+                ));
+            }
+            GeneratorCommand::Phrase {
+                words_pos, words, ..
+            } => {
+                let count = words_pos
+                    .or(*words)
+                    .unwrap_or(PassphraseGenerator::DEFAULT_WORD_COUNT);
+                format!("--words {}", needed(count))
+            }
+            GeneratorCommand::Normal {
+                length_pos, length, ..
+            } => format!(
+                "--length {}",
+                needed(
+                    length_pos
+                        .or(*length)
+                        .unwrap_or(MarkovGenerator::DEFAULT_LENGTH)
+                )
+            ),
+            GeneratorCommand::Secure {
+                length_pos, length, ..
+            } => format!(
+                "--length {}",
+                needed(
+                    length_pos
+                        .or(*length)
+                        .unwrap_or(SecureGenerator::DEFAULT_LENGTH)
+                )
+            ),
+            GeneratorCommand::Temp { length_pos, length } => format!(
+                "--length {}",
+                needed(
+                    length_pos
+                        .or(*length)
+                        .unwrap_or(TemporaryGenerator::DEFAULT_LENGTH)
+                )
+            ),
+        };
+        Some(format!(
+            "the weakest password has {}; try {}",
+            below, suggestion
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accepts() {
+        let threshold = Threshold {
+            min_bits: Some(60.0),
+            min_strength: Some(StrengthLevel::Strong),
+            ..Threshold::default()
+        };
+        assert!(!threshold.accepts(&EntropyInfo::new(70.0, "test")));
+        assert!(threshold.accepts(&EntropyInfo::new(80.0, "test")));
+        assert!(Threshold::default().accepts(&EntropyInfo::new(0.0, "test")));
+
+        let online = Threshold {
+            attacker: AttackerModel::Online,
+            ..threshold
+        };
+        assert!(online.accepts(&EntropyInfo::new(70.0, "test")));
+    }
+
+    #[test]
+    fn test_check_report() {
+        let threshold = Threshold {
+            min_bits: Some(96.0),
+            min_strength: None,
+            ..Threshold::default()
+        };
+        let pass = threshold.check("Secure random", Some(104.9));
+        assert!(pass.pass);
+        assert_eq!(pass.to_json()["required_bits"], 96.0);
+
+        let fail = threshold.check("Numeric PIN", Some(19.9));
+        assert!(!fail.pass);
+        assert_eq!(
+            fail.reason,
+            "19.9 bits (Very Weak) is below the required 96 bits"
+        );
+        assert_eq!(fail.to_json()["estimated_strength"], "Very Weak");
+
+        assert!(!threshold.check("Unknown", None).pass);
+    }
+
+    #[test]
+    fn test_entropy_floor() {
+        let floor = EntropyFloor::default();
+        let pin = GeneratorCommand::parse_policy("pin").unwrap();
+        let warning = floor.warning(&pin, 6.0 * 10f64.log2()).unwrap();
+        assert!(warning.contains("16 digits"), "{}", warning);
+
+        let phrase = GeneratorCommand::parse_policy("phrase 3").unwrap();
+        let warning = floor.warning(&phrase, 3.0 * 12.9).unwrap();
+        assert!(warning.ends_with("try --words 4"), "{}", warning);
+
+        let secure = GeneratorCommand::parse_policy("secure 8").unwrap();
+        assert!(floor.warning(&secure, 52.4).is_none());
+        assert!(EntropyFloor { bits: 0.0 }.warning(&secure, 1.0).is_none());
+
+        // Nothing to scale: suggest a larger alphabet, not an absurd length
+        let warning = floor.warning(&secure, 0.0).unwrap();
+        assert!(warning.ends_with("Use a larger alphabet (--charset, or more --chars)"));
+        assert!(!warning.contains("--length"), "{}", warning);
+    }
+
+    #[test]
+    fn test_danger_zone() {
+        let pin = |line: &str| GeneratorCommand::parse_policy(line).unwrap();
+        assert!(check_danger(&pin("pin"), 6.0 * 10f64.log2()).is_ok());
+        assert_eq!(
+            check_danger(&pin("pin 4"), 4.0 * 10f64.log2()),
+            Err(PwgenError::TooWeak {
+                bits: 13,
+                minimum: 19
+            })
+        );
+        assert!(check_danger(&pin("secure 4"), 26.2).is_err());
+        let six = PasscodeGenerator::entropy_bits(6, false);
+        assert!(check_danger(&pin("passcode"), six).is_ok());
+        assert!(check_danger(
+            &pin("passcode 4"),
+            PasscodeGenerator::entropy_bits(4, false)
+        )
+        .is_err());
+        assert!(check_danger(&pin("passcode 4 --alphanumeric"), 20.5).is_ok());
+        assert!(check_danger(&pin("secure 5"), 32.8).is_ok());
+    }
+}
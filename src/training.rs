@@ -0,0 +1,184 @@
+//! Training the Markov model on a custom corpus (`normal --corpus`)
+//!
+//! The corpus is streamed line by line, so its size doesn't matter; what
+//! grows is the table of distinct trigrams, which stays small for one
+//! alphabet but can reach hundreds of megabytes for large mixed-script
+//! corpora. Training stops at a memory limit on that table or at a time
+//! limit, and the model learned up to that point is used.
+
+use std::io::{self, BufRead, Read};
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+
+use crate::generators::markov::{MarkovModel, ModelCounts};
+
+#[derive(Debug, Error)]
+pub enum TrainingError {
+    #[error("could not read the corpus")]
+    Io(#[from] io::Error),
+    #[error("the corpus has no words of three or more letters")]
+    Empty,
+}
+
+/// Per-invocation bounds on training
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrainingLimits {
+    /// Approximate bytes the trigram table may use
+    pub max_memory: usize,
+    /// Wall-clock time allowed, if limited
+    pub timeout: Option<Duration>,
+}
+
+impl Default for TrainingLimits {
+    fn default() -> Self {
+        Self {
+            max_memory: 256 * 1024 * 1024,
+            timeout: None,
+        }
+    }
+}
+
+/// Why training ended before the end of the corpus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    MemoryLimit,
+    Timeout,
+}
+
+/// How far training got, reported while it runs and at the end
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// Corpus bytes read
+    pub bytes: u64,
+    /// Corpus size, when known
+    pub total: Option<u64>,
+    pub words: u64,
+    /// Approximate bytes used by the trigram table
+    pub memory: usize,
+    pub elapsed: Duration,
+}
+
+/// A trained model and how training went
+#[derive(Debug)]
+pub struct Trained {
+    pub model: MarkovModel,
+    pub progress: Progress,
+    /// Set if a limit ended training early
+    pub stopped: Option<Stop>,
+}
+
+/// Bytes read between progress reports and limit checks
+pub const REPORT_INTERVAL: u64 = 1024 * 1024;
+
+/// Longest line read in one piece; longer lines are split, which can cut one word
+const MAX_LINE_BYTES: u64 = 64 * 1024;
+
+/// Train on whitespace-separated words from `corpus`, of `total` bytes if known
+///
+/// `on_progress` is called about every [`REPORT_INTERVAL`] bytes.
+pub fn train(
+    mut corpus: impl BufRead,
+    total: Option<u64>,
+    limits: TrainingLimits,
+    on_progress: &mut dyn FnMut(&Progress),
+) -> Result<Trained, TrainingError> {
+    let start = Instant::now();
+    let mut counts = ModelCounts::default();
+    let mut line = Vec::new();
+    let mut bytes = 0u64;
+    let mut next_report = REPORT_INTERVAL;
+    let mut stopped = None;
+    let progress = |bytes, counts: &ModelCounts| Progress {
+        bytes,
+        total,
+        words: counts.words(),
+        memory: counts.memory(),
+        elapsed: start.elapsed(),
+    };
+
+    loop {
+        line.clear();
+        let read = (&mut corpus)
+            .take(MAX_LINE_BYTES)
+            .read_until(b'\n', &mut line)?;
+        if read == 0 {
+            break;
+        }
+        bytes += read as u64;
+        for word in String::from_utf8_lossy(&line).split_whitespace() {
+            counts.add_word(word);
+        }
+
+        if counts.memory() > limits.max_memory {
+            stopped = Some(Stop::MemoryLimit);
+            break;
+        }
+        if bytes >= next_report {
+            next_report = bytes + REPORT_INTERVAL;
+            on_progress(&progress(bytes, &counts));
+            if limits
+                .timeout
+                .is_some_and(|timeout| start.elapsed() >= timeout)
+            {
+                stopped = Some(Stop::Timeout);
+                break;
+            }
+        }
+    }
+
+    let progress = progress(bytes, &counts);
+    let model = counts.into_model().ok_or(TrainingError::Empty)?;
+    Ok(Trained {
+        model,
+        progress,
+        stopped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_train_streams_and_stops_at_limits() {
+        let corpus = "the quick brown fox jumps over the lazy dog\n".repeat(50_000);
+        let mut reports = 0;
+        let trained = train(
+            corpus.as_bytes(),
+            Some(corpus.len() as u64),
+            TrainingLimits::default(),
+            &mut |progress| {
+                reports += 1;
+                assert!(progress.bytes <= progress.total.unwrap());
+            },
+        )
+        .unwrap();
+        assert_eq!(trained.stopped, None);
+        assert_eq!(trained.progress.bytes, corpus.len() as u64);
+        assert_eq!(trained.progress.words, 50_000 * 9);
+        assert_eq!(reports, corpus.len() / REPORT_INTERVAL as usize);
+        assert!(trained.model.bigrams() > 10);
+
+        let limits = TrainingLimits {
+            max_memory: 1024,
+            timeout: None,
+        };
+        let trained = train(corpus.as_bytes(), None, limits, &mut |_| {}).unwrap();
+        assert_eq!(trained.stopped, Some(Stop::MemoryLimit));
+        assert!(trained.progress.bytes < corpus.len() as u64);
+
+        let limits = TrainingLimits {
+            max_memory: usize::MAX,
+            timeout: Some(Duration::ZERO),
+        };
+        let trained = train(corpus.as_bytes(), None, limits, &mut |_| {}).unwrap();
+        assert_eq!(trained.stopped, Some(Stop::Timeout));
+        assert_eq!(trained.progress.bytes, REPORT_INTERVAL.div_ceil(44) * 44);
+
+        assert!(matches!(
+            train("a an to\n".as_bytes(), None, limits, &mut |_| {}),
+            Err(TrainingError::Empty)
+        ));
+    }
+}
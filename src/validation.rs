@@ -0,0 +1,114 @@
+//! Bounds checks shared by all subcommands
+//!
+//! Zero values are rejected by the generator constructors themselves; these
+//! limits additionally catch absurdly large requests before any memory is
+//! allocated for them.
+
+use crate::config::{Config, Value};
+use crate::error::PwgenError;
+
+/// Upper bounds for generation requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Maximum password length in characters
+    pub max_length: usize,
+    /// Maximum number of words in a passphrase
+    pub max_words: usize,
+    /// Maximum number of passwords per invocation
+    pub max_count: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_length: 4096,
+            max_words: 512,
+            max_count: 100_000,
+        }
+    }
+}
+
+impl Limits {
+    /// Defaults overridden by the `[limits]` config section
+    pub fn from_config(config: &Config) -> Self {
+        let mut limits = Self::default();
+        let get = |key: &str| match config.get(key) {
+            Some(Value::Integer(n)) if *n > 0 => usize::try_from(*n).ok(),
+            _ => None,
+        };
+        if let Some(n) = get("limits.max_length") {
+            limits.max_length = n;
+        }
+        if let Some(n) = get("limits.max_words") {
+            limits.max_words = n;
+        }
+        if let Some(n) = get("limits.max_count") {
+            limits.max_count = n;
+        }
+        limits
+    }
+
+    pub fn check_length(&self, length: usize) -> Result<(), PwgenError> {
+        match length {
+            0 => Err(PwgenError::ZeroLength),
+            n if n > self.max_length => Err(PwgenError::LengthTooLarge {
+                length: n,
+                max: self.max_length,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_words(&self, words: usize) -> Result<(), PwgenError> {
+        match words {
+            0 => Err(PwgenError::ZeroWords),
+            n if n > self.max_words => Err(PwgenError::TooManyWords {
+                words: n,
+                max: self.max_words,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_count(&self, count: usize) -> Result<(), PwgenError> {
+        match count {
+            0 => Err(PwgenError::ZeroCount),
+            n if n > self.max_count => Err(PwgenError::CountTooLarge {
+                count: n,
+                max: self.max_count,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_limits() {
+        let limits = Limits::default();
+        assert!(limits.check_length(64).is_ok());
+        assert_eq!(limits.check_length(0), Err(PwgenError::ZeroLength));
+        assert_eq!(
+            limits.check_length(1_000_000_000),
+            Err(PwgenError::LengthTooLarge {
+                length: 1_000_000_000,
+                max: 4096
+            })
+        );
+        assert_eq!(limits.check_words(0), Err(PwgenError::ZeroWords));
+        assert_eq!(limits.check_count(0), Err(PwgenError::ZeroCount));
+    }
+
+    #[test]
+    fn test_limits_from_config() {
+        let config = Config::parse("[limits]\nmax_length = 64\nmax_words = -1\n").unwrap();
+        let limits = Limits::from_config(&config);
+        assert_eq!(limits.max_length, 64);
+        // Non-positive values are ignored
+        assert_eq!(limits.max_words, Limits::default().max_words);
+        assert!(limits.check_length(65).is_err());
+    }
+}
@@ -0,0 +1,90 @@
+//! Diagnostic logging for `-v/--verbose`
+//!
+//! Messages go to stderr, prefixed with their level, and are only formatted
+//! when the level is enabled. They describe decisions (charset sizes,
+//! rejection counts, fallbacks), never password material: nothing derived
+//! from a generated value may be passed to these macros.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Verbosity, in increasing detail
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// `-v`: configuration decisions
+    Info = 1,
+    /// `-vv`: retry and rejection counts per batch
+    Debug = 2,
+    /// `-vvv`: per-candidate details
+    Trace = 3,
+}
+
+impl Level {
+    pub fn label(self) -> &'static str {
+        match self {
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        }
+    }
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+
+/// Enable messages up to the level given by the number of `-v` flags
+pub fn set_verbosity(count: u8) {
+    VERBOSITY.store(count, Ordering::Relaxed);
+}
+
+pub fn enabled(level: Level) -> bool {
+    VERBOSITY.load(Ordering::Relaxed) >= level as u8
+}
+
+#[doc(hidden)]
+pub fn log(level: Level, args: std::fmt::Arguments<'_>) {
+    eprintln!("{}: {}", level.label(), args);
+}
+
+/// Log at [`Level::Info`]; never pass secret material
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if $crate::verbose::enabled($crate::verbose::Level::Info) {
+            $crate::verbose::log($crate::verbose::Level::Info, format_args!($($arg)*));
+        }
+    };
+}
+
+/// Log at [`Level::Debug`]; never pass secret material
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if $crate::verbose::enabled($crate::verbose::Level::Debug) {
+            $crate::verbose::log($crate::verbose::Level::Debug, format_args!($($arg)*));
+        }
+    };
+}
+
+/// Log at [`Level::Trace`]; never pass secret material
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        if $crate::verbose::enabled($crate::verbose::Level::Trace) {
+            $crate::verbose::log($crate::verbose::Level::Trace, format_args!($($arg)*));
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels() {
+        set_verbosity(2);
+        assert!(enabled(Level::Info));
+        assert!(enabled(Level::Debug));
+        assert!(!enabled(Level::Trace));
+        set_verbosity(0);
+        assert!(!enabled(Level::Info));
+    }
+}
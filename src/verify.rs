@@ -0,0 +1,328 @@
+//! Empirical checks of a generator's output distribution
+//!
+//! Generates many samples and compares character frequencies, per-position
+//! frequencies and character-class coverage against what a uniform generator
+//! must produce. Checks are expressed as z-scores so a single threshold
+//! applies regardless of alphabet size or sample count.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use rand::RngCore;
+
+use crate::generators::{PasswordGenerator, PasswordGeneratorExt};
+
+/// z-score above which a check fails; a false alarm at this level is
+/// astronomically unlikely for a correct generator
+pub const Z_THRESHOLD: f64 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharClass {
+    Lower,
+    Upper,
+    Digit,
+    Symbol,
+}
+
+impl CharClass {
+    pub const ALL: [CharClass; 4] = [
+        CharClass::Lower,
+        CharClass::Upper,
+        CharClass::Digit,
+        CharClass::Symbol,
+    ];
+
+    pub fn of(c: char) -> Self {
+        if c.is_lowercase() {
+            CharClass::Lower
+        } else if c.is_uppercase() {
+            CharClass::Upper
+        } else if c.is_numeric() {
+            CharClass::Digit
+        } else {
+            CharClass::Symbol
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CharClass::Lower => "lowercase",
+            CharClass::Upper => "uppercase",
+            CharClass::Digit => "digit",
+            CharClass::Symbol => "symbol",
+        }
+    }
+}
+
+/// Outcome of one statistical check
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: String,
+    /// Largest z-score observed, None when the check does not apply
+    pub z: Option<f64>,
+}
+
+impl Check {
+    pub fn passed(&self) -> bool {
+        self.z.is_none_or(|z| z <= Z_THRESHOLD)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VerifyReport {
+    pub samples: usize,
+    pub total_chars: u64,
+    /// Distinct characters seen across all samples
+    pub observed_alphabet: usize,
+    /// Alphabet size the generator claims to sample uniformly from
+    pub expected_alphabet: Option<usize>,
+    /// Largest relative deviation of a character's frequency from uniform
+    pub max_char_deviation: Option<f64>,
+    /// Fraction of samples containing each character class
+    pub class_coverage: Vec<(CharClass, f64)>,
+    pub checks: Vec<Check>,
+}
+
+impl VerifyReport {
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(Check::passed)
+    }
+}
+
+/// Chi-square statistic of `counts` against a uniform distribution over `alphabet`
+/// symbols, converted to a z-score via the normal approximation
+fn uniform_chi_square_z(counts: &HashMap<char, u64>, alphabet: usize) -> f64 {
+    let total: u64 = counts.values().sum();
+    if total == 0 || alphabet < 2 {
+        return 0.0;
+    }
+    let expected = total as f64 / alphabet as f64;
+    let observed: f64 = counts
+        .values()
+        .map(|&o| (o as f64 - expected).powi(2) / expected)
+        .sum();
+    // Symbols that never appeared contribute (0 - e)^2 / e = e each
+    let missing = alphabet.saturating_sub(counts.len()) as f64 * expected;
+    let dof = (alphabet - 1) as f64;
+    (observed + missing - dof) / (2.0 * dof).sqrt()
+}
+
+/// Generate `samples` passwords and analyze their distribution
+pub fn verify(
+    generator: &dyn PasswordGenerator,
+    rng: &mut dyn RngCore,
+    samples: usize,
+) -> VerifyReport {
+    let mut counts: HashMap<char, u64> = HashMap::new();
+    let mut positional: Vec<HashMap<char, u64>> = Vec::new();
+    let mut class_hits = [0u64; 4];
+    let mut lengths: HashMap<usize, u64> = HashMap::new();
+
+    for password in generator.iter(rng).take(samples) {
+        let mut seen = [false; 4];
+        let mut len = 0;
+        for (pos, c) in password.value.chars().enumerate() {
+            *counts.entry(c).or_insert(0) += 1;
+            if positional.len() <= pos {
+                positional.push(HashMap::new());
+            }
+            *positional[pos].entry(c).or_insert(0) += 1;
+            seen[CharClass::of(c) as usize] = true;
+            len += 1;
+        }
+        *lengths.entry(len).or_insert(0) += 1;
+        for (hit, seen) in class_hits.iter_mut().zip(seen) {
+            *hit += seen as u64;
+        }
+    }
+
+    let total_chars: u64 = counts.values().sum();
+    let expected_alphabet = generator.uniform_alphabet();
+    let class_coverage: Vec<(CharClass, f64)> = CharClass::ALL
+        .iter()
+        .map(|&class| {
+            (
+                class,
+                class_hits[class as usize] as f64 / samples.max(1) as f64,
+            )
+        })
+        .collect();
+
+    let mut checks = Vec::new();
+    let mut max_char_deviation = None;
+
+    if let Some(alphabet) = expected_alphabet {
+        let expected = total_chars as f64 / alphabet as f64;
+        // A symbol that never appeared is 100% below its expected frequency
+        let missing = if counts.len() < alphabet { 1.0 } else { 0.0 };
+        max_char_deviation = Some(
+            counts
+                .values()
+                .map(|&o| (o as f64 - expected).abs() / expected)
+                .fold(missing, f64::max),
+        );
+
+        checks.push(Check {
+            name: "character frequency (chi-square)".to_string(),
+            z: Some(uniform_chi_square_z(&counts, alphabet)),
+        });
+
+        let positional_z = positional
+            .iter()
+            .map(|pos| uniform_chi_square_z(pos, alphabet))
+            .fold(f64::NEG_INFINITY, f64::max);
+        checks.push(Check {
+            name: "positional bias (worst position)".to_string(),
+            z: positional_z.is_finite().then_some(positional_z),
+        });
+
+        // Probability a uniform sample of length L contains class c:
+        // 1 - (1 - k_c/N)^L, with k_c estimated from the observed alphabet
+        let mut class_sizes = [0usize; 4];
+        for &c in counts.keys() {
+            class_sizes[CharClass::of(c) as usize] += 1;
+        }
+        let class_z = CharClass::ALL
+            .iter()
+            .filter(|&&class| class_sizes[class as usize] > 0)
+            .map(|&class| {
+                let p_char = class_sizes[class as usize] as f64 / alphabet as f64;
+                let expected: f64 = lengths
+                    .iter()
+                    .map(|(&len, &n)| n as f64 * (1.0 - (1.0 - p_char).powi(len as i32)))
+                    .sum::<f64>()
+                    / samples as f64;
+                let observed = class_hits[class as usize] as f64 / samples as f64;
+                let sigma = (expected * (1.0 - expected) / samples as f64).sqrt();
+                if sigma > 0.0 {
+                    (observed - expected).abs() / sigma
+                } else {
+                    0.0
+                }
+            })
+            .fold(0.0, f64::max);
+        checks.push(Check {
+            name: "character class coverage".to_string(),
+            z: Some(class_z),
+        });
+    } else {
+        checks.push(Check {
+            name: "uniformity (generator is not uniform by design)".to_string(),
+            z: None,
+        });
+    }
+
+    VerifyReport {
+        samples,
+        total_chars,
+        observed_alphabet: counts.len(),
+        expected_alphabet,
+        max_char_deviation,
+        class_coverage,
+        checks,
+    }
+}
+
+impl fmt::Display for VerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Samples:            {}", self.samples)?;
+        writeln!(f, "Characters:         {}", self.total_chars)?;
+        match self.expected_alphabet {
+            Some(n) => writeln!(
+                f,
+                "Alphabet:           {} observed / {} expected",
+                self.observed_alphabet, n
+            )?,
+            None => writeln!(f, "Alphabet:           {} observed", self.observed_alphabet)?,
+        }
+        if let Some(dev) = self.max_char_deviation {
+            writeln!(f, "Max char deviation: {:.2}%", dev * 100.0)?;
+        }
+        writeln!(f, "Class coverage:")?;
+        for (class, fraction) in &self.class_coverage {
+            writeln!(
+                f,
+                "  {:<10} {:>6.2}% of samples",
+                class.name(),
+                fraction * 100.0
+            )?;
+        }
+        writeln!(f, "Checks (fail above z = {}):", Z_THRESHOLD)?;
+        for check in &self.checks {
+            let status = match (check.z, check.passed()) {
+                (None, _) => "SKIP",
+                (Some(_), true) => "PASS",
+                (Some(_), false) => "FAIL",
+            };
+            match check.z {
+                Some(z) => writeln!(f, "  [{}] {} (z = {:.2})", status, check.name, z)?,
+                None => writeln!(f, "  [{}] {}", status, check.name)?,
+            }
+        }
+        write!(f, "Result: {}", if self.passed() { "PASS" } else { "FAIL" })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entropy::EntropyInfo;
+    use crate::generators::CharSet;
+    use crate::generators::{GeneratedPassword, PinGenerator, SecureGenerator};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use zeroize::Zeroizing;
+
+    #[test]
+    fn test_uniform_generators_pass() {
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let secure = SecureGenerator::new(16, &CharSet::AlphanumericSymbols, false).unwrap();
+        let report = verify(&secure, &mut rng, 5_000);
+        assert!(report.passed(), "{}", report);
+        assert_eq!(report.observed_alphabet, 88);
+
+        let pin = PinGenerator::new(6).unwrap();
+        let report = verify(&pin, &mut rng, 5_000);
+        assert!(report.passed(), "{}", report);
+    }
+
+    /// Claims to be uniform over digits but never emits '9'
+    struct Biased;
+
+    impl PasswordGenerator for Biased {
+        fn generate(&self, rng: &mut dyn RngCore) -> GeneratedPassword {
+            let value: String = (0..6)
+                .map(|_| (b'0' + (rng.next_u32() % 9) as u8) as char)
+                .collect();
+            GeneratedPassword {
+                value: Zeroizing::new(value),
+                entropy: EntropyInfo::new(0.0, "test"),
+                mnemonic: None,
+            }
+        }
+
+        fn description(&self) -> &'static str {
+            "biased"
+        }
+
+        fn uniform_alphabet(&self) -> Option<usize> {
+            Some(10)
+        }
+    }
+
+    #[test]
+    fn test_biased_generator_fails() {
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let report = verify(&Biased, &mut rng, 5_000);
+        assert!(!report.passed());
+        assert_eq!(report.max_char_deviation, Some(1.0));
+    }
+
+    #[test]
+    fn test_char_class() {
+        assert_eq!(CharClass::of('a'), CharClass::Lower);
+        assert_eq!(CharClass::of('Z'), CharClass::Upper);
+        assert_eq!(CharClass::of('7'), CharClass::Digit);
+        assert_eq!(CharClass::of('#'), CharClass::Symbol);
+    }
+}
@@ -0,0 +1,84 @@
+//! Version report (`--version`, `--version --format json`)
+//!
+//! Besides the crate version, the JSON report identifies the data compiled
+//! into the binary: each embedded table with its size and a content hash, the
+//! Markov model version, and the enabled cargo features. Fleet tooling can
+//! compare it across hosts to confirm they all generate from the same data,
+//! and the SHA-256 hashes against the upstream files.
+
+use serde_json::{json, Value};
+
+use crate::digest::{hex, sha256};
+use crate::generators::markov::{self, MarkovGenerator};
+use crate::rules;
+use crate::wordlist;
+
+/// Cargo features compiled in
+pub fn features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "serde") {
+        features.push("serde");
+    }
+    if cfg!(feature = "breach") {
+        features.push("breach");
+    }
+    features
+}
+
+/// One-line version, matching clap's format
+pub fn text() -> String {
+    format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+}
+
+pub fn to_json() -> Value {
+    json!({
+        "name": env!("CARGO_PKG_NAME"),
+        "version": env!("CARGO_PKG_VERSION"),
+        "features": features(),
+        "data": {
+            "wordlists": [
+                embedded(wordlist::BUILTIN_NAME, wordlist::EMBEDDED, "words"),
+            ],
+            "markov_model": {
+                "version": MarkovGenerator::MODEL_VERSION,
+                "order": MarkovGenerator::ORDER,
+                "trained_on": wordlist::BUILTIN_NAME,
+                "sha256": hex(&sha256(markov::TRAINING_WORDS.as_bytes())),
+            },
+            "site_rules": embedded("site-rules", rules::SITE_RULES, "sites"),
+        },
+    })
+}
+
+/// Name, entry count and hash of an embedded table
+fn embedded(name: &str, text: &str, unit: &str) -> Value {
+    let entries = text
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .count();
+    json!({
+        "name": name,
+        unit: entries,
+        "sha256": hex(&sha256(text.as_bytes())),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_json() {
+        let report = to_json();
+        assert_eq!(report["version"], env!("CARGO_PKG_VERSION"));
+        let eff = &report["data"]["wordlists"][0];
+        assert_eq!(eff["name"], wordlist::BUILTIN_NAME);
+        assert_eq!(eff["words"], 7776);
+        assert_eq!(eff["sha256"].as_str().unwrap().len(), 64);
+        assert_eq!(
+            report["data"]["markov_model"]["version"],
+            MarkovGenerator::MODEL_VERSION
+        );
+        assert!(report["data"]["site_rules"]["sites"].as_u64().unwrap() > 0);
+    }
+}
@@ -0,0 +1,207 @@
+//! Interactive wizard (`wizard` subcommand)
+//!
+//! Asks a few plain-language questions and maps the answers to a generator
+//! command line, so users who don't know the options still get a sensible
+//! password. The resulting command is shown so it can be reused directly.
+
+use std::io::{self, BufRead, Write};
+
+use crate::rules;
+
+/// What the password will protect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Purpose {
+    Website,
+    WiFi,
+    DiskEncryption,
+    Pin,
+}
+
+/// Answers collected by the wizard
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Answers {
+    pub purpose: Purpose,
+    /// Site with known password rules, for websites
+    pub site: Option<String>,
+    /// The user must remember it rather than store it in a password manager
+    pub memorable: bool,
+}
+
+impl Answers {
+    /// Generator command line (without the program name) for these answers
+    pub fn command_line(&self) -> Vec<String> {
+        let line: &[&str] = match (self.purpose, self.memorable) {
+            (Purpose::Pin, _) => &["pin", "6"],
+            (Purpose::Website, _) if self.site.is_some() => &["secure", "--for"],
+            (Purpose::Website, true) => &["phrase", "5", "--capitalize"],
+            (Purpose::Website, false) => &["secure", "20"],
+            // Typed on TVs and phones: avoid symbols and look-alike characters
+            (Purpose::WiFi, true) => &["phrase", "5", "--no-mutate"],
+            (Purpose::WiFi, false) => &[
+                "secure",
+                "24",
+                "--charset",
+                "alphanumeric",
+                "--no-ambiguous",
+            ],
+            // Protects data at rest against offline attacks: aim for ~90+ bits
+            (Purpose::DiskEncryption, true) => &["phrase", "7", "--no-mutate"],
+            (Purpose::DiskEncryption, false) => &["secure", "32"],
+        };
+        let mut args: Vec<String> = line.iter().map(|s| s.to_string()).collect();
+        if let Some(site) = &self.site {
+            args.push(site.clone());
+        }
+        args
+    }
+}
+
+/// Print `question` and its numbered `options`, returning the chosen index
+fn choose(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    question: &str,
+    options: &[&str],
+) -> io::Result<usize> {
+    writeln!(output, "{}", question)?;
+    for (i, option) in options.iter().enumerate() {
+        writeln!(output, "  {}) {}", i + 1, option)?;
+    }
+    loop {
+        let answer = ask(input, output, &format!("Choose 1-{}: ", options.len()))?;
+        match answer.parse::<usize>() {
+            Ok(n) if (1..=options.len()).contains(&n) => {
+                writeln!(output)?;
+                return Ok(n - 1);
+            }
+            _ => writeln!(output, "Please enter a number from 1 to {}.", options.len())?,
+        }
+    }
+}
+
+/// Print a prompt and read one trimmed line; end of input is an error
+fn ask(input: &mut impl BufRead, output: &mut impl Write, prompt: &str) -> io::Result<String> {
+    write!(output, "{}", prompt)?;
+    output.flush()?;
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "wizard cancelled",
+        ));
+    }
+    Ok(line.trim().to_string())
+}
+
+/// Ask the questions and collect the answers
+pub fn run(input: &mut impl BufRead, output: &mut impl Write) -> io::Result<Answers> {
+    let purpose = match choose(
+        input,
+        output,
+        "What is this password for?",
+        &[
+            "A website or app account",
+            "A Wi-Fi network",
+            "Disk encryption or a password manager's master password",
+            "A PIN (phone, card, door code)",
+        ],
+    )? {
+        0 => Purpose::Website,
+        1 => Purpose::WiFi,
+        2 => Purpose::DiskEncryption,
+        _ => Purpose::Pin,
+    };
+
+    let mut site = None;
+    if purpose == Purpose::Website {
+        let answer = ask(
+            input,
+            output,
+            "Which website (e.g. github.com)? Leave empty to skip: ",
+        )?;
+        if !answer.is_empty() {
+            if rules::for_site(&answer).is_ok() {
+                writeln!(output, "Using the password rules known for {}.", answer)?;
+                site = Some(rules::normalize_site(&answer));
+            } else {
+                writeln!(
+                    output,
+                    "No rules are known for {}; a generally accepted password will be used.",
+                    answer
+                )?;
+            }
+        }
+        writeln!(output)?;
+    }
+
+    let memorable = purpose != Purpose::Pin
+        && site.is_none()
+        && choose(
+            input,
+            output,
+            "Will you need to remember and type it yourself?",
+            &[
+                "Yes, I'll memorize it",
+                "No, a password manager or device will store it",
+            ],
+        )? == 0;
+
+    Ok(Answers {
+        purpose,
+        site,
+        memorable,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answers(script: &str) -> io::Result<Answers> {
+        let mut output = Vec::new();
+        run(&mut script.as_bytes(), &mut output)
+    }
+
+    #[test]
+    fn test_wizard_paths() {
+        let wifi = answers("2\n2\n").unwrap();
+        assert_eq!(wifi.purpose, Purpose::WiFi);
+        assert!(!wifi.memorable);
+        assert_eq!(
+            wifi.command_line(),
+            [
+                "secure",
+                "24",
+                "--charset",
+                "alphanumeric",
+                "--no-ambiguous"
+            ]
+        );
+
+        // Invalid choices are asked again
+        let disk = answers("9\nthree\n3\n1\n").unwrap();
+        assert_eq!(disk.command_line(), ["phrase", "7", "--no-mutate"]);
+
+        let pin = answers("4\n").unwrap();
+        assert_eq!(pin.command_line(), ["pin", "6"]);
+    }
+
+    #[test]
+    fn test_wizard_site_rules() {
+        let site = answers("1\nhttps://www.chase.com/\n").unwrap();
+        assert_eq!(site.site.as_deref(), Some("chase.com"));
+        assert_eq!(site.command_line(), ["secure", "--for", "chase.com"]);
+
+        let unknown = answers("1\nexample.invalid\n1\n").unwrap();
+        assert_eq!(unknown.site, None);
+        assert_eq!(unknown.command_line(), ["phrase", "5", "--capitalize"]);
+    }
+
+    #[test]
+    fn test_wizard_eof_cancels() {
+        assert_eq!(
+            answers("1\n").unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+}
@@ -0,0 +1,382 @@
+//! Passphrase wordlists: the embedded EFF list plus user-supplied lists
+//!
+//! User lists are plain text files in `~/.local/share/pwgen-rs/wordlists/`
+//! (honoring `XDG_DATA_HOME`) or in the directory named by
+//! `PWGEN_RS_WORDLIST_DIR`. Each file is selectable by its name without the
+//! extension, e.g. `--wordlist german` for `german.txt`. Files may contain one
+//! word per line, diceware-style `11111<TAB>word` lines, or frequency lists
+//! with `word<TAB>count` lines. Counts are only used by familiar-word weighting.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::lang::Language;
+
+/// EFF large wordlist (7776 words)
+pub(crate) const EMBEDDED: &str = include_str!("../data/eff_large_wordlist.txt");
+
+/// Name of the embedded wordlist
+pub const BUILTIN_NAME: &str = "eff-large";
+
+/// Environment variable naming an extra wordlist directory, searched first
+pub const DIR_ENV: &str = "PWGEN_RS_WORDLIST_DIR";
+
+/// Fewest distinct words a list must contain to be usable
+pub const MIN_WORDS: usize = 2;
+
+#[derive(Debug, Error)]
+pub enum WordlistError {
+    #[error("{path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("unknown wordlist '{0}' (see `wordlist list`)")]
+    NotFound(String),
+    #[error(
+        "no '{0}' wordlist is bundled yet; save a diceware list as {0}.txt in a wordlist \
+         directory (see `wordlist list`)"
+    )]
+    NoLanguageList(&'static str),
+    #[error("wordlist '{name}' has {found} distinct word(s), at least {MIN_WORDS} are required")]
+    TooSmall { name: String, found: usize },
+}
+
+/// Where a wordlist comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Builtin,
+    File(PathBuf),
+}
+
+/// A wordlist found by [`discover`], not yet loaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub name: String,
+    pub source: Source,
+}
+
+impl Entry {
+    pub fn load(&self) -> Result<Wordlist, WordlistError> {
+        match &self.source {
+            Source::Builtin => Ok(Wordlist::builtin()),
+            Source::File(path) => Wordlist::load(&self.name, path),
+        }
+    }
+}
+
+/// A loaded list of distinct words
+#[derive(Debug, Clone)]
+pub struct Wordlist {
+    name: String,
+    words: Vec<String>,
+    /// Usage counts from the file, present only when every word had one
+    frequencies: Option<Vec<f64>>,
+}
+
+impl Wordlist {
+    /// The embedded EFF large wordlist
+    pub fn builtin() -> Self {
+        Self {
+            name: BUILTIN_NAME.to_string(),
+            words: parse(EMBEDDED),
+            frequencies: None,
+        }
+    }
+
+    pub fn load(name: &str, path: &Path) -> Result<Self, WordlistError> {
+        let text = fs::read_to_string(path).map_err(|source| WordlistError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_entries(name, parse_entries(&text))
+    }
+
+    /// Build a wordlist from already-split words, dropping duplicates
+    pub fn from_words(name: &str, words: Vec<String>) -> Result<Self, WordlistError> {
+        Self::from_entries(name, words.into_iter().map(|w| (w, None)).collect())
+    }
+
+    fn from_entries(
+        name: &str,
+        entries: Vec<(String, Option<f64>)>,
+    ) -> Result<Self, WordlistError> {
+        let mut seen = HashSet::new();
+        let (words, frequencies): (Vec<String>, Vec<Option<f64>>) = entries
+            .into_iter()
+            .filter(|(w, _)| seen.insert(w.clone()))
+            .unzip();
+        if words.len() < MIN_WORDS {
+            return Err(WordlistError::TooSmall {
+                name: name.to_string(),
+                found: words.len(),
+            });
+        }
+        Ok(Self {
+            name: name.to_string(),
+            words,
+            frequencies: frequencies.into_iter().collect(),
+        })
+    }
+
+    /// The words `keep` accepts, with their frequencies
+    pub fn filtered(&self, keep: impl Fn(&str) -> bool) -> Result<Self, WordlistError> {
+        let entries = self
+            .words
+            .iter()
+            .enumerate()
+            .filter(|(_, word)| keep(word))
+            .map(|(i, word)| (word.clone(), self.frequencies.as_ref().map(|f| f[i])))
+            .collect();
+        Self::from_entries(&self.name, entries)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Bits of entropy contributed by one uniformly chosen word
+    pub fn entropy_per_word(&self) -> f64 {
+        (self.words.len() as f64).log2()
+    }
+
+    /// Relative selection weights favoring familiar words
+    ///
+    /// Uses the file's usage counts when it has them. Otherwise word length is
+    /// the proxy: common words tend to be short, so each extra letter halves
+    /// a word's weight.
+    pub fn familiarity_weights(&self) -> Vec<f64> {
+        match &self.frequencies {
+            Some(frequencies) => frequencies.clone(),
+            None => self
+                .words
+                .iter()
+                .map(|w| 0.5f64.powi(w.chars().count() as i32))
+                .collect(),
+        }
+    }
+}
+
+/// Extract words from a wordlist file, accepting diceware `11111<TAB>word` lines
+pub fn parse(text: &str) -> Vec<String> {
+    parse_entries(text)
+        .into_iter()
+        .map(|(word, _)| word)
+        .collect()
+}
+
+/// Words with their usage count, if the line had one
+fn parse_entries(text: &str) -> Vec<(String, Option<f64>)> {
+    text.lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let (word, frequency) = match line.split_once(char::is_whitespace) {
+                Some((index, word)) if index.chars().all(|c| c.is_ascii_digit()) => {
+                    (word.trim(), None)
+                }
+                Some((word, count)) => match count.trim().parse::<f64>() {
+                    Ok(count) if count > 0.0 && count.is_finite() => (word, Some(count)),
+                    _ => (line, None),
+                },
+                None => (line, None),
+            };
+            (!word.is_empty() && !word.starts_with('#')).then(|| (word.to_string(), frequency))
+        })
+        .collect()
+}
+
+/// Directories searched for user wordlists, in priority order
+pub fn search_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(dir) = std::env::var_os(DIR_ENV) {
+        dirs.push(PathBuf::from(dir));
+    }
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")));
+    if let Some(base) = data_home {
+        dirs.push(base.join("pwgen-rs").join("wordlists"));
+    }
+    dirs
+}
+
+/// All available wordlists: the built-in one first, then user lists by name
+pub fn discover() -> Vec<Entry> {
+    discover_in(&search_dirs())
+}
+
+/// Like [`discover`], scanning the given directories; earlier directories win on name clashes
+pub fn discover_in(dirs: &[PathBuf]) -> Vec<Entry> {
+    let mut entries = vec![Entry {
+        name: BUILTIN_NAME.to_string(),
+        source: Source::Builtin,
+    }];
+    for dir in dirs {
+        let Ok(read_dir) = fs::read_dir(dir) else {
+            continue;
+        };
+        let mut found: Vec<Entry> = read_dir
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if !path.is_file() {
+                    return None;
+                }
+                let name = path.file_stem()?.to_str()?.to_string();
+                (!name.starts_with('.')).then_some(Entry {
+                    name,
+                    source: Source::File(path),
+                })
+            })
+            .collect();
+        found.sort_by(|a, b| a.name.cmp(&b.name));
+        for entry in found {
+            if entries.iter().all(|e| e.name != entry.name) {
+                entries.push(entry);
+            }
+        }
+    }
+    entries
+}
+
+/// The diceware list for `language`
+///
+/// English uses the embedded EFF list; other languages use the user list
+/// named by their code, e.g. `de.txt` for German.
+pub fn for_language(language: Language) -> Result<Wordlist, WordlistError> {
+    for_language_in(language, &discover())
+}
+
+fn for_language_in(language: Language, entries: &[Entry]) -> Result<Wordlist, WordlistError> {
+    if language == Language::English {
+        return Ok(Wordlist::builtin());
+    }
+    entries
+        .iter()
+        .find(|e| e.name == language.code())
+        .ok_or(WordlistError::NoLanguageList(language.code()))?
+        .load()
+}
+
+/// Load a wordlist by discovered name, or by path if the argument names a file
+pub fn resolve(name_or_path: &str) -> Result<Wordlist, WordlistError> {
+    resolve_in(name_or_path, &discover())
+}
+
+fn resolve_in(name_or_path: &str, entries: &[Entry]) -> Result<Wordlist, WordlistError> {
+    if let Some(entry) = entries.iter().find(|e| e.name == name_or_path) {
+        return entry.load();
+    }
+    let path = Path::new(name_or_path);
+    if path.is_file() {
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or(name_or_path);
+        return Wordlist::load(name, path);
+    }
+    Err(WordlistError::NotFound(name_or_path.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_wordlist() {
+        let list = Wordlist::builtin();
+        assert_eq!(list.len(), 7776);
+        assert!((list.entropy_per_word() - 12.925).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_parse_formats() {
+        let words = parse("11111\tabacus\n11112 abdomen\n\n# comment\n  plain  \n");
+        assert_eq!(words, vec!["abacus", "abdomen", "plain"]);
+    }
+
+    #[test]
+    fn test_familiarity_weights() {
+        let entries = parse_entries("the\t500\nof 300\nzymurgy\t2\n");
+        let list = Wordlist::from_entries("freq", entries).unwrap();
+        assert_eq!(list.familiarity_weights(), vec![500.0, 300.0, 2.0]);
+
+        // Without counts, shorter words weigh more
+        let list = Wordlist::from_words("x", vec!["cat".into(), "lantern".into()]).unwrap();
+        let weights = list.familiarity_weights();
+        assert!(weights[0] > weights[1]);
+
+        // A missing count anywhere falls back to the length proxy
+        let list = Wordlist::from_entries("mixed", parse_entries("the 500\nof\n")).unwrap();
+        assert_eq!(list.familiarity_weights(), vec![0.125, 0.25]);
+    }
+
+    #[test]
+    fn test_duplicates_and_minimum_size() {
+        let list = Wordlist::from_words("x", vec!["a".into(), "b".into(), "a".into()]).unwrap();
+        assert_eq!(list.words(), ["a", "b"]);
+        assert!(matches!(
+            Wordlist::from_words("x", vec!["a".into(), "a".into()]),
+            Err(WordlistError::TooSmall { found: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_discover_and_resolve() {
+        let base = std::env::temp_dir().join(format!("pwgen-wordlists-{}", std::process::id()));
+        let first = base.join("first");
+        let second = base.join("second");
+        fs::create_dir_all(&first).unwrap();
+        fs::create_dir_all(&second).unwrap();
+        fs::write(first.join("animals.txt"), "otter\nbadger\nheron\n").unwrap();
+        fs::write(second.join("animals.txt"), "shadowed\nlist\n").unwrap();
+        fs::write(second.join("colors"), "red\ngreen\n").unwrap();
+        fs::write(
+            first.join("de.txt"),
+            "11111\tabend\n11112\tähre\n11113\töl\n",
+        )
+        .unwrap();
+
+        let entries = discover_in(&[first.clone(), second.clone(), base.join("missing")]);
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec![BUILTIN_NAME, "animals", "de", "colors"]);
+
+        let german = for_language_in(Language::German, &entries).unwrap();
+        assert_eq!(german.words(), ["abend", "ähre", "öl"]);
+        assert_eq!(
+            for_language_in(Language::English, &entries).unwrap().name(),
+            BUILTIN_NAME
+        );
+        assert!(matches!(
+            for_language_in(Language::French, &entries),
+            Err(WordlistError::NoLanguageList("fr"))
+        ));
+
+        let animals = resolve_in("animals", &entries).unwrap();
+        assert_eq!(animals.len(), 3);
+
+        let by_path = resolve_in(second.join("colors").to_str().unwrap(), &[]).unwrap();
+        assert_eq!(by_path.name(), "colors");
+
+        assert!(matches!(
+            resolve_in("nope", &entries),
+            Err(WordlistError::NotFound(_))
+        ));
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+}